@@ -0,0 +1,10 @@
+-- Account lifecycle: suspended accounts cannot obtain tokens, closed
+-- accounts are treated as deleted.
+CREATE TYPE account_status AS ENUM (
+    'active',
+    'suspended',
+    'closed'
+);
+
+ALTER TABLE impala_account
+    ADD COLUMN IF NOT EXISTS status account_status NOT NULL DEFAULT 'active';
@@ -0,0 +1,11 @@
+-- Webhook ownership verification: cron_sync only invokes callbacks whose
+-- origin has echoed the verification token at /.well-known/impala-verify.
+ALTER TABLE cron_sync ADD COLUMN IF NOT EXISTS verification_token TEXT;
+ALTER TABLE cron_sync ADD COLUMN IF NOT EXISTS verified BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE cron_sync ADD COLUMN IF NOT EXISTS verified_at TIMESTAMPTZ;
+
+-- Existing callbacks must be re-verified; issue each one a token
+UPDATE cron_sync SET verification_token = replace(gen_random_uuid()::text, '-', '')
+    WHERE verification_token IS NULL;
+
+ALTER TABLE cron_sync ALTER COLUMN verification_token SET NOT NULL;
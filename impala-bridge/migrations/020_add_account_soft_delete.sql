@@ -0,0 +1,4 @@
+-- Soft-delete flag for DELETE /account: deleted rows stay for audit but
+-- are invisible to lookups, updates and authentication.
+ALTER TABLE impala_account
+    ADD COLUMN IF NOT EXISTS is_deleted BOOLEAN NOT NULL DEFAULT FALSE;
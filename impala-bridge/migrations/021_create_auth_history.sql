@@ -0,0 +1,15 @@
+-- Previous password hashes, so PUT /password can refuse a recently used
+-- password.  Trimmed to PASSWORD_HISTORY_SIZE rows per account.
+CREATE TABLE IF NOT EXISTS impala_auth_history (
+    id SERIAL PRIMARY KEY,
+    account_id VARCHAR(255) NOT NULL,
+    password_hash VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_auth_history_account
+        FOREIGN KEY (account_id)
+        REFERENCES impala_account(payala_account_id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_impala_auth_history_account
+    ON impala_auth_history(account_id, created_at DESC);
@@ -0,0 +1,13 @@
+-- Roles granted to an account.  Each role is embedded as a scope in the
+-- tokens issued to the account; the "admin" scope unlocks /admin and the
+-- other admin-only endpoints.
+CREATE TABLE IF NOT EXISTS impala_account_roles (
+    account_id VARCHAR(64) NOT NULL,
+    role VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, role),
+    CONSTRAINT fk_account_roles_account
+        FOREIGN KEY (account_id)
+        REFERENCES impala_account(payala_account_id)
+        ON DELETE CASCADE
+);
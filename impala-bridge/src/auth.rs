@@ -1,15 +1,33 @@
-use crate::constants::{JWT_ISSUER, TOKEN_TYPE_TEMPORAL};
+use crate::constants::{SCOPE_ADMIN, TOKEN_TYPE_TEMPORAL};
 use crate::error::AppError;
-use crate::models::Claims;
+use crate::jwt::JwtKeys;
 use axum::extract::{Extension, FromRequestParts};
 use axum::http::request::Parts;
-use jsonwebtoken::{decode, DecodingKey, Validation};
 use std::sync::Arc;
 
 /// Represents an authenticated user extracted from a valid JWT temporal token.
 #[derive(Debug, Clone)]
 pub struct AuthenticatedUser {
     pub account_id: String,
+    /// Scopes carried by the temporal token.
+    pub scopes: Vec<String>,
+}
+
+impl AuthenticatedUser {
+    /// Verify that the token grants `scope`.
+    /// Returns `Err(AppError::Forbidden)` otherwise.
+    pub fn require_scope(&self, scope: &str) -> Result<(), AppError> {
+        if !self.scopes.iter().any(|s| s == scope) {
+            return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
+}
+
+/// An authenticated user whose token carries the `admin` scope.
+#[derive(Debug, Clone)]
+pub struct AdminUser {
+    pub account_id: String,
 }
 
 /// Verify that the authenticated user owns the specified account.
@@ -28,9 +46,9 @@ where
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        // Extract the JWT secret from extensions
-        let Extension(jwt_secret) =
-            Extension::<Arc<String>>::from_request_parts(parts, state)
+        // Extract the JWT keys from extensions
+        let Extension(jwt_keys) =
+            Extension::<Arc<JwtKeys>>::from_request_parts(parts, state)
                 .await
                 .map_err(|_| {
                     AppError::InternalError("JWT secret not configured".to_string())
@@ -48,16 +66,11 @@ where
             .strip_prefix("Bearer ")
             .ok_or(AppError::Unauthorized)?;
 
-        // Decode and validate the JWT with explicit HS256 and issuer check
-        let mut validation = Validation::new(jsonwebtoken::Algorithm::HS256);
-        validation.set_issuer(&[JWT_ISSUER]);
-
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(jwt_secret.as_bytes()),
-            &validation,
-        )
-        .map_err(|_| AppError::Unauthorized)?;
+        // Decode and validate the JWT (signature, issuer check) against the
+        // primary and any still-valid previous secret
+        let token_data = jwt_keys
+            .decode_claims(token)
+            .map_err(|_| AppError::Unauthorized)?;
 
         // Must be a temporal token
         if token_data.claims.token_type != TOKEN_TYPE_TEMPORAL {
@@ -70,18 +83,36 @@ where
                 .await
                 .map_err(|_| AppError::Unauthorized)?;
 
-        if crate::redis_helpers::is_token_revoked(&redis_pool, &token_data.claims.jti).await? {
+        if crate::redis_helpers::is_token_revoked(&redis_pool, &token_data.claims).await? {
             return Err(AppError::Unauthorized);
         }
 
         Ok(AuthenticatedUser {
             account_id: token_data.claims.sub,
+            scopes: token_data.claims.scopes,
+        })
+    }
+}
+
+impl<S> FromRequestParts<S> for AdminUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = AuthenticatedUser::from_request_parts(parts, state).await?;
+        user.require_scope(SCOPE_ADMIN)?;
+
+        Ok(AdminUser {
+            account_id: user.account_id,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{AppError, AuthenticatedUser};
     use crate::constants::{JWT_ISSUER, REFRESH_TOKEN_TTL_SECS, TEMPORAL_TOKEN_TTL_SECS};
     use crate::models::Claims;
     use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
@@ -98,6 +129,8 @@ mod tests {
             exp: now + TEMPORAL_TOKEN_TTL_SECS,
             jti: uuid::Uuid::new_v4().to_string(),
             iss: JWT_ISSUER.to_string(),
+            family: String::new(),
+            scopes: Vec::new(),
         };
 
         let token = encode(
@@ -133,6 +166,8 @@ mod tests {
             exp: now + REFRESH_TOKEN_TTL_SECS,
             jti: uuid::Uuid::new_v4().to_string(),
             iss: JWT_ISSUER.to_string(),
+            family: String::new(),
+            scopes: Vec::new(),
         };
 
         let token = encode(
@@ -165,6 +200,8 @@ mod tests {
             exp: 1001, // Already expired
             jti: uuid::Uuid::new_v4().to_string(),
             iss: JWT_ISSUER.to_string(),
+            family: String::new(),
+            scopes: Vec::new(),
         };
 
         let token = encode(
@@ -196,6 +233,8 @@ mod tests {
             exp: now + TEMPORAL_TOKEN_TTL_SECS,
             jti: uuid::Uuid::new_v4().to_string(),
             iss: JWT_ISSUER.to_string(),
+            family: String::new(),
+            scopes: Vec::new(),
         };
 
         let token = encode(
@@ -227,6 +266,8 @@ mod tests {
             exp: now + TEMPORAL_TOKEN_TTL_SECS,
             jti: uuid::Uuid::new_v4().to_string(),
             iss: "wrong-issuer".to_string(),
+            family: String::new(),
+            scopes: Vec::new(),
         };
 
         let token = encode(
@@ -247,4 +288,77 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    fn user_with_scopes(scopes: &[&str]) -> AuthenticatedUser {
+        AuthenticatedUser {
+            account_id: "ops".to_string(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_require_scope_allows_granted_scope() {
+        let user = user_with_scopes(&["support", "admin"]);
+        assert!(user.require_scope("admin").is_ok());
+        assert!(user.require_scope("support").is_ok());
+    }
+
+    #[test]
+    fn test_require_scope_rejects_missing_scope() {
+        let err = user_with_scopes(&["support"]).require_scope("admin").unwrap_err();
+        assert!(matches!(err, AppError::Forbidden));
+        assert!(user_with_scopes(&[]).require_scope("admin").is_err());
+        // Scopes match exactly, not by prefix
+        assert!(user_with_scopes(&["administrator"]).require_scope("admin").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL for the revocation check"]
+    async fn test_admin_endpoint_requires_admin_scope() {
+        use super::AdminUser;
+        use axum::body::Body;
+        use axum::http::{header, Request, StatusCode};
+        use axum::routing::get;
+        use axum::{Extension, Router};
+        use std::sync::Arc;
+        use tower::ServiceExt;
+
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let redis_pool = deadpool_redis::Config::from_url(url)
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        redis_pool.get().await.expect("Redis not reachable");
+        let app = Router::new()
+            .route("/admin-only", get(|admin: AdminUser| async move { admin.account_id }))
+            .route("/whoami", get(|user: AuthenticatedUser| async move { user.account_id }))
+            .layer(Extension(Arc::new(redis_pool)))
+            .layer(Extension(Arc::new(crate::jwt::JwtKeys::new(TEST_SECRET.to_string(), Vec::new()))));
+        let key = crate::jwt::SigningKey::hmac(TEST_SECRET.as_bytes());
+        let status = |path: &'static str, scopes: &[&str]| {
+            let scopes: Vec<String> = scopes.iter().map(|s| s.to_string()).collect();
+            let (_, temporal) = crate::jwt::encode_token_pair(&key, "ops", &scopes).unwrap();
+            let app = app.clone();
+            async move {
+                let request = Request::get(path)
+                    .header(header::AUTHORIZATION, format!("Bearer {temporal}"))
+                    .body(Body::empty())
+                    .unwrap();
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(status("/admin-only", &["admin"]).await, StatusCode::OK);
+        assert_eq!(status("/admin-only", &["support", "admin"]).await, StatusCode::OK);
+        assert_eq!(status("/admin-only", &[]).await, StatusCode::FORBIDDEN);
+        assert_eq!(status("/admin-only", &["support"]).await, StatusCode::FORBIDDEN);
+        // Endpoints that need no scope accept any valid token
+        assert_eq!(status("/whoami", &[]).await, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_claims_without_scopes_decode_as_empty() {
+        let json = r#"{"sub":"ops","token_type":"temporal","exp":1,"iat":0,"jti":"j","iss":"impala-bridge"}"#;
+        let claims: Claims = serde_json::from_str(json).unwrap();
+        assert!(claims.scopes.is_empty());
+    }
 }
@@ -0,0 +1,175 @@
+//! Typed JSON values in Redis.
+//!
+//! Keys are namespaced with the configured `REDIS_KEY_PREFIX` and values are
+//! stored as `serde_json` strings, so callers deal only in their own types.
+
+use redis::aio::ConnectionLike;
+use redis::AsyncCommands;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Errors from [`redis_get_json`] / [`redis_set_json`].
+#[derive(Debug)]
+pub enum CacheError {
+    Redis(redis::RedisError),
+    /// The value could not be serialized for storage.
+    Encode(serde_json::Error),
+    /// The stored value is not valid JSON for the requested type.
+    Decode(serde_json::Error),
+}
+
+impl std::fmt::Display for CacheError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheError::Redis(e) => write!(f, "Redis error: {}", e),
+            CacheError::Encode(e) => write!(f, "failed to encode cached value: {}", e),
+            CacheError::Decode(e) => write!(f, "failed to decode cached value: {}", e),
+        }
+    }
+}
+
+impl From<redis::RedisError> for CacheError {
+    fn from(e: redis::RedisError) -> Self {
+        CacheError::Redis(e)
+    }
+}
+
+/// Apply the configured key prefix.
+pub fn cache_key(prefix: &str, key: &str) -> String {
+    format!("{prefix}{key}")
+}
+
+/// Read and deserialize the JSON value at `key`; `Ok(None)` when absent.
+pub async fn redis_get_json<T, C>(conn: &mut C, prefix: &str, key: &str) -> Result<Option<T>, CacheError>
+where
+    T: DeserializeOwned,
+    C: ConnectionLike + Send + Sync,
+{
+    let raw: Option<String> = conn.get(cache_key(prefix, key)).await?;
+    raw.map(|s| serde_json::from_str(&s).map_err(CacheError::Decode))
+        .transpose()
+}
+
+/// Serialize `value` as JSON and store it at `key`, expiring after
+/// `ttl_secs` when given.
+pub async fn redis_set_json<T, C>(
+    conn: &mut C,
+    prefix: &str,
+    key: &str,
+    value: &T,
+    ttl_secs: Option<u64>,
+) -> Result<(), CacheError>
+where
+    T: Serialize + ?Sized,
+    C: ConnectionLike + Send + Sync,
+{
+    let raw = serde_json::to_string(value).map_err(CacheError::Encode)?;
+    let key = cache_key(prefix, key);
+    match ttl_secs {
+        Some(ttl) => conn.set_ex::<_, _, ()>(key, raw, ttl).await?,
+        None => conn.set::<_, _, ()>(key, raw).await?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use redis::{Cmd, Pipeline, RedisFuture, Value};
+    use std::collections::HashMap;
+
+    /// In-memory stand-in for a Redis connection supporting GET, SET and
+    /// SETEX.  Expiries are recorded, not enforced.
+    #[derive(Default)]
+    pub(crate) struct MockRedis {
+        pub values: HashMap<String, Vec<u8>>,
+        pub ttls: HashMap<String, u64>,
+    }
+
+    impl MockRedis {
+        fn execute(&mut self, cmd: &Cmd) -> Value {
+            let args: Vec<Vec<u8>> = cmd
+                .args_iter()
+                .filter_map(|a| match a {
+                    redis::Arg::Simple(bytes) => Some(bytes.to_vec()),
+                    _ => None,
+                })
+                .collect();
+            let text = |i: usize| String::from_utf8_lossy(&args[i]).to_string();
+            match text(0).to_uppercase().as_str() {
+                "GET" => match self.values.get(&text(1)) {
+                    Some(v) => Value::BulkString(v.clone()),
+                    None => Value::Nil,
+                },
+                "SET" => {
+                    self.values.insert(text(1), args[2].clone());
+                    self.ttls.remove(&text(1));
+                    Value::Okay
+                }
+                "SETEX" => {
+                    self.values.insert(text(1), args[3].clone());
+                    self.ttls.insert(text(1), text(2).parse().unwrap());
+                    Value::Okay
+                }
+                other => panic!("MockRedis: unsupported command {}", other),
+            }
+        }
+    }
+
+    impl ConnectionLike for MockRedis {
+        fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+            let value = self.execute(cmd);
+            Box::pin(async move { Ok(value) })
+        }
+
+        fn req_packed_commands<'a>(
+            &'a mut self,
+            pipeline: &'a Pipeline,
+            offset: usize,
+            count: usize,
+        ) -> RedisFuture<'a, Vec<Value>> {
+            let values: Vec<Value> = pipeline.cmd_iter().map(|c| self.execute(c)).collect();
+            Box::pin(async move { Ok(values.into_iter().skip(offset).take(count).collect()) })
+        }
+
+        fn get_db(&self) -> i64 {
+            0
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+    struct Sample {
+        name: String,
+        count: u32,
+    }
+
+    #[tokio::test]
+    async fn test_json_roundtrip_with_prefix_and_ttl() {
+        let mut conn = MockRedis::default();
+        let value = Sample { name: "ledger".to_string(), count: 3 };
+        redis_set_json(&mut conn, "impala:", "sample", &value, Some(60)).await.unwrap();
+
+        assert!(conn.values.contains_key("impala:sample"));
+        assert_eq!(conn.ttls.get("impala:sample"), Some(&60));
+        let read: Option<Sample> = redis_get_json(&mut conn, "impala:", "sample").await.unwrap();
+        assert_eq!(read, Some(value));
+    }
+
+    #[tokio::test]
+    async fn test_get_missing_key_is_none() {
+        let mut conn = MockRedis::default();
+        let read: Option<Sample> = redis_get_json(&mut conn, "", "absent").await.unwrap();
+        assert_eq!(read, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_wrong_shape_is_decode_error() {
+        let mut conn = MockRedis::default();
+        redis_set_json(&mut conn, "", "k", &serde_json::json!({"other": true}), None)
+            .await
+            .unwrap();
+        assert!(conn.ttls.is_empty());
+        let err = redis_get_json::<Sample, _>(&mut conn, "", "k").await.unwrap_err();
+        assert!(matches!(err, CacheError::Decode(_)));
+    }
+}
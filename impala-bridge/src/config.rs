@@ -1,8 +1,13 @@
 use crate::constants::*;
+use crate::jwt::PreviousJwtSecret;
+use crate::notifications::NotificationTemplates;
+use serde::{Serialize, Serializer};
 use std::env;
 use std::fs;
+use std::net::IpAddr;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum StellarNetwork {
     Testnet,
     Pubnet,
@@ -31,35 +36,139 @@ pub struct StellarConfig {
     pub rpc_url: String,
     pub network_passphrase: String,
     pub contract_id: Option<String>,
+    /// Refuse to stream from a Horizon below this protocol version.
+    pub min_protocol_version: Option<u32>,
 }
 
-#[derive(Debug, Clone)]
+/// Length and complexity rules for new passwords, enforced by
+/// `validate::validate_password`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PasswordPolicy {
+    /// Never below `MIN_PASSWORD_LENGTH`.
+    pub min_length: usize,
+    pub require_digit: bool,
+    pub require_upper: bool,
+    pub require_symbol: bool,
+    /// Previous passwords a change may not reuse; 0 keeps no history.
+    pub history_size: usize,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            min_length: MIN_PASSWORD_LENGTH,
+            require_digit: false,
+            require_upper: false,
+            require_symbol: false,
+            history_size: DEFAULT_PASSWORD_HISTORY_SIZE,
+        }
+    }
+}
+
+/// Feature flags for optional endpoint groups and background tasks.
+/// Every feature defaults to enabled.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeatureFlags {
+    pub mfa: bool,
+    pub cards: bool,
+    pub subscribe: bool,
+    pub notifications: bool,
+    pub contract: bool,
+    pub ldap: bool,
+    pub cron_sync: bool,
+    /// `POST /authenticate` creates credentials for accounts that have none.
+    pub auto_register: bool,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        FeatureFlags {
+            mfa: true,
+            cards: true,
+            subscribe: true,
+            notifications: true,
+            contract: true,
+            ldap: true,
+            cron_sync: true,
+            auto_register: true,
+        }
+    }
+}
+
+/// Effective service configuration.  Serializes with secret fields replaced
+/// by `"***"` (see `GET /admin/config`).
+#[derive(Debug, Clone, Serialize)]
 pub struct Config {
     pub public_endpoint: String,
     pub service_address: String,
+    /// PEM certificate chain; when set the server listens with TLS.
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    /// Minimum accepted TLS version ("1.2" or "1.3").
+    pub tls_min_version: String,
     pub log_file: String,
     pub debug_mode: bool,
     pub twilio_sid: Option<String>,
+    #[serde(serialize_with = "redact")]
     pub twilio_token: Option<String>,
     pub twilio_number: Option<String>,
     pub ldap_url: Option<String>,
     pub ldap_bind_dn: Option<String>,
+    #[serde(serialize_with = "redact")]
     pub ldap_bind_password: Option<String>,
     pub ldap_base_dn: Option<String>,
     pub ldap_search_filter: Option<String>,
     pub db_max_connections: u32,
+    /// Connection attempts per dependency at startup (at least one).
+    pub startup_retry_attempts: u32,
+    /// Initial backoff between startup attempts, in milliseconds.
+    pub startup_retry_delay_ms: u64,
+    /// Prefix for Redis keys written through the `cache` module.
+    pub redis_key_prefix: String,
     pub cors_allowed_origins: String,
     pub http_client_timeout_secs: u64,
+    /// Longest a request may take before it is answered with 504.
+    pub request_timeout_secs: u64,
+    /// Password hashes run at once per account; extra attempts queue.
+    pub auth_max_concurrent_per_account: usize,
+    /// Pwned Passwords-compatible range API checked by `POST /register`;
+    /// unset disables the breach check.
+    pub password_breach_check_url: Option<String>,
     pub okta_issuer_url: Option<String>,
     pub okta_client_id: Option<String>,
     pub okta_jwks_refresh_secs: u64,
     pub sqs_queue_url: Option<String>,
     pub sns_topic_arn: Option<String>,
     pub worker_concurrency: usize,
+    /// ± jitter on the cron_sync interval, as a percentage (0–100).
+    pub cron_sync_jitter_percent: u64,
+    /// Delay between callbacks within one cron_sync run, in milliseconds.
+    pub cron_sync_stagger_ms: u64,
+    /// Contract build/submit calls allowed per account and contract per window.
+    pub contract_rate_limit_max_requests: u64,
+    pub contract_rate_limit_window_secs: usize,
+    /// Login attempts allowed per client IP per window, across accounts.
+    pub ip_rate_limit_max_requests: u64,
+    pub ip_rate_limit_window_secs: usize,
+    /// Load balancer addresses whose `X-Forwarded-For` entry is taken as
+    /// the client IP.  Requests from any other peer are limited by the
+    /// peer address itself.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Failed `/mfa/verify` attempts per account and MFA type allowed
+    /// within `mfa_lockout_window_secs`.
+    pub mfa_lockout_threshold: u64,
+    pub mfa_lockout_window_secs: usize,
+    /// TOTP steps accepted either side of the current one, at most
+    /// `MAX_TOTP_SKEW_STEPS`.
+    pub mfa_totp_skew_steps: u8,
+    /// Maximum bridge/ledger clock difference accepted when scheduling a
+    /// timelock by `unlock_at`.
+    pub ledger_clock_tolerance_secs: u64,
     pub sqs_wait_time_seconds: i32,
     pub sqs_visibility_timeout: i32,
     pub ses_from_address: Option<String>,
     pub fcm_project_id: Option<String>,
+    #[serde(serialize_with = "redact")]
     pub fcm_service_account_key: Option<String>,
     pub otel_exporter_endpoint: Option<String>,
     pub otel_service_name: Option<String>,
@@ -69,6 +178,45 @@ pub struct Config {
     pub stellar_rpc_url: String,
     pub stellar_network_passphrase: String,
     pub soroban_contract_id: Option<String>,
+    /// Minimum Horizon `current_protocol_version` accepted by `/subscribe`.
+    pub stellar_min_protocol_version: Option<u32>,
+    /// Number of raw Horizon ledger payloads kept for `/index/reprocess`
+    /// (0 disables raw storage).
+    pub stellar_raw_event_retention: usize,
+    pub admin_accounts: Vec<String>,
+    /// Log 1 in this many successful requests; errors and slow requests
+    /// are always logged.  0 disables logging of successful requests.
+    pub request_log_sample_rate: u64,
+    /// Latency (ms) above which a request is always logged.
+    pub request_log_slow_ms: u64,
+    /// Wrap every JSON response in `{ok, data, error}`.
+    pub response_envelope: bool,
+    /// Map `success: false` business failures to 4xx statuses.
+    pub business_failure_status: bool,
+    /// Keep maintenance mode on regardless of `PUT /admin/maintenance`.
+    pub maintenance_mode: bool,
+    /// Retired JWT secrets still accepted for verification until their
+    /// `valid_until`; new tokens are always signed with `JWT_SECRET`.
+    pub jwt_previous_secrets: Vec<PreviousJwtSecret>,
+    /// JWT signing algorithm: "HS256" (shared `JWT_SECRET`) or "RS256".
+    pub jwt_algorithm: String,
+    /// PEM RSA private key used to sign tokens when `jwt_algorithm` is RS256.
+    pub jwt_private_key_path: Option<String>,
+    /// PEM RSA public key used to verify tokens when `jwt_algorithm` is RS256.
+    pub jwt_public_key_path: Option<String>,
+    /// Clock skew tolerated when checking `exp`/`nbf`, for local and Okta tokens.
+    pub jwt_leeway_secs: u64,
+    pub features: FeatureFlags,
+    pub password_policy: PasswordPolicy,
+    pub notification_templates: NotificationTemplates,
+}
+
+/// Serialize a secret as `"***"` when set and `null` when unset.
+fn redact<S: Serializer>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(_) => serializer.serialize_str(REDACTED),
+        None => serializer.serialize_none(),
+    }
 }
 
 /// Load configuration from a JSON config file (if present) and environment variables.
@@ -99,6 +247,21 @@ pub fn load_config() -> Config {
         .or_else(|| from_file("service_address"))
         .unwrap_or_else(|| "0.0.0.0:8080".to_string());
 
+    let tls_cert_path = env::var("TLS_CERT_PATH")
+        .ok()
+        .or_else(|| from_file("tls_cert_path"))
+        .filter(|s| !s.is_empty());
+
+    let tls_key_path = env::var("TLS_KEY_PATH")
+        .ok()
+        .or_else(|| from_file("tls_key_path"))
+        .filter(|s| !s.is_empty());
+
+    let tls_min_version = env::var("TLS_MIN_VERSION")
+        .ok()
+        .or_else(|| from_file("tls_min_version"))
+        .unwrap_or_else(|| DEFAULT_TLS_MIN_VERSION.to_string());
+
     let log_file = env::var("LOG_FILE")
         .ok()
         .or_else(|| from_file("log_file"))
@@ -148,6 +311,19 @@ pub fn load_config() -> Config {
         .and_then(|v| v.parse().ok())
         .unwrap_or(DEFAULT_DB_MAX_CONNECTIONS);
 
+    let startup_retry_attempts = env::var("STARTUP_RETRY_ATTEMPTS")
+        .ok()
+        .or_else(|| from_file("startup_retry_attempts"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_STARTUP_RETRY_ATTEMPTS)
+        .max(1);
+
+    let startup_retry_delay_ms = env::var("STARTUP_RETRY_DELAY_MS")
+        .ok()
+        .or_else(|| from_file("startup_retry_delay_ms"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_STARTUP_RETRY_DELAY_MS);
+
     let cors_allowed_origins = env::var("CORS_ALLOWED_ORIGINS")
         .ok()
         .or_else(|| from_file("cors_allowed_origins"))
@@ -159,6 +335,24 @@ pub fn load_config() -> Config {
         .and_then(|v| v.parse().ok())
         .unwrap_or(crate::constants::DEFAULT_HTTP_CLIENT_TIMEOUT_SECS);
 
+    let request_timeout_secs = env::var("REQUEST_TIMEOUT_SECS")
+        .ok()
+        .or_else(|| from_file("request_timeout_secs"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(crate::constants::DEFAULT_REQUEST_TIMEOUT_SECS)
+        .max(1);
+
+    let auth_max_concurrent_per_account = env::var("AUTH_MAX_CONCURRENT_PER_ACCOUNT")
+        .ok()
+        .or_else(|| from_file("auth_max_concurrent_per_account"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_AUTH_MAX_CONCURRENT_PER_ACCOUNT)
+        .max(1);
+
+    let password_breach_check_url = env::var("PASSWORD_BREACH_CHECK_URL")
+        .ok()
+        .or_else(|| from_file("password_breach_check_url"));
+
     let okta_issuer_url = env::var("OKTA_ISSUER_URL")
         .ok()
         .or_else(|| from_file("okta_issuer_url"));
@@ -187,6 +381,82 @@ pub fn load_config() -> Config {
         .and_then(|v| v.parse().ok())
         .unwrap_or(crate::constants::DEFAULT_WORKER_CONCURRENCY);
 
+    let cron_sync_jitter_percent = env::var("CRON_SYNC_JITTER_PERCENT")
+        .ok()
+        .or_else(|| from_file("cron_sync_jitter_percent"))
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_CRON_SYNC_JITTER_PERCENT)
+        .min(100);
+
+    let cron_sync_stagger_ms = env::var("CRON_SYNC_STAGGER_MS")
+        .ok()
+        .or_else(|| from_file("cron_sync_stagger_ms"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_CRON_SYNC_STAGGER_MS);
+
+    let contract_rate_limit_max_requests = env::var("CONTRACT_RATE_LIMIT_MAX_REQUESTS")
+        .ok()
+        .or_else(|| from_file("contract_rate_limit_max_requests"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_CONTRACT_RATE_LIMIT_MAX_REQUESTS);
+
+    let contract_rate_limit_window_secs = env::var("CONTRACT_RATE_LIMIT_WINDOW_SECS")
+        .ok()
+        .or_else(|| from_file("contract_rate_limit_window_secs"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_CONTRACT_RATE_LIMIT_WINDOW_SECS);
+
+    let ip_rate_limit_max_requests = env::var("IP_RATE_LIMIT_MAX_REQUESTS")
+        .ok()
+        .or_else(|| from_file("ip_rate_limit_max_requests"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_IP_RATE_LIMIT_MAX_REQUESTS);
+
+    let ip_rate_limit_window_secs = env::var("IP_RATE_LIMIT_WINDOW_SECS")
+        .ok()
+        .or_else(|| from_file("ip_rate_limit_window_secs"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_IP_RATE_LIMIT_WINDOW_SECS);
+
+    let trusted_proxies = env::var("TRUSTED_PROXIES")
+        .ok()
+        .or_else(|| from_file("trusted_proxies"))
+        .map(|v| parse_ip_list(&v))
+        .unwrap_or_default();
+
+    let mfa_lockout_threshold = env::var("MFA_LOCKOUT_THRESHOLD")
+        .ok()
+        .or_else(|| from_file("mfa_lockout_threshold"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MFA_LOCKOUT_THRESHOLD);
+
+    let mfa_lockout_window_secs = env::var("MFA_LOCKOUT_WINDOW_SECS")
+        .ok()
+        .or_else(|| from_file("mfa_lockout_window_secs"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MFA_LOCKOUT_WINDOW_SECS);
+
+    let mfa_totp_skew_steps = env::var("MFA_TOTP_SKEW_STEPS")
+        .ok()
+        .or_else(|| from_file("mfa_totp_skew_steps"))
+        .and_then(|v| v.parse::<u8>().ok())
+        .map(|steps| {
+            if steps > MAX_TOTP_SKEW_STEPS {
+                eprintln!(
+                    "Warning: MFA_TOTP_SKEW_STEPS {} exceeds {}, using {}",
+                    steps, MAX_TOTP_SKEW_STEPS, MAX_TOTP_SKEW_STEPS
+                );
+            }
+            steps.min(MAX_TOTP_SKEW_STEPS)
+        })
+        .unwrap_or(DEFAULT_TOTP_SKEW_STEPS);
+
+    let ledger_clock_tolerance_secs = env::var("LEDGER_CLOCK_TOLERANCE_SECS")
+        .ok()
+        .or_else(|| from_file("ledger_clock_tolerance_secs"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_LEDGER_CLOCK_TOLERANCE_SECS);
+
     let sqs_wait_time_seconds = env::var("SQS_WAIT_TIME_SECONDS")
         .ok()
         .or_else(|| from_file("sqs_wait_time_seconds"))
@@ -261,9 +531,182 @@ pub fn load_config() -> Config {
         .ok()
         .or_else(|| from_file("soroban_contract_id"));
 
+    let redis_key_prefix = env::var("REDIS_KEY_PREFIX")
+        .ok()
+        .or_else(|| from_file("redis_key_prefix"))
+        .unwrap_or_default();
+
+    let stellar_min_protocol_version = env::var("STELLAR_MIN_PROTOCOL_VERSION")
+        .ok()
+        .or_else(|| from_file("stellar_min_protocol_version"))
+        .and_then(|v| v.parse().ok());
+
+    let stellar_raw_event_retention = env::var("STELLAR_RAW_EVENT_RETENTION")
+        .ok()
+        .or_else(|| from_file("stellar_raw_event_retention"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    let admin_accounts = env::var("ADMIN_ACCOUNTS")
+        .ok()
+        .or_else(|| from_file("admin_accounts"))
+        .map(|v| {
+            v.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let request_log_sample_rate = env::var("REQUEST_LOG_SAMPLE_RATE")
+        .ok()
+        .or_else(|| from_file("request_log_sample_rate"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_REQUEST_LOG_SAMPLE_RATE);
+
+    let request_log_slow_ms = env::var("REQUEST_LOG_SLOW_MS")
+        .ok()
+        .or_else(|| from_file("request_log_slow_ms"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_REQUEST_LOG_SLOW_MS);
+
+    let response_envelope = env::var("RESPONSE_ENVELOPE")
+        .ok()
+        .or_else(|| from_file("response_envelope"))
+        .map(|v| parse_flag(&v))
+        .unwrap_or(false);
+
+    let maintenance_mode = env::var("MAINTENANCE_MODE")
+        .ok()
+        .or_else(|| from_file("maintenance_mode"))
+        .map(|v| parse_flag(&v))
+        .unwrap_or(false);
+
+    let business_failure_status = env::var("BUSINESS_FAILURE_STATUS")
+        .ok()
+        .or_else(|| from_file("business_failure_status"))
+        .map(|v| parse_flag(&v))
+        .unwrap_or(false);
+
+    // Previous JWT secrets: JWT_PREVIOUS_SECRETS as a JSON array of
+    // `{"secret", "valid_until"}` objects, else `jwt_previous_secrets` in the
+    // config file.
+    let jwt_previous_secrets = env::var("JWT_PREVIOUS_SECRETS")
+        .ok()
+        .and_then(|v| {
+            serde_json::from_str(&v)
+                .map_err(|e| eprintln!("Warning: invalid JWT_PREVIOUS_SECRETS: {}", e))
+                .ok()
+        })
+        .or_else(|| file_values.get("jwt_previous_secrets").cloned())
+        .and_then(|v| {
+            serde_json::from_value(v)
+                .map_err(|e| eprintln!("Warning: invalid jwt_previous_secrets: {}", e))
+                .ok()
+        })
+        .unwrap_or_default();
+
+    let jwt_algorithm = env::var("JWT_ALGORITHM")
+        .ok()
+        .or_else(|| from_file("jwt_algorithm"))
+        .unwrap_or_else(|| DEFAULT_JWT_ALGORITHM.to_string());
+
+    let jwt_private_key_path = env::var("JWT_PRIVATE_KEY_PATH")
+        .ok()
+        .or_else(|| from_file("jwt_private_key_path"))
+        .filter(|s| !s.is_empty());
+
+    let jwt_public_key_path = env::var("JWT_PUBLIC_KEY_PATH")
+        .ok()
+        .or_else(|| from_file("jwt_public_key_path"))
+        .filter(|s| !s.is_empty());
+
+    let jwt_leeway_secs = env::var("JWT_LEEWAY_SECS")
+        .ok()
+        .or_else(|| from_file("jwt_leeway_secs"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_JWT_LEEWAY_SECS);
+
+    // Feature flags: FEATURE_<NAME> env var, then `features.<name>` in the
+    // config file (bool or "true"/"false"), defaulting to enabled.
+    let feature = |name: &str| -> bool {
+        env::var(format!("FEATURE_{}", name.to_uppercase()))
+            .ok()
+            .or_else(|| {
+                file_values.get("features").and_then(|f| f.get(name)).map(|v| match v {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+            })
+            .map(|v| parse_flag(&v))
+            .unwrap_or(true)
+    };
+    let features = FeatureFlags {
+        mfa: feature("mfa"),
+        cards: feature("cards"),
+        subscribe: feature("subscribe"),
+        notifications: feature("notifications"),
+        contract: feature("contract"),
+        ldap: feature("ldap"),
+        cron_sync: feature("cron_sync"),
+        auto_register: feature("auto_register"),
+    };
+
+    // Password policy: PASSWORD_<RULE> env var, then `password_<rule>` in the
+    // config file
+    let password_rule = |name: &str| -> Option<bool> {
+        env::var(format!("PASSWORD_{}", name.to_uppercase()))
+            .ok()
+            .or_else(|| from_file(&format!("password_{name}")))
+            .map(|v| parse_flag(&v))
+    };
+    let default_policy = PasswordPolicy::default();
+    let password_policy = PasswordPolicy {
+        min_length: env::var("PASSWORD_MIN_LENGTH")
+            .ok()
+            .or_else(|| from_file("password_min_length"))
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default_policy.min_length)
+            .max(MIN_PASSWORD_LENGTH),
+        require_digit: password_rule("require_digit").unwrap_or(default_policy.require_digit),
+        require_upper: password_rule("require_upper").unwrap_or(default_policy.require_upper),
+        require_symbol: password_rule("require_symbol").unwrap_or(default_policy.require_symbol),
+        history_size: env::var("PASSWORD_HISTORY_SIZE")
+            .ok()
+            .or_else(|| from_file("password_history_size"))
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default_policy.history_size),
+    };
+
+    // Notification templates: a JSON file named by NOTIFICATION_TEMPLATES_FILE
+    // (or `notification_templates_file`), else an inline
+    // `notification_templates` object in the config file.  Logging is not
+    // initialised yet, so problems are reported on stderr.
+    let templates_value = match env::var("NOTIFICATION_TEMPLATES_FILE")
+        .ok()
+        .or_else(|| from_file("notification_templates_file"))
+    {
+        Some(path) => fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+            .map_err(|e| eprintln!("Warning: ignoring notification templates {}: {}", path, e))
+            .ok(),
+        None => file_values.get("notification_templates").cloned(),
+    };
+    let notification_templates = templates_value
+        .and_then(|v| {
+            serde_json::from_value(v)
+                .map_err(|e| eprintln!("Warning: invalid notification templates: {}", e))
+                .ok()
+        })
+        .unwrap_or_default();
+
     Config {
         public_endpoint,
         service_address,
+        tls_cert_path,
+        tls_key_path,
+        tls_min_version,
         log_file,
         debug_mode,
         twilio_sid,
@@ -275,14 +718,31 @@ pub fn load_config() -> Config {
         ldap_base_dn,
         ldap_search_filter,
         db_max_connections,
+        startup_retry_attempts,
+        startup_retry_delay_ms,
+        redis_key_prefix,
         cors_allowed_origins,
         http_client_timeout_secs,
+        request_timeout_secs,
+        auth_max_concurrent_per_account,
+        password_breach_check_url,
         okta_issuer_url,
         okta_client_id,
         okta_jwks_refresh_secs,
         sqs_queue_url,
         sns_topic_arn,
         worker_concurrency,
+        cron_sync_jitter_percent,
+        cron_sync_stagger_ms,
+        contract_rate_limit_max_requests,
+        contract_rate_limit_window_secs,
+        ip_rate_limit_max_requests,
+        ip_rate_limit_window_secs,
+        trusted_proxies,
+        mfa_lockout_threshold,
+        mfa_lockout_window_secs,
+        mfa_totp_skew_steps,
+        ledger_clock_tolerance_secs,
         sqs_wait_time_seconds,
         sqs_visibility_timeout,
         ses_from_address,
@@ -296,9 +756,39 @@ pub fn load_config() -> Config {
         stellar_rpc_url,
         stellar_network_passphrase,
         soroban_contract_id,
+        stellar_min_protocol_version,
+        stellar_raw_event_retention,
+        admin_accounts,
+        request_log_sample_rate,
+        request_log_slow_ms,
+        response_envelope,
+        business_failure_status,
+        maintenance_mode,
+        jwt_previous_secrets,
+        jwt_algorithm,
+        jwt_private_key_path,
+        jwt_public_key_path,
+        jwt_leeway_secs,
+        features,
+        password_policy,
+        notification_templates,
     }
 }
 
+/// Interpret a boolean-ish config value ("true"/"1"/"yes"/"on").
+fn parse_flag(value: &str) -> bool {
+    matches!(value.trim().to_lowercase().as_str(), "true" | "1" | "yes" | "on")
+}
+
+/// Parse a comma-separated list of IP addresses, dropping blank or
+/// malformed entries.
+fn parse_ip_list(value: &str) -> Vec<IpAddr> {
+    value
+        .split(',')
+        .filter_map(|s| s.trim().parse().ok())
+        .collect()
+}
+
 impl Config {
     pub fn stellar_config(&self) -> StellarConfig {
         StellarConfig {
@@ -307,6 +797,75 @@ impl Config {
             rpc_url: self.stellar_rpc_url.clone(),
             network_passphrase: self.stellar_network_passphrase.clone(),
             contract_id: self.soroban_contract_id.clone(),
+            min_protocol_version: self.stellar_min_protocol_version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialized_config_redacts_secrets() {
+        let mut config = load_config();
+        config.twilio_token = Some("twilio-secret-token".to_string());
+        config.ldap_bind_password = Some("ldap-secret-password".to_string());
+        config.fcm_service_account_key = Some("{\"private_key\":\"fcm-secret\"}".to_string());
+        config.twilio_sid = Some("AC123".to_string());
+        config.jwt_previous_secrets = vec![PreviousJwtSecret {
+            secret: "previous-jwt-secret".to_string(),
+            valid_until: 1_700_000_000,
+        }];
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["twilio_token"], REDACTED);
+        assert_eq!(json["jwt_previous_secrets"][0]["secret"], REDACTED);
+        assert_eq!(json["jwt_previous_secrets"][0]["valid_until"], 1_700_000_000);
+        assert_eq!(json["ldap_bind_password"], REDACTED);
+        assert_eq!(json["fcm_service_account_key"], REDACTED);
+
+        let text = json.to_string();
+        for secret in ["twilio-secret-token", "ldap-secret-password", "fcm-secret", "previous-jwt-secret"] {
+            assert!(!text.contains(secret), "secret leaked: {}", secret);
         }
     }
+
+    #[test]
+    fn test_serialized_config_keeps_non_secret_fields() {
+        let mut config = load_config();
+        config.twilio_sid = Some("AC123".to_string());
+        config.service_address = "127.0.0.1:9999".to_string();
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["twilio_sid"], "AC123");
+        assert_eq!(json["service_address"], "127.0.0.1:9999");
+        assert_eq!(json["stellar_network"], config.stellar_network.as_str());
+        assert!(json.get("db_max_connections").is_some());
+    }
+
+    #[test]
+    fn test_parse_flag() {
+        for v in ["true", "1", "yes", "ON", " True "] {
+            assert!(parse_flag(v), "{}", v);
+        }
+        for v in ["false", "0", "no", "off", ""] {
+            assert!(!parse_flag(v), "{}", v);
+        }
+    }
+
+    #[test]
+    fn test_parse_ip_list() {
+        let ips = parse_ip_list(" 10.0.0.5, ,not-an-ip,::1 ");
+        assert_eq!(ips, vec!["10.0.0.5".parse::<IpAddr>().unwrap(), "::1".parse().unwrap()]);
+        assert!(parse_ip_list("").is_empty());
+    }
+
+    #[test]
+    fn test_unset_secret_serializes_as_null() {
+        let mut config = load_config();
+        config.twilio_token = None;
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json["twilio_token"].is_null());
+    }
 }
@@ -1,6 +1,12 @@
 /// Minimum password length for authentication.
 pub const MIN_PASSWORD_LENGTH: usize = 8;
 
+/// Maximum password length accepted at registration; bounds Argon2 work.
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// Default number of previous passwords `PUT /password` refuses to reuse.
+pub const DEFAULT_PASSWORD_HISTORY_SIZE: usize = 5;
+
 /// Maximum length for name fields (first_name, last_name, etc.).
 pub const MAX_NAME_LENGTH: usize = 64;
 
@@ -13,6 +19,16 @@ pub const TEMPORAL_TOKEN_TTL_SECS: usize = 3600;
 /// Default database connection pool size.
 pub const DEFAULT_DB_MAX_CONNECTIONS: u32 = 20;
 
+/// Attempts made to reach Postgres and Redis at startup before giving up.
+pub const DEFAULT_STARTUP_RETRY_ATTEMPTS: u32 = 10;
+
+/// Delay before the first startup connection retry, in milliseconds; doubles
+/// after each failure.
+pub const DEFAULT_STARTUP_RETRY_DELAY_MS: u64 = 1000;
+
+/// Upper bound on the delay between startup connection retries.
+pub const STARTUP_RETRY_MAX_DELAY_SECS: u64 = 30;
+
 /// Database pool: acquire timeout in seconds.
 pub const DB_ACQUIRE_TIMEOUT_SECS: u64 = 5;
 
@@ -25,8 +41,8 @@ pub const DB_MAX_LIFETIME_SECS: u64 = 1800;
 /// Default Redis connection pool size.
 pub const DEFAULT_REDIS_POOL_SIZE: usize = 16;
 
-/// Request timeout in seconds (applied globally via middleware).
-pub const REQUEST_TIMEOUT_SECS: u64 = 30;
+/// Default request timeout in seconds (applied globally via middleware).
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
 
 /// Rate limit: maximum requests per window.
 pub const RATE_LIMIT_MAX_REQUESTS: u64 = 10;
@@ -34,21 +50,123 @@ pub const RATE_LIMIT_MAX_REQUESTS: u64 = 10;
 /// Rate limit: window duration in seconds.
 pub const RATE_LIMIT_WINDOW_SECS: usize = 60;
 
+/// Default per-IP limit on login attempts (`/authenticate`, `/token`),
+/// across all accounts.
+pub const DEFAULT_IP_RATE_LIMIT_MAX_REQUESTS: u64 = 30;
+
+/// Default window for the per-IP login limit, in seconds.
+pub const DEFAULT_IP_RATE_LIMIT_WINDOW_SECS: usize = 60;
+
+/// Default rate limit for contract build/submit calls per account and contract.
+pub const DEFAULT_CONTRACT_RATE_LIMIT_MAX_REQUESTS: u64 = 5;
+
+/// Default window for the contract operation rate limit, in seconds.
+pub const DEFAULT_CONTRACT_RATE_LIMIT_WINDOW_SECS: usize = 300;
+
+/// Default TOTP clock-drift tolerance, in 30-second steps either side of
+/// the current one.
+pub const DEFAULT_TOTP_SKEW_STEPS: u8 = 1;
+
+/// Largest accepted TOTP skew; wider windows would accept stale codes.
+pub const MAX_TOTP_SKEW_STEPS: u8 = 2;
+
+/// How long a TOTP secret from `POST /mfa` waits for `POST /mfa/confirm`,
+/// in seconds.
+pub const MFA_PENDING_TOTP_TTL_SECS: u64 = 600;
+
+/// Lifetime of an SMS MFA code sent by `POST /mfa/send`, in seconds.
+pub const MFA_SMS_CODE_TTL_SECS: u64 = 300;
+
+/// Maximum `POST /mfa/send` requests per account per window.
+pub const MFA_SMS_SEND_MAX_REQUESTS: u64 = 3;
+
+/// Window for the `POST /mfa/send` rate limit, in seconds.
+pub const MFA_SMS_SEND_WINDOW_SECS: usize = 600;
+
+/// Maximum `POST /mfa/send` requests per client IP per window, across
+/// accounts.
+pub const MFA_SMS_SEND_IP_MAX_REQUESTS: u64 = 10;
+
+/// Window for the per-IP `POST /mfa/send` rate limit, in seconds.
+pub const MFA_SMS_SEND_IP_WINDOW_SECS: usize = 600;
+
+/// How often each instance re-reads the maintenance flag from Redis.
+pub const MAINTENANCE_REFRESH_SECS: u64 = 5;
+
+/// Body message for requests refused while maintenance mode is on.
+pub const MAINTENANCE_MESSAGE: &str = "Service is temporarily down for maintenance";
+
+/// Password hash/verify operations allowed to run at once for one account
+/// (`/authenticate`, `/register`); further attempts wait their turn.
+pub const DEFAULT_AUTH_MAX_CONCURRENT_PER_ACCOUNT: usize = 1;
+
 /// Account lockout: number of failed login attempts before lockout.
 pub const LOCKOUT_THRESHOLD: u64 = 5;
 
 /// Account lockout: duration in seconds (15 minutes).
 pub const LOCKOUT_DURATION_SECS: usize = 15 * 60;
 
+/// Default number of failed `/mfa/verify` attempts per account and MFA type
+/// before further attempts are refused.
+pub const DEFAULT_MFA_LOCKOUT_THRESHOLD: u64 = 5;
+
+/// Default window, in seconds, over which failed MFA attempts are counted
+/// (and how long the lockout lasts once the threshold is hit).
+pub const DEFAULT_MFA_LOCKOUT_WINDOW_SECS: usize = 15 * 60;
+
 /// Maximum Stellar account ID length.
 pub const STELLAR_ACCOUNT_ID_LENGTH: usize = 56;
 
 /// Maximum SSE buffer size in bytes (1 MB).
 pub const MAX_SSE_BUFFER_SIZE: usize = 1_048_576;
 
+/// Redis sorted set of raw Stellar ledger payloads, scored by ledger sequence.
+pub const STELLAR_RAW_EVENTS_KEY: &str = "stellar:raw_events";
+
+/// Idle expiry for the raw ledger payload set (7 days).
+pub const STELLAR_RAW_EVENTS_TTL_SECS: i64 = 604_800;
+
 /// Cron sync polling interval in seconds.
 pub const CRON_SYNC_INTERVAL_SECS: u64 = 60;
 
+/// Default ± jitter applied to the cron sync interval, as a percentage.
+pub const DEFAULT_CRON_SYNC_JITTER_PERCENT: u64 = 10;
+
+/// Default delay between callbacks within one cron sync run (0 = no stagger).
+pub const DEFAULT_CRON_SYNC_STAGGER_MS: u64 = 0;
+
+/// Maximum time to wait for open Payala connections to drain on unsubscribe.
+pub const PAYALA_DRAIN_TIMEOUT_SECS: u64 = 5;
+
+/// Upper bound on how long `/contract/submit` may wait for confirmation.
+pub const MAX_SUBMIT_WAIT_SECS: u64 = 30;
+
+/// Time `/contract/submit` keeps back from `REQUEST_TIMEOUT_SECS` to answer
+/// after it stops waiting, so the hash is returned instead of a 504.
+pub const SUBMIT_RESPONSE_MARGIN_SECS: u64 = 2;
+
+/// Interval between `getTransaction` polls while waiting for confirmation.
+pub const TX_POLL_INTERVAL_MS: u64 = 1000;
+
+/// Log 1 in this many successful, fast requests (0 logs none, 1 logs all).
+pub const DEFAULT_REQUEST_LOG_SAMPLE_RATE: u64 = 1;
+
+/// Requests slower than this many milliseconds are always logged.
+pub const DEFAULT_REQUEST_LOG_SLOW_MS: u64 = 1000;
+
+/// Distinct LDAP filters whose results `directory_sync` keeps during one run.
+pub const LDAP_SEARCH_CACHE_CAPACITY: usize = 1024;
+
+/// Per-dependency timeout for `--check` probes.
+pub const PREFLIGHT_PROBE_TIMEOUT_SECS: u64 = 5;
+
+/// Default allowed difference between the bridge clock and the ledger close
+/// time when converting `unlock_at` into a timelock delay.
+pub const DEFAULT_LEDGER_CLOCK_TOLERANCE_SECS: u64 = 30;
+
+/// Contract functions whose last argument is a `delay_seconds` timelock delay.
+pub const TIMELOCK_SCHEDULE_FUNCTIONS: &[&str] = &["schedule_unwrap", "schedule_transfer"];
+
 /// Default HTTP client timeout in seconds.
 pub const DEFAULT_HTTP_CLIENT_TIMEOUT_SECS: u64 = 30;
 
@@ -76,6 +194,18 @@ pub const JWT_SECRET_MIN_LENGTH: usize = 32;
 /// JWT issuer claim value for locally-issued tokens.
 pub const JWT_ISSUER: &str = "impala-bridge";
 
+/// Maximum number of previous JWT secrets kept for verification.
+pub const MAX_PREVIOUS_JWT_SECRETS: usize = 8;
+
+/// Default JWT signing algorithm.
+pub const DEFAULT_JWT_ALGORITHM: &str = "HS256";
+
+/// Default clock-skew leeway, in seconds, when validating JWT `exp`/`nbf`.
+pub const DEFAULT_JWT_LEEWAY_SECS: u64 = 30;
+
+/// Token scope required by admin-only endpoints.
+pub const SCOPE_ADMIN: &str = "admin";
+
 /// Default worker concurrency (max in-flight SQS messages).
 pub const DEFAULT_WORKER_CONCURRENCY: usize = 10;
 
@@ -102,3 +232,24 @@ pub const STELLAR_PUBNET_RPC_URL: &str = "https://soroban-rpc.stellar.org";
 
 /// Stellar public network (mainnet) passphrase.
 pub const STELLAR_PUBNET_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+
+/// Base inclusion fee for submitted transactions, in stroops.
+pub const STELLAR_BASE_FEE_STROOPS: u32 = 100;
+
+/// Placeholder substituted for secret values in operator-facing output.
+pub const REDACTED: &str = "***";
+
+/// `Accept` media type with which a client asks for enveloped responses.
+pub const ENVELOPE_MEDIA_TYPE: &str = "application/vnd.impala.envelope+json";
+
+/// Message returned to a suspended account that presented valid credentials.
+pub const ACCOUNT_SUSPENDED_MESSAGE: &str = "Account suspended";
+
+/// Minimum TLS version accepted when the HTTPS listener is enabled.
+pub const DEFAULT_TLS_MIN_VERSION: &str = "1.2";
+
+/// Seconds in-flight requests are given to finish when a TLS server shuts down.
+pub const TLS_GRACEFUL_SHUTDOWN_SECS: u64 = 30;
+
+/// Path, relative to a callback's origin, that must echo its verification token.
+pub const CALLBACK_VERIFY_PATH: &str = "/.well-known/impala-verify";
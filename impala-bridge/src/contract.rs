@@ -0,0 +1,400 @@
+//! Soroban contract interaction helpers: conversion between the bridge's JSON
+//! argument model and XDR `ScVal`s, and assembly of `InvokeHostFunction`
+//! transactions for the configured contract.
+//!
+//! 64- and 128-bit integers are carried as decimal strings in JSON so values
+//! above 2^53 survive clients that parse numbers as doubles.
+
+use std::str::FromStr;
+
+use stellar_xdr::curr::{
+    Hash, HostFunction, InvokeContractArgs, InvokeHostFunctionOp, Limits, Memo, MuxedAccount,
+    Operation, OperationBody, Preconditions, ReadXdr, ScAddress, ScBytes, ScMap, ScMapEntry,
+    ScString, ScSymbol, ScVal, ScVec, SequenceNumber, SorobanAuthorizationEntry,
+    SorobanTransactionData, Transaction, TransactionEnvelope, TransactionExt,
+    TransactionMeta, TransactionV1Envelope, UInt128Parts, Uint256, VecM, WriteXdr,
+};
+
+use crate::constants::STELLAR_BASE_FEE_STROOPS;
+use crate::error::AppError;
+use crate::models::{ContractMapEntry, ContractValue};
+
+/// Serde adapter that (de)serializes an integer as a decimal string.
+/// Deserialization also accepts a bare JSON number for convenience.
+pub mod int_string {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn serialize<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StringOrNumber {
+            String(String),
+            Number(serde_json::Number),
+        }
+
+        let raw = match StringOrNumber::deserialize(deserializer)? {
+            StringOrNumber::String(s) => s,
+            StringOrNumber::Number(n) => n.to_string(),
+        };
+        raw.trim().parse::<T>().map_err(de::Error::custom)
+    }
+}
+
+impl TryFrom<&ContractValue> for ScVal {
+    type Error = AppError;
+
+    fn try_from(value: &ContractValue) -> Result<Self, AppError> {
+        let invalid = |what: &str, e: String| AppError::BadRequest(format!("Invalid {}: {}", what, e));
+        Ok(match value {
+            ContractValue::Void => ScVal::Void,
+            ContractValue::Bool(b) => ScVal::Bool(*b),
+            ContractValue::U32(n) => ScVal::U32(*n),
+            ContractValue::I32(n) => ScVal::I32(*n),
+            ContractValue::U64(n) => ScVal::U64(*n),
+            ContractValue::I64(n) => ScVal::I64(*n),
+            ContractValue::U128(n) => ScVal::U128(UInt128Parts {
+                hi: (*n >> 64) as u64,
+                lo: *n as u64,
+            }),
+            ContractValue::I128(n) => ScVal::from(*n),
+            ContractValue::Symbol(s) => ScVal::Symbol(ScSymbol(
+                s.as_str().try_into().map_err(|e| invalid("symbol", format!("{:?}", e)))?,
+            )),
+            ContractValue::String(s) => ScVal::String(ScString(
+                s.as_str().try_into().map_err(|e| invalid("string", format!("{:?}", e)))?,
+            )),
+            ContractValue::Address(a) => ScVal::Address(
+                ScAddress::from_str(a).map_err(|e| invalid("address", e.to_string()))?,
+            ),
+            ContractValue::Bytes(hex) => {
+                let bytes = decode_hex(hex).map_err(|e| invalid("bytes", e))?;
+                ScVal::Bytes(ScBytes(
+                    bytes.try_into().map_err(|e| invalid("bytes", format!("{:?}", e)))?,
+                ))
+            }
+            ContractValue::Vec(items) => {
+                let vals = items
+                    .iter()
+                    .map(ScVal::try_from)
+                    .collect::<Result<Vec<_>, _>>()?;
+                ScVal::Vec(Some(ScVec(
+                    vals.try_into().map_err(|e| invalid("vec", format!("{:?}", e)))?,
+                )))
+            }
+            ContractValue::Map(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|e| {
+                        Ok(ScMapEntry {
+                            key: ScVal::try_from(&e.key)?,
+                            val: ScVal::try_from(&e.value)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, AppError>>()?;
+                ScVal::Map(Some(ScMap(
+                    entries.try_into().map_err(|e| invalid("map", format!("{:?}", e)))?,
+                )))
+            }
+        })
+    }
+}
+
+impl TryFrom<&ScVal> for ContractValue {
+    type Error = String;
+
+    fn try_from(value: &ScVal) -> Result<Self, Self::Error> {
+        Ok(match value {
+            ScVal::Void => ContractValue::Void,
+            ScVal::Bool(b) => ContractValue::Bool(*b),
+            ScVal::U32(n) => ContractValue::U32(*n),
+            ScVal::I32(n) => ContractValue::I32(*n),
+            ScVal::U64(n) => ContractValue::U64(*n),
+            ScVal::I64(n) => ContractValue::I64(*n),
+            ScVal::U128(parts) => {
+                ContractValue::U128(((parts.hi as u128) << 64) | parts.lo as u128)
+            }
+            ScVal::I128(parts) => ContractValue::I128(i128::from(parts)),
+            ScVal::Symbol(s) => ContractValue::Symbol(s.to_utf8_string_lossy()),
+            ScVal::String(s) => ContractValue::String(s.to_utf8_string_lossy()),
+            ScVal::Address(a) => ContractValue::Address(a.to_string()),
+            ScVal::Bytes(b) => ContractValue::Bytes(encode_hex(b.as_slice())),
+            ScVal::Vec(items) => ContractValue::Vec(
+                items
+                    .iter()
+                    .flat_map(|v| v.iter())
+                    .map(ContractValue::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            ScVal::Map(entries) => ContractValue::Map(
+                entries
+                    .iter()
+                    .flat_map(|m| m.iter())
+                    .map(|e| {
+                        Ok(ContractMapEntry {
+                            key: ContractValue::try_from(&e.key)?,
+                            value: ContractValue::try_from(&e.val)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, String>>()?,
+            ),
+            other => return Err(format!("Unsupported ScVal type: {}", other.name())),
+        })
+    }
+}
+
+/// Decode a base64 `ScVal` into a `ContractValue`.
+pub fn decode_scval(xdr: &str) -> Result<ContractValue, String> {
+    let val = ScVal::from_xdr_base64(xdr, Limits::none())
+        .map_err(|e| format!("Invalid ScVal XDR: {}", e))?;
+    ContractValue::try_from(&val)
+}
+
+/// Extract the contract return value from a base64 `TransactionMeta`.
+pub fn return_value_from_meta(meta_xdr: &str) -> Result<Option<ContractValue>, String> {
+    let meta = TransactionMeta::from_xdr_base64(meta_xdr, Limits::none())
+        .map_err(|e| format!("Invalid TransactionMeta XDR: {}", e))?;
+    let val = match meta {
+        TransactionMeta::V3(v3) => v3.soroban_meta.map(|m| m.return_value),
+        TransactionMeta::V4(v4) => v4.soroban_meta.and_then(|m| m.return_value),
+        _ => None,
+    };
+    val.as_ref().map(ContractValue::try_from).transpose()
+}
+
+/// Build an unsigned `InvokeHostFunction` envelope calling `function` on
+/// `contract_id`, using `sequence + 1` as the transaction sequence number.
+pub fn build_invoke_envelope(
+    source_account: &str,
+    sequence: i64,
+    contract_id: &str,
+    function: &str,
+    args: &[ContractValue],
+) -> Result<TransactionEnvelope, AppError> {
+    let source = stellar_strkey::ed25519::PublicKey::from_string(source_account)
+        .map_err(|_| AppError::BadRequest("Invalid source_account".to_string()))?;
+    let contract = stellar_strkey::Contract::from_string(contract_id)
+        .map_err(|_| AppError::InternalError("Invalid Soroban contract ID".to_string()))?;
+    let function_name = ScSymbol(
+        function
+            .try_into()
+            .map_err(|_| AppError::BadRequest("Invalid contract function name".to_string()))?,
+    );
+    let args: VecM<ScVal> = args
+        .iter()
+        .map(ScVal::try_from)
+        .collect::<Result<Vec<_>, _>>()?
+        .try_into()
+        .map_err(|_| AppError::BadRequest("Too many contract arguments".to_string()))?;
+
+    let operation = Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function: HostFunction::InvokeContract(InvokeContractArgs {
+                contract_address: ScAddress::Contract(Hash(contract.0).into()),
+                function_name,
+                args,
+            }),
+            auth: VecM::default(),
+        }),
+    };
+
+    let tx = Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256(source.0)),
+        fee: STELLAR_BASE_FEE_STROOPS,
+        seq_num: SequenceNumber(sequence + 1),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![operation]
+            .try_into()
+            .map_err(|_| AppError::InternalError("Failed to build transaction".to_string()))?,
+        ext: TransactionExt::V0,
+    };
+
+    Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: VecM::default(),
+    }))
+}
+
+/// Apply simulation results to an envelope: attach the Soroban resource data
+/// and authorization entries, and add the minimum resource fee.
+pub fn assemble_envelope(
+    envelope: TransactionEnvelope,
+    transaction_data_xdr: &str,
+    min_resource_fee: u32,
+    auth_xdr: &[String],
+) -> Result<TransactionEnvelope, String> {
+    let TransactionEnvelope::Tx(mut v1) = envelope else {
+        return Err("Only v1 transaction envelopes can be assembled".to_string());
+    };
+
+    let data = SorobanTransactionData::from_xdr_base64(transaction_data_xdr, Limits::none())
+        .map_err(|e| format!("Invalid transactionData XDR: {}", e))?;
+    let auth = auth_xdr
+        .iter()
+        .map(|a| SorobanAuthorizationEntry::from_xdr_base64(a, Limits::none()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid auth entry XDR: {}", e))?;
+
+    let mut operations = v1.tx.operations.to_vec();
+    if let Some(Operation {
+        body: OperationBody::InvokeHostFunction(op),
+        ..
+    }) = operations.first_mut()
+    {
+        op.auth = auth.try_into().map_err(|_| "Too many auth entries".to_string())?;
+    }
+    v1.tx.operations = operations
+        .try_into()
+        .map_err(|_| "Too many operations".to_string())?;
+    v1.tx.fee = v1
+        .tx
+        .fee
+        .checked_add(min_resource_fee)
+        .ok_or_else(|| "Transaction fee overflow".to_string())?;
+    v1.tx.ext = TransactionExt::V1(data);
+
+    Ok(TransactionEnvelope::Tx(v1))
+}
+
+/// Encode an envelope as base64 XDR.
+pub fn envelope_to_base64(envelope: &TransactionEnvelope) -> Result<String, String> {
+    envelope
+        .to_xdr_base64(Limits::none())
+        .map_err(|e| format!("Failed to encode transaction: {}", e))
+}
+
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if !hex.len().is_multiple_of(2) {
+        return Err("hex string must have an even length".to_string());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| "hex string contains non-hex characters".to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: &str = "GAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB7JZX";
+    const CONTRACT: &str = "CAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6N4O";
+
+    #[test]
+    fn test_i128_max_roundtrips_through_json() {
+        let value = ContractValue::I128(i128::MAX);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"i128","value":"170141183460469231731687303715884105727"}"#
+        );
+        let back: ContractValue = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, value);
+    }
+
+    #[test]
+    fn test_i128_min_roundtrips_through_scval() {
+        let value = ContractValue::I128(i128::MIN);
+        let scval = ScVal::try_from(&value).unwrap();
+        assert_eq!(ContractValue::try_from(&scval).unwrap(), value);
+    }
+
+    #[test]
+    fn test_u64_and_u128_roundtrip() {
+        for value in [ContractValue::U64(u64::MAX), ContractValue::U128(u128::MAX)] {
+            let json = serde_json::to_string(&value).unwrap();
+            let back: ContractValue = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, value);
+            let scval = ScVal::try_from(&value).unwrap();
+            assert_eq!(ContractValue::try_from(&scval).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_i128_accepts_json_number() {
+        let value: ContractValue = serde_json::from_str(r#"{"type":"i128","value":1000}"#).unwrap();
+        assert_eq!(value, ContractValue::I128(1000));
+    }
+
+    #[test]
+    fn test_i128_rejects_garbage() {
+        assert!(serde_json::from_str::<ContractValue>(r#"{"type":"i128","value":"12abc"}"#).is_err());
+        assert!(serde_json::from_str::<ContractValue>(r#"{"type":"i128","value":1.5}"#).is_err());
+    }
+
+    #[test]
+    fn test_address_and_vec_conversion() {
+        let value = ContractValue::Vec(vec![
+            ContractValue::Address(ACCOUNT.to_string()),
+            ContractValue::Address(CONTRACT.to_string()),
+        ]);
+        let scval = ScVal::try_from(&value).unwrap();
+        assert_eq!(ContractValue::try_from(&scval).unwrap(), value);
+    }
+
+    #[test]
+    fn test_invalid_address_rejected() {
+        let value = ContractValue::Address("GNOTANADDRESS".to_string());
+        assert!(ScVal::try_from(&value).is_err());
+    }
+
+    #[test]
+    fn test_decode_scval_base64() {
+        let xdr = ScVal::from(i128::MAX).to_xdr_base64(Limits::none()).unwrap();
+        assert_eq!(decode_scval(&xdr).unwrap(), ContractValue::I128(i128::MAX));
+    }
+
+    #[test]
+    fn test_build_invoke_envelope() {
+        let args = [ContractValue::I128(i128::MAX)];
+        let envelope = build_invoke_envelope(ACCOUNT, 41, CONTRACT, "wrap", &args).unwrap();
+        let TransactionEnvelope::Tx(v1) = &envelope else {
+            panic!("expected v1 envelope");
+        };
+        assert_eq!(v1.tx.seq_num, SequenceNumber(42));
+        assert_eq!(v1.tx.fee, STELLAR_BASE_FEE_STROOPS);
+        assert!(v1.signatures.is_empty());
+
+        let encoded = envelope_to_base64(&envelope).unwrap();
+        let decoded = TransactionEnvelope::from_xdr_base64(&encoded, Limits::none()).unwrap();
+        assert_eq!(decoded, envelope);
+    }
+
+    #[test]
+    fn test_build_invoke_envelope_rejects_bad_source() {
+        assert!(build_invoke_envelope("GBAD", 1, CONTRACT, "wrap", &[]).is_err());
+    }
+
+    #[test]
+    fn test_assemble_envelope_adds_fee_and_data() {
+        let envelope = build_invoke_envelope(ACCOUNT, 1, CONTRACT, "balance", &[]).unwrap();
+        let data = SorobanTransactionData::default()
+            .to_xdr_base64(Limits::none())
+            .unwrap();
+        let assembled = assemble_envelope(envelope, &data, 5000, &[]).unwrap();
+        let TransactionEnvelope::Tx(v1) = assembled else {
+            panic!("expected v1 envelope");
+        };
+        assert_eq!(v1.tx.fee, STELLAR_BASE_FEE_STROOPS + 5000);
+        assert!(matches!(v1.tx.ext, TransactionExt::V1(_)));
+    }
+}
@@ -10,6 +10,9 @@ pub enum AppError {
     RateLimited { retry_after: u64 },
     InternalError(String),
     Forbidden,
+    ServiceUnavailable(String),
+    /// The request ran past `REQUEST_TIMEOUT_SECS`.
+    GatewayTimeout,
     Conflict(String),
 }
 
@@ -22,6 +25,9 @@ struct ErrorBody {
 struct ErrorDetail {
     code: String,
     message: String,
+    /// `x-request-id` of the failed request, for matching reports to logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
 }
 
 impl axum::response::IntoResponse for AppError {
@@ -32,6 +38,7 @@ impl axum::response::IntoResponse for AppError {
                 error: ErrorDetail {
                     code: "rate_limited".to_string(),
                     message: "Too many requests, please try again later".to_string(),
+                    request_id: crate::middleware::current_request_id(),
                 },
             };
             let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
@@ -60,6 +67,16 @@ impl axum::response::IntoResponse for AppError {
                 "forbidden",
                 "Access denied".to_string(),
             ),
+            AppError::ServiceUnavailable(msg) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "service_unavailable",
+                msg,
+            ),
+            AppError::GatewayTimeout => (
+                StatusCode::GATEWAY_TIMEOUT,
+                "timeout",
+                "Request timed out".to_string(),
+            ),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, "conflict", msg),
         };
 
@@ -67,6 +84,7 @@ impl axum::response::IntoResponse for AppError {
             error: ErrorDetail {
                 code: code.to_string(),
                 message,
+                request_id: crate::middleware::current_request_id(),
             },
         };
 
@@ -83,6 +101,8 @@ impl std::fmt::Display for AppError {
             AppError::RateLimited { retry_after } => write!(f, "Rate limited (retry after {}s)", retry_after),
             AppError::InternalError(msg) => write!(f, "Internal error: {}", msg),
             AppError::Forbidden => write!(f, "Forbidden"),
+            AppError::ServiceUnavailable(msg) => write!(f, "Service unavailable: {}", msg),
+            AppError::GatewayTimeout => write!(f, "Gateway timeout"),
             AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
         }
     }
@@ -117,6 +137,12 @@ mod tests {
         assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
     }
 
+    #[test]
+    fn test_rate_limited_sets_retry_after() {
+        let response = AppError::RateLimited { retry_after: 42 }.into_response();
+        assert_eq!(response.headers()[header::RETRY_AFTER], "42");
+    }
+
     #[test]
     fn test_internal_error_status() {
         let response = AppError::InternalError("test".to_string()).into_response();
@@ -134,4 +160,25 @@ mod tests {
         let response = AppError::Conflict("test".to_string()).into_response();
         assert_eq!(response.status(), StatusCode::CONFLICT);
     }
+
+    #[test]
+    fn test_request_id_omitted_outside_a_request() {
+        let body = ErrorBody {
+            error: ErrorDetail {
+                code: "not_found".to_string(),
+                message: "gone".to_string(),
+                request_id: crate::middleware::current_request_id(),
+            },
+        };
+        assert_eq!(
+            serde_json::to_string(&body).unwrap(),
+            r#"{"error":{"code":"not_found","message":"gone"}}"#
+        );
+    }
+
+    #[test]
+    fn test_service_unavailable_status() {
+        let response = AppError::ServiceUnavailable("test".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
 }
@@ -0,0 +1,144 @@
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, OptionalFromRequest, Request};
+use log::warn;
+use std::error::Error as _;
+
+use crate::error::AppError;
+
+/// JSON body extractor whose rejection is an `AppError::BadRequest`, so
+/// malformed bodies get the standard `{ error: { code, message } }` shape
+/// instead of axum's plain-text rejection.
+pub struct AppJson<T>(pub T);
+
+impl<S, T> FromRequest<S> for AppJson<T>
+where
+    axum::Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match axum::Json::<T>::from_request(req, state).await {
+            Ok(axum::Json(value)) => Ok(AppJson(value)),
+            Err(rejection) => {
+                let message = rejection_message(&rejection);
+                warn!("AppJson: rejected request body: {}", message);
+                Err(AppError::BadRequest(message))
+            }
+        }
+    }
+}
+
+/// `Option<AppJson<T>>`: `None` when the request has no `Content-Type`, so
+/// a body can be optional; a body that is present must still be valid.
+impl<S, T> OptionalFromRequest<S> for AppJson<T>
+where
+    axum::Json<T>: OptionalFromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Option<Self>, Self::Rejection> {
+        match <axum::Json<T> as OptionalFromRequest<S>>::from_request(req, state).await {
+            Ok(value) => Ok(value.map(|axum::Json(value)| AppJson(value))),
+            Err(rejection) => {
+                let message = rejection_message(&rejection);
+                warn!("AppJson: rejected request body: {}", message);
+                Err(AppError::BadRequest(message))
+            }
+        }
+    }
+}
+
+/// Describe a JSON rejection.  Deserialization errors carry the serde
+/// message, which names the missing or mistyped field.
+fn rejection_message(rejection: &JsonRejection) -> String {
+    match rejection {
+        JsonRejection::JsonDataError(e) => match e.source() {
+            Some(source) => format!("Invalid request body: {}", source),
+            None => "Invalid request body".to_string(),
+        },
+        JsonRejection::JsonSyntaxError(_) => "Malformed JSON in request body".to_string(),
+        JsonRejection::MissingJsonContentType(_) => {
+            "Expected request with `Content-Type: application/json`".to_string()
+        }
+        other => other.body_text(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{header, StatusCode};
+    use axum::routing::post;
+    use axum::Router;
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize)]
+    struct Payload {
+        #[allow(dead_code)]
+        name: String,
+        #[allow(dead_code)]
+        count: u32,
+    }
+
+    async fn post_json(body: &str) -> (StatusCode, serde_json::Value) {
+        let app = Router::new().route("/", post(|AppJson(_p): AppJson<Payload>| async { "ok" }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_missing_field_is_bad_request() {
+        let (status, body) = post_json(r#"{"name":"a"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "bad_request");
+        assert!(body["error"]["message"].as_str().unwrap().contains("count"));
+    }
+
+    #[tokio::test]
+    async fn test_type_mismatch_is_bad_request() {
+        let (status, body) = post_json(r#"{"name":"a","count":"three"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "bad_request");
+        assert!(body["error"]["message"].as_str().unwrap().contains("count"));
+    }
+
+    #[tokio::test]
+    async fn test_syntax_error_is_bad_request() {
+        let (status, body) = post_json("{not json").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["message"], "Malformed JSON in request body");
+    }
+
+    #[tokio::test]
+    async fn test_valid_body_is_accepted() {
+        let app = Router::new().route("/", post(|AppJson(p): AppJson<Payload>| async move { p.name }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"{"name":"a","count":3}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
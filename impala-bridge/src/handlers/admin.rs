@@ -0,0 +1,85 @@
+use axum::extract::Extension;
+use axum::Json;
+use log::{error, info};
+use std::sync::Arc;
+
+use crate::auth::AdminUser;
+use crate::config::Config;
+use crate::error::AppError;
+use crate::extract::AppJson;
+use crate::maintenance::{MaintenanceRecord, MaintenanceState};
+use crate::models::{MaintenanceRequest, MaintenanceResponse};
+
+/// Return the effective configuration with secrets redacted (`GET /admin/config`).
+pub async fn get_config(
+    admin: AdminUser,
+    Extension(config): Extension<Arc<Config>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    info!("GET /admin/config: requested by {}", admin.account_id);
+    let value = serde_json::to_value(config.as_ref()).map_err(|e| {
+        error!("get_config: failed to serialize config: {}", e);
+        AppError::InternalError("Failed to serialize config".to_string())
+    })?;
+    Ok(Json(value))
+}
+
+/// Report whether maintenance mode is on (`GET /admin/maintenance`).
+pub async fn get_maintenance(
+    admin: AdminUser,
+    Extension(state): Extension<Arc<MaintenanceState>>,
+) -> Json<MaintenanceResponse> {
+    info!("GET /admin/maintenance: requested by {}", admin.account_id);
+    Json(maintenance_response(&state, "Maintenance status"))
+}
+
+/// Switch maintenance mode on or off for every instance
+/// (`PUT /admin/maintenance`).  The flag is stored in Redis, so it outlives
+/// this process; `MAINTENANCE_MODE` in configuration keeps it on regardless.
+pub async fn set_maintenance(
+    admin: AdminUser,
+    Extension(state): Extension<Arc<MaintenanceState>>,
+    Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
+    Extension(config): Extension<Arc<Config>>,
+    AppJson(payload): AppJson<MaintenanceRequest>,
+) -> Result<Json<MaintenanceResponse>, AppError> {
+    info!(
+        "PUT /admin/maintenance: enabled={} requested by {}",
+        payload.enabled, admin.account_id
+    );
+
+    let record = MaintenanceRecord {
+        enabled: payload.enabled,
+        set_by: admin.account_id.clone(),
+        set_at: chrono::Utc::now().to_rfc3339(),
+    };
+    crate::maintenance::store(&redis_pool, &config.redis_key_prefix, &record)
+        .await
+        .map_err(|e| {
+            error!("set_maintenance: failed to store flag: {}", e);
+            AppError::InternalError("Failed to store maintenance flag".to_string())
+        })?;
+    state.set_runtime(payload.enabled);
+    info!(
+        "set_maintenance: maintenance {} by {}",
+        if payload.enabled { "enabled" } else { "disabled" },
+        admin.account_id
+    );
+
+    let message = if !payload.enabled && state.is_forced() {
+        "Runtime flag cleared; MAINTENANCE_MODE keeps maintenance on"
+    } else if payload.enabled {
+        "Maintenance mode enabled"
+    } else {
+        "Maintenance mode disabled"
+    };
+    Ok(Json(maintenance_response(&state, message)))
+}
+
+fn maintenance_response(state: &MaintenanceState, message: &str) -> MaintenanceResponse {
+    MaintenanceResponse {
+        success: true,
+        message: message.to_string(),
+        enabled: state.is_enabled(),
+        forced_by_config: state.is_forced(),
+    }
+}
@@ -5,13 +5,15 @@ use sqlx::PgPool;
 
 use crate::auth::AuthenticatedUser;
 use crate::error::AppError;
+use crate::handlers::account::{account_reference_error, require_account_exists};
+use crate::extract::AppJson;
 use crate::models::{CardResponse, CreateCardRequest, DeleteCardRequest};
 
 /// Register a hardware smartcard (`POST /card`).
 pub async fn create_card(
     user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<CreateCardRequest>,
+    AppJson(payload): AppJson<CreateCardRequest>,
 ) -> Result<Json<CardResponse>, AppError> {
     crate::auth::require_owner(&user, &payload.account_id)?;
     crate::validate::validate_card_id(&payload.card_id)?;
@@ -21,6 +23,7 @@ pub async fn create_card(
         "POST /card: registering card_id={} for account_id={}",
         payload.card_id, payload.account_id
     );
+    require_account_exists(&pool, &payload.account_id, "create_card").await?;
     let result = sqlx::query(
         r#"
         INSERT INTO card (account_id, card_id, ec_pubkey, rsa_pubkey)
@@ -42,10 +45,7 @@ pub async fn create_card(
                 message: "Card created successfully".to_string(),
             }))
         }
-        Err(e) => {
-            error!("create_card: database error: {}", e);
-            Err(AppError::InternalError("Database error".to_string()))
-        }
+        Err(e) => Err(account_reference_error("create_card", e)),
     }
 }
 
@@ -53,7 +53,7 @@ pub async fn create_card(
 pub async fn delete_card(
     user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<DeleteCardRequest>,
+    AppJson(payload): AppJson<DeleteCardRequest>,
 ) -> Result<Json<CardResponse>, AppError> {
     info!("DELETE /card: card_id={}", payload.card_id);
     let result = sqlx::query(
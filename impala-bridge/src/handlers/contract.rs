@@ -0,0 +1,312 @@
+use axum::extract::{Extension, Query};
+use axum::Json;
+use log::{error, info, warn};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::auth::AuthenticatedUser;
+use crate::config::{Config, StellarConfig};
+use crate::constants::{
+    MAX_SUBMIT_WAIT_SECS, SUBMIT_RESPONSE_MARGIN_SECS, TIMELOCK_SCHEDULE_FUNCTIONS, TX_POLL_INTERVAL_MS,
+};
+use crate::contract;
+use crate::error::AppError;
+use crate::extract::AppJson;
+use crate::models::{
+    BuildInvokeRequest, BuildInvokeResponse, ContractValue, SubmitTransactionRequest, SubmitTransactionResponse,
+    TransactionStatusQuery, TransactionStatusResponse,
+};
+use crate::redis_helpers::check_contract_rate_limit;
+use crate::stellar_rpc::{GetTransactionResponse, PollStatus, StellarRpcClient};
+use crate::validate::validate_stellar_account_id;
+
+/// Decode the return value from a `getTransaction` result, if present.
+fn return_value_of(hash: &str, result: &GetTransactionResponse) -> Option<ContractValue> {
+    let meta = result.result_meta_xdr.as_deref()?;
+    contract::return_value_from_meta(meta).unwrap_or_else(|e| {
+        warn!("contract: could not decode return value for {}: {}", hash, e);
+        None
+    })
+}
+
+/// Fetch the current sequence number of `account_id` from Horizon.
+async fn fetch_sequence(
+    client: &reqwest::Client,
+    horizon_url: &str,
+    account_id: &str,
+) -> Result<i64, AppError> {
+    let url = format!("{}/accounts/{}", horizon_url.trim_end_matches('/'), account_id);
+    let response = client.get(&url).send().await.map_err(|e| {
+        error!("contract: Horizon account lookup failed: {}", e);
+        AppError::InternalError("Horizon unavailable".to_string())
+    })?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(AppError::NotFound("Source account not found on network".to_string()));
+    }
+    let body: serde_json::Value = response.json().await.map_err(|e| {
+        error!("contract: Horizon returned invalid JSON: {}", e);
+        AppError::InternalError("Invalid Horizon response".to_string())
+    })?;
+    body["sequence"]
+        .as_str()
+        .and_then(|s| s.parse::<i64>().ok())
+        .ok_or_else(|| AppError::InternalError("Invalid Horizon response".to_string()))
+}
+
+/// Convert an absolute `unlock_at` into the contract's `delay_seconds`,
+/// measured from the ledger clock the contract checks against.  Rejects the
+/// request when the bridge clock (`local_now`) and `ledger_time` differ by
+/// more than `tolerance` seconds, since a delay derived from a skewed clock
+/// would fail on-chain.
+fn ledger_relative_delay(
+    unlock_at: u64,
+    ledger_time: u64,
+    local_now: u64,
+    tolerance: u64,
+) -> Result<u64, AppError> {
+    let drift = local_now.abs_diff(ledger_time);
+    if drift > tolerance {
+        warn!(
+            "contract: bridge clock differs from ledger clock by {}s (tolerance {}s)",
+            drift, tolerance
+        );
+        return Err(AppError::BadRequest(format!(
+            "Bridge clock differs from ledger clock by {}s (tolerance {}s)",
+            drift, tolerance
+        )));
+    }
+    if unlock_at <= ledger_time {
+        return Err(AppError::BadRequest(
+            "unlock_at must be after the current ledger time".to_string(),
+        ));
+    }
+    Ok(unlock_at - ledger_time)
+}
+
+/// Build and simulate an unsigned contract invocation (`POST /contract/build-invoke`).
+///
+/// Returns an envelope with resource data, auth entries and fees applied,
+/// ready for the client to sign and pass to `/contract/submit`.
+pub async fn build_invoke(
+    user: AuthenticatedUser,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
+    Extension(stellar_config): Extension<Arc<StellarConfig>>,
+    Extension(rpc): Extension<Arc<StellarRpcClient>>,
+    AppJson(payload): AppJson<BuildInvokeRequest>,
+) -> Result<Json<BuildInvokeResponse>, AppError> {
+    info!(
+        "POST /contract/build-invoke: function={} source={}",
+        payload.function, payload.source_account
+    );
+    let contract_id = stellar_config
+        .contract_id
+        .as_deref()
+        .ok_or_else(|| AppError::BadRequest("No Soroban contract configured".to_string()))?;
+    validate_stellar_account_id(&payload.source_account)?;
+    check_contract_rate_limit(
+        &redis_pool,
+        &user.account_id,
+        contract_id,
+        config.contract_rate_limit_max_requests,
+        config.contract_rate_limit_window_secs,
+    )
+    .await?;
+
+    let mut args = payload.args;
+    if let Some(unlock_at) = payload.unlock_at {
+        if !TIMELOCK_SCHEDULE_FUNCTIONS.contains(&payload.function.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "unlock_at is only supported for {}",
+                TIMELOCK_SCHEDULE_FUNCTIONS.join(", ")
+            )));
+        }
+        let ledger_time = rpc.ledger_time().await?;
+        let local_now = chrono::Utc::now().timestamp().max(0) as u64;
+        let delay = ledger_relative_delay(
+            unlock_at,
+            ledger_time,
+            local_now,
+            config.ledger_clock_tolerance_secs,
+        )?;
+        args.push(ContractValue::U64(delay));
+    }
+    let sequence =
+        fetch_sequence(rpc.http(), &stellar_config.horizon_url, &payload.source_account).await?;
+    let envelope = contract::build_invoke_envelope(
+        &payload.source_account,
+        sequence,
+        contract_id,
+        &payload.function,
+        &args,
+    )?;
+    let envelope_xdr = contract::envelope_to_base64(&envelope).map_err(AppError::InternalError)?;
+
+    let sim = rpc.simulate_transaction(&envelope_xdr).await?;
+
+    if let Some(err) = sim.error.as_deref() {
+        warn!("build_invoke: simulation failed: {}", err);
+        return Err(AppError::BadRequest(format!("Simulation failed: {}", err)));
+    }
+
+    let transaction_data = sim.transaction_data.as_deref().ok_or_else(|| {
+        error!("build_invoke: simulation response missing transactionData");
+        AppError::InternalError("Invalid simulation response".to_string())
+    })?;
+    let min_resource_fee = sim.min_resource_fee.clone().unwrap_or_else(|| "0".to_string());
+    let fee: u32 = min_resource_fee
+        .parse()
+        .map_err(|_| AppError::InternalError("Invalid simulation response".to_string()))?;
+    let first_result = sim.results.first();
+    let auth: Vec<String> = first_result.map(|r| r.auth.clone()).unwrap_or_default();
+    let simulated_result = first_result.and_then(|r| contract::decode_scval(&r.xdr).ok());
+
+    let assembled = contract::assemble_envelope(envelope, transaction_data, fee, &auth)
+        .map_err(|e| {
+            error!("build_invoke: failed to assemble transaction: {}", e);
+            AppError::InternalError("Failed to assemble transaction".to_string())
+        })?;
+
+    Ok(Json(BuildInvokeResponse {
+        transaction_xdr: contract::envelope_to_base64(&assembled)
+            .map_err(AppError::InternalError)?,
+        min_resource_fee,
+        latest_ledger: sim.latest_ledger,
+        simulated_result,
+    }))
+}
+
+/// How long `/contract/submit` may poll for confirmation: `wait_secs`
+/// capped at `MAX_SUBMIT_WAIT_SECS`, and at whatever is left of the request
+/// timeout after `elapsed` and `SUBMIT_RESPONSE_MARGIN_SECS`.
+fn submit_wait(wait_secs: u64, request_timeout_secs: u64, elapsed: Duration) -> Duration {
+    let budget = Duration::from_secs(request_timeout_secs.saturating_sub(SUBMIT_RESPONSE_MARGIN_SECS))
+        .saturating_sub(elapsed);
+    Duration::from_secs(wait_secs.min(MAX_SUBMIT_WAIT_SECS)).min(budget)
+}
+
+/// Submit a signed transaction to Soroban RPC (`POST /contract/submit`).
+///
+/// With `wait_secs` it polls for confirmation within the request timeout.
+/// A transaction not confirmed in time is reported `PENDING` with its hash,
+/// to be followed up with `GET /contract/status`.
+pub async fn submit(
+    user: AuthenticatedUser,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
+    Extension(stellar_config): Extension<Arc<StellarConfig>>,
+    Extension(rpc): Extension<Arc<StellarRpcClient>>,
+    AppJson(payload): AppJson<SubmitTransactionRequest>,
+) -> Result<Json<SubmitTransactionResponse>, AppError> {
+    info!("POST /contract/submit");
+    let started = Instant::now();
+    check_contract_rate_limit(
+        &redis_pool,
+        &user.account_id,
+        stellar_config.contract_id.as_deref().unwrap_or("none"),
+        config.contract_rate_limit_max_requests,
+        config.contract_rate_limit_window_secs,
+    )
+    .await?;
+    let result = rpc.send_transaction(&payload.transaction_xdr).await?;
+
+    let hash = result.hash;
+    let mut status = result.status;
+    info!("submit: hash={} status={}", hash, status);
+
+    let mut ledger = None;
+    let mut return_value = None;
+    if let Some(wait_secs) = payload.wait_secs.filter(|_| status == "PENDING") {
+        let polled = rpc
+            .poll_transaction(
+                &hash,
+                submit_wait(wait_secs, config.request_timeout_secs, started.elapsed()),
+                Duration::from_millis(TX_POLL_INTERVAL_MS),
+            )
+            .await?;
+        info!("submit: hash={} confirmation={}", hash, polled.status.as_str());
+        // Not confirmed within the wait: the transaction is still pending
+        if polled.status != PollStatus::NotFound {
+            status = polled.status.as_str().to_string();
+        }
+        if let Some(result) = &polled.result {
+            ledger = result.ledger;
+            return_value = return_value_of(&hash, result);
+        }
+    }
+
+    Ok(Json(SubmitTransactionResponse {
+        hash,
+        status,
+        latest_ledger: result.latest_ledger,
+        error_result_xdr: result.error_result_xdr,
+        ledger,
+        return_value,
+    }))
+}
+
+/// Look up the status of a submitted transaction (`GET /contract/status`).
+pub async fn status(
+    _user: AuthenticatedUser,
+    Extension(rpc): Extension<Arc<StellarRpcClient>>,
+    Query(query): Query<TransactionStatusQuery>,
+) -> Result<Json<TransactionStatusResponse>, AppError> {
+    info!("GET /contract/status: hash={}", query.hash);
+    let result = rpc.get_transaction(&query.hash).await?;
+
+    let return_value = return_value_of(&query.hash, &result);
+
+    Ok(Json(TransactionStatusResponse {
+        hash: query.hash,
+        status: result.status,
+        ledger: result.ledger,
+        return_value,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submit_wait_stays_within_request_timeout() {
+        // Short waits are honoured as asked
+        assert_eq!(submit_wait(5, 30, Duration::from_secs(1)), Duration::from_secs(5));
+        // The cap and the request timeout both bound it, leaving the margin
+        assert_eq!(
+            submit_wait(MAX_SUBMIT_WAIT_SECS, 30, Duration::from_secs(1)),
+            Duration::from_secs(30 - SUBMIT_RESPONSE_MARGIN_SECS - 1)
+        );
+        assert_eq!(submit_wait(300, 120, Duration::ZERO), Duration::from_secs(MAX_SUBMIT_WAIT_SECS));
+        // Nothing left to wait once the budget is spent
+        assert_eq!(submit_wait(10, 30, Duration::from_secs(40)), Duration::ZERO);
+        assert_eq!(submit_wait(10, 1, Duration::ZERO), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_ledger_relative_delay_uses_ledger_clock() {
+        // Bridge 10s ahead of the ledger, within tolerance: the delay is
+        // measured from the ledger time, not the bridge time.
+        let delay = ledger_relative_delay(1_000_600, 1_000_000, 1_000_010, 30).unwrap();
+        assert_eq!(delay, 600);
+    }
+
+    #[test]
+    fn test_ledger_relative_delay_rejects_skew() {
+        for local_now in [1_000_031, 999_969] {
+            let err = ledger_relative_delay(1_000_600, 1_000_000, local_now, 30).unwrap_err();
+            match err {
+                AppError::BadRequest(msg) => assert!(msg.contains("31s"), "{}", msg),
+                other => panic!("expected BadRequest, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_ledger_relative_delay_rejects_past_unlock() {
+        assert!(matches!(
+            ledger_relative_delay(1_000_000, 1_000_000, 1_000_000, 30),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
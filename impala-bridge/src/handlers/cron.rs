@@ -0,0 +1,150 @@
+use axum::extract::{Extension, Query};
+use axum::Json;
+use log::{error, info, warn};
+use sqlx::PgPool;
+
+use crate::auth::AdminUser;
+use crate::constants::DEFAULT_HTTP_CLIENT_TIMEOUT_SECS;
+use crate::error::AppError;
+use crate::extract::AppJson;
+use crate::models::{
+    CronRunQuery, CronRunResponse, RegisterCallbackRequest, RegisterCallbackResponse, VerifyCallbackRequest,
+    VerifyCallbackResponse,
+};
+use crate::streams;
+use crate::validate::validate_callback_url;
+
+/// Register a cron_sync callback (`POST /cron/callback`).
+///
+/// The callback starts unverified; its origin must serve the returned token
+/// at `/.well-known/impala-verify` before `POST /cron/callback/verify`
+/// enables it.
+pub async fn register_callback(
+    admin: AdminUser,
+    Extension(pool): Extension<PgPool>,
+    AppJson(payload): AppJson<RegisterCallbackRequest>,
+) -> Result<Json<RegisterCallbackResponse>, AppError> {
+    info!("POST /cron/callback: requested by {}", admin.account_id);
+    validate_callback_url(&payload.callback_uri)?;
+    let verify_url = streams::verification_url(&payload.callback_uri).map_err(AppError::BadRequest)?;
+    let token = streams::new_verification_token();
+
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO cron_sync (callback_uri, verification_token) VALUES ($1, $2) RETURNING id",
+    )
+    .bind(&payload.callback_uri)
+    .bind(&token)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| {
+        error!("register_callback: database error: {}", e);
+        AppError::InternalError("Database error".to_string())
+    })?;
+
+    info!("register_callback: registered id {} ({})", id, payload.callback_uri);
+    Ok(Json(RegisterCallbackResponse {
+        id,
+        verification_token: token,
+        verify_url,
+        verified: false,
+    }))
+}
+
+/// Check ownership of a registered callback (`POST /cron/callback/verify`).
+pub async fn verify_callback(
+    admin: AdminUser,
+    Extension(pool): Extension<PgPool>,
+    AppJson(payload): AppJson<VerifyCallbackRequest>,
+) -> Result<Json<VerifyCallbackResponse>, AppError> {
+    info!("POST /cron/callback/verify: id={} requested by {}", payload.id, admin.account_id);
+    let row = sqlx::query_as::<_, (String, String, bool)>(
+        "SELECT callback_uri, verification_token, verified FROM cron_sync WHERE id = $1",
+    )
+    .bind(payload.id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        error!("verify_callback: database error: {}", e);
+        AppError::InternalError("Database error".to_string())
+    })?;
+    let (callback_uri, token, already_verified) =
+        row.ok_or_else(|| AppError::NotFound("Callback not found".to_string()))?;
+
+    if already_verified {
+        return Ok(Json(VerifyCallbackResponse {
+            id: payload.id,
+            verified: true,
+            message: "Callback already verified".to_string(),
+        }));
+    }
+
+    validate_callback_url(&callback_uri)?;
+    let verify_url = streams::verification_url(&callback_uri).map_err(AppError::BadRequest)?;
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(DEFAULT_HTTP_CLIENT_TIMEOUT_SECS))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| {
+            error!("verify_callback: failed to create HTTP client: {}", e);
+            AppError::InternalError("Failed to create HTTP client".to_string())
+        })?;
+
+    if !streams::check_callback_ownership(&client, &verify_url, &token).await {
+        warn!("verify_callback: id {} failed verification at {}", payload.id, verify_url);
+        return Ok(Json(VerifyCallbackResponse {
+            id: payload.id,
+            verified: false,
+            message: format!("Verification token not found at {}", verify_url),
+        }));
+    }
+
+    sqlx::query("UPDATE cron_sync SET verified = TRUE, verified_at = NOW() WHERE id = $1")
+        .bind(payload.id)
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            error!("verify_callback: database error: {}", e);
+            AppError::InternalError("Database error".to_string())
+        })?;
+
+    info!("verify_callback: id {} verified", payload.id);
+    Ok(Json(VerifyCallbackResponse {
+        id: payload.id,
+        verified: true,
+        message: "Callback verified".to_string(),
+    }))
+}
+
+/// Run verified callbacks immediately (`POST /cron/run?id=...`), or all of
+/// them when no id is given, storing and returning each outcome.
+pub async fn run_now(
+    admin: AdminUser,
+    Extension(pool): Extension<PgPool>,
+    Query(query): Query<CronRunQuery>,
+) -> Result<Json<CronRunResponse>, AppError> {
+    info!("POST /cron/run: id={:?} requested by {}", query.id, admin.account_id);
+    let rows = streams::fetch_verified_callbacks(&pool, query.id)
+        .await
+        .map_err(|e| {
+            error!("run_now: database error: {}", e);
+            AppError::InternalError("Database error".to_string())
+        })?;
+    if query.id.is_some() && rows.is_empty() {
+        return Err(AppError::NotFound("Verified callback not found".to_string()));
+    }
+
+    let client = streams::callback_http_client().map_err(|e| {
+        error!("run_now: failed to create HTTP client: {}", e);
+        AppError::InternalError("Failed to create HTTP client".to_string())
+    })?;
+    let mut results = Vec::with_capacity(rows.len());
+    for (id, callback_uri) in rows {
+        results.push(
+            streams::run_callback(&client, id, &callback_uri, async |id, body| {
+                streams::store_callback_result(&pool, id, body).await
+            })
+            .await,
+        );
+    }
+    Ok(Json(CronRunResponse { results }))
+}
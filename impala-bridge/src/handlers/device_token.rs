@@ -5,13 +5,14 @@ use sqlx::PgPool;
 
 use crate::auth::AuthenticatedUser;
 use crate::error::AppError;
+use crate::extract::AppJson;
 use crate::models::{DeleteDeviceTokenRequest, DeviceTokenResponse, RegisterDeviceTokenRequest};
 
 /// Register or refresh a device token (`POST /device-token`).
 pub async fn register_device_token(
     user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<RegisterDeviceTokenRequest>,
+    AppJson(payload): AppJson<RegisterDeviceTokenRequest>,
 ) -> Result<Json<DeviceTokenResponse>, AppError> {
     info!(
         "POST /device-token: platform={} for account_id={}",
@@ -61,7 +62,7 @@ pub async fn register_device_token(
 pub async fn delete_device_token(
     user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<DeleteDeviceTokenRequest>,
+    AppJson(payload): AppJson<DeleteDeviceTokenRequest>,
 ) -> Result<Json<DeviceTokenResponse>, AppError> {
     info!(
         "DELETE /device-token: for account_id={}",
@@ -0,0 +1,32 @@
+use axum::extract::Extension;
+use axum::Json;
+use std::sync::Arc;
+
+use crate::config::{Config, FeatureFlags};
+
+/// Report which feature groups this deployment exposes (`GET /features`).
+pub async fn list_features(Extension(config): Extension<Arc<Config>>) -> Json<FeatureFlags> {
+    Json(config.features.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::load_config;
+
+    #[tokio::test]
+    async fn test_features_match_config() {
+        let mut config = load_config();
+        config.features.mfa = false;
+        config.features.ldap = false;
+        let expected = config.features.clone();
+
+        let Json(reported) = list_features(Extension(Arc::new(config))).await;
+        assert_eq!(reported, expected);
+
+        let json = serde_json::to_value(&reported).unwrap();
+        assert_eq!(json["mfa"], false);
+        assert_eq!(json["ldap"], false);
+        assert_eq!(json["cards"], true);
+    }
+}
@@ -32,6 +32,25 @@ pub async fn get_version(Extension(pool): Extension<PgPool>) -> Json<VersionResp
     })
 }
 
+/// Run `SELECT 1` against the database.
+pub async fn probe_database(pool: &PgPool) -> Result<(), String> {
+    sqlx::query_scalar::<_, i32>("SELECT 1")
+        .fetch_one(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Check out a Redis connection and `PING` it.
+pub async fn probe_redis(redis_pool: &deadpool_redis::Pool) -> Result<(), String> {
+    let mut conn = redis_pool.get().await.map_err(|e| e.to_string())?;
+    redis::cmd("PING")
+        .query_async::<String>(&mut *conn)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
 /// Health check that verifies DB and Redis connectivity (`GET /health`).
 pub async fn health_check(
     Extension(pool): Extension<PgPool>,
@@ -39,11 +58,8 @@ pub async fn health_check(
     Extension(stellar_config): Extension<Arc<crate::config::StellarConfig>>,
 ) -> Result<Json<HealthResponse>, AppError> {
     // Check database
-    let db_status = match sqlx::query_scalar::<_, i32>("SELECT 1")
-        .fetch_one(&pool)
-        .await
-    {
-        Ok(_) => "ok".to_string(),
+    let db_status = match probe_database(&pool).await {
+        Ok(()) => "ok".to_string(),
         Err(e) => {
             error!("health_check: database error: {}", e);
             "error".to_string()
@@ -51,19 +67,10 @@ pub async fn health_check(
     };
 
     // Check Redis
-    let redis_status = match redis_pool.get().await {
-        Ok(mut conn) => {
-            let result: Result<String, _> = redis::cmd("PING").query_async(&mut *conn).await;
-            match result {
-                Ok(_) => "ok".to_string(),
-                Err(e) => {
-                    error!("health_check: Redis PING error: {}", e);
-                    "error".to_string()
-                }
-            }
-        }
+    let redis_status = match probe_redis(&redis_pool).await {
+        Ok(()) => "ok".to_string(),
         Err(e) => {
-            error!("health_check: Redis connection error: {}", e);
+            error!("health_check: Redis error: {}", e);
             "error".to_string()
         }
     };
@@ -93,20 +100,8 @@ pub async fn readiness(
     Extension(pool): Extension<PgPool>,
     Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
 ) -> StatusCode {
-    // Check database
-    let db_ok = sqlx::query_scalar::<_, i32>("SELECT 1")
-        .fetch_one(&pool)
-        .await
-        .is_ok();
-
-    // Check Redis
-    let redis_ok = match redis_pool.get().await {
-        Ok(mut conn) => {
-            let result: Result<String, _> = redis::cmd("PING").query_async(&mut *conn).await;
-            result.is_ok()
-        }
-        Err(_) => false,
-    };
+    let db_ok = probe_database(&pool).await.is_ok();
+    let redis_ok = probe_redis(&redis_pool).await.is_ok();
 
     if db_ok && redis_ok {
         StatusCode::OK
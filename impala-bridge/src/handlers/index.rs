@@ -0,0 +1,61 @@
+use axum::extract::{Extension, Query};
+use axum::Json;
+use log::{error, info};
+use redis::AsyncCommands;
+use std::sync::Arc;
+
+use crate::auth::AdminUser;
+use crate::constants::STELLAR_RAW_EVENTS_KEY;
+use crate::error::AppError;
+use crate::models::{ReprocessQuery, ReprocessResponse};
+use crate::streams::reprocess_raw_events;
+
+/// Re-run the ledger parser over stored raw payloads (`POST /index/reprocess`).
+///
+/// Only ledgers captured while `STELLAR_RAW_EVENT_RETENTION` was enabled
+/// (and not yet evicted) can be reprocessed.
+pub async fn reprocess(
+    admin: AdminUser,
+    Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
+    Query(query): Query<ReprocessQuery>,
+) -> Result<Json<ReprocessResponse>, AppError> {
+    info!(
+        "POST /index/reprocess: from={} to={} by {}",
+        query.from, query.to, admin.account_id
+    );
+    if query.from > query.to {
+        return Err(AppError::BadRequest("from must not exceed to".to_string()));
+    }
+
+    let mut conn = redis_pool.get().await.map_err(|e| {
+        error!("reprocess: failed to get Redis connection: {}", e);
+        AppError::InternalError("Service temporarily unavailable".to_string())
+    })?;
+    let members: Vec<String> = conn
+        .zrangebyscore(STELLAR_RAW_EVENTS_KEY, query.from, query.to)
+        .await
+        .map_err(|e| {
+            error!("reprocess: Redis ZRANGEBYSCORE failed: {}", e);
+            AppError::InternalError("Service temporarily unavailable".to_string())
+        })?;
+
+    let (entries, failed) = reprocess_raw_events(&members);
+    for (key, value) in &entries {
+        let _: () = conn.set(key, value).await.map_err(|e| {
+            error!("reprocess: Redis SET failed for {}: {}", key, e);
+            AppError::InternalError("Service temporarily unavailable".to_string())
+        })?;
+    }
+
+    info!(
+        "reprocess: scanned={} reprocessed={} failed={}",
+        members.len(),
+        entries.len(),
+        failed
+    );
+    Ok(Json(ReprocessResponse {
+        scanned: members.len(),
+        reprocessed: entries.len(),
+        failed,
+    }))
+}
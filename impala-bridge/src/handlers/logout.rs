@@ -3,14 +3,16 @@ use axum::Json;
 use axum_extra::headers::authorization::Bearer;
 use axum_extra::headers::Authorization;
 use axum_extra::TypedHeader;
-use jsonwebtoken::{decode, DecodingKey, Validation};
+use jsonwebtoken::errors::ErrorKind;
 use log::info;
 use serde::Serialize;
 use std::sync::Arc;
 
-use crate::constants::JWT_ISSUER;
+use crate::constants::REFRESH_TOKEN_TTL_SECS;
 use crate::error::AppError;
-use crate::models::Claims;
+use crate::extract::AppJson;
+use crate::jwt::JwtKeys;
+use crate::models::{Claims, LogoutRequest, RevokeTokenRequest};
 
 #[derive(Serialize)]
 pub struct LogoutResponse {
@@ -18,38 +20,247 @@ pub struct LogoutResponse {
     pub message: String,
 }
 
-/// Revoke the current JWT token (`POST /logout`).
+/// End a session server-side (`POST /logout`).
 ///
-/// Adds the token's JTI to a Redis blacklist with TTL matching
-/// the token's remaining lifetime.
+/// Takes the bearer temporal token, a `refresh_token` in the body, or both.
+/// Each token's JTI is blacklisted for its remaining lifetime and its token
+/// family is revoked, so every refresh and temporal token rotated from the
+/// same login stops working.  Temporal tokens expire within the hour anyway;
+/// revoking the refresh side is what actually ends the session.  Idempotent:
+/// an already expired token is reported as logged out.
 pub async fn logout(
     Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
-    Extension(jwt_secret): Extension<Arc<String>>,
-    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Extension(jwt_keys): Extension<Arc<JwtKeys>>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+    payload: Option<AppJson<LogoutRequest>>,
 ) -> Result<Json<LogoutResponse>, AppError> {
-    let token = bearer.token();
-
-    // Decode the token (validates signature and expiry)
-    let mut validation = Validation::new(jsonwebtoken::Algorithm::HS256);
-    validation.set_issuer(&[JWT_ISSUER]);
+    let bearer_token = bearer.as_ref().map(|TypedHeader(Authorization(b))| b.token());
+    let refresh_token = payload.as_ref().and_then(|AppJson(p)| p.refresh_token.as_deref());
+    if bearer_token.is_none() && refresh_token.is_none() {
+        return Err(AppError::Unauthorized);
+    }
 
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(jwt_secret.as_bytes()),
-        &validation,
-    )
-    .map_err(|_| AppError::Unauthorized)?;
+    for token in bearer_token.into_iter().chain(refresh_token) {
+        let claims = match jwt_keys.decode_claims(token) {
+            Ok(token_data) => token_data.claims,
+            // Nothing left to revoke
+            Err(e) if matches!(e.kind(), ErrorKind::ExpiredSignature) => continue,
+            Err(_) => return Err(AppError::Unauthorized),
+        };
 
-    // Add JTI to Redis blacklist with TTL matching remaining token lifetime
-    let now = chrono::Utc::now().timestamp() as usize;
-    let remaining = token_data.claims.exp.saturating_sub(now);
-    if remaining > 0 {
-        crate::redis_helpers::revoke_token(&redis_pool, &token_data.claims.jti, remaining).await;
+        blacklist(&redis_pool, &claims).await?;
+        if !claims.family.is_empty() {
+            crate::redis_helpers::revoke_token_family(&redis_pool, &claims.family, REFRESH_TOKEN_TTL_SECS).await?;
+        }
+        info!("logout: {} token revoked for sub={}", claims.token_type, claims.sub);
     }
 
-    info!("logout: token revoked for sub={}", token_data.claims.sub);
+    Ok(Json(LogoutResponse {
+        success: true,
+        message: "Logged out".to_string(),
+    }))
+}
+
+/// Revoke a refresh or temporal token (`POST /token/revoke`).
+///
+/// Holding the token is enough to revoke it, so clients can drop their
+/// refresh token on sign-out even after the temporal token has expired.
+pub async fn revoke_token(
+    Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
+    Extension(jwt_keys): Extension<Arc<JwtKeys>>,
+    AppJson(payload): AppJson<RevokeTokenRequest>,
+) -> Result<Json<LogoutResponse>, AppError> {
+    let token_data = jwt_keys
+        .decode_claims(&payload.token)
+        .map_err(|_| AppError::Unauthorized)?;
+
+    blacklist(&redis_pool, &token_data.claims).await?;
+
+    info!(
+        "revoke_token: {} token revoked for sub={}",
+        token_data.claims.token_type, token_data.claims.sub
+    );
     Ok(Json(LogoutResponse {
         success: true,
         message: "Token revoked successfully".to_string(),
     }))
 }
+
+/// Add the token's JTI to the Redis blacklist with a TTL matching its
+/// remaining lifetime.
+async fn blacklist(redis_pool: &deadpool_redis::Pool, claims: &Claims) -> Result<(), AppError> {
+    let now = chrono::Utc::now().timestamp() as usize;
+    let remaining = claims.exp.saturating_sub(now);
+    if remaining > 0 {
+        crate::redis_helpers::revoke_token(redis_pool, &claims.jti, remaining).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthenticatedUser;
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use axum::routing::{get, post};
+    use axum::Router;
+    use tower::ServiceExt;
+
+    const TEST_SECRET: &str = "test-secret-for-revocation-at-least-32-chars";
+
+    fn app(redis_url: &str) -> Router {
+        let redis_pool = deadpool_redis::Config::from_url(redis_url)
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        Router::new()
+            .route("/token/revoke", post(revoke_token))
+            .route("/logout", post(logout))
+            .route("/token", post(crate::handlers::token::token))
+            .route("/whoami", get(|user: AuthenticatedUser| async move { user.account_id }))
+            .layer(Extension(Arc::new(redis_pool)))
+            .layer(Extension(sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap()))
+            .layer(Extension(Arc::new(crate::config::load_config())))
+            .layer(Extension(Arc::new(JwtKeys::new(TEST_SECRET.to_string(), Vec::new()))))
+    }
+
+    fn revoke_request(token: &str) -> Request<Body> {
+        Request::post("/token/revoke")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::json!({ "token": token }).to_string()))
+            .unwrap()
+    }
+
+    fn logout_request(refresh_token: &str) -> Request<Body> {
+        Request::post("/logout")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::json!({ "refresh_token": refresh_token }).to_string()))
+            .unwrap()
+    }
+
+    fn whoami_request(token: &str) -> Request<Body> {
+        Request::get("/whoami")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_revoke_rejects_invalid_token() {
+        let response = app("redis://127.0.0.1:1/")
+            .oneshot(revoke_request("not-a-jwt"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    fn expired_refresh_token() -> String {
+        let now = chrono::Utc::now().timestamp() as usize;
+        let claims = Claims {
+            sub: "payala-1".to_string(),
+            token_type: crate::constants::TOKEN_TYPE_REFRESH.to_string(),
+            iat: now - 7_200,
+            exp: now - 3_600,
+            jti: uuid::Uuid::new_v4().to_string(),
+            iss: crate::constants::JWT_ISSUER.to_string(),
+            family: crate::jwt::new_token_family(),
+            scopes: Vec::new(),
+        };
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(TEST_SECRET.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_revoke_fails_when_redis_is_down() {
+        let token = crate::jwt::encode_temporal_token(&crate::jwt::SigningKey::hmac(TEST_SECRET.as_bytes()), "payala-1").unwrap();
+        let response = app("redis://127.0.0.1:1/")
+            .oneshot(revoke_request(&token))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let (refresh, _) =
+            crate::jwt::encode_token_pair(&crate::jwt::SigningKey::hmac(TEST_SECRET.as_bytes()), "payala-1", &[]).unwrap();
+        let logout = app("redis://127.0.0.1:1/")
+            .oneshot(logout_request(&refresh))
+            .await
+            .unwrap();
+        assert_eq!(logout.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_logout_with_expired_token_succeeds() {
+        let response = app("redis://127.0.0.1:1/")
+            .oneshot(logout_request(&expired_refresh_token()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_logout_requires_a_token() {
+        let response = app("redis://127.0.0.1:1/")
+            .oneshot(Request::post("/logout").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let forged = app("redis://127.0.0.1:1/")
+            .oneshot(logout_request("not-a-jwt"))
+            .await
+            .unwrap();
+        assert_eq!(forged.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn test_logged_out_refresh_token_cannot_mint_tokens() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let app = app(&url);
+        let (refresh, temporal) =
+            crate::jwt::encode_token_pair(&crate::jwt::SigningKey::hmac(TEST_SECRET.as_bytes()), "payala-1", &[]).unwrap();
+
+        let before = app.clone().oneshot(whoami_request(&temporal)).await.unwrap();
+        assert_eq!(before.status(), StatusCode::OK);
+
+        let logout = app.clone().oneshot(logout_request(&refresh)).await.unwrap();
+        assert_eq!(logout.status(), StatusCode::OK);
+
+        let exchange = app
+            .clone()
+            .oneshot(
+                Request::post("/token")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::json!({ "refresh_token": refresh }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(exchange.status(), StatusCode::UNAUTHORIZED);
+
+        // The temporal token from the same login is gone too
+        let after = app.oneshot(whoami_request(&temporal)).await.unwrap();
+        assert_eq!(after.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn test_revoked_token_is_rejected() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let app = app(&url);
+        let token = crate::jwt::encode_temporal_token(&crate::jwt::SigningKey::hmac(TEST_SECRET.as_bytes()), "payala-1").unwrap();
+
+        let before = app.clone().oneshot(whoami_request(&token)).await.unwrap();
+        assert_eq!(before.status(), StatusCode::OK);
+
+        let revoked = app.clone().oneshot(revoke_request(&token)).await.unwrap();
+        assert_eq!(revoked.status(), StatusCode::OK);
+
+        let after = app.oneshot(whoami_request(&token)).await.unwrap();
+        assert_eq!(after.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -5,23 +5,81 @@ use sqlx::PgPool;
 use std::sync::Arc;
 use totp_rs::{Algorithm, Secret, TOTP};
 
-use crate::auth::AuthenticatedUser;
+use crate::auth::{AdminUser, AuthenticatedUser};
+use crate::config::Config;
+use crate::constants::{
+    DEFAULT_TOTP_SKEW_STEPS, MFA_PENDING_TOTP_TTL_SECS, MFA_SMS_CODE_TTL_SECS, MFA_SMS_SEND_IP_MAX_REQUESTS,
+    MFA_SMS_SEND_IP_WINDOW_SECS, MFA_SMS_SEND_MAX_REQUESTS, MFA_SMS_SEND_WINDOW_SECS,
+};
 use crate::error::AppError;
+use crate::handlers::account::{account_reference_error, require_account_exists};
+use crate::extract::AppJson;
 use crate::models::{
-    EnrollMfaRequest, MfaEnrollment, MfaQuery, MfaResponse, VerifyMfaRequest,
+    ConfirmMfaRequest, EnrollMfaRequest, MfaAdminFilter, MfaAdminListItem, MfaEnrollment, MfaEnrollmentSummary, MfaQuery, MfaResponse,
+    PaginatedResponse, PaginationParams, SendMfaCodeRequest, VerifyMfaRequest,
 };
+use crate::ratelimit::ClientIp;
 use crate::telemetry::AppMetrics;
 use opentelemetry::KeyValue;
 
+/// The TOTP generator/verifier for an enrollment: SHA-1, 6 digits, 30-second
+/// steps, accepting codes up to `skew_steps` steps either side of the
+/// current one.
+fn build_totp(secret: Vec<u8>, account_id: &str, skew_steps: u8) -> Result<TOTP, totp_rs::TotpUrlError> {
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        skew_steps,
+        30,
+        secret,
+        Some("Impala".to_string()),
+        account_id.to_string(),
+    )
+}
+
+/// Redis key holding a TOTP secret awaiting `POST /mfa/confirm`.
+fn pending_totp_key(account_id: &str) -> String {
+    format!("mfa:pending_totp:{}", account_id)
+}
+
+/// Insert or replace an enabled enrollment in `impala_mfa`.
+async fn save_enrollment(
+    pool: &PgPool,
+    account_id: &str,
+    mfa_type: &str,
+    secret: Option<&str>,
+    phone_number: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO impala_mfa (account_id, mfa_type, secret, phone_number, enabled)
+         VALUES ($1, $2, $3, $4, TRUE)
+         ON CONFLICT (account_id, mfa_type)
+         DO UPDATE SET secret = EXCLUDED.secret,
+                       phone_number = EXCLUDED.phone_number,
+                       enabled = TRUE",
+    )
+    .bind(account_id)
+    .bind(mfa_type)
+    .bind(secret)
+    .bind(phone_number)
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
 /// Enroll or re-enroll an MFA method (`POST /mfa`).
 ///
 /// For TOTP: generates a secret and returns a provisioning URI for QR codes.
+/// The secret is held in Redis for `MFA_PENDING_TOTP_TTL_SECS` and only
+/// saved once `POST /mfa/confirm` proves the authenticator app produces
+/// valid codes; an existing TOTP enrollment stays in force until then.
 /// For SMS: requires a phone_number.
 pub async fn enroll_mfa(
     user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
+    Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
     Extension(metrics): Extension<Arc<AppMetrics>>,
-    Json(payload): Json<EnrollMfaRequest>,
+    AppJson(payload): AppJson<EnrollMfaRequest>,
 ) -> Result<Json<MfaResponse>, AppError> {
     crate::auth::require_owner(&user, &payload.account_id)?;
     info!(
@@ -54,47 +112,52 @@ pub async fn enroll_mfa(
         }
     }
 
-    let (secret_value, provisioning_uri) = if payload.mfa_type == "totp" {
+    require_account_exists(&pool, &payload.account_id, "enroll_mfa").await?;
+
+    if payload.mfa_type == "totp" {
         // Generate a TOTP secret
         let secret = Secret::generate_secret();
-        let totp = TOTP::new(
-            Algorithm::SHA1,
-            6,
-            1,
-            30,
-            secret.to_bytes().unwrap(),
-            Some("Impala".to_string()),
-            payload.account_id.clone(),
+        let totp = build_totp(secret.to_bytes().unwrap(), &payload.account_id, DEFAULT_TOTP_SKEW_STEPS)
+            .map_err(|e| {
+                error!("enroll_mfa: failed to create TOTP: {}", e);
+                AppError::InternalError("Failed to generate TOTP".to_string())
+            })?;
+
+        let mut conn = redis_pool.get().await.map_err(|e| {
+            error!("enroll_mfa: Redis connection error: {}", e);
+            AppError::InternalError("Redis connection error".to_string())
+        })?;
+        redis::AsyncCommands::set_ex::<_, _, ()>(
+            &mut *conn,
+            pending_totp_key(&payload.account_id),
+            secret.to_encoded().to_string(),
+            MFA_PENDING_TOTP_TTL_SECS,
         )
+        .await
         .map_err(|e| {
-            error!("enroll_mfa: failed to create TOTP: {}", e);
-            AppError::InternalError("Failed to generate TOTP".to_string())
+            error!("enroll_mfa: failed to store pending TOTP secret: {}", e);
+            AppError::InternalError("Redis error".to_string())
         })?;
 
-        let uri = totp.get_url();
-        let secret_base32 = secret.to_encoded().to_string();
-        (Some(secret_base32), Some(uri))
-    } else {
-        (None, None)
-    };
+        info!("enroll_mfa: TOTP pending confirmation for account_id={}", payload.account_id);
+        return Ok(Json(MfaResponse {
+            success: true,
+            message: "Scan the code, then confirm with POST /mfa/confirm".to_string(),
+            provisioning_uri: Some(totp.get_url()),
+        }));
+    }
 
-    let result = sqlx::query(
-        "INSERT INTO impala_mfa (account_id, mfa_type, secret, phone_number, enabled)
-         VALUES ($1, $2, $3, $4, TRUE)
-         ON CONFLICT (account_id, mfa_type)
-         DO UPDATE SET secret = EXCLUDED.secret,
-                       phone_number = EXCLUDED.phone_number,
-                       enabled = TRUE",
+    let result = save_enrollment(
+        &pool,
+        &payload.account_id,
+        &payload.mfa_type,
+        None,
+        payload.phone_number.as_deref(),
     )
-    .bind(&payload.account_id)
-    .bind(&payload.mfa_type)
-    .bind(&secret_value)
-    .bind(&payload.phone_number)
-    .execute(&pool)
     .await;
 
     match result {
-        Ok(_) => {
+        Ok(()) => {
             info!(
                 "enroll_mfa: {} enrolled for account_id={}",
                 payload.mfa_type, payload.account_id
@@ -106,12 +169,116 @@ pub async fn enroll_mfa(
             Ok(Json(MfaResponse {
                 success: true,
                 message: "MFA enrolled successfully".to_string(),
-                provisioning_uri,
+                provisioning_uri: None,
             }))
         }
-        Err(e) => {
-            error!("enroll_mfa: database error: {}", e);
-            Err(AppError::InternalError("Database error".to_string()))
+        Err(e) => Err(account_reference_error("enroll_mfa", e)),
+    }
+}
+
+/// Outcome of checking a code against a pending TOTP enrollment.
+#[derive(Debug, PartialEq)]
+enum PendingTotp {
+    /// Nothing pending: never enrolled, already confirmed, or timed out.
+    Expired,
+    WrongCode,
+    Confirmed,
+}
+
+fn check_pending_totp(
+    pending_secret: Option<&str>,
+    account_id: &str,
+    code: &str,
+    skew_steps: u8,
+    now: u64,
+) -> Result<PendingTotp, AppError> {
+    let Some(pending_secret) = pending_secret else {
+        return Ok(PendingTotp::Expired);
+    };
+    let secret = Secret::Encoded(pending_secret.to_string()).to_bytes().map_err(|e| {
+        error!("confirm_mfa: invalid pending TOTP secret: {}", e);
+        AppError::InternalError("Invalid TOTP configuration".to_string())
+    })?;
+    let totp = build_totp(secret, account_id, skew_steps).map_err(|e| {
+        error!("confirm_mfa: failed to create TOTP verifier: {}", e);
+        AppError::InternalError("TOTP verification error".to_string())
+    })?;
+    Ok(if totp.check(code, now) {
+        PendingTotp::Confirmed
+    } else {
+        PendingTotp::WrongCode
+    })
+}
+
+/// Confirm a pending TOTP enrollment (`POST /mfa/confirm`).
+///
+/// Checks `code` against the secret issued by `POST /mfa` and, if it
+/// matches, saves the enrollment as enabled.  Every attempt counts toward
+/// the TOTP verification lockout until one succeeds.
+pub async fn confirm_mfa(
+    user: AuthenticatedUser,
+    Extension(pool): Extension<PgPool>,
+    Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(metrics): Extension<Arc<AppMetrics>>,
+    AppJson(payload): AppJson<ConfirmMfaRequest>,
+) -> Result<Json<MfaResponse>, AppError> {
+    crate::auth::require_owner(&user, &payload.account_id)?;
+    info!("POST /mfa/confirm: account_id={}", payload.account_id);
+
+    crate::redis_helpers::claim_mfa_attempt(
+        &redis_pool,
+        &payload.account_id,
+        "totp",
+        config.mfa_lockout_threshold,
+        config.mfa_lockout_window_secs,
+    )
+    .await?;
+
+    let key = pending_totp_key(&payload.account_id);
+    let mut conn = redis_pool.get().await.map_err(|e| {
+        error!("confirm_mfa: Redis connection error: {}", e);
+        AppError::InternalError("Redis connection error".to_string())
+    })?;
+    let pending: Option<String> = redis::AsyncCommands::get(&mut *conn, &key).await.map_err(|e| {
+        error!("confirm_mfa: Redis GET failed for {}: {}", key, e);
+        AppError::InternalError("Redis error".to_string())
+    })?;
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    match check_pending_totp(pending.as_deref(), &payload.account_id, &payload.code, config.mfa_totp_skew_steps, now)? {
+        PendingTotp::Expired => {
+            warn!("confirm_mfa: no pending TOTP enrollment for account_id={}", payload.account_id);
+            Ok(Json(MfaResponse {
+                success: false,
+                message: "No pending TOTP enrollment; it may have expired, enroll again".to_string(),
+                provisioning_uri: None,
+            }))
+        }
+        PendingTotp::WrongCode => {
+            warn!("confirm_mfa: invalid TOTP code for account_id={}", payload.account_id);
+            Ok(Json(MfaResponse {
+                success: false,
+                message: "Invalid verification code".to_string(),
+                provisioning_uri: None,
+            }))
+        }
+        PendingTotp::Confirmed => {
+            save_enrollment(&pool, &payload.account_id, "totp", pending.as_deref(), None)
+                .await
+                .map_err(|e| account_reference_error("confirm_mfa", e))?;
+            let _: Result<(), _> = redis::AsyncCommands::del(&mut *conn, &key).await;
+            crate::redis_helpers::clear_mfa_attempts(&redis_pool, &payload.account_id, "totp").await;
+            info!("confirm_mfa: totp enrolled for account_id={}", payload.account_id);
+            metrics.mfa_enrollments.add(1, &[
+                KeyValue::new("mfa_type", "totp"),
+                KeyValue::new("outcome", "success"),
+            ]);
+            Ok(Json(MfaResponse {
+                success: true,
+                message: "MFA enrolled successfully".to_string(),
+                provisioning_uri: None,
+            }))
         }
     }
 }
@@ -121,7 +288,7 @@ pub async fn get_mfa(
     user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
     Query(params): Query<MfaQuery>,
-) -> Result<Json<Vec<MfaEnrollment>>, AppError> {
+) -> Result<Json<Vec<MfaEnrollmentSummary>>, AppError> {
     crate::auth::require_owner(&user, &params.account_id)?;
     debug!("GET /mfa: account_id={}", params.account_id);
     let rows = sqlx::query_as::<_, MfaEnrollment>(
@@ -139,7 +306,7 @@ pub async fn get_mfa(
                 enrollments.len(),
                 params.account_id
             );
-            Ok(Json(enrollments))
+            Ok(Json(enrollments.into_iter().map(redact_enrollment).collect()))
         }
         Err(e) => {
             error!("get_mfa: database error: {}", e);
@@ -148,23 +315,246 @@ pub async fn get_mfa(
     }
 }
 
+/// List MFA enrollments across accounts (`GET /admin/mfa`).
+///
+/// Admin-only.  Filters with `?mfa_type=totp|sms&enabled=true|false` and
+/// paginates with `?page=&per_page=`.  Secrets are never selected and phone
+/// numbers are masked to their last four digits.
+pub async fn list_mfa_enrollments(
+    admin: AdminUser,
+    Extension(pool): Extension<PgPool>,
+    Query(pagination): Query<PaginationParams>,
+    Query(filter): Query<MfaAdminFilter>,
+) -> Result<Json<PaginatedResponse<MfaAdminListItem>>, AppError> {
+    info!(
+        "GET /admin/mfa: requested by {} (mfa_type={:?} enabled={:?})",
+        admin.account_id, filter.mfa_type, filter.enabled
+    );
+    validate_mfa_filter(&filter)?;
+    let (per_page, offset) = pagination.clamped();
+
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM impala_mfa
+         WHERE ($1::text IS NULL OR mfa_type = $1) AND ($2::bool IS NULL OR enabled = $2)",
+    )
+    .bind(&filter.mfa_type)
+    .bind(filter.enabled)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| {
+        error!("list_mfa_enrollments: count query error: {}", e);
+        AppError::InternalError("Database error".to_string())
+    })?;
+
+    let rows = sqlx::query_as::<_, MfaAdminListItem>(
+        r#"
+        SELECT account_id, mfa_type, phone_number, enabled,
+               EXTRACT(EPOCH FROM updated_at)::bigint AS updated_at
+        FROM impala_mfa
+        WHERE ($1::text IS NULL OR mfa_type = $1) AND ($2::bool IS NULL OR enabled = $2)
+        ORDER BY account_id, mfa_type
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(&filter.mfa_type)
+    .bind(filter.enabled)
+    .bind(per_page)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        error!("list_mfa_enrollments: database error: {}", e);
+        AppError::InternalError("Database error".to_string())
+    })?;
+
+    Ok(Json(PaginatedResponse {
+        data: rows.into_iter().map(mask_enrollment).collect(),
+        page: pagination.page.max(1),
+        per_page: per_page as u64,
+        total: total as u64,
+    }))
+}
+
+/// Reject an `mfa_type` filter other than `totp` or `sms`.
+fn validate_mfa_filter(filter: &MfaAdminFilter) -> Result<(), AppError> {
+    match filter.mfa_type.as_deref() {
+        None | Some("totp") | Some("sms") => Ok(()),
+        Some(_) => Err(AppError::BadRequest(
+            "mfa_type must be 'totp' or 'sms'".to_string(),
+        )),
+    }
+}
+
+/// Drop the TOTP secret and mask the phone number before an enrollment
+/// leaves the bridge.
+fn redact_enrollment(enrollment: MfaEnrollment) -> MfaEnrollmentSummary {
+    MfaEnrollmentSummary {
+        account_id: enrollment.account_id,
+        mfa_type: enrollment.mfa_type,
+        phone_number: enrollment.phone_number.as_deref().map(mask_phone_number),
+        enabled: enrollment.enabled,
+    }
+}
+
+fn mask_enrollment(mut item: MfaAdminListItem) -> MfaAdminListItem {
+    item.phone_number = item.phone_number.as_deref().map(mask_phone_number);
+    item
+}
+
+/// Keep only the last four digits of a phone number, e.g. `***4567`.
+fn mask_phone_number(phone: &str) -> String {
+    let digits: Vec<char> = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    let tail: String = digits[digits.len().saturating_sub(4)..].iter().collect();
+    format!("***{}", tail)
+}
+
+/// Redis key holding the pending SMS code for an account.
+fn sms_code_key(account_id: &str) -> String {
+    format!("mfa:sms:{}:sms", account_id)
+}
+
+/// A random 6-digit SMS code, zero-padded.
+fn generate_sms_code() -> String {
+    format!("{:06}", rand::random_range(0..1_000_000u32))
+}
+
+/// The `POST /mfa/send` reply, identical whether or not a code went out so
+/// it can't be used to discover which accounts have SMS enrolled.
+fn sms_code_sent_response() -> MfaResponse {
+    MfaResponse {
+        success: true,
+        message: "If SMS MFA is enrolled, a code has been sent".to_string(),
+        provisioning_uri: None,
+    }
+}
+
+/// Send an SMS MFA code (`POST /mfa/send`).
+///
+/// Generates a 6-digit code, stores it in Redis for `MFA_SMS_CODE_TTL_SECS`
+/// for `POST /mfa/verify`, and texts it via Twilio to the enrolled phone
+/// number.  Only the account itself may ask; rate-limited per account and
+/// per client IP.
+pub async fn send_mfa_code(
+    user: AuthenticatedUser,
+    client_ip: ClientIp,
+    Extension(pool): Extension<PgPool>,
+    Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(http_client): Extension<reqwest::Client>,
+    AppJson(payload): AppJson<SendMfaCodeRequest>,
+) -> Result<Json<MfaResponse>, AppError> {
+    crate::auth::require_owner(&user, &payload.account_id)?;
+    info!("POST /mfa/send: account_id={}", payload.account_id);
+
+    let (Some(sid), Some(token), Some(from_number)) = (
+        config.twilio_sid.as_deref(),
+        config.twilio_token.as_deref(),
+        config.twilio_number.as_deref(),
+    ) else {
+        error!("send_mfa_code: TWILIO_SID, TWILIO_TOKEN and TWILIO_NUMBER must be set");
+        return Err(AppError::InternalError("SMS delivery is not configured".to_string()));
+    };
+
+    crate::redis_helpers::check_rate_limit(
+        &redis_pool,
+        "mfa_send",
+        &payload.account_id,
+        MFA_SMS_SEND_MAX_REQUESTS,
+        MFA_SMS_SEND_WINDOW_SECS,
+    )
+    .await?;
+    if let ClientIp(Some(ip)) = &client_ip {
+        crate::redis_helpers::check_rate_limit(
+            &redis_pool,
+            "mfa_send_ip",
+            ip,
+            MFA_SMS_SEND_IP_MAX_REQUESTS,
+            MFA_SMS_SEND_IP_WINDOW_SECS,
+        )
+        .await?;
+    }
+
+    let phone = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT phone_number FROM impala_mfa
+         WHERE account_id = $1 AND mfa_type = 'sms' AND enabled",
+    )
+    .bind(&payload.account_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        error!("send_mfa_code: database error: {}", e);
+        AppError::InternalError("Database error".to_string())
+    })?
+    .flatten();
+
+    let Some(phone) = phone else {
+        debug!("send_mfa_code: no SMS enrollment for account_id={}", payload.account_id);
+        return Ok(Json(sms_code_sent_response()));
+    };
+    if let Err(e) = crate::validate::validate_phone_number(&phone) {
+        warn!(
+            "send_mfa_code: enrolled phone number for account_id={} is invalid: {}",
+            payload.account_id, e
+        );
+        return Ok(Json(sms_code_sent_response()));
+    }
+
+    let code = generate_sms_code();
+    let mut conn = redis_pool.get().await.map_err(|e| {
+        error!("send_mfa_code: Redis connection error: {}", e);
+        AppError::InternalError("Redis connection error".to_string())
+    })?;
+    redis::AsyncCommands::set_ex::<_, _, ()>(&mut *conn, sms_code_key(&payload.account_id), &code, MFA_SMS_CODE_TTL_SECS)
+        .await
+        .map_err(|e| {
+            error!("send_mfa_code: failed to store code: {}", e);
+            AppError::InternalError("Redis error".to_string())
+        })?;
+
+    let body = format!("Your Impala verification code is {}", code);
+    let masked = mask_phone_number(&phone);
+    match crate::twilio::send_sms(&http_client, sid, token, from_number, &phone, &body).await {
+        Ok((status, _)) if (200..300).contains(&status) => {
+            info!("send_mfa_code: code sent to {} for account_id={}", masked, payload.account_id);
+        }
+        Ok((status, response_body)) => {
+            error!("send_mfa_code: Twilio returned HTTP {} for {}: {}", status, masked, response_body);
+        }
+        Err(e) => {
+            error!("send_mfa_code: Twilio request failed for {}: {}", masked, e);
+        }
+    }
+
+    Ok(Json(sms_code_sent_response()))
+}
+
 /// Verify an MFA code (`POST /mfa/verify`).
 ///
 /// For TOTP: validates the 6-digit code against the stored secret using totp-rs.
 /// For SMS: validates the code stored in Redis.
 pub async fn verify_mfa(
+    user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
     Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
+    Extension(config): Extension<Arc<Config>>,
     Extension(metrics): Extension<Arc<AppMetrics>>,
-    Json(payload): Json<VerifyMfaRequest>,
+    AppJson(payload): AppJson<VerifyMfaRequest>,
 ) -> Result<Json<MfaResponse>, AppError> {
+    crate::auth::require_owner(&user, &payload.account_id)?;
     info!(
         "POST /mfa/verify: mfa_type={} for account_id={}",
         payload.mfa_type, payload.account_id
     );
 
-    // Brute force protection: check if account is locked out
-    crate::redis_helpers::check_mfa_lockout(&redis_pool, &payload.account_id, &payload.mfa_type, crate::constants::LOCKOUT_THRESHOLD).await?;
+    // Brute force protection: count the attempt, refuse it if locked out
+    crate::redis_helpers::claim_mfa_attempt(
+        &redis_pool,
+        &payload.account_id,
+        &payload.mfa_type,
+        config.mfa_lockout_threshold,
+        config.mfa_lockout_window_secs,
+    )
+    .await?;
 
     let enrollment = sqlx::query_as::<_, MfaEnrollment>(
         "SELECT account_id, mfa_type, secret, phone_number, enabled
@@ -236,24 +626,16 @@ pub async fn verify_mfa(
                             AppError::InternalError("Invalid TOTP configuration".to_string())
                         })?;
 
-                    let totp = TOTP::new(
-                        Algorithm::SHA1,
-                        6,
-                        1,
-                        30,
-                        secret,
-                        Some("Impala".to_string()),
-                        payload.account_id.clone(),
-                    )
-                    .map_err(|e| {
-                        error!("verify_mfa: failed to create TOTP verifier: {}", e);
-                        AppError::InternalError("TOTP verification error".to_string())
-                    })?;
+                    let totp = build_totp(secret, &payload.account_id, config.mfa_totp_skew_steps)
+                        .map_err(|e| {
+                            error!("verify_mfa: failed to create TOTP verifier: {}", e);
+                            AppError::InternalError("TOTP verification error".to_string())
+                        })?;
 
-                    let is_valid = totp.check_current(&payload.code).map_err(|e| {
-                        error!("verify_mfa: TOTP check error: {}", e);
-                        AppError::InternalError("TOTP verification error".to_string())
-                    })?;
+                    // Accepts the current step and `mfa_totp_skew_steps`
+                    // either side, to tolerate device clock drift
+                    let now = chrono::Utc::now().timestamp() as u64;
+                    let is_valid = totp.check(&payload.code, now);
 
                     if is_valid {
                         info!(
@@ -280,8 +662,6 @@ pub async fn verify_mfa(
                             KeyValue::new("mfa_type", "totp"),
                             KeyValue::new("outcome", "failed"),
                         ]);
-                        // Track failed attempt for brute force protection
-                        crate::redis_helpers::increment_mfa_attempts(&redis_pool, &payload.account_id, &payload.mfa_type, crate::constants::LOCKOUT_DURATION_SECS).await;
                         Ok(Json(MfaResponse {
                             success: false,
                             message: "Invalid verification code".to_string(),
@@ -291,7 +671,7 @@ pub async fn verify_mfa(
                 }
                 "sms" => {
                     // SMS: verify against code stored in Redis
-                    let sms_key = format!("mfa:sms:{}:{}", payload.account_id, payload.mfa_type);
+                    let sms_key = sms_code_key(&payload.account_id);
                     let mut conn = redis_pool.get().await.map_err(|e| {
                         error!("verify_mfa: Redis connection error: {}", e);
                         AppError::InternalError("Redis connection error".to_string())
@@ -328,7 +708,6 @@ pub async fn verify_mfa(
                                     KeyValue::new("mfa_type", "sms"),
                                     KeyValue::new("outcome", "failed"),
                                 ]);
-                                crate::redis_helpers::increment_mfa_attempts(&redis_pool, &payload.account_id, &payload.mfa_type, crate::constants::LOCKOUT_DURATION_SECS).await;
                                 Ok(Json(MfaResponse {
                                     success: false,
                                     message: "Invalid verification code".to_string(),
@@ -345,7 +724,6 @@ pub async fn verify_mfa(
                                 KeyValue::new("mfa_type", "sms"),
                                 KeyValue::new("outcome", "failed"),
                             ]);
-                            crate::redis_helpers::increment_mfa_attempts(&redis_pool, &payload.account_id, &payload.mfa_type, crate::constants::LOCKOUT_DURATION_SECS).await;
                             Ok(Json(MfaResponse {
                                 success: false,
                                 message: "Invalid verification code".to_string(),
@@ -367,3 +745,249 @@ pub async fn verify_mfa(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Uri;
+
+    fn filter_from(query: &str) -> MfaAdminFilter {
+        let uri: Uri = format!("/admin/mfa?{}", query).parse().unwrap();
+        Query::<MfaAdminFilter>::try_from_uri(&uri).unwrap().0
+    }
+
+    #[test]
+    fn test_filter_by_type_and_enabled() {
+        let filter = filter_from("mfa_type=sms&enabled=false&page=2");
+        assert_eq!(filter.mfa_type.as_deref(), Some("sms"));
+        assert_eq!(filter.enabled, Some(false));
+        assert!(validate_mfa_filter(&filter).is_ok());
+
+        let filter = filter_from("enabled=true");
+        assert_eq!(filter.mfa_type, None);
+        assert_eq!(filter.enabled, Some(true));
+
+        let filter = filter_from("");
+        assert!(filter.mfa_type.is_none() && filter.enabled.is_none());
+    }
+
+    #[test]
+    fn test_filter_rejects_unknown_type() {
+        let filter = filter_from("mfa_type=email");
+        assert!(matches!(validate_mfa_filter(&filter), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_listing_omits_secret_and_masks_phone() {
+        let item = mask_enrollment(MfaAdminListItem {
+            account_id: "alice".to_string(),
+            mfa_type: "sms".to_string(),
+            phone_number: Some("+1 (555) 123-4567".to_string()),
+            enabled: true,
+            updated_at: Some(1_700_000_000),
+        });
+        let json = serde_json::to_value(&item).unwrap();
+        assert!(json.get("secret").is_none());
+        assert_eq!(json["phone_number"], "***4567");
+        assert_eq!(json["updated_at"], 1_700_000_000);
+    }
+
+    #[test]
+    fn test_owner_listing_never_serializes_secret() {
+        let secret = "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP";
+        let enrollments = vec![
+            redact_enrollment(MfaEnrollment {
+                account_id: "alice".to_string(),
+                mfa_type: "totp".to_string(),
+                secret: Some(secret.to_string()),
+                phone_number: None,
+                enabled: true,
+            }),
+            redact_enrollment(MfaEnrollment {
+                account_id: "alice".to_string(),
+                mfa_type: "sms".to_string(),
+                secret: None,
+                phone_number: Some("+15551234567".to_string()),
+                enabled: false,
+            }),
+        ];
+        let body = serde_json::to_string(&enrollments).unwrap();
+        assert!(!body.contains(secret));
+        assert!(!body.contains("secret"));
+        assert!(!body.contains("+15551234567"));
+
+        let json = serde_json::to_value(&enrollments).unwrap();
+        assert_eq!(json[0]["mfa_type"], "totp");
+        assert_eq!(json[0]["enabled"], true);
+        assert_eq!(json[1]["phone_number"], "***4567");
+        assert_eq!(json[1]["enabled"], false);
+    }
+
+    #[test]
+    fn test_mask_phone_number_short_input() {
+        assert_eq!(mask_phone_number("12"), "***12");
+        assert_eq!(mask_phone_number(""), "***");
+    }
+
+    #[test]
+    fn test_sms_code_is_six_digits() {
+        for _ in 0..1_000 {
+            let code = generate_sms_code();
+            assert_eq!(code.len(), 6, "{}", code);
+            assert!(code.chars().all(|c| c.is_ascii_digit()), "{}", code);
+        }
+        assert_eq!(sms_code_key("payala-1"), "mfa:sms:payala-1:sms");
+    }
+
+    #[tokio::test]
+    async fn test_send_requires_twilio_configuration() {
+        let mut config = crate::config::load_config();
+        config.twilio_sid = None;
+        let redis_pool = deadpool_redis::Config::from_url("redis://127.0.0.1:1/")
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        let result = send_mfa_code(
+            AuthenticatedUser { account_id: "payala-1".to_string(), scopes: Vec::new() },
+            ClientIp(None),
+            Extension(PgPool::connect_lazy("postgres://localhost/unused").unwrap()),
+            Extension(Arc::new(redis_pool)),
+            Extension(Arc::new(config)),
+            Extension(reqwest::Client::new()),
+            AppJson(SendMfaCodeRequest { account_id: "payala-1".to_string() }),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::InternalError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_send_rejects_other_account() {
+        let redis_pool = deadpool_redis::Config::from_url("redis://127.0.0.1:1/")
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        let result = send_mfa_code(
+            AuthenticatedUser { account_id: "payala-2".to_string(), scopes: Vec::new() },
+            ClientIp(Some("203.0.113.7".to_string())),
+            Extension(PgPool::connect_lazy("postgres://localhost/unused").unwrap()),
+            Extension(Arc::new(redis_pool)),
+            Extension(Arc::new(crate::config::load_config())),
+            Extension(reqwest::Client::new()),
+            AppJson(SendMfaCodeRequest { account_id: "payala-1".to_string() }),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::Forbidden)));
+    }
+
+    const TOTP_TEST_SECRET: &[u8] = b"totp-test-secret-20b";
+
+    #[test]
+    fn test_totp_accepts_adjacent_steps_within_skew() {
+        let totp = build_totp(TOTP_TEST_SECRET.to_vec(), "payala-1", DEFAULT_TOTP_SKEW_STEPS).unwrap();
+        let now = 1_700_000_010;
+        assert!(totp.check(&totp.generate(now), now));
+        // Device clock one step behind or ahead
+        assert!(totp.check(&totp.generate(now - 30), now));
+        assert!(totp.check(&totp.generate(now + 30), now));
+        // Two steps off is outside the default window
+        assert!(!totp.check(&totp.generate(now - 60), now));
+        assert!(!totp.check(&totp.generate(now + 60), now));
+    }
+
+    #[test]
+    fn test_totp_skew_is_configurable() {
+        let now = 1_700_000_010;
+        let exact = build_totp(TOTP_TEST_SECRET.to_vec(), "payala-1", 0).unwrap();
+        assert!(exact.check(&exact.generate(now), now));
+        assert!(!exact.check(&exact.generate(now - 30), now));
+
+        let wide = build_totp(TOTP_TEST_SECRET.to_vec(), "payala-1", crate::constants::MAX_TOTP_SKEW_STEPS).unwrap();
+        assert!(wide.check(&wide.generate(now - 60), now));
+        // Even the widest window refuses codes older than it
+        assert!(!wide.check(&wide.generate(now - 90), now));
+    }
+
+    #[test]
+    fn test_confirm_accepts_code_from_pending_secret() {
+        let secret = Secret::generate_secret();
+        let encoded = secret.to_encoded().to_string();
+        let totp = build_totp(secret.to_bytes().unwrap(), "payala-1", DEFAULT_TOTP_SKEW_STEPS).unwrap();
+        let now = 1_700_000_010;
+
+        let outcome = check_pending_totp(Some(&encoded), "payala-1", &totp.generate(now), 1, now).unwrap();
+        assert_eq!(outcome, PendingTotp::Confirmed);
+        let outcome = check_pending_totp(Some(&encoded), "payala-1", &totp.generate(now - 600), 1, now).unwrap();
+        assert_eq!(outcome, PendingTotp::WrongCode);
+    }
+
+    #[test]
+    fn test_confirm_after_pending_secret_expired() {
+        // Redis has dropped the pending secret: even a well-formed code fails
+        let outcome = check_pending_totp(None, "payala-1", "123456", 1, 1_700_000_010).unwrap();
+        assert_eq!(outcome, PendingTotp::Expired);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn test_pending_totp_secret_expires() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let mut conn = redis::Client::open(url)
+            .unwrap()
+            .get_multiplexed_async_connection()
+            .await
+            .expect("Redis not reachable");
+        let key = pending_totp_key(&format!("test-{}", uuid::Uuid::new_v4()));
+        let _: () = redis::AsyncCommands::set_ex(&mut conn, &key, "JBSWY3DPEHPK3PXP", 1).await.unwrap();
+        let pending: Option<String> = redis::AsyncCommands::get(&mut conn, &key).await.unwrap();
+        assert!(pending.is_some());
+
+        tokio::time::sleep(std::time::Duration::from_millis(1_100)).await;
+        let pending: Option<String> = redis::AsyncCommands::get(&mut conn, &key).await.unwrap();
+        assert_eq!(
+            check_pending_totp(pending.as_deref(), "payala-1", "123456", 1, 1_700_000_010).unwrap(),
+            PendingTotp::Expired
+        );
+    }
+
+    fn verify_app() -> axum::Router {
+        let redis_pool = deadpool_redis::Config::from_url(
+            std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string()),
+        )
+        .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+        .unwrap();
+        axum::Router::new()
+            .route("/mfa/verify", axum::routing::post(verify_mfa))
+            .layer(Extension(Arc::new(redis_pool)))
+            .layer(Extension(PgPool::connect_lazy("postgres://localhost/unused").unwrap()))
+            .layer(Extension(Arc::new(crate::config::load_config())))
+            .layer(Extension(crate::telemetry::create_metrics()))
+            .layer(Extension(Arc::new(crate::jwt::JwtKeys::new(TEST_SECRET.to_string(), Vec::new()))))
+    }
+
+    const TEST_SECRET: &str = "test-secret-for-mfa-verify-handler";
+
+    fn verify_request(bearer: Option<&str>) -> axum::http::Request<axum::body::Body> {
+        let mut request = axum::http::Request::post("/mfa/verify")
+            .header(axum::http::header::CONTENT_TYPE, "application/json");
+        if let Some(token) = bearer {
+            request = request.header(axum::http::header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let body = serde_json::json!({ "account_id": "payala-1", "mfa_type": "totp", "code": "000000" });
+        request.body(axum::body::Body::from(body.to_string())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_anonymous_request() {
+        use tower::ServiceExt;
+        let response = verify_app().oneshot(verify_request(None)).await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL for the revocation check"]
+    async fn test_verify_rejects_other_accounts_token() {
+        use tower::ServiceExt;
+        let key = crate::jwt::SigningKey::hmac(TEST_SECRET.as_bytes());
+        let token = crate::jwt::encode_temporal_token(&key, "payala-2").unwrap();
+        let response = verify_app().oneshot(verify_request(Some(&token))).await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::FORBIDDEN);
+    }
+}
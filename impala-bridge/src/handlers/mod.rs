@@ -1,14 +1,20 @@
 pub mod account;
+pub mod admin;
 pub mod authenticate;
 pub mod card;
+pub mod contract;
+pub mod cron;
 pub mod device_token;
+pub mod features;
 pub mod health;
+pub mod index;
 pub mod logout;
 pub mod mfa;
 pub mod network;
 pub mod notification_subscription;
 pub mod notify;
 pub mod okta;
+pub mod strkey;
 pub mod subscribe;
 pub mod sync;
 pub mod token;
@@ -5,6 +5,7 @@ use sqlx::PgPool;
 
 use crate::auth::AuthenticatedUser;
 use crate::error::AppError;
+use crate::extract::AppJson;
 use crate::models::{
     CreateSubscriptionRequest, PaginatedResponse, PaginationParams, SubscriptionListItem,
     SubscriptionResponse, UpdateSubscriptionRequest,
@@ -72,7 +73,7 @@ pub async fn list_subscriptions(
 pub async fn create_subscription(
     user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<CreateSubscriptionRequest>,
+    AppJson(payload): AppJson<CreateSubscriptionRequest>,
 ) -> Result<Json<SubscriptionResponse>, AppError> {
     info!(
         "POST /notification/subscriptions: event_type={} medium={} for account_id={}",
@@ -146,7 +147,7 @@ pub async fn update_subscription(
     user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
     Path(id): Path<i32>,
-    Json(payload): Json<UpdateSubscriptionRequest>,
+    AppJson(payload): AppJson<UpdateSubscriptionRequest>,
 ) -> Result<Json<SubscriptionResponse>, AppError> {
     info!(
         "PUT /notification/subscriptions/{}: enabled={} for account_id={}",
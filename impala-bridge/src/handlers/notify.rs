@@ -2,9 +2,13 @@ use axum::extract::{Extension, Query};
 use axum::Json;
 use log::{debug, error, info, warn};
 use sqlx::PgPool;
+use std::sync::Arc;
 
 use crate::auth::AuthenticatedUser;
+use crate::config::Config;
 use crate::error::AppError;
+use crate::handlers::account::{account_reference_error, require_account_exists};
+use crate::extract::AppJson;
 use crate::models::{
     CreateNotifyRequest, NotifyListItem, NotifyResponse, PaginatedResponse, PaginationParams,
     UpdateNotifyRequest,
@@ -61,7 +65,7 @@ pub async fn list_notify(
 pub async fn create_notify(
     user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<CreateNotifyRequest>,
+    AppJson(payload): AppJson<CreateNotifyRequest>,
 ) -> Result<Json<NotifyResponse>, AppError> {
     crate::auth::require_owner(&user, &payload.account_id)?;
     info!(
@@ -79,6 +83,7 @@ pub async fn create_notify(
                 payload.medium
             ),
             id: None,
+            changes: None,
         }));
     }
 
@@ -92,6 +97,8 @@ pub async fn create_notify(
         crate::validate::validate_callback_url(url)?;
     }
 
+    require_account_exists(&pool, &payload.account_id, "create_notify").await?;
+
     let result = sqlx::query_scalar::<_, i32>(
         r#"
         INSERT INTO notify (account_id, medium, mobile, wa, signal, tel, email, url, app)
@@ -121,12 +128,10 @@ pub async fn create_notify(
                 success: true,
                 message: "Notification record created successfully".to_string(),
                 id: Some(id),
+                changes: None,
             }))
         }
-        Err(e) => {
-            error!("create_notify: database error: {}", e);
-            Err(AppError::InternalError("Database error".to_string()))
-        }
+        Err(e) => Err(account_reference_error("create_notify", e)),
     }
 }
 
@@ -134,7 +139,8 @@ pub async fn create_notify(
 pub async fn update_notify(
     user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<UpdateNotifyRequest>,
+    Extension(config): Extension<Arc<Config>>,
+    AppJson(payload): AppJson<UpdateNotifyRequest>,
 ) -> Result<Json<NotifyResponse>, AppError> {
     info!("PUT /notify: updating id={}", payload.id);
 
@@ -149,6 +155,7 @@ pub async fn update_notify(
                     medium
                 ),
                 id: None,
+                changes: None,
             }));
         }
     }
@@ -163,43 +170,9 @@ pub async fn update_notify(
         crate::validate::validate_callback_url(url)?;
     }
 
-    let mut set_parts = Vec::new();
-    let mut param_index = 2u32;
+    let fields = notify_update_fields(&payload);
 
-    if payload.medium.is_some() {
-        set_parts.push(format!("medium = ${}::notify_medium", param_index));
-        param_index += 1;
-    }
-    if payload.mobile.is_some() {
-        set_parts.push(format!("mobile = ${}", param_index));
-        param_index += 1;
-    }
-    if payload.wa.is_some() {
-        set_parts.push(format!("wa = ${}", param_index));
-        param_index += 1;
-    }
-    if payload.signal.is_some() {
-        set_parts.push(format!("signal = ${}", param_index));
-        param_index += 1;
-    }
-    if payload.tel.is_some() {
-        set_parts.push(format!("tel = ${}", param_index));
-        param_index += 1;
-    }
-    if payload.email.is_some() {
-        set_parts.push(format!("email = ${}", param_index));
-        param_index += 1;
-    }
-    if payload.url.is_some() {
-        set_parts.push(format!("url = ${}", param_index));
-        param_index += 1;
-    }
-    if payload.app.is_some() {
-        set_parts.push(format!("app = ${}", param_index));
-        param_index += 1;
-    }
-
-    if set_parts.is_empty() {
+    if fields.is_empty() {
         warn!(
             "update_notify: no fields provided to update for id={}",
             payload.id
@@ -208,41 +181,39 @@ pub async fn update_notify(
             success: false,
             message: "No fields provided to update".to_string(),
             id: None,
+            changes: None,
         }));
     }
 
-    let sql = format!(
-        "UPDATE notify SET {} WHERE id = $1 AND account_id = ${}",
-        set_parts.join(", "),
-        param_index
-    );
+    if payload.dry_run {
+        let select = format!(
+            "SELECT medium::text AS medium, {} FROM notify WHERE id = $1 AND account_id = $2",
+            NOTIFY_UPDATE_COLUMNS[1..].join(", ")
+        );
+        let current = sqlx::query(&select)
+            .bind(payload.id)
+            .bind(&user.account_id)
+            .fetch_optional(&pool)
+            .await
+            .and_then(|row| {
+                row.map(|r| crate::sql::row_values(&r, NOTIFY_UPDATE_COLUMNS))
+                    .transpose()
+            })
+            .map_err(|e| {
+                error!("update_notify: database error reading current row: {}", e);
+                AppError::InternalError("Database error".to_string())
+            })?;
+        return Ok(Json(preview_notify_update(payload.id, current.as_deref(), &fields)));
+    }
+
+    let sql = build_notify_update(&fields);
+    // id, each field, account_id
+    crate::sql::log_dynamic_query("update_notify", &sql, fields.len() + 2, config.debug_mode);
 
     let mut query = sqlx::query(&sql);
     query = query.bind(payload.id);
-
-    if let Some(ref val) = payload.medium {
-        query = query.bind(val);
-    }
-    if let Some(ref val) = payload.mobile {
-        query = query.bind(val);
-    }
-    if let Some(ref val) = payload.wa {
-        query = query.bind(val);
-    }
-    if let Some(ref val) = payload.signal {
-        query = query.bind(val);
-    }
-    if let Some(ref val) = payload.tel {
-        query = query.bind(val);
-    }
-    if let Some(ref val) = payload.email {
-        query = query.bind(val);
-    }
-    if let Some(ref val) = payload.url {
-        query = query.bind(val);
-    }
-    if let Some(ref val) = payload.app {
-        query = query.bind(val);
+    for (_, value) in &fields {
+        query = query.bind(*value);
     }
     query = query.bind(&user.account_id);
 
@@ -256,6 +227,7 @@ pub async fn update_notify(
                     success: false,
                     message: "No notification record found with the provided id".to_string(),
                     id: None,
+                    changes: None,
                 }))
             } else {
                 info!("update_notify: updated id={}", payload.id);
@@ -263,6 +235,7 @@ pub async fn update_notify(
                     success: true,
                     message: "Notification record updated successfully".to_string(),
                     id: Some(payload.id),
+                    changes: None,
                 }))
             }
         }
@@ -272,3 +245,130 @@ pub async fn update_notify(
         }
     }
 }
+
+/// Columns `PUT /notify` may set; read back for dry runs.
+const NOTIFY_UPDATE_COLUMNS: &[&str] = &["medium", "mobile", "wa", "signal", "tel", "email", "url", "app"];
+
+/// Dry-run response for `PUT /notify`: the diff between the current record
+/// (if found) and `fields`.  Nothing is written.
+fn preview_notify_update(
+    id: i32,
+    current: Option<&[(&str, Option<String>)]>,
+    fields: &[(&str, &str)],
+) -> NotifyResponse {
+    match current {
+        None => NotifyResponse {
+            success: false,
+            message: "No notification record found with the provided id".to_string(),
+            id: None,
+            changes: None,
+        },
+        Some(current) => {
+            let changes = crate::sql::diff_fields(current, fields);
+            NotifyResponse {
+                success: true,
+                message: format!("Dry run: {} field(s) would change", changes.len()),
+                id: Some(id),
+                changes: Some(changes),
+            }
+        }
+    }
+}
+
+/// Columns set by a `PUT /notify` request with their new values, in bind order.
+fn notify_update_fields(payload: &UpdateNotifyRequest) -> Vec<(&'static str, &str)> {
+    [
+        ("medium", payload.medium.as_deref()),
+        ("mobile", payload.mobile.as_deref()),
+        ("wa", payload.wa.as_deref()),
+        ("signal", payload.signal.as_deref()),
+        ("tel", payload.tel.as_deref()),
+        ("email", payload.email.as_deref()),
+        ("url", payload.url.as_deref()),
+        ("app", payload.app.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(column, value)| value.map(|v| (column, v)))
+    .collect()
+}
+
+/// Build the `UPDATE notify` statement: `$1` is the record id, `$2..` the
+/// fields, and the last placeholder the owning account.
+fn build_notify_update(fields: &[(&str, &str)]) -> String {
+    let set_parts: Vec<String> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, (column, _))| {
+            let cast = if *column == "medium" { "::notify_medium" } else { "" };
+            format!("{} = ${}{}", column, i + 2, cast)
+        })
+        .collect();
+    format!(
+        "UPDATE notify SET {} WHERE id = $1 AND account_id = ${}",
+        set_parts.join(", "),
+        fields.len() + 2
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> UpdateNotifyRequest {
+        UpdateNotifyRequest {
+            id: 7,
+            medium: Some("email".to_string()),
+            mobile: None,
+            wa: None,
+            signal: None,
+            tel: Some("+15551234567".to_string()),
+            email: Some("a@example.com".to_string()),
+            url: None,
+            app: None,
+            dry_run: true,
+        }
+    }
+
+    #[test]
+    fn test_dry_run_returns_diff() {
+        let payload = request();
+        let fields = notify_update_fields(&payload);
+        let current = vec![
+            ("medium", Some("email".to_string())),
+            ("tel", Some("+15550000000".to_string())),
+            ("email", None),
+        ];
+        let response = preview_notify_update(7, Some(&current), &fields);
+        assert!(response.success);
+        let changes = response.changes.unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "tel");
+        assert_eq!(changes[1].from, None);
+
+        assert!(!preview_notify_update(7, None, &fields).success);
+    }
+
+    #[test]
+    fn test_notify_update_bind_count_matches_set_fields() {
+        let payload = request();
+        let fields = notify_update_fields(&payload);
+        assert_eq!(fields.len(), 3);
+
+        let sql = build_notify_update(&fields);
+        assert_eq!(
+            sql,
+            "UPDATE notify SET medium = $2::notify_medium, tel = $3, email = $4 WHERE id = $1 AND account_id = $5"
+        );
+        assert_eq!(crate::sql::placeholder_count(&sql), fields.len() + 2);
+    }
+
+    #[test]
+    fn test_notify_update_single_field() {
+        let mut payload = request();
+        payload.medium = None;
+        payload.tel = None;
+        let fields = notify_update_fields(&payload);
+        assert_eq!(fields, [("email", "a@example.com")]);
+        assert_eq!(crate::sql::placeholder_count(&build_notify_update(&fields)), 3);
+    }
+}
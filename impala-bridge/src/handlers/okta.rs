@@ -4,11 +4,14 @@ use log::{debug, error, info, warn};
 use sqlx::PgPool;
 use std::sync::Arc;
 
+use crate::config::Config;
 use crate::constants::{
     AUTH_PROVIDER_OKTA, LOCKOUT_THRESHOLD, MAX_EMAIL_LENGTH,
     RATE_LIMIT_MAX_REQUESTS, RATE_LIMIT_WINDOW_SECS,
 };
 use crate::error::AppError;
+use crate::extract::AppJson;
+use crate::jwt::JwtKeys;
 use crate::models::{OktaConfigResponse, OktaTokenExchangeRequest, TokenResponse};
 use crate::okta::{self, OktaProvider};
 
@@ -18,10 +21,11 @@ use crate::okta::{self, OktaProvider};
 /// a local refresh + temporal token pair. Rate-limited per account.
 pub async fn okta_token_exchange(
     Extension(pool): Extension<PgPool>,
-    Extension(jwt_secret): Extension<Arc<String>>,
+    Extension(jwt_keys): Extension<Arc<JwtKeys>>,
     Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
+    Extension(config): Extension<Arc<Config>>,
     okta_provider: Option<Extension<Arc<OktaProvider>>>,
-    Json(payload): Json<OktaTokenExchangeRequest>,
+    AppJson(payload): AppJson<OktaTokenExchangeRequest>,
 ) -> Result<Json<TokenResponse>, AppError> {
     debug!("POST /auth/okta: token exchange request received");
 
@@ -117,9 +121,18 @@ pub async fn okta_token_exchange(
         AppError::InternalError("Database error".to_string())
     })?;
 
+    // Suspended or closed accounts can't obtain tokens through Okta either
+    let status = crate::handlers::account::fetch_account_status(&pool, &account_id).await?;
+    if !status.is_some_and(|s| s.can_authenticate()) {
+        warn!("okta: token exchange refused for inactive account_id={}", account_id);
+        return Err(AppError::Forbidden);
+    }
+
     // Issue local JWT tokens
-    let key = jwt_secret.as_bytes();
-    let (refresh_token, temporal_token) = crate::jwt::encode_token_pair(key, &account_id)?;
+    let scopes =
+        crate::handlers::account::fetch_account_scopes(&pool, &account_id, &config.admin_accounts).await?;
+    let (refresh_token, temporal_token) =
+        crate::jwt::encode_token_pair(&jwt_keys.signing_key(), &account_id, &scopes)?;
 
     info!("okta: tokens issued for account_id={}", account_id);
 
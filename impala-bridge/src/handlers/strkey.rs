@@ -0,0 +1,84 @@
+use axum::Json;
+use log::info;
+
+use crate::contract::encode_hex;
+use crate::extract::AppJson;
+use crate::models::{StrkeyValidateRequest, StrkeyValidateResponse};
+use crate::validate::{decode_strkey, strkey_checksum_valid, strkey_type_for_prefix};
+
+/// Decode and classify a Stellar strkey (`POST /stellar/strkey/validate`).
+///
+/// Secret seeds are recognised but their bytes are never returned.
+pub async fn validate_strkey(
+    AppJson(payload): AppJson<StrkeyValidateRequest>,
+) -> Json<StrkeyValidateResponse> {
+    let key = payload.strkey.trim();
+    let key_type = key.chars().next().and_then(strkey_type_for_prefix);
+    // Never log the key itself: it may be a secret seed.
+    info!("POST /stellar/strkey/validate: type={:?}", key_type);
+
+    let checksum_valid = strkey_checksum_valid(key).unwrap_or(false);
+    let response = match decode_strkey(key) {
+        Ok(decoded) => StrkeyValidateResponse {
+            valid: true,
+            key_type: Some(decoded.key_type.to_string()),
+            checksum_valid,
+            raw_hex: decoded.raw.as_deref().map(encode_hex),
+            muxed_id: decoded.muxed_id.map(|id| id.to_string()),
+        },
+        Err(_) => StrkeyValidateResponse {
+            valid: false,
+            key_type: key_type.map(str::to_string),
+            checksum_valid,
+            raw_hex: None,
+            muxed_id: None,
+        },
+    };
+    Json(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn validate(key: &str) -> StrkeyValidateResponse {
+        let Json(response) = validate_strkey(AppJson(StrkeyValidateRequest {
+            strkey: key.to_string(),
+        }))
+        .await;
+        response
+    }
+
+    #[tokio::test]
+    async fn test_validate_account() {
+        let r = validate("GAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB7JZX").await;
+        assert!(r.valid && r.checksum_valid);
+        assert_eq!(r.key_type.as_deref(), Some("account"));
+        assert!(r.raw_hex.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_validate_secret_never_echoes_bytes() {
+        let r = validate("SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI").await;
+        assert!(r.valid);
+        assert_eq!(r.key_type.as_deref(), Some("secret_seed"));
+        assert_eq!(r.raw_hex, None);
+    }
+
+    #[tokio::test]
+    async fn test_validate_muxed_reports_id() {
+        let r = validate("MAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6AAAAAAAAAAAA6X66").await;
+        assert!(r.valid);
+        assert_eq!(r.key_type.as_deref(), Some("muxed_account"));
+        assert_eq!(r.muxed_id.as_deref(), Some("7"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_corrupted_checksum() {
+        let r = validate("CAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6N4P").await;
+        assert!(!r.valid);
+        assert!(!r.checksum_valid);
+        assert_eq!(r.key_type.as_deref(), Some("contract"));
+        assert_eq!(r.raw_hex, None);
+    }
+}
@@ -4,16 +4,20 @@ use log::{error, info, warn};
 use std::sync::Arc;
 
 use crate::auth::AuthenticatedUser;
+use crate::constants::DEFAULT_HTTP_CLIENT_TIMEOUT_SECS;
 use crate::error::AppError;
-use crate::models::{SubscribeRequest, SubscribeResponse};
-use crate::streams;
+use crate::extract::AppJson;
+use crate::models::{SubscribeRequest, SubscribeResponse, UnsubscribeRequest};
+use crate::streams::{self, PayalaListeners};
 
 /// Subscribe to network event streams (`POST /subscribe`).
 pub async fn subscribe(
     _user: AuthenticatedUser,
+    Extension(config): Extension<Arc<crate::config::Config>>,
     Extension(stellar_config): Extension<Arc<crate::config::StellarConfig>>,
     Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
-    Json(payload): Json<SubscribeRequest>,
+    Extension(payala_listeners): Extension<Arc<PayalaListeners>>,
+    AppJson(payload): AppJson<SubscribeRequest>,
 ) -> Result<Json<SubscribeResponse>, AppError> {
     info!("POST /subscribe: network={}", payload.network);
     match payload.network.as_str() {
@@ -22,11 +26,32 @@ pub async fn subscribe(
                 "{}/ledgers?cursor=now&order=asc",
                 stellar_config.horizon_url.trim_end_matches('/')
             );
+            if let Some(min) = stellar_config.min_protocol_version {
+                let client = reqwest::Client::builder()
+                    .timeout(std::time::Duration::from_secs(DEFAULT_HTTP_CLIENT_TIMEOUT_SECS))
+                    .build()
+                    .map_err(|e| {
+                        error!("subscribe: failed to create HTTP client: {}", e);
+                        AppError::InternalError("Failed to create HTTP client".to_string())
+                    })?;
+                match streams::check_horizon_protocol(&client, &stellar_config.horizon_url, min).await {
+                    Ok(version) => info!("subscribe: Horizon protocol version {}", version),
+                    Err(message) => {
+                        error!("subscribe: refusing Stellar stream: {}", message);
+                        return Ok(Json(SubscribeResponse {
+                            success: false,
+                            message,
+                        }));
+                    }
+                }
+            }
+
             let redis = redis_pool.clone();
+            let raw_retention = config.stellar_raw_event_retention;
 
             info!("subscribe: starting Stellar Horizon SSE stream");
             tokio::spawn(async move {
-                if let Err(e) = streams::stellar_stream(&url, &redis).await {
+                if let Err(e) = streams::stellar_stream(&url, &redis, raw_retention).await {
                     error!("subscribe: Stellar stream terminated with error: {}", e);
                 }
             });
@@ -48,18 +73,26 @@ pub async fn subscribe(
                 }
             };
 
-            let redis = redis_pool.clone();
-
             info!(
                 "subscribe: starting Payala TCP listener on {}",
                 listen_endpoint
             );
-            let ep_clone = listen_endpoint.clone();
-            tokio::spawn(async move {
-                if let Err(e) = streams::payala_stream(&ep_clone, &redis).await {
-                    error!("subscribe: Payala stream terminated with error: {}", e);
-                }
-            });
+            if !payala_listeners
+                .start(&listen_endpoint, redis_pool.clone())
+                .await
+            {
+                warn!(
+                    "subscribe: Payala listener already running on {}",
+                    listen_endpoint
+                );
+                return Ok(Json(SubscribeResponse {
+                    success: false,
+                    message: format!(
+                        "Already subscribed to Payala network events on {}",
+                        listen_endpoint
+                    ),
+                }));
+            }
 
             Ok(Json(SubscribeResponse {
                 success: true,
@@ -78,3 +111,89 @@ pub async fn subscribe(
         }
     }
 }
+
+/// Stop a network event stream started by `/subscribe` (`POST /unsubscribe`).
+///
+/// For the Payala network, open connections are drained and the listener
+/// socket is released before this returns, so the endpoint can be reused.
+pub async fn unsubscribe(
+    _user: AuthenticatedUser,
+    Extension(payala_listeners): Extension<Arc<PayalaListeners>>,
+    AppJson(payload): AppJson<UnsubscribeRequest>,
+) -> Result<Json<SubscribeResponse>, AppError> {
+    info!("POST /unsubscribe: network={}", payload.network);
+    match payload.network.as_str() {
+        "payala" => {
+            let listen_endpoint = match payload.listen_endpoint {
+                Some(ref ep) if !ep.is_empty() => ep.clone(),
+                _ => {
+                    warn!("unsubscribe: missing listen_endpoint for payala network");
+                    return Ok(Json(SubscribeResponse {
+                        success: false,
+                        message: "listen_endpoint is required for the payala network".to_string(),
+                    }));
+                }
+            };
+
+            if payala_listeners.stop(&listen_endpoint).await {
+                info!("unsubscribe: Payala listener on {} closed", listen_endpoint);
+                Ok(Json(SubscribeResponse {
+                    success: true,
+                    message: format!(
+                        "Unsubscribed from Payala network events on {}",
+                        listen_endpoint
+                    ),
+                }))
+            } else {
+                warn!("unsubscribe: no Payala listener on {}", listen_endpoint);
+                Ok(Json(SubscribeResponse {
+                    success: false,
+                    message: format!("No Payala subscription on {}", listen_endpoint),
+                }))
+            }
+        }
+        _ => {
+            warn!("unsubscribe: unsupported network '{}'", payload.network);
+            Ok(Json(SubscribeResponse {
+                success: false,
+                message: format!("Unsubscribe not supported for network: {}", payload.network),
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::load_config;
+    use crate::streams::tests::serve_horizon_root;
+    use tokio_util::sync::CancellationToken;
+
+    #[tokio::test]
+    async fn test_subscribe_refuses_old_horizon_protocol() {
+        let config = load_config();
+        let mut stellar_config = config.stellar_config();
+        stellar_config.horizon_url = serve_horizon_root(20).await;
+        stellar_config.min_protocol_version = Some(22);
+        let redis_pool = deadpool_redis::Config::from_url("redis://127.0.0.1:1")
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+
+        let Json(response) = subscribe(
+            AuthenticatedUser { account_id: "acct".to_string(), scopes: Vec::new() },
+            Extension(Arc::new(config)),
+            Extension(Arc::new(stellar_config)),
+            Extension(Arc::new(redis_pool)),
+            Extension(Arc::new(PayalaListeners::new(CancellationToken::new(), ""))),
+            AppJson(SubscribeRequest {
+                network: "stellar".to_string(),
+                listen_endpoint: None,
+            }),
+        )
+        .await
+        .unwrap();
+
+        assert!(!response.success);
+        assert!(response.message.contains("protocol version 20"), "{}", response.message);
+    }
+}
@@ -1,21 +1,23 @@
 use axum::extract::Extension;
 use axum::Json;
 use log::{debug, error, info};
-use redis::AsyncCommands;
 use sqlx::PgPool;
 use std::sync::Arc;
 
 use crate::auth::AuthenticatedUser;
-use crate::constants::DEFAULT_HTTP_CLIENT_TIMEOUT_SECS;
+use crate::cache::redis_set_json;
 use crate::error::AppError;
-use crate::models::{SyncRequest, SyncResponse};
+use crate::extract::AppJson;
+use crate::models::{SyncRecord, SyncRequest, SyncResponse};
+use crate::stellar_rpc::{GetTransactionsRequest, StellarRpcClient};
 
-/// Core sync logic: record a sync timestamp in Redis and reconcile with Stellar RPC.
-/// Returns the recorded timestamp on success.
+/// Core sync logic: record a sync timestamp in Redis (`sync:{account_id}`)
+/// and reconcile with Stellar RPC.  Returns the recorded timestamp on success.
 pub async fn sync_account_core(
     pool: &PgPool,
     redis_pool: &deadpool_redis::Pool,
-    stellar_rpc_url: &str,
+    key_prefix: &str,
+    rpc: &StellarRpcClient,
     account_id: &str,
 ) -> Result<String, String> {
     let mut conn = redis_pool.get().await.map_err(|e| {
@@ -27,53 +29,34 @@ pub async fn sync_account_core(
         .format("%Y-%m-%dT%H:%M:%S%.6fZ")
         .to_string();
 
-    conn.set::<_, _, ()>(account_id, &timestamp)
+    let record = SyncRecord { synced_at: timestamp.clone() };
+    redis_set_json(&mut conn, key_prefix, &format!("sync:{}", account_id), &record, None)
         .await
         .map_err(|e| {
-            error!("sync_account_core: Redis set error: {}", e);
-            format!("Redis error: {}", e)
+            error!("sync_account_core: {}", e);
+            e.to_string()
         })?;
 
     // Call Stellar Soroban RPC getTransactions and check against local DB
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(DEFAULT_HTTP_CLIENT_TIMEOUT_SECS))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-    let rpc_request = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "getTransactions",
-        "params": {}
-    });
+    match rpc.get_transactions(&GetTransactionsRequest::default()).await {
+        Ok(page) => {
+            for tx in &page.transactions {
+                let exists = sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM transaction WHERE stellar_tx_id = $1",
+                )
+                .bind(&tx.tx_hash)
+                .fetch_one(pool)
+                .await;
 
-    match client.post(stellar_rpc_url).json(&rpc_request).send().await {
-        Ok(response) => {
-            if let Ok(body) = response.json::<serde_json::Value>().await {
-                if let Some(transactions) = body["result"]["transactions"].as_array() {
-                    for tx in transactions {
-                        if let Some(tx_id) = tx["id"].as_str() {
-                            let exists = sqlx::query_scalar::<_, i64>(
-                                "SELECT COUNT(*) FROM transaction WHERE stellar_tx_id = $1",
-                            )
-                            .bind(tx_id)
-                            .fetch_one(pool)
-                            .await;
-
-                            if let Ok(count) = exists {
-                                if count > 0 {
-                                    debug!("sync_account_core: matched local tx {}", tx_id);
-                                }
-                            }
-                        }
+                if let Ok(count) = exists {
+                    if count > 0 {
+                        debug!("sync_account_core: matched local tx {}", tx.tx_hash);
                     }
                 }
             }
         }
         Err(e) => {
-            error!(
-                "sync_account_core: Stellar RPC getTransactions error: {}",
-                e
-            );
+            error!("sync_account_core: getTransactions failed: {}", e);
         }
     }
 
@@ -84,16 +67,22 @@ pub async fn sync_account_core(
 pub async fn sync_account(
     _user: AuthenticatedUser,
     Extension(pool): Extension<PgPool>,
+    Extension(config): Extension<Arc<crate::config::Config>>,
     Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
-    Extension(stellar_config): Extension<Arc<crate::config::StellarConfig>>,
-    Json(payload): Json<SyncRequest>,
+    Extension(rpc): Extension<Arc<StellarRpcClient>>,
+    AppJson(payload): AppJson<SyncRequest>,
 ) -> Result<Json<SyncResponse>, AppError> {
     info!("POST /sync: account_id={}", payload.account_id);
 
-    let timestamp =
-        sync_account_core(&pool, &redis_pool, &stellar_config.rpc_url, &payload.account_id)
-            .await
-            .map_err(AppError::InternalError)?;
+    let timestamp = sync_account_core(
+        &pool,
+        &redis_pool,
+        &config.redis_key_prefix,
+        &rpc,
+        &payload.account_id,
+    )
+    .await
+    .map_err(AppError::InternalError)?;
 
     Ok(Json(SyncResponse {
         success: true,
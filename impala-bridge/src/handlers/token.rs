@@ -1,16 +1,17 @@
 use axum::extract::Extension;
 use axum::Json;
-use jsonwebtoken::{decode, DecodingKey, Validation};
 use log::{debug, error, info, warn};
 use password_auth::verify_password;
 use sqlx::PgPool;
 use std::sync::Arc;
 
-use crate::constants::{
-    JWT_ISSUER, RATE_LIMIT_MAX_REQUESTS, RATE_LIMIT_WINDOW_SECS, TOKEN_TYPE_REFRESH,
-};
+use crate::config::Config;
+use crate::constants::{ACCOUNT_SUSPENDED_MESSAGE, REFRESH_TOKEN_TTL_SECS, TOKEN_TYPE_REFRESH};
 use crate::error::AppError;
+use crate::extract::AppJson;
+use crate::jwt::{JwtKeys, SigningKey};
 use crate::models::{Claims, TokenRequest, TokenResponse};
+use crate::ratelimit::ClientIp;
 
 /// Issue JWT tokens (`POST /token`).
 ///
@@ -19,24 +20,18 @@ use crate::models::{Claims, TokenRequest, TokenResponse};
 /// - Username + password -> refresh token
 pub async fn token(
     Extension(pool): Extension<PgPool>,
-    Extension(jwt_secret): Extension<Arc<String>>,
+    Extension(jwt_keys): Extension<Arc<JwtKeys>>,
     Extension(redis_pool): Extension<Arc<deadpool_redis::Pool>>,
-    Json(payload): Json<TokenRequest>,
+    Extension(config): Extension<Arc<Config>>,
+    client_ip: ClientIp,
+    AppJson(payload): AppJson<TokenRequest>,
 ) -> Result<Json<TokenResponse>, AppError> {
     debug!("POST /token: request received");
-    let key = jwt_secret.as_bytes();
+    let key = jwt_keys.signing_key();
 
     // Flow 1: refresh_token provided -> return a short-lived temporal_token
     if let Some(ref refresh_token) = payload.refresh_token {
-        let mut validation = Validation::new(jsonwebtoken::Algorithm::HS256);
-        validation.set_issuer(&[JWT_ISSUER]);
-
-        let token_data = decode::<Claims>(
-            refresh_token,
-            &DecodingKey::from_secret(key),
-            &validation,
-        )
-        .map_err(|e| {
+        let token_data = jwt_keys.decode_claims(refresh_token).map_err(|e| {
             warn!("token: invalid refresh token presented: {}", e);
             AppError::Unauthorized
         })?;
@@ -55,23 +50,27 @@ pub async fn token(
         }
 
         // Check if refresh token has been revoked
-        if crate::redis_helpers::is_token_revoked(&redis_pool, &token_data.claims.jti).await? {
+        if crate::redis_helpers::is_token_revoked(&redis_pool, &token_data.claims).await? {
             warn!("token: revoked refresh token presented");
             return Err(AppError::Unauthorized);
         }
 
-        let now = chrono::Utc::now().timestamp() as usize;
         let sub = token_data.claims.sub.clone();
 
-        // Issue rotated refresh + temporal token pair
-        let (new_refresh_token, temporal_token) = crate::jwt::encode_token_pair(key, &sub)?;
-
-        // Revoke the old refresh token
-        let remaining = token_data.claims.exp.saturating_sub(now);
-        if remaining > 0 {
-            crate::redis_helpers::revoke_token(&redis_pool, &token_data.claims.jti, remaining).await;
+        // Suspended or closed accounts can't exchange refresh tokens
+        let status = crate::handlers::account::fetch_account_status(&pool, &sub).await?;
+        if !status.is_some_and(|s| s.can_authenticate()) {
+            warn!("token: refresh refused for inactive account sub={}", sub);
+            return Err(AppError::Unauthorized);
         }
 
+        // Roles are re-read on every rotation, so a revoked role lapses
+        // with the refresh token rather than lasting its whole lifetime
+        let scopes =
+            crate::handlers::account::fetch_account_scopes(&pool, &sub, &config.admin_accounts).await?;
+        let (new_refresh_token, temporal_token) =
+            rotate_refresh_token(&redis_pool, &key, &token_data.claims, &scopes).await?;
+
         info!("token: tokens issued (with refresh rotation) for sub={}", sub);
         return Ok(Json(TokenResponse {
             success: true,
@@ -96,7 +95,7 @@ pub async fn token(
     }
 
     // Rate limiting check
-    crate::redis_helpers::check_rate_limit(&redis_pool, "token", username, RATE_LIMIT_MAX_REQUESTS, RATE_LIMIT_WINDOW_SECS).await?;
+    crate::ratelimit::check_login_rate_limit(&redis_pool, &config, "token", username, &client_ip).await?;
 
     let stored = sqlx::query_as::<_, (String, String)>(
         "SELECT password_hash, auth_provider FROM impala_auth WHERE account_id = $1",
@@ -139,7 +138,31 @@ pub async fn token(
         }));
     }
 
-    let refresh_token = crate::jwt::encode_refresh_token(key, username)?;
+    match crate::handlers::account::fetch_account_status(&pool, username).await? {
+        Some(status) if status.can_authenticate() => {}
+        Some(status) if !status.is_closed() => {
+            warn!("token: refresh token refused for suspended username={}", username);
+            return Ok(Json(TokenResponse {
+                success: false,
+                message: ACCOUNT_SUSPENDED_MESSAGE.to_string(),
+                refresh_token: None,
+                temporal_token: None,
+            }));
+        }
+        _ => {
+            warn!("token: no active account for username={}", username);
+            return Ok(Json(TokenResponse {
+                success: false,
+                message: "Invalid credentials".to_string(),
+                refresh_token: None,
+                temporal_token: None,
+            }));
+        }
+    }
+
+    let scopes =
+        crate::handlers::account::fetch_account_scopes(&pool, username, &config.admin_accounts).await?;
+    let refresh_token = crate::jwt::encode_refresh_token(&key, username, &scopes)?;
 
     info!("token: refresh token issued for username={}", username);
     Ok(Json(TokenResponse {
@@ -149,3 +172,85 @@ pub async fn token(
         temporal_token: None,
     }))
 }
+
+/// Exchange a refresh token for a new refresh + temporal pair in the same
+/// token family, consuming the old one.  Presenting an already consumed
+/// refresh token means it was copied: the whole family is revoked, so
+/// neither the thief nor the legitimate holder can keep using it.
+async fn rotate_refresh_token(
+    redis_pool: &deadpool_redis::Pool,
+    key: &SigningKey,
+    claims: &Claims,
+    scopes: &[String],
+) -> Result<(String, String), AppError> {
+    let now = chrono::Utc::now().timestamp() as usize;
+    let remaining = claims.exp.saturating_sub(now);
+
+    if !crate::redis_helpers::consume_refresh_token(redis_pool, &claims.jti, remaining).await? {
+        warn!("token: consumed refresh token replayed for sub={}, revoking its family", claims.sub);
+        if !claims.family.is_empty() {
+            crate::redis_helpers::revoke_token_family(redis_pool, &claims.family, REFRESH_TOKEN_TTL_SECS).await?;
+        }
+        return Err(AppError::Unauthorized);
+    }
+
+    // Tokens from before families existed start one now
+    let family = if claims.family.is_empty() {
+        crate::jwt::new_token_family()
+    } else {
+        claims.family.clone()
+    };
+    crate::jwt::encode_token_pair_in(key, &claims.sub, scopes, &family)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_SECRET: &[u8] = b"test-secret-for-refresh-rotation-32chars";
+
+    fn refresh_claims(token: &str) -> Claims {
+        JwtKeys::new(String::from_utf8(TEST_SECRET.to_vec()).unwrap(), Vec::new())
+            .decode_claims(token)
+            .unwrap()
+            .claims
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn test_replayed_refresh_token_revokes_family() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let redis_pool = deadpool_redis::Config::from_url(url)
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        redis_pool.get().await.expect("Redis not reachable");
+
+        let original = refresh_claims(&crate::jwt::encode_refresh_token(&SigningKey::hmac(TEST_SECRET), "payala-1", &[]).unwrap());
+        let (rotated, temporal) = rotate_refresh_token(&redis_pool, &SigningKey::hmac(TEST_SECRET), &original, &[]).await.unwrap();
+        let rotated = refresh_claims(&rotated);
+        let temporal = refresh_claims(&temporal);
+        assert_eq!(rotated.family, original.family);
+        assert_eq!(temporal.family, original.family);
+        assert!(!crate::redis_helpers::is_token_revoked(&redis_pool, &rotated).await.unwrap());
+
+        // Replaying the consumed token fails and kills the rotated tokens
+        assert!(matches!(
+            rotate_refresh_token(&redis_pool, &SigningKey::hmac(TEST_SECRET), &original, &[]).await,
+            Err(AppError::Unauthorized)
+        ));
+        assert!(crate::redis_helpers::is_token_revoked(&redis_pool, &rotated).await.unwrap());
+        assert!(crate::redis_helpers::is_token_revoked(&redis_pool, &temporal).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_rotation_fails_closed_without_redis() {
+        let redis_pool = deadpool_redis::Config::from_url("redis://127.0.0.1:1/")
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        let claims = refresh_claims(&crate::jwt::encode_refresh_token(&SigningKey::hmac(TEST_SECRET), "payala-1", &[]).unwrap());
+        assert!(matches!(
+            rotate_refresh_token(&redis_pool, &SigningKey::hmac(TEST_SECRET), &claims, &[]).await,
+            Err(AppError::Unauthorized)
+        ));
+    }
+}
@@ -7,6 +7,7 @@ use uuid::Uuid;
 
 use crate::auth::AuthenticatedUser;
 use crate::error::AppError;
+use crate::extract::AppJson;
 use crate::models::{CreateTransactionRequest, CreateTransactionResponse};
 use crate::notifications::{self, NotificationEvent};
 use crate::telemetry::AppMetrics;
@@ -18,7 +19,7 @@ pub async fn create_transaction(
     Extension(metrics): Extension<Arc<AppMetrics>>,
     sns_client: Option<Extension<Arc<aws_sdk_sns::Client>>>,
     sns_topic_arn: Option<Extension<Arc<String>>>,
-    Json(payload): Json<CreateTransactionRequest>,
+    AppJson(payload): AppJson<CreateTransactionRequest>,
 ) -> Result<Json<CreateTransactionResponse>, AppError> {
     info!(
         "POST /transaction: stellar_tx_id={:?} payala_tx_id={:?}",
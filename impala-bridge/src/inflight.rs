@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Caps how many operations run at once for the same key.  Used to bound
+/// concurrent Argon2 work per account: a burst of identical `/authenticate`
+/// retries is serialized instead of hashing in parallel.
+///
+/// Keys with nothing in flight are dropped, so the map only holds accounts
+/// that are currently authenticating.
+pub struct InFlightLimiter {
+    limit: usize,
+    slots: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+/// Held while an operation runs; releases its slot on drop.
+pub struct InFlightPermit<'a> {
+    limiter: &'a InFlightLimiter,
+    key: String,
+    semaphore: Arc<Semaphore>,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl InFlightLimiter {
+    pub fn new(limit: usize) -> Self {
+        InFlightLimiter {
+            limit: limit.max(1),
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for one of the `limit` slots for `key`.
+    pub async fn acquire(&self, key: &str) -> InFlightPermit<'_> {
+        let semaphore = self
+            .slots
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limit)))
+            .clone();
+        let permit = semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("in-flight semaphore is never closed");
+        InFlightPermit {
+            limiter: self,
+            key: key.to_string(),
+            semaphore,
+            permit: Some(permit),
+        }
+    }
+
+    #[cfg(test)]
+    fn tracked_keys(&self) -> usize {
+        self.slots.lock().unwrap().len()
+    }
+}
+
+impl Drop for InFlightPermit<'_> {
+    fn drop(&mut self) {
+        self.permit.take();
+        let mut slots = self.limiter.slots.lock().unwrap();
+        // One reference is the map's and one is ours; anything more is a
+        // waiter that still needs the semaphore.
+        if Arc::strong_count(&self.semaphore) == 2 {
+            slots.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Run `attempts` concurrent operations per key and return the highest
+    /// number seen running at once for any single key.
+    async fn peak_concurrency(limiter: Arc<InFlightLimiter>, keys: &[&str], attempts: usize) -> usize {
+        let running: Arc<Mutex<HashMap<String, usize>>> = Arc::default();
+        let peak = Arc::new(AtomicUsize::new(0));
+        let mut tasks = Vec::new();
+        for key in keys {
+            for _ in 0..attempts {
+                let (limiter, running, peak, key) =
+                    (limiter.clone(), running.clone(), peak.clone(), key.to_string());
+                tasks.push(tokio::spawn(async move {
+                    let _permit = limiter.acquire(&key).await;
+                    let now = {
+                        let mut running = running.lock().unwrap();
+                        let count = running.entry(key.clone()).or_default();
+                        *count += 1;
+                        *count
+                    };
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    // Stand-in for an Argon2 verification
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                    *running.lock().unwrap().get_mut(&key).unwrap() -= 1;
+                }));
+            }
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_attempts_for_one_account_are_bounded() {
+        let limiter = Arc::new(InFlightLimiter::new(1));
+        assert_eq!(peak_concurrency(limiter.clone(), &["payala-1"], 8).await, 1);
+        assert_eq!(limiter.tracked_keys(), 0, "idle accounts are forgotten");
+
+        let limiter = Arc::new(InFlightLimiter::new(2));
+        assert_eq!(peak_concurrency(limiter.clone(), &["payala-1", "payala-2"], 8).await, 2);
+        assert_eq!(limiter.tracked_keys(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_other_accounts_are_not_blocked() {
+        let limiter = InFlightLimiter::new(1);
+        let _held = limiter.acquire("payala-1").await;
+        // Would hang if keys shared a slot
+        let other = tokio::time::timeout(Duration::from_secs(1), limiter.acquire("payala-2")).await;
+        assert!(other.is_ok());
+    }
+}
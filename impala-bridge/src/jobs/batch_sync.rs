@@ -31,7 +31,8 @@ pub async fn execute(ctx: &WorkerContext, payload: &serde_json::Value) -> Result
         match sync_account_core(
             &ctx.pool,
             &ctx.redis_pool,
-            &ctx.stellar_rpc_url,
+            &ctx.config.redis_key_prefix,
+            &ctx.stellar_rpc,
             account_id,
         )
         .await
@@ -19,11 +19,17 @@ struct NotificationPayload {
     webhook_url: Option<String>,
     #[serde(default)]
     device_tokens: Option<Vec<String>>,
+    /// Event type and variables for operator templates; absent on jobs
+    /// that carry a ready-made message.
+    #[serde(default)]
+    event_type: Option<String>,
+    #[serde(default)]
+    variables: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Deliver a notification via webhook or SMS (Twilio).
 pub async fn execute(ctx: &WorkerContext, payload: &serde_json::Value) -> Result<(), JobError> {
-    let parsed: NotificationPayload =
+    let mut parsed: NotificationPayload =
         serde_json::from_value(payload.clone()).map_err(|e| {
             JobError::Permanent(format!("Invalid send_notification payload: {}", e))
         })?;
@@ -33,6 +39,17 @@ pub async fn execute(ctx: &WorkerContext, payload: &serde_json::Value) -> Result
         parsed.account_id, parsed.medium
     );
 
+    if let Some(ref event_type) = parsed.event_type {
+        if let Some((title, body)) =
+            ctx.config
+                .notification_templates
+                .render(event_type, &parsed.medium, &parsed.variables)
+        {
+            parsed.message_title = title.or(parsed.message_title);
+            parsed.message_body = body;
+        }
+    }
+
     let start = std::time::Instant::now();
     let delivery_result = match parsed.medium.as_str() {
         "webhook" => send_webhook(ctx, &parsed).await,
@@ -52,7 +69,7 @@ pub async fn execute(ctx: &WorkerContext, payload: &serde_json::Value) -> Result
     let medium_attr = KeyValue::new("medium", parsed.medium.clone());
     ctx.metrics
         .notification_delivery_duration
-        .record(duration, &[medium_attr.clone()]);
+        .record(duration, std::slice::from_ref(&medium_attr));
 
     let (service_id, service_response, result) = match delivery_result {
         Ok(tuple) => {
@@ -122,7 +139,7 @@ async fn send_webhook(
     let status = response.status().as_u16();
     let response_body = response.text().await.unwrap_or_default();
 
-    if status >= 200 && status < 300 {
+    if (200..300).contains(&status) {
         info!("send_notification: webhook to {} returned {}", url, status);
         Ok((
             url.to_string(),
@@ -154,30 +171,20 @@ async fn send_sms(
         JobError::Permanent("SMS medium requires destination phone number".to_string())
     })?;
 
-    let twilio_url = format!(
-        "https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
-        sid
-    );
-
-    let response = ctx
-        .http_client
-        .post(&twilio_url)
-        .basic_auth(sid, Some(token))
-        .form(&[
-            ("To", to_number),
-            ("From", from_number),
-            ("Body", &payload.message_body),
-        ])
-        .send()
-        .await
-        .map_err(|e| {
-            JobError::Transient(format!("Twilio request failed: {}", e))
-        })?;
-
-    let status = response.status().as_u16();
-    let response_body = response.text().await.unwrap_or_default();
+    let (status, response_body) = crate::twilio::send_sms(
+        &ctx.http_client,
+        sid,
+        token,
+        from_number,
+        to_number,
+        &payload.message_body,
+    )
+    .await
+    .map_err(|e| {
+        JobError::Transient(format!("Twilio request failed: {}", e))
+    })?;
 
-    if status >= 200 && status < 300 {
+    if (200..300).contains(&status) {
         info!(
             "send_notification: SMS to {} via Twilio returned {}",
             to_number, status
@@ -333,7 +340,7 @@ async fn send_push(
         let status = response.status().as_u16();
         let response_body = response.text().await.unwrap_or_default();
 
-        if status >= 200 && status < 300 {
+        if (200..300).contains(&status) {
             sent_count += 1;
             last_response = response_body;
         } else {
@@ -2,6 +2,8 @@ use log::{debug, info, warn};
 use opentelemetry::KeyValue;
 use serde::Deserialize;
 
+use crate::cache::redis_set_json;
+use crate::stellar_rpc::{GetTransactionsRequest, RpcError};
 use crate::worker::{JobError, WorkerContext};
 
 #[derive(Deserialize)]
@@ -24,61 +26,49 @@ pub async fn execute(ctx: &WorkerContext, payload: &serde_json::Value) -> Result
         parsed.start_ledger, parsed.end_ledger
     );
 
-    let rpc_request = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "getTransactions",
-        "params": {}
-    });
-
-    let response = ctx
-        .http_client
-        .post(&ctx.stellar_rpc_url)
-        .json(&rpc_request)
-        .send()
+    let request = GetTransactionsRequest {
+        start_ledger: parsed.start_ledger,
+        ..Default::default()
+    };
+    let page = ctx
+        .stellar_rpc
+        .get_transactions(&request)
         .await
-        .map_err(|e| {
-            JobError::Transient(format!("Stellar RPC request failed: {}", e))
+        .map_err(|e| match e {
+            RpcError::Rpc { .. } => JobError::Permanent(e.to_string()),
+            _ => JobError::Transient(e.to_string()),
         })?;
-
-    let body: serde_json::Value = response.json().await.map_err(|e| {
-        JobError::Transient(format!("Failed to parse Stellar RPC response: {}", e))
-    })?;
-
-    let transactions = body["result"]["transactions"]
-        .as_array()
-        .cloned()
-        .unwrap_or_default();
+    let transactions = page.transactions;
 
     info!(
-        "stellar_reconcile: fetched {} transaction(s) from RPC",
-        transactions.len()
+        "stellar_reconcile: fetched {} transaction(s) from RPC (latest ledger {})",
+        transactions.len(),
+        page.latest_ledger
     );
 
     let mut matched = 0u64;
     let mut unmatched = 0u64;
 
     for tx in &transactions {
-        if let Some(tx_id) = tx["id"].as_str() {
-            let count = sqlx::query_scalar::<_, i64>(
-                "SELECT COUNT(*) FROM transaction WHERE stellar_tx_id = $1",
-            )
-            .bind(tx_id)
-            .fetch_one(&ctx.pool)
-            .await
-            .map_err(|e| {
-                JobError::Transient(format!("Database query failed: {}", e))
-            })?;
+        let tx_id = tx.tx_hash.as_str();
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM transaction WHERE stellar_tx_id = $1",
+        )
+        .bind(tx_id)
+        .fetch_one(&ctx.pool)
+        .await
+        .map_err(|e| {
+            JobError::Transient(format!("Database query failed: {}", e))
+        })?;
 
-            if count > 0 {
-                debug!("stellar_reconcile: matched tx {}", tx_id);
-                matched += 1;
-                ctx.metrics.stellar_reconcile_txns.add(1, &[KeyValue::new("status", "matched")]);
-            } else {
-                warn!("stellar_reconcile: unmatched Stellar tx {}", tx_id);
-                unmatched += 1;
-                ctx.metrics.stellar_reconcile_txns.add(1, &[KeyValue::new("status", "unmatched")]);
-            }
+        if count > 0 {
+            debug!("stellar_reconcile: matched tx {}", tx_id);
+            matched += 1;
+            ctx.metrics.stellar_reconcile_txns.add(1, &[KeyValue::new("status", "matched")]);
+        } else {
+            warn!("stellar_reconcile: unmatched Stellar tx {} ({})", tx_id, tx.status);
+            unmatched += 1;
+            ctx.metrics.stellar_reconcile_txns.add(1, &[KeyValue::new("status", "unmatched")]);
         }
     }
 
@@ -94,13 +84,19 @@ pub async fn execute(ctx: &WorkerContext, payload: &serde_json::Value) -> Result
             "total": transactions.len(),
             "matched": matched,
             "unmatched": unmatched,
+            "cursor": page.cursor,
         });
-        let _: Result<(), _> = redis::AsyncCommands::set(
-            &mut *conn,
+        if let Err(e) = redis_set_json(
+            &mut conn,
+            &ctx.config.redis_key_prefix,
             "stellar:reconcile:latest",
-            serde_json::to_string(&summary).unwrap_or_default(),
+            &summary,
+            None,
         )
-        .await;
+        .await
+        {
+            warn!("stellar_reconcile: failed to store summary: {}", e);
+        }
     }
 
     Ok(())
@@ -1,15 +1,206 @@
-use jsonwebtoken::{encode, EncodingKey, Header};
+use jsonwebtoken::errors::{Error as JwtError, ErrorKind};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation};
 use log::error;
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::constants::{
-    JWT_ISSUER, REFRESH_TOKEN_TTL_SECS, TEMPORAL_TOKEN_TTL_SECS, TOKEN_TYPE_REFRESH,
-    TOKEN_TYPE_TEMPORAL,
+    DEFAULT_JWT_LEEWAY_SECS, JWT_ISSUER, MAX_PREVIOUS_JWT_SECRETS, REFRESH_TOKEN_TTL_SECS,
+    TEMPORAL_TOKEN_TTL_SECS, TOKEN_TYPE_REFRESH, TOKEN_TYPE_TEMPORAL,
 };
 use crate::error::AppError;
 use crate::models::Claims;
 
-/// Encode a long-lived refresh token for the given subject.
-pub fn encode_refresh_token(secret: &[u8], subject: &str) -> Result<String, AppError> {
+/// A retired signing secret still accepted for verification until
+/// `valid_until` (unix seconds).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PreviousJwtSecret {
+    #[serde(serialize_with = "redact_secret")]
+    pub secret: String,
+    pub valid_until: i64,
+}
+
+fn redact_secret<S: Serializer>(_: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(crate::constants::REDACTED)
+}
+
+/// A secret together with its `DecodingKey`, built once when the secret is
+/// installed rather than on every verification.
+struct CachedKey {
+    secret: String,
+    key: DecodingKey,
+}
+
+impl CachedKey {
+    fn new(secret: String) -> Self {
+        #[cfg(test)]
+        tests::KEYS_BUILT.with(|n| n.set(n.get() + 1));
+        let key = DecodingKey::from_secret(secret.as_bytes());
+        Self { secret, key }
+    }
+}
+
+struct PreviousKey {
+    cached: CachedKey,
+    valid_until: i64,
+}
+
+struct KeySet {
+    primary: CachedKey,
+    /// Newest first, at most `MAX_PREVIOUS_JWT_SECRETS` entries.
+    previous: Vec<PreviousKey>,
+}
+
+/// RS256 key pair.  Only the private half can sign; resource servers that
+/// verify tokens need just the public key.
+struct RsaKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+}
+
+enum Keys {
+    Hmac(KeySet),
+    Rsa(RsaKeys),
+}
+
+/// Key material and algorithm used to sign one token.
+pub struct SigningKey {
+    key: EncodingKey,
+    algorithm: Algorithm,
+}
+
+impl SigningKey {
+    /// An HS256 key for the shared secret `secret`.
+    pub fn hmac(secret: &[u8]) -> Self {
+        Self {
+            key: EncodingKey::from_secret(secret),
+            algorithm: Algorithm::HS256,
+        }
+    }
+}
+
+/// JWT signing material.  With HS256, tokens are signed with the primary
+/// secret, and verified against the primary and then any previous secret
+/// still inside its validity window, so the secret can be rotated without
+/// invalidating outstanding tokens.  With RS256, tokens are signed with the
+/// private key and verified with the public key.
+pub struct JwtKeys {
+    keys: Keys,
+    /// Clock skew tolerated on `exp`/`nbf`, in seconds.
+    leeway_secs: u64,
+}
+
+impl JwtKeys {
+    pub fn new(primary: String, mut previous: Vec<PreviousJwtSecret>) -> Self {
+        previous.truncate(MAX_PREVIOUS_JWT_SECRETS);
+        let previous = previous
+            .into_iter()
+            .map(|p| PreviousKey {
+                cached: CachedKey::new(p.secret),
+                valid_until: p.valid_until,
+            })
+            .collect();
+        Self {
+            keys: Keys::Hmac(KeySet {
+                primary: CachedKey::new(primary),
+                previous,
+            }),
+            leeway_secs: DEFAULT_JWT_LEEWAY_SECS,
+        }
+    }
+
+    /// RS256 keys from a PEM private key (PKCS#1 or PKCS#8) and the PEM
+    /// public key it pairs with.
+    pub fn rs256(private_pem: &[u8], public_pem: &[u8]) -> Result<Self, JwtError> {
+        Ok(Self {
+            keys: Keys::Rsa(RsaKeys {
+                encoding: EncodingKey::from_rsa_pem(private_pem)?,
+                decoding: DecodingKey::from_rsa_pem(public_pem)?,
+            }),
+            leeway_secs: DEFAULT_JWT_LEEWAY_SECS,
+        })
+    }
+
+    /// Tolerate `leeway_secs` of clock skew between issuer and verifier
+    /// when checking `exp` and `nbf`.
+    pub fn with_leeway(mut self, leeway_secs: u64) -> Self {
+        self.leeway_secs = leeway_secs;
+        self
+    }
+
+    /// The algorithm tokens are signed and verified with.
+    pub fn algorithm(&self) -> Algorithm {
+        match self.keys {
+            Keys::Hmac(_) => Algorithm::HS256,
+            Keys::Rsa(_) => Algorithm::RS256,
+        }
+    }
+
+    /// The key new tokens are signed with.
+    pub fn signing_key(&self) -> SigningKey {
+        match &self.keys {
+            Keys::Hmac(keys) => SigningKey::hmac(keys.primary.secret.as_bytes()),
+            Keys::Rsa(rsa) => SigningKey {
+                key: rsa.encoding.clone(),
+                algorithm: Algorithm::RS256,
+            },
+        }
+    }
+
+    /// Decode and validate a token (signature, issuer check) against every
+    /// key currently accepted.
+    pub fn decode_claims(&self, token: &str) -> Result<TokenData<Claims>, JwtError> {
+        self.decode_claims_at(token, chrono::Utc::now().timestamp())
+    }
+
+    fn decode_claims_at(&self, token: &str, now: i64) -> Result<TokenData<Claims>, JwtError> {
+        let mut validation = Validation::new(self.algorithm());
+        validation.set_issuer(&[JWT_ISSUER]);
+        validation.leeway = self.leeway_secs;
+        validation.validate_exp = true;
+        validation.validate_nbf = true;
+
+        let keys = match &self.keys {
+            Keys::Rsa(rsa) => return decode::<Claims>(token, &rsa.decoding, &validation),
+            Keys::Hmac(keys) => keys,
+        };
+        let accepted = std::iter::once(&keys.primary.key).chain(
+            keys.previous
+                .iter()
+                .filter(|p| p.valid_until > now)
+                .map(|p| &p.cached.key),
+        );
+
+        let mut last_err = JwtError::from(ErrorKind::InvalidSignature);
+        for key in accepted {
+            match decode::<Claims>(token, key, &validation) {
+                Ok(data) => return Ok(data),
+                // Only a signature mismatch means another secret may apply;
+                // anything else (expiry, issuer, malformed) is final.
+                Err(e) if matches!(e.kind(), ErrorKind::InvalidSignature) => last_err = e,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_err)
+    }
+}
+
+/// A fresh token family id, for a login that starts a new refresh chain.
+pub fn new_token_family() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Encode a long-lived refresh token for the given subject and scopes,
+/// starting a new token family.
+pub fn encode_refresh_token(key: &SigningKey, subject: &str, scopes: &[String]) -> Result<String, AppError> {
+    encode_refresh_token_in(key, subject, scopes, &new_token_family())
+}
+
+fn encode_refresh_token_in(
+    key: &SigningKey,
+    subject: &str,
+    scopes: &[String],
+    family: &str,
+) -> Result<String, AppError> {
     let now = chrono::Utc::now().timestamp() as usize;
 
     let claims = Claims {
@@ -19,21 +210,30 @@ pub fn encode_refresh_token(secret: &[u8], subject: &str) -> Result<String, AppE
         exp: now + REFRESH_TOKEN_TTL_SECS,
         jti: uuid::Uuid::new_v4().to_string(),
         iss: JWT_ISSUER.to_string(),
+        family: family.to_string(),
+        scopes: scopes.to_vec(),
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret),
-    )
+    encode(&Header::new(key.algorithm), &claims, &key.key)
     .map_err(|e| {
         error!("encode_refresh_token: failed to encode JWT: {}", e);
         AppError::InternalError("Failed to generate token".to_string())
     })
 }
 
-/// Encode a short-lived temporal token for the given subject.
-pub fn encode_temporal_token(secret: &[u8], subject: &str) -> Result<String, AppError> {
+/// Encode a short-lived temporal token for the given subject, in a new
+/// token family.  Handlers always issue temporal tokens in pairs.
+#[cfg(test)]
+pub fn encode_temporal_token(key: &SigningKey, subject: &str) -> Result<String, AppError> {
+    encode_temporal_token_in(key, subject, &[], &new_token_family())
+}
+
+fn encode_temporal_token_in(
+    key: &SigningKey,
+    subject: &str,
+    scopes: &[String],
+    family: &str,
+) -> Result<String, AppError> {
     let now = chrono::Utc::now().timestamp() as usize;
 
     let claims = Claims {
@@ -43,28 +243,39 @@ pub fn encode_temporal_token(secret: &[u8], subject: &str) -> Result<String, App
         exp: now + TEMPORAL_TOKEN_TTL_SECS,
         jti: uuid::Uuid::new_v4().to_string(),
         iss: JWT_ISSUER.to_string(),
+        family: family.to_string(),
+        scopes: scopes.to_vec(),
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret),
-    )
+    encode(&Header::new(key.algorithm), &claims, &key.key)
     .map_err(|e| {
         error!("encode_temporal_token: failed to encode JWT: {}", e);
         AppError::InternalError("Failed to generate token".to_string())
     })
 }
 
-/// Encode both a refresh and a temporal token for the given subject.
+/// Encode both a refresh and a temporal token for the given subject and
+/// scopes, in a new token family.
 ///
 /// Returns `(refresh_token, temporal_token)`.
 pub fn encode_token_pair(
-    secret: &[u8],
+    key: &SigningKey,
+    subject: &str,
+    scopes: &[String],
+) -> Result<(String, String), AppError> {
+    encode_token_pair_in(key, subject, scopes, &new_token_family())
+}
+
+/// Encode a refresh and temporal token pair in an existing token family,
+/// for refresh-token rotation.
+pub fn encode_token_pair_in(
+    key: &SigningKey,
     subject: &str,
+    scopes: &[String],
+    family: &str,
 ) -> Result<(String, String), AppError> {
-    let refresh = encode_refresh_token(secret, subject)?;
-    let temporal = encode_temporal_token(secret, subject)?;
+    let refresh = encode_refresh_token_in(key, subject, scopes, family)?;
+    let temporal = encode_temporal_token_in(key, subject, scopes, family)?;
     Ok((refresh, temporal))
 }
 
@@ -80,7 +291,7 @@ mod tests {
     #[test]
     fn test_encode_token_pair_returns_two_different_tokens() {
         let (refresh, temporal) =
-            encode_token_pair(TEST_SECRET, "alice").expect("token pair should succeed");
+            encode_token_pair(&SigningKey::hmac(TEST_SECRET), "alice", &[]).expect("token pair should succeed");
 
         assert_ne!(refresh, temporal, "refresh and temporal tokens must differ");
         assert!(!refresh.is_empty());
@@ -90,7 +301,7 @@ mod tests {
     #[test]
     fn test_tokens_decode_with_same_secret() {
         let (refresh, temporal) =
-            encode_token_pair(TEST_SECRET, "bob").expect("token pair should succeed");
+            encode_token_pair(&SigningKey::hmac(TEST_SECRET), "bob", &[]).expect("token pair should succeed");
 
         let mut validation = Validation::new(jsonwebtoken::Algorithm::HS256);
         validation.set_issuer(&[JWT_ISSUER]);
@@ -116,7 +327,8 @@ mod tests {
     #[test]
     fn test_tokens_contain_correct_claims() {
         let (refresh, temporal) =
-            encode_token_pair(TEST_SECRET, "carol").expect("token pair should succeed");
+            encode_token_pair(&SigningKey::hmac(TEST_SECRET), "carol", &["admin".to_string()])
+                .expect("token pair should succeed");
 
         let mut validation = Validation::new(jsonwebtoken::Algorithm::HS256);
         validation.set_issuer(&[JWT_ISSUER]);
@@ -145,6 +357,10 @@ mod tests {
         assert_eq!(refresh_claims.token_type, TOKEN_TYPE_REFRESH);
         assert_eq!(temporal_claims.token_type, TOKEN_TYPE_TEMPORAL);
 
+        // Scopes
+        assert_eq!(refresh_claims.scopes, ["admin"]);
+        assert_eq!(temporal_claims.scopes, ["admin"]);
+
         // Issuer
         assert_eq!(refresh_claims.iss, JWT_ISSUER);
         assert_eq!(temporal_claims.iss, JWT_ISSUER);
@@ -159,4 +375,171 @@ mod tests {
         assert!(refresh_claims.exp > now);
         assert!(temporal_claims.exp > now);
     }
+
+    thread_local! {
+        /// `DecodingKey`s built on this thread (tests run one per thread).
+        pub(super) static KEYS_BUILT: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    fn keys_built() -> usize {
+        KEYS_BUILT.with(|n| n.get())
+    }
+
+    const OLD_SECRET: &str = "old-secret-key-for-jwt-rotation-tests";
+
+    fn keys_with_previous(valid_until: i64) -> JwtKeys {
+        JwtKeys::new(
+            String::from_utf8(TEST_SECRET.to_vec()).unwrap(),
+            vec![PreviousJwtSecret {
+                secret: OLD_SECRET.to_string(),
+                valid_until,
+            }],
+        )
+    }
+
+    #[test]
+    fn test_keys_verify_primary_signed_token() {
+        let keys = keys_with_previous(0);
+        let token = encode_temporal_token(&keys.signing_key(), "dave").unwrap();
+        assert_eq!(keys.decode_claims(&token).unwrap().claims.sub, "dave");
+    }
+
+    #[test]
+    fn test_keys_verify_token_signed_with_previous_secret_in_window() {
+        let now = chrono::Utc::now().timestamp();
+        let keys = keys_with_previous(now + 600);
+        let token = encode_temporal_token(&SigningKey::hmac(OLD_SECRET.as_bytes()), "erin").unwrap();
+        assert_eq!(keys.decode_claims_at(&token, now).unwrap().claims.sub, "erin");
+    }
+
+    #[test]
+    fn test_keys_reject_token_signed_with_retired_secret() {
+        let now = chrono::Utc::now().timestamp();
+        let keys = keys_with_previous(now - 1);
+        let token = encode_temporal_token(&SigningKey::hmac(OLD_SECRET.as_bytes()), "frank").unwrap();
+        let err = keys.decode_claims_at(&token, now).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidSignature));
+    }
+
+    #[test]
+    fn test_keys_reject_unknown_secret() {
+        let keys = keys_with_previous(i64::MAX);
+        let token = encode_temporal_token(&SigningKey::hmac(b"some-other-secret-nobody-configured"), "gina").unwrap();
+        assert!(keys.decode_claims(&token).is_err());
+    }
+
+    #[test]
+    fn test_decoding_keys_built_once_and_reused() {
+        let now = chrono::Utc::now().timestamp();
+        let start = keys_built();
+        let keys = keys_with_previous(now + 600);
+        assert_eq!(keys_built() - start, 2, "one key per secret at construction");
+
+        let current = encode_temporal_token(&keys.signing_key(), "ivan").unwrap();
+        let old = encode_temporal_token(&SigningKey::hmac(OLD_SECRET.as_bytes()), "ivan").unwrap();
+        for _ in 0..1_000 {
+            assert!(keys.decode_claims_at(&current, now).is_ok());
+            assert!(keys.decode_claims_at(&old, now).is_ok());
+        }
+        assert_eq!(keys_built() - start, 2, "verification must reuse cached keys");
+    }
+
+    #[test]
+    fn test_previous_secrets_are_bounded() {
+        let now = chrono::Utc::now().timestamp();
+        let mut previous: Vec<PreviousJwtSecret> = (0..MAX_PREVIOUS_JWT_SECRETS + 3)
+            .map(|i| PreviousJwtSecret {
+                secret: format!("previous-secret-number-{:02}-padding-padding", i),
+                valid_until: now + 600,
+            })
+            .collect();
+        previous.push(PreviousJwtSecret { secret: OLD_SECRET.to_string(), valid_until: now + 600 });
+        let keys = JwtKeys::new(String::from_utf8(TEST_SECRET.to_vec()).unwrap(), previous);
+        let Keys::Hmac(set) = &keys.keys else { unreachable!() };
+        assert_eq!(set.previous.len(), MAX_PREVIOUS_JWT_SECRETS);
+
+        // Only the newest previous secrets are kept
+        let token = encode_temporal_token(&SigningKey::hmac(OLD_SECRET.as_bytes()), "lena").unwrap();
+        assert!(keys.decode_claims_at(&token, now).is_err(), "oldest secret dropped");
+    }
+
+    /// An HS256 temporal token whose `exp` and `nbf` are offset from now.
+    fn token_with_times(exp_offset: i64, nbf_offset: i64) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let claims = serde_json::json!({
+            "sub": "kim",
+            "token_type": TOKEN_TYPE_TEMPORAL,
+            "iat": now,
+            "nbf": now + nbf_offset,
+            "exp": now + exp_offset,
+            "jti": "leeway-test",
+            "iss": JWT_ISSUER,
+        });
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(TEST_SECRET)).unwrap()
+    }
+
+    fn test_keys() -> JwtKeys {
+        JwtKeys::new(String::from_utf8(TEST_SECRET.to_vec()).unwrap(), Vec::new())
+    }
+
+    #[test]
+    fn test_leeway_accepts_clock_skew_within_window() {
+        let keys = test_keys();
+        // Expired 10s ago by our clock; the issuer's may be behind
+        assert!(keys.decode_claims(&token_with_times(-10, -60)).is_ok());
+        // Not valid for another 10s; the issuer's clock may be ahead
+        assert!(keys.decode_claims(&token_with_times(600, 10)).is_ok());
+    }
+
+    #[test]
+    fn test_leeway_rejects_skew_beyond_window() {
+        let keys = test_keys();
+        let expired = keys.decode_claims(&token_with_times(-60, -120)).unwrap_err();
+        assert!(matches!(expired.kind(), ErrorKind::ExpiredSignature));
+        let immature = keys.decode_claims(&token_with_times(600, 60)).unwrap_err();
+        assert!(matches!(immature.kind(), ErrorKind::ImmatureSignature));
+
+        // With no leeway configured, any skew is rejected
+        let strict = test_keys().with_leeway(0);
+        assert!(strict.decode_claims(&token_with_times(-10, -60)).is_err());
+        assert!(strict.decode_claims(&token_with_times(600, 10)).is_err());
+    }
+
+    const RSA_PRIVATE_PEM: &[u8] = include_bytes!("../testdata/jwt_rs256_private.pem");
+    const RSA_PUBLIC_PEM: &[u8] = include_bytes!("../testdata/jwt_rs256_public.pem");
+
+    fn rsa_keys() -> JwtKeys {
+        JwtKeys::rs256(RSA_PRIVATE_PEM, RSA_PUBLIC_PEM).expect("test key pair should load")
+    }
+
+    #[test]
+    fn test_rs256_token_verifies_with_public_key_only() {
+        let keys = rsa_keys();
+        assert_eq!(keys.algorithm(), Algorithm::RS256);
+        let (refresh, temporal) = encode_token_pair(&keys.signing_key(), "judy", &[]).unwrap();
+
+        // What a resource server holding just the public key would do
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_issuer(&[JWT_ISSUER]);
+        let public = DecodingKey::from_rsa_pem(RSA_PUBLIC_PEM).unwrap();
+        for token in [&refresh, &temporal] {
+            let data = decode::<Claims>(token, &public, &validation).expect("RS256 token should verify");
+            assert_eq!(data.header.alg, Algorithm::RS256);
+            assert_eq!(data.claims.sub, "judy");
+        }
+        assert_eq!(keys.decode_claims(&refresh).unwrap().claims.token_type, TOKEN_TYPE_REFRESH);
+    }
+
+    #[test]
+    fn test_rs256_keys_reject_hs256_token() {
+        let keys = rsa_keys();
+        let forged = encode_temporal_token(&SigningKey::hmac(RSA_PUBLIC_PEM), "mallory").unwrap();
+        assert!(keys.decode_claims(&forged).is_err());
+    }
+
+    #[test]
+    fn test_rs256_rejects_malformed_pem() {
+        assert!(JwtKeys::rs256(b"not a pem", RSA_PUBLIC_PEM).is_err());
+        assert!(JwtKeys::rs256(RSA_PRIVATE_PEM, b"not a pem").is_err());
+    }
 }
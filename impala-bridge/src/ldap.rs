@@ -1,13 +1,16 @@
 use crate::config::Config;
+use crate::constants::LDAP_SEARCH_CACHE_CAPACITY;
 use crate::validate::ldap_escape;
 use log::{debug, error, info, warn};
 use sqlx::PgPool;
+use std::collections::{HashMap, VecDeque};
 
 /// Synchronize local accounts with an LDAP directory.
 ///
 /// Connects to the configured LDAP server, iterates over every
 /// `payala_account_id` in the `impala_account` table, and performs an
 /// LDAP search for each one using the configured filter and base DN.
+/// Identical filters are searched once per run (see `SearchCache`).
 pub async fn directory_sync(pool: &PgPool, config: &Config) {
     let ldap_url = match config.ldap_url {
         Some(ref url) => url.clone(),
@@ -68,7 +71,7 @@ pub async fn directory_sync(pool: &PgPool, config: &Config) {
     }
 
     let accounts =
-        sqlx::query_as::<_, (String,)>("SELECT payala_account_id FROM impala_account")
+        sqlx::query_as::<_, (String,)>("SELECT payala_account_id FROM impala_account WHERE NOT is_deleted")
             .fetch_all(pool)
             .await;
 
@@ -86,60 +89,229 @@ pub async fn directory_sync(pool: &PgPool, config: &Config) {
         account_ids.len()
     );
 
-    let mut found = 0u64;
-    let mut not_found = 0u64;
-    let mut errors = 0u64;
+    let account_ids: Vec<String> = account_ids.into_iter().map(|(id,)| id).collect();
+    let mut directory = LdapDirectory { ldap, base_dn };
+    let counts = check_accounts(&mut directory, &account_ids, &filter_template).await;
 
-    for (account_id,) in &account_ids {
+    info!(
+        "directory_sync: complete — {} found, {} not found, {} errors (out of {} accounts, {} LDAP searches)",
+        counts.found,
+        counts.not_found,
+        counts.errors,
+        account_ids.len(),
+        counts.searches
+    );
+
+    let _ = directory.ldap.unbind().await;
+}
+
+/// Something `check_accounts` can run LDAP searches against.
+trait DirectorySearch {
+    async fn search(&mut self, filter: &str) -> Result<Vec<ldap3::SearchEntry>, String>;
+}
+
+struct LdapDirectory {
+    ldap: ldap3::Ldap,
+    base_dn: String,
+}
+
+impl DirectorySearch for LdapDirectory {
+    async fn search(&mut self, filter: &str) -> Result<Vec<ldap3::SearchEntry>, String> {
+        let result = self
+            .ldap
+            .search(&self.base_dn, ldap3::Scope::Subtree, filter, vec!["*"])
+            .await
+            .map_err(|e| e.to_string())?;
+        // A non-success result code counts as no match
+        let entries = match result.success() {
+            Ok((entries, _res)) => entries,
+            Err(_) => vec![],
+        };
+        Ok(entries.into_iter().map(ldap3::SearchEntry::construct).collect())
+    }
+}
+
+/// Bounded least-recently-used cache of search results keyed by filter.
+/// Lives for one sync run only, so results never go stale across runs.
+struct SearchCache {
+    capacity: usize,
+    entries: HashMap<String, Vec<ldap3::SearchEntry>>,
+    /// Filters from least to most recently used.
+    order: VecDeque<String>,
+}
+
+impl SearchCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, filter: &str) -> Option<&Vec<ldap3::SearchEntry>> {
+        if self.entries.contains_key(filter) {
+            self.touch(filter);
+        }
+        self.entries.get(filter)
+    }
+
+    fn insert(&mut self, filter: String, result: Vec<ldap3::SearchEntry>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.contains_key(&filter) {
+            self.touch(&filter);
+        } else {
+            if self.entries.len() >= self.capacity {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.entries.remove(&oldest);
+                }
+            }
+            self.order.push_back(filter.clone());
+        }
+        self.entries.insert(filter, result);
+    }
+
+    fn touch(&mut self, filter: &str) {
+        if let Some(pos) = self.order.iter().position(|f| f == filter) {
+            if let Some(f) = self.order.remove(pos) {
+                self.order.push_back(f);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct SyncCounts {
+    found: u64,
+    not_found: u64,
+    errors: u64,
+    /// Searches actually sent to the directory (cache misses).
+    searches: u64,
+}
+
+/// Look up every account in the directory, reusing results for filters
+/// already searched in this run.  Failed searches are not cached.
+async fn check_accounts<D: DirectorySearch>(
+    directory: &mut D,
+    account_ids: &[String],
+    filter_template: &str,
+) -> SyncCounts {
+    let mut cache = SearchCache::new(LDAP_SEARCH_CACHE_CAPACITY);
+    let mut counts = SyncCounts::default();
+
+    for account_id in account_ids {
         // Escape the account_id before inserting into the LDAP filter
         let escaped_id = ldap_escape(account_id);
         let filter = filter_template.replace("{}", &escaped_id);
 
-        match ldap
-            .search(&base_dn, ldap3::Scope::Subtree, &filter, vec!["*"])
-            .await
-        {
-            Ok(search_result) => {
-                let entries = match search_result.success() {
-                    Ok((entries, _res)) => entries,
-                    Err(_) => vec![],
-                };
-                if entries.is_empty() {
-                    warn!(
-                        "directory_sync: account_id={} NOT found in LDAP (filter={})",
-                        account_id, filter
-                    );
-                    not_found += 1;
-                } else {
-                    for entry in &entries {
-                        let se = ldap3::SearchEntry::construct(entry.clone());
-                        debug!(
-                            "directory_sync: account_id={} found: dn={} attrs={:?}",
-                            account_id,
-                            se.dn,
-                            se.attrs.keys().collect::<Vec<_>>()
+        let entries = match cache.get(&filter) {
+            Some(entries) => entries.clone(),
+            None => {
+                counts.searches += 1;
+                match directory.search(&filter).await {
+                    Ok(entries) => {
+                        cache.insert(filter.clone(), entries.clone());
+                        entries
+                    }
+                    Err(e) => {
+                        error!(
+                            "directory_sync: LDAP search error for account_id={}: {}",
+                            account_id, e
                         );
+                        counts.errors += 1;
+                        continue;
                     }
-                    found += 1;
                 }
             }
-            Err(e) => {
-                error!(
-                    "directory_sync: LDAP search error for account_id={}: {}",
-                    account_id, e
+        };
+
+        if entries.is_empty() {
+            warn!(
+                "directory_sync: account_id={} NOT found in LDAP (filter={})",
+                account_id, filter
+            );
+            counts.not_found += 1;
+        } else {
+            for se in &entries {
+                debug!(
+                    "directory_sync: account_id={} found: dn={} attrs={:?}",
+                    account_id,
+                    se.dn,
+                    se.attrs.keys().collect::<Vec<_>>()
                 );
-                errors += 1;
             }
+            counts.found += 1;
         }
     }
 
-    info!(
-        "directory_sync: complete — {} found, {} not found, {} errors (out of {} accounts)",
-        found,
-        not_found,
-        errors,
-        account_ids.len()
-    );
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let _ = ldap.unbind().await;
+    /// Directory answering every filter with one entry, counting searches.
+    #[derive(Default)]
+    struct FakeDirectory {
+        filters: Vec<String>,
+        fail: bool,
+    }
+
+    impl DirectorySearch for FakeDirectory {
+        async fn search(&mut self, filter: &str) -> Result<Vec<ldap3::SearchEntry>, String> {
+            self.filters.push(filter.to_string());
+            if self.fail {
+                return Err("connection reset".to_string());
+            }
+            Ok(vec![ldap3::SearchEntry {
+                dn: "uid=shared,ou=people,dc=example,dc=com".to_string(),
+                attrs: HashMap::new(),
+                bin_attrs: HashMap::new(),
+            }])
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_same_filter_is_searched_once() {
+        let mut directory = FakeDirectory::default();
+        // The template ignores the account id, so both accounts share a filter
+        let counts = check_accounts(&mut directory, &ids(&["alice", "bob"]), "(ou=payala)").await;
+        assert_eq!(directory.filters, ["(ou=payala)"]);
+        assert_eq!(counts, SyncCounts { found: 2, not_found: 0, errors: 0, searches: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_distinct_filters_are_each_searched() {
+        let mut directory = FakeDirectory::default();
+        let counts = check_accounts(&mut directory, &ids(&["alice", "bob", "alice"]), "(uid={})").await;
+        assert_eq!(directory.filters, ["(uid=alice)", "(uid=bob)"]);
+        assert_eq!(counts.found, 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_searches_are_not_cached() {
+        let mut directory = FakeDirectory { fail: true, ..Default::default() };
+        let counts = check_accounts(&mut directory, &ids(&["alice", "bob"]), "(ou=payala)").await;
+        assert_eq!(directory.filters.len(), 2);
+        assert_eq!(counts.errors, 2);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let mut cache = SearchCache::new(2);
+        cache.insert("a".to_string(), vec![]);
+        cache.insert("b".to_string(), vec![]);
+        assert!(cache.get("a").is_some());
+        cache.insert("c".to_string(), vec![]);
+        assert!(cache.get("b").is_none(), "b was least recently used");
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+    }
 }
@@ -1,27 +1,39 @@
 mod auth;
+mod cache;
 mod config;
 mod constants;
+mod contract;
 mod error;
+mod extract;
 mod handlers;
+mod inflight;
 mod jobs;
 mod jwt;
 mod ldap;
+mod maintenance;
 mod middleware;
 mod models;
 mod notifications;
 mod okta;
+mod password;
+mod preflight;
+mod ratelimit;
 mod redis_helpers;
+mod retry;
+mod routes;
 mod sns;
+mod sql;
+mod stellar_rpc;
 mod streams;
 mod telemetry;
+mod tls;
+mod twilio;
 mod validate;
 mod vault;
 mod worker;
 
-use axum::routing::{get, post, put};
-use axum::Router;
 use axum::extract::Extension;
-use axum::http::{header, HeaderName, HeaderValue, Method};
+use axum::http::{header, HeaderName, HeaderValue};
 use log::{debug, error, info, warn};
 use sqlx::postgres::PgPoolOptions;
 use std::env;
@@ -29,14 +41,12 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio_util::sync::CancellationToken;
 use tower_http::compression::CompressionLayer;
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::set_header::SetResponseHeaderLayer;
 use tower_http::trace::TraceLayer;
 
 use config::load_config;
-use handlers::{account, authenticate, card, device_token, health, logout, mfa, network, notification_subscription, notify, okta as okta_handler, subscribe, sync, token, transaction};
 
 #[tokio::main]
 async fn main() {
@@ -74,6 +84,11 @@ async fn main() {
     // Create application metrics (no-op when OTEL is not configured)
     let metrics = telemetry::create_metrics();
 
+    // Pre-flight mode: probe dependencies, print a report and exit
+    if env::args().skip(1).any(|a| a == "--check") {
+        std::process::exit(preflight::run(&config).await);
+    }
+
     let run_mode = env::var("RUN_MODE").unwrap_or_else(|_| "server".to_string());
     info!("impala-bridge starting up (mode={})", run_mode);
     debug!("Config: {:?}", config);
@@ -90,7 +105,9 @@ async fn main() {
                     .to_string()
             }
             Err(e) => {
+                // Also on stderr: syslog may not be where the operator is looking
                 error!("Failed to unwrap DATABASE_URL from Vault: {}", e);
+                eprintln!("Failed to unwrap DATABASE_URL from Vault: {}", e);
                 std::process::exit(1);
             }
         }
@@ -99,15 +116,34 @@ async fn main() {
             .expect("Either DATABASE_URL or DATABASE_URL_WRAPPED must be set")
     };
 
-    // Create database connection pool with timeouts
-    let pool = PgPoolOptions::new()
-        .max_connections(config.db_max_connections)
-        .acquire_timeout(Duration::from_secs(constants::DB_ACQUIRE_TIMEOUT_SECS))
-        .idle_timeout(Duration::from_secs(constants::DB_IDLE_TIMEOUT_SECS))
-        .max_lifetime(Duration::from_secs(constants::DB_MAX_LIFETIME_SECS))
-        .connect(&database_url)
-        .await
-        .expect("Failed to connect to database");
+    // Create database connection pool with timeouts, waiting for Postgres
+    // to come up if it is not ready yet
+    let retry_delay = Duration::from_millis(config.startup_retry_delay_ms);
+    let pool = retry::retry_with_backoff(
+        "database",
+        config.startup_retry_attempts,
+        retry_delay,
+        || {
+            PgPoolOptions::new()
+                .max_connections(config.db_max_connections)
+                .acquire_timeout(Duration::from_secs(constants::DB_ACQUIRE_TIMEOUT_SECS))
+                .idle_timeout(Duration::from_secs(constants::DB_IDLE_TIMEOUT_SECS))
+                .max_lifetime(Duration::from_secs(constants::DB_MAX_LIFETIME_SECS))
+                .connect(&database_url)
+        },
+    )
+    .await
+    .unwrap_or_else(|e| {
+        error!(
+            "Failed to connect to database after {} attempt(s): {}",
+            config.startup_retry_attempts, e
+        );
+        eprintln!(
+            "Failed to connect to database after {} attempt(s): {}",
+            config.startup_retry_attempts, e
+        );
+        std::process::exit(1);
+    });
     info!(
         "Database connection pool established (max_connections={})",
         config.db_max_connections
@@ -115,10 +151,30 @@ async fn main() {
 
     // Create Redis connection pool
     let redis_url = env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
-    let redis_cfg = deadpool_redis::Config::from_url(&redis_url);
+    let mut redis_cfg = deadpool_redis::Config::from_url(&redis_url);
+    redis_cfg.pool = Some(deadpool_redis::PoolConfig::new(constants::DEFAULT_REDIS_POOL_SIZE));
     let redis_pool = redis_cfg
         .create_pool(Some(deadpool_redis::Runtime::Tokio1))
         .expect("Failed to create Redis connection pool");
+    // The pool connects lazily; make sure Redis answers before going on
+    if let Err(e) = retry::retry_with_backoff(
+        "redis",
+        config.startup_retry_attempts,
+        retry_delay,
+        || handlers::health::probe_redis(&redis_pool),
+    )
+    .await
+    {
+        error!(
+            "Failed to connect to Redis after {} attempt(s): {}",
+            config.startup_retry_attempts, e
+        );
+        eprintln!(
+            "Failed to connect to Redis after {} attempt(s): {}",
+            config.startup_retry_attempts, e
+        );
+        std::process::exit(1);
+    }
     let redis_pool = Arc::new(redis_pool);
     info!("Redis connection pool created");
 
@@ -144,20 +200,68 @@ async fn main() {
     }
 }
 
+/// HS256 keys from `JWT_SECRET` plus any previous secrets still in their
+/// overlap window.
+fn load_hs256_keys(config: &config::Config) -> jwt::JwtKeys {
+    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET environment variable must be set");
+    if jwt_secret.len() < crate::constants::JWT_SECRET_MIN_LENGTH {
+        error!("JWT_SECRET must be at least {} characters for security", crate::constants::JWT_SECRET_MIN_LENGTH);
+        std::process::exit(1);
+    }
+    let now = chrono::Utc::now().timestamp();
+    let previous_secrets: Vec<_> = config
+        .jwt_previous_secrets
+        .iter()
+        .filter(|p| p.valid_until > now)
+        .cloned()
+        .collect();
+    if previous_secrets.len() > crate::constants::MAX_PREVIOUS_JWT_SECRETS {
+        warn!("JWT: only the first {} previous secrets are accepted", crate::constants::MAX_PREVIOUS_JWT_SECRETS);
+    }
+    if !previous_secrets.is_empty() {
+        info!("JWT: accepting {} previous secret(s) for verification", previous_secrets.len());
+    }
+    jwt::JwtKeys::new(jwt_secret, previous_secrets)
+}
+
+/// RS256 keys from the PEM files at `JWT_PRIVATE_KEY_PATH` and
+/// `JWT_PUBLIC_KEY_PATH`.
+fn load_rs256_keys(config: &config::Config) -> jwt::JwtKeys {
+    let read_pem = |path: Option<&String>, name: &str| -> Vec<u8> {
+        let Some(path) = path else {
+            error!("{} must be set when JWT_ALGORITHM is RS256", name);
+            std::process::exit(1);
+        };
+        std::fs::read(path).unwrap_or_else(|e| {
+            error!("Failed to read {} '{}': {}", name, path, e);
+            std::process::exit(1);
+        })
+    };
+    let private_pem = read_pem(config.jwt_private_key_path.as_ref(), "JWT_PRIVATE_KEY_PATH");
+    let public_pem = read_pem(config.jwt_public_key_path.as_ref(), "JWT_PUBLIC_KEY_PATH");
+    jwt::JwtKeys::rs256(&private_pem, &public_pem).unwrap_or_else(|e| {
+        error!("Invalid RS256 key pair: {}", e);
+        std::process::exit(1);
+    })
+}
+
 async fn run_server(
     pool: sqlx::PgPool,
     redis_pool: Arc<deadpool_redis::Pool>,
     config: config::Config,
     metrics: Arc<telemetry::AppMetrics>,
 ) {
-    // JWT signing secret
-    let jwt_secret = Arc::new(
-        env::var("JWT_SECRET").expect("JWT_SECRET environment variable must be set"),
-    );
-    if jwt_secret.len() < crate::constants::JWT_SECRET_MIN_LENGTH {
-        error!("JWT_SECRET must be at least {} characters for security", crate::constants::JWT_SECRET_MIN_LENGTH);
-        std::process::exit(1);
-    }
+    // JWT signing keys
+    let jwt_keys = match config.jwt_algorithm.to_uppercase().as_str() {
+        "HS256" => load_hs256_keys(&config),
+        "RS256" => load_rs256_keys(&config),
+        other => {
+            error!("JWT_ALGORITHM must be HS256 or RS256, got '{}'", other);
+            std::process::exit(1);
+        }
+    };
+    let jwt_keys = Arc::new(jwt_keys.with_leeway(config.jwt_leeway_secs));
+    info!("JWT: signing with {:?}", jwt_keys.algorithm());
 
     // Stellar network configuration
     let stellar_config = Arc::new(config.stellar_config());
@@ -168,6 +272,18 @@ async fn run_server(
     if let Some(ref cid) = stellar_config.contract_id {
         info!("Soroban contract ID: {}", cid);
     }
+    // Outbound HTTP client shared by the RPC client and handlers
+    let http_client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.http_client_timeout_secs))
+        .build()
+        .expect("Failed to create HTTP client");
+    let stellar_rpc = Arc::new(stellar_rpc::StellarRpcClient::new(
+        http_client.clone(),
+        stellar_config.rpc_url.clone(),
+    ));
+
+    // Per-account bound on concurrent password hashing
+    let auth_limiter = Arc::new(inflight::InFlightLimiter::new(config.auth_max_concurrent_per_account));
 
     // Initialize Okta provider (if configured)
     let okta_provider = okta::init_okta_provider(&config).await;
@@ -189,61 +305,67 @@ async fn run_server(
             warn!("CORS_ALLOWED_ORIGINS is set to wildcard '*' — consider restricting to specific origins in production");
         }
     }
-    let cors = if config.cors_allowed_origins == "*" {
-        CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-            .allow_headers([
-                header::AUTHORIZATION,
-                header::CONTENT_TYPE,
-                HeaderName::from_static("x-request-nonce"),
-            ])
-    } else {
-        let origins: Vec<HeaderValue> = config
-            .cors_allowed_origins
-            .split(',')
-            .filter_map(|o| o.trim().parse().ok())
-            .collect();
-        CorsLayer::new()
-            .allow_origin(origins)
-            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-            .allow_headers([
-                header::AUTHORIZATION,
-                header::CONTENT_TYPE,
-                HeaderName::from_static("x-request-nonce"),
-            ])
-    };
+    let cors = routes::cors_layer(&config.cors_allowed_origins).unwrap_or_else(|e| {
+        error!("CORS_ALLOWED_ORIGINS: {}", e);
+        std::process::exit(1);
+    });
 
-    // Build router with routes
-    let app = Router::new()
-        .route("/", get(health::default_route))
-        .route("/health", get(health::health_check))
-        .route("/version", get(health::get_version))
-        .route("/account", post(account::create_account).get(account::get_account).put(account::update_account))
-        .route("/authenticate", post(authenticate::authenticate))
-        .route("/sync", post(sync::sync_account))
-        .route("/token", post(token::token))
-        .route("/subscribe", post(subscribe::subscribe))
-        .route("/transaction", post(transaction::create_transaction))
-        .route("/card", post(card::create_card).delete(card::delete_card))
-        .route("/mfa", post(mfa::enroll_mfa).get(mfa::get_mfa))
-        .route("/mfa/verify", post(mfa::verify_mfa))
-        .route("/notify", get(notify::list_notify).post(notify::create_notify).put(notify::update_notify))
-        .route("/notification/subscriptions", get(notification_subscription::list_subscriptions).post(notification_subscription::create_subscription))
-        .route("/notification/subscriptions/:id", put(notification_subscription::update_subscription).delete(notification_subscription::delete_subscription))
-        .route("/device-token", post(device_token::register_device_token).delete(device_token::delete_device_token))
-        .route("/logout", post(logout::logout))
-        .route("/auth/okta", post(okta_handler::okta_token_exchange))
-        .route("/auth/okta/config", get(okta_handler::okta_config))
-        .route("/healthz", get(health::liveness))
-        .route("/readyz", get(health::readiness))
-        .route("/network", get(network::network_info))
+    // Shared effective config (admin endpoints, admin checks)
+    let shared_config = Arc::new(config.clone());
+    if shared_config.admin_accounts.is_empty() {
+        warn!("ADMIN_ACCOUNTS is empty — only accounts granted the admin role in impala_account_roles can use admin endpoints");
+    }
+
+    // Cancellation token for graceful background task shutdown
+    let cancel = CancellationToken::new();
+
+    // Payala listeners started via /subscribe stop with the server
+    let payala_listeners = Arc::new(streams::PayalaListeners::new(cancel.clone(), &config.redis_key_prefix));
+
+    // Maintenance mode: MAINTENANCE_MODE forces it on; the runtime flag
+    // lives in Redis and is re-read by every instance
+    let maintenance_state = Arc::new(maintenance::MaintenanceState::new(config.maintenance_mode));
+    if config.maintenance_mode {
+        warn!("MAINTENANCE_MODE is set; non-admin routes will return 503");
+    }
+    {
+        let state = maintenance_state.clone();
+        let redis = redis_pool.clone();
+        let key_prefix = config.redis_key_prefix.clone();
+        let maintenance_cancel = cancel.clone();
+        tokio::spawn(async move {
+            maintenance::refresh_task(
+                state,
+                redis,
+                key_prefix,
+                Duration::from_secs(constants::MAINTENANCE_REFRESH_SECS),
+                maintenance_cancel,
+            )
+            .await;
+        });
+    }
+
+    // Build router with routes (disabled feature groups are omitted)
+    let app = routes::build_router(&config.features)
+        .layer(middleware::RequestTimeoutLayer::new(Duration::from_secs(config.request_timeout_secs)))
+        .layer(middleware::MaintenanceLayer::new(maintenance_state.clone()))
+        .layer(middleware::EnvelopeLayer::new(middleware::EnvelopeOptions {
+            always: config.response_envelope,
+            failure_status: config.business_failure_status,
+        }))
         .layer(cors)
         .layer(RequestBodyLimitLayer::new(1_048_576)) // 1 MB body limit
         .layer(CompressionLayer::new())
-        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .layer(middleware::RequestContextLayer)
+        // Layers added later wrap the ones before, so the ID is set before
+        // the context layer reads it and the propagate layer echoes it
         .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .layer(middleware::MetricsLayer::new(metrics.clone()))
+        .layer(middleware::RequestLogLayer::new(middleware::RequestLogSampler::new(
+            config.request_log_sample_rate,
+            Duration::from_millis(config.request_log_slow_ms),
+        )))
         .layer(TraceLayer::new_for_http())
         .layer(SetResponseHeaderLayer::overriding(
             header::X_CONTENT_TYPE_OPTIONS,
@@ -271,8 +393,14 @@ async fn run_server(
         ))
         .layer(Extension(pool.clone()))
         .layer(Extension(redis_pool.clone()))
-        .layer(Extension(jwt_secret))
+        .layer(Extension(jwt_keys))
         .layer(Extension(stellar_config.clone()))
+        .layer(Extension(stellar_rpc))
+        .layer(Extension(http_client))
+        .layer(Extension(auth_limiter))
+        .layer(Extension(maintenance_state))
+        .layer(Extension(payala_listeners))
+        .layer(Extension(shared_config))
         .layer(Extension(metrics));
 
     // Add optional SNS client extension
@@ -282,9 +410,6 @@ async fn run_server(
         app
     };
 
-    // Cancellation token for graceful background task shutdown
-    let cancel = CancellationToken::new();
-
     // Add Okta provider extension and spawn JWKS refresh task (if configured)
     let app = if let Some(ref provider) = okta_provider {
         let refresh_provider = provider.clone();
@@ -299,25 +424,74 @@ async fn run_server(
     };
 
     // LDAP directory sync
-    ldap::directory_sync(&pool, &config).await;
+    if config.features.ldap {
+        ldap::directory_sync(&pool, &config).await;
+    } else {
+        info!("LDAP sync disabled by feature flag");
+    }
 
     // Spawn background cron_sync task with cancellation support
-    let cron_pool = pool.clone();
-    let cron_cancel = cancel.clone();
-    tokio::spawn(async move {
-        streams::cron_sync_task(cron_pool, cron_cancel).await;
-    });
+    if config.features.cron_sync {
+        let cron_pool = pool.clone();
+        let cron_cancel = cancel.clone();
+        let cron_schedule = streams::CronSchedule::from_config(&config);
+        tokio::spawn(async move {
+            streams::cron_sync_task(cron_pool, cron_cancel, cron_schedule).await;
+        });
+    } else {
+        info!("cron_sync task disabled by feature flag");
+    }
 
-    // Run server with graceful shutdown
-    info!("Server listening on {}", config.service_address);
-    let listener = tokio::net::TcpListener::bind(&config.service_address)
-        .await
-        .expect("Failed to bind SERVICE_ADDRESS");
-    if let Err(e) = axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(cancel))
+    // Run server with graceful shutdown; TLS when a certificate is configured
+    if let Some(cert_path) = config.tls_cert_path.as_deref() {
+        let key_path = config
+            .tls_key_path
+            .as_deref()
+            .expect("TLS_CERT_PATH is set but TLS_KEY_PATH is not");
+        let tls_config = tls::load_server_config(cert_path, key_path, &config.tls_min_version)
+            .unwrap_or_else(|e| panic!("Failed to load TLS configuration: {}", e));
+        let addr: std::net::SocketAddr = config
+            .service_address
+            .parse()
+            .expect("SERVICE_ADDRESS must be a socket address when TLS is enabled");
+
+        let handle = axum_server::Handle::new();
+        let shutdown_handle = handle.clone();
+        tokio::spawn(async move {
+            shutdown_signal(cancel).await;
+            shutdown_handle.graceful_shutdown(Some(Duration::from_secs(
+                constants::TLS_GRACEFUL_SHUTDOWN_SECS,
+            )));
+        });
+
+        info!(
+            "Server listening on {} (TLS, minimum version {})",
+            addr, config.tls_min_version
+        );
+        if let Err(e) = axum_server::bind_rustls(
+            addr,
+            axum_server::tls_rustls::RustlsConfig::from_config(Arc::new(tls_config)),
+        )
+        .handle(handle)
+        .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>())
         .await
-    {
-        error!("Server error: {}", e);
+        {
+            error!("Server error: {}", e);
+        }
+    } else {
+        info!("Server listening on {}", config.service_address);
+        let listener = tokio::net::TcpListener::bind(&config.service_address)
+            .await
+            .expect("Failed to bind SERVICE_ADDRESS");
+        if let Err(e) = axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+            .with_graceful_shutdown(shutdown_signal(cancel))
+            .await
+        {
+            error!("Server error: {}", e);
+        }
     }
 
     telemetry::shutdown_otel();
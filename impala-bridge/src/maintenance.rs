@@ -0,0 +1,156 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::cache::{redis_get_json, redis_set_json};
+
+/// Redis key (under the cache prefix) holding the runtime maintenance flag,
+/// shared by every instance.
+const MAINTENANCE_KEY: &str = "maintenance";
+
+/// Stored form of the runtime flag.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MaintenanceRecord {
+    pub enabled: bool,
+    pub set_by: String,
+    pub set_at: String,
+}
+
+/// Whether this instance is in maintenance mode: forced on by
+/// `MAINTENANCE_MODE`, or switched on at runtime via
+/// `PUT /admin/maintenance` (stored in Redis and re-read periodically so
+/// all instances follow).
+pub struct MaintenanceState {
+    forced: bool,
+    runtime: AtomicBool,
+}
+
+impl MaintenanceState {
+    pub fn new(forced: bool) -> Self {
+        MaintenanceState {
+            forced,
+            runtime: AtomicBool::new(false),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.forced || self.runtime.load(Ordering::Relaxed)
+    }
+
+    pub fn is_forced(&self) -> bool {
+        self.forced
+    }
+
+    /// Set the runtime flag; returns the previous value.
+    pub fn set_runtime(&self, enabled: bool) -> bool {
+        self.runtime.swap(enabled, Ordering::Relaxed)
+    }
+}
+
+/// Read the runtime flag from Redis.  A missing key means off.
+pub async fn load(redis_pool: &deadpool_redis::Pool, key_prefix: &str) -> Result<bool, String> {
+    let mut conn = redis_pool
+        .get()
+        .await
+        .map_err(|e| format!("Redis connection error: {}", e))?;
+    let record: Option<MaintenanceRecord> = redis_get_json(&mut conn, key_prefix, MAINTENANCE_KEY)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(record.is_some_and(|r| r.enabled))
+}
+
+/// Persist the runtime flag to Redis (no expiry).
+pub async fn store(
+    redis_pool: &deadpool_redis::Pool,
+    key_prefix: &str,
+    record: &MaintenanceRecord,
+) -> Result<(), String> {
+    let mut conn = redis_pool
+        .get()
+        .await
+        .map_err(|e| format!("Redis connection error: {}", e))?;
+    redis_set_json(&mut conn, key_prefix, MAINTENANCE_KEY, record, None)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Keep `state` in step with the flag in Redis, so a toggle on one instance
+/// (or before a restart) reaches every instance within `interval`.  Read
+/// failures keep the last known value.
+pub async fn refresh_task(
+    state: Arc<MaintenanceState>,
+    redis_pool: Arc<deadpool_redis::Pool>,
+    key_prefix: String,
+    interval: Duration,
+    cancel: CancellationToken,
+) {
+    loop {
+        match load(&redis_pool, &key_prefix).await {
+            Ok(enabled) => {
+                if state.set_runtime(enabled) != enabled {
+                    info!("maintenance: mode {} (from Redis)", if enabled { "enabled" } else { "disabled" });
+                }
+            }
+            Err(e) => warn!("maintenance: failed to read flag: {}", e),
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = cancel.cancelled() => {
+                info!("maintenance: refresh_task shutting down");
+                return;
+            }
+        }
+    }
+}
+
+/// Routes outside `/admin/` that keep answering in maintenance mode: the
+/// probes, the sign-in and token endpoints an admin needs to reach the
+/// toggle, and the admin-only routes registered outside `/admin/`.
+pub const EXEMPT_ROUTES: &[&str] = &[
+    "/healthz",
+    "/readyz",
+    "/authenticate",
+    "/token",
+    "/auth/okta",
+    "/account/status",
+    "/index/reprocess",
+    "/cron/callback",
+    "/cron/callback/verify",
+    "/cron/run",
+];
+
+/// Routes that keep answering in maintenance mode: `EXEMPT_ROUTES` and
+/// everything under `/admin/` (including the toggle itself).
+pub fn is_exempt(path: &str) -> bool {
+    EXEMPT_ROUTES.contains(&path) || path.starts_with("/admin/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forced_mode_ignores_runtime_flag() {
+        let state = MaintenanceState::new(true);
+        state.set_runtime(false);
+        assert!(state.is_enabled());
+
+        let state = MaintenanceState::new(false);
+        assert!(!state.is_enabled());
+        assert!(!state.set_runtime(true));
+        assert!(state.is_enabled());
+    }
+
+    #[test]
+    fn test_exempt_routes() {
+        for path in ["/healthz", "/readyz", "/token", "/authenticate", "/account/status", "/admin/config", "/admin/maintenance"] {
+            assert!(is_exempt(path), "{}", path);
+        }
+        for path in ["/", "/account", "/register", "/administrator", "/readyz/extra", "/livez", "/metrics"] {
+            assert!(!is_exempt(path), "{}", path);
+        }
+    }
+}
@@ -1,13 +1,20 @@
-use axum::http::{Request, Response};
+use axum::body::Body;
+use axum::http::{header, HeaderValue, Request, Response, StatusCode};
+use log::{info, warn};
 use opentelemetry::KeyValue;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tower::Layer;
 use tower::Service;
+use tracing::Instrument;
 
+use crate::constants::{ACCOUNT_SUSPENDED_MESSAGE, ENVELOPE_MEDIA_TYPE, MAINTENANCE_MESSAGE};
+use crate::error::AppError;
+use crate::maintenance::{self, MaintenanceState};
 use crate::telemetry::AppMetrics;
 
 /// Tower layer that records HTTP request metrics (duration, active requests, status codes).
@@ -90,6 +97,510 @@ where
     }
 }
 
+/// Decides which requests `RequestLogLayer` logs: every error (4xx/5xx)
+/// and every request slower than `slow`, plus 1 in `sample_rate` of the
+/// rest (0 logs none of them).
+pub struct RequestLogSampler {
+    sample_rate: u64,
+    slow: Duration,
+    /// Successful fast requests seen so far, for 1-in-N sampling.
+    seen: AtomicU64,
+}
+
+impl RequestLogSampler {
+    pub fn new(sample_rate: u64, slow: Duration) -> Self {
+        Self {
+            sample_rate,
+            slow,
+            seen: AtomicU64::new(0),
+        }
+    }
+
+    pub fn should_log(&self, status: StatusCode, elapsed: Duration) -> bool {
+        if status.is_client_error() || status.is_server_error() || elapsed >= self.slow {
+            return true;
+        }
+        if self.sample_rate == 0 {
+            return false;
+        }
+        self.seen.fetch_add(1, Ordering::Relaxed).is_multiple_of(self.sample_rate)
+    }
+}
+
+/// Tower layer that logs one line per request (method, path, status,
+/// latency, request ID), sampled by a shared `RequestLogSampler`.  The
+/// query string is never logged.
+#[derive(Clone)]
+pub struct RequestLogLayer {
+    sampler: Arc<RequestLogSampler>,
+}
+
+impl RequestLogLayer {
+    pub fn new(sampler: RequestLogSampler) -> Self {
+        Self {
+            sampler: Arc::new(sampler),
+        }
+    }
+}
+
+impl<S> Layer<S> for RequestLogLayer {
+    type Service = RequestLogService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestLogService {
+            inner,
+            sampler: self.sampler.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestLogService<S> {
+    inner: S,
+    sampler: Arc<RequestLogSampler>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestLogService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
+        let sampler = self.sampler.clone();
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = inner.call(req).await;
+            let elapsed = start.elapsed();
+
+            let (status, request_id) = match &result {
+                Ok(response) => (
+                    response.status(),
+                    response
+                        .headers()
+                        .get("x-request-id")
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or("-")
+                        .to_string(),
+                ),
+                Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "-".to_string()),
+            };
+
+            if sampler.should_log(status, elapsed) {
+                #[cfg(test)]
+                tests::REQUESTS_LOGGED.with(|n| n.set(n.get() + 1));
+                let ms = elapsed.as_millis();
+                if status.is_server_error() || elapsed >= sampler.slow {
+                    warn!("{} {} {} {}ms request_id={}", method, path, status.as_u16(), ms, request_id);
+                } else {
+                    info!("{} {} {} {}ms request_id={}", method, path, status.as_u16(), ms, request_id);
+                }
+            }
+
+            result
+        })
+    }
+}
+
+tokio::task_local! {
+    /// `x-request-id` of the request being handled on this task.
+    static REQUEST_ID: String;
+}
+
+/// The current request's ID, if called while handling a request under
+/// `RequestContextLayer`.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Tower layer that runs each request inside a `request` tracing span
+/// carrying its `x-request-id` (set by `SetRequestIdLayer`, which must sit
+/// outside this layer), and makes the ID available to
+/// `current_request_id` so error bodies can quote it.
+#[derive(Clone)]
+pub struct RequestContextLayer;
+
+impl<S> Layer<S> for RequestContextLayer {
+    type Service = RequestContextService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestContextService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestContextService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestContextService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let request_id = req
+            .headers()
+            .get("x-request-id")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("-")
+            .to_string();
+        let span = tracing::info_span!(
+            "request",
+            request_id = %request_id,
+            method = %req.method(),
+            path = %req.uri().path(),
+        );
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(REQUEST_ID.scope(request_id, async move { inner.call(req).await }.instrument(span)))
+    }
+}
+
+/// Tower layer that answers 503 for every non-exempt route while
+/// maintenance mode is on (see `maintenance::is_exempt`).
+#[derive(Clone)]
+pub struct MaintenanceLayer {
+    state: Arc<MaintenanceState>,
+}
+
+impl MaintenanceLayer {
+    pub fn new(state: Arc<MaintenanceState>) -> Self {
+        Self { state }
+    }
+}
+
+impl<S> Layer<S> for MaintenanceLayer {
+    type Service = MaintenanceService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MaintenanceService {
+            inner,
+            state: self.state.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MaintenanceService<S> {
+    inner: S,
+    state: Arc<MaintenanceState>,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for MaintenanceService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        if self.state.is_enabled() && !maintenance::is_exempt(req.uri().path()) {
+            let response = axum::response::IntoResponse::into_response(AppError::ServiceUnavailable(
+                MAINTENANCE_MESSAGE.to_string(),
+            ));
+            return Box::pin(async move { Ok(response) });
+        }
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move { inner.call(req).await })
+    }
+}
+
+/// Tower layer that answers 504 (`AppError::GatewayTimeout`) when the
+/// inner service takes longer than `timeout`, dropping its future.  Work a
+/// handler hands to `tokio::spawn`, such as the `/subscribe` stream
+/// listeners, is not cut short.
+#[derive(Clone)]
+pub struct RequestTimeoutLayer {
+    timeout: Duration,
+}
+
+impl RequestTimeoutLayer {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl<S> Layer<S> for RequestTimeoutLayer {
+    type Service = RequestTimeoutService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestTimeoutService {
+            inner,
+            timeout: self.timeout,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestTimeoutService<S> {
+    inner: S,
+    timeout: Duration,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for RequestTimeoutService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let timeout = self.timeout;
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, inner.call(req)).await {
+                Ok(result) => result,
+                Err(_) => {
+                    warn!("{} {} timed out after {}ms", method, path, timeout.as_millis());
+                    Ok(axum::response::IntoResponse::into_response(AppError::GatewayTimeout))
+                }
+            }
+        })
+    }
+}
+
+/// How success responses are shaped (see `EnvelopeLayer`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvelopeOptions {
+    /// Wrap every JSON response, not only requests that ask for it via
+    /// `Accept: application/vnd.impala.envelope+json`.
+    pub always: bool,
+    /// Return `success: false` business failures with a 4xx status instead
+    /// of 200.
+    pub failure_status: bool,
+}
+
+/// Tower layer that rewrites JSON responses into a uniform
+/// `{ "ok", "data", "error" }` envelope and/or maps `success: false` bodies
+/// to 4xx statuses.  With both options off and no envelope `Accept` header,
+/// responses pass through unchanged.
+#[derive(Clone)]
+pub struct EnvelopeLayer {
+    options: EnvelopeOptions,
+}
+
+impl EnvelopeLayer {
+    pub fn new(options: EnvelopeOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl<S> Layer<S> for EnvelopeLayer {
+    type Service = EnvelopeService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        EnvelopeService {
+            inner,
+            options: self.options,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EnvelopeService<S> {
+    inner: S,
+    options: EnvelopeOptions,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for EnvelopeService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let wants_envelope = self.options.always
+            || req
+                .headers()
+                .get_all(header::ACCEPT)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .any(|v| v.contains(ENVELOPE_MEDIA_TYPE));
+        let failure_status = self.options.failure_status;
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let response = inner.call(req).await?;
+            if !wants_envelope && !failure_status {
+                return Ok(response);
+            }
+            Ok(reshape_response(response, wants_envelope, failure_status).await)
+        })
+    }
+}
+
+/// Apply the envelope / failure-status rewrite to a response.  Non-JSON
+/// responses are returned untouched.
+async fn reshape_response(response: Response<Body>, envelope: bool, failure_status: bool) -> Response<Body> {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("reshape_response: failed to read response body: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let value: serde_json::Value = match serde_json::from_slice(&bytes) {
+        Ok(value) => value,
+        Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+    };
+
+    let (status, value) = reshape(parts.status, value, envelope, failure_status);
+    parts.status = status;
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    Response::from_parts(parts, Body::from(value.to_string()))
+}
+
+/// Rewrite one JSON body.
+///
+/// - 2xx with `success: false` is a business failure: with `failure_status`
+///   its status becomes a 4xx chosen by `business_failure_status`.
+/// - With `envelope`, successes become `{ok: true, data, error: null}`,
+///   business failures `{ok: false, data, error: {code, message}}`, and
+///   `AppError` bodies `{ok: false, data: null, error}`.
+pub fn reshape(
+    status: StatusCode,
+    body: serde_json::Value,
+    envelope: bool,
+    failure_status: bool,
+) -> (StatusCode, serde_json::Value) {
+    let business_failure = status.is_success()
+        && body.get("success").and_then(|v| v.as_bool()) == Some(false);
+    let failure = business_failure.then(|| {
+        let message = body
+            .get("message")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        (business_failure_status(&message), message)
+    });
+
+    let status = match &failure {
+        Some((code, _)) if failure_status => *code,
+        _ => status,
+    };
+    if !envelope {
+        return (status, body);
+    }
+
+    let wrapped = if let Some((code, message)) = failure {
+        serde_json::json!({
+            "ok": false,
+            "data": body,
+            "error": { "code": error_code(code), "message": message },
+        })
+    } else if status.is_success() {
+        serde_json::json!({ "ok": true, "data": body, "error": null })
+    } else {
+        let error = body.get("error").cloned().unwrap_or(body);
+        serde_json::json!({ "ok": false, "data": null, "error": error })
+    };
+    (status, wrapped)
+}
+
+/// Pick a 4xx status for a `success: false` message.
+fn business_failure_status(message: &str) -> StatusCode {
+    let lower = message.to_lowercase();
+    if lower.contains("not found") || (lower.starts_with("no ") && lower.contains("found")) {
+        StatusCode::NOT_FOUND
+    } else if lower.contains("already exists") {
+        StatusCode::CONFLICT
+    } else if lower.starts_with("invalid credentials")
+        || lower.starts_with("invalid token")
+        || lower.starts_with("invalid verification code")
+    {
+        StatusCode::UNAUTHORIZED
+    } else if message == ACCOUNT_SUSPENDED_MESSAGE {
+        StatusCode::FORBIDDEN
+    } else {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// Error code for an enveloped business failure, matching `AppError` codes.
+fn error_code(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::FORBIDDEN => "forbidden",
+        _ => "bad_request",
+    }
+}
+
 /// Normalize URI path for metrics to avoid high cardinality.
 /// Replaces numeric and UUID path segments with `:id`.
 fn normalize_path(path: &str) -> String {
@@ -97,9 +608,9 @@ fn normalize_path(path: &str) -> String {
         .map(|segment| {
             if segment.is_empty() {
                 segment
-            } else if segment.chars().all(|c| c.is_ascii_digit()) {
-                ":id"
-            } else if uuid::Uuid::parse_str(segment).is_ok() {
+            } else if segment.chars().all(|c| c.is_ascii_digit())
+                || uuid::Uuid::parse_str(segment).is_ok()
+            {
                 ":id"
             } else {
                 segment
@@ -108,3 +619,272 @@ fn normalize_path(path: &str) -> String {
         .collect::<Vec<_>>()
         .join("/")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    fn app(options: EnvelopeOptions) -> Router {
+        Router::new()
+            .route("/ok", get(|| async { Json(json!({"success": true, "message": "Account created"})) }))
+            .route("/dup", get(|| async {
+                Json(json!({"success": false, "message": "An account with this identifier already exists"}))
+            }))
+            .route("/err", get(|| async { crate::error::AppError::Forbidden }))
+            .route("/text", get(|| async { "OK" }))
+            .layer(EnvelopeLayer::new(options))
+    }
+
+    async fn call(options: EnvelopeOptions, uri: &str, accept: Option<&str>) -> (StatusCode, String) {
+        let mut req = Request::builder().uri(uri);
+        if let Some(accept) = accept {
+            req = req.header(header::ACCEPT, accept);
+        }
+        let resp = app(options).oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let status = resp.status();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    fn parse(body: &str) -> Value {
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_mode_refuses_user_routes() {
+        let state = Arc::new(MaintenanceState::new(false));
+        let app = Router::new()
+            .route("/account", get(|| async { "OK" }))
+            .route("/readyz", get(|| async { "OK" }))
+            .route("/healthz", get(|| async { "OK" }))
+            .route("/admin/maintenance", get(|| async { "OK" }))
+            .layer(MaintenanceLayer::new(state.clone()));
+        let status = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+                app.oneshot(req).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(status("/account").await, StatusCode::OK);
+
+        state.set_runtime(true);
+        assert_eq!(status("/account").await, StatusCode::SERVICE_UNAVAILABLE);
+        for uri in ["/readyz", "/healthz", "/admin/maintenance"] {
+            assert_eq!(status(uri).await, StatusCode::OK, "{}", uri);
+        }
+
+        let req = Request::builder().uri("/account").body(Body::empty()).unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        let body = parse(std::str::from_utf8(&bytes).unwrap());
+        assert_eq!(body["error"]["code"], "service_unavailable");
+        assert_eq!(body["error"]["message"], MAINTENANCE_MESSAGE);
+
+        state.set_runtime(false);
+        assert_eq!(status("/account").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_admin_can_sign_in_and_end_maintenance() {
+        let state = Arc::new(MaintenanceState::new(false));
+        let toggle = state.clone();
+        let app = Router::new()
+            .route("/account", get(|| async { "OK" }))
+            .route("/authenticate", axum::routing::post(|| async { "refresh" }))
+            .route("/token", axum::routing::post(|| async { "temporal" }))
+            .route("/account/status", axum::routing::put(|| async { "OK" }))
+            .route("/admin/maintenance", axum::routing::put(move || async move {
+                toggle.set_runtime(false);
+                "OK"
+            }))
+            .layer(MaintenanceLayer::new(state.clone()));
+        let status = |method: &'static str, uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let req = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+                app.oneshot(req).await.unwrap().status()
+            }
+        };
+
+        state.set_runtime(true);
+        assert_eq!(status("GET", "/account").await, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status("POST", "/authenticate").await, StatusCode::OK);
+        assert_eq!(status("POST", "/token").await, StatusCode::OK);
+        assert_eq!(status("PUT", "/account/status").await, StatusCode::OK);
+        assert_eq!(status("PUT", "/admin/maintenance").await, StatusCode::OK);
+        assert_eq!(status("GET", "/account").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_slow_handler_times_out_with_504() {
+        let app = Router::new()
+            .route("/slow", get(|| async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                "OK"
+            }))
+            .route("/fast", get(|| async { "OK" }))
+            .layer(RequestTimeoutLayer::new(Duration::from_millis(50)));
+
+        let req = Request::builder().uri("/slow").body(Body::empty()).unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(parse(std::str::from_utf8(&bytes).unwrap())["error"]["code"], "timeout");
+
+        let req = Request::builder().uri("/fast").body(Body::empty()).unwrap();
+        assert_eq!(app.oneshot(req).await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_error_body_quotes_request_id() {
+        use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+
+        let app = Router::new()
+            .route("/err", get(|| async { crate::error::AppError::NotFound("gone".to_string()) }))
+            .layer(RequestContextLayer)
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
+
+        let req = Request::builder()
+            .uri("/err")
+            .header("x-request-id", "req-123")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.headers()["x-request-id"], "req-123");
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(parse(std::str::from_utf8(&bytes).unwrap())["error"]["request_id"], "req-123");
+
+        // Without an incoming ID one is generated and echoed in both places
+        let req = Request::builder().uri("/err").body(Body::empty()).unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        let echoed = resp.headers()["x-request-id"].to_str().unwrap().to_string();
+        assert!(uuid::Uuid::parse_str(&echoed).is_ok());
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(parse(std::str::from_utf8(&bytes).unwrap())["error"]["request_id"], echoed.as_str());
+    }
+
+    thread_local! {
+        /// Lines `RequestLogLayer` emitted on this thread.
+        pub(super) static REQUESTS_LOGGED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    fn log_app(sample_rate: u64, slow: Duration) -> Router {
+        Router::new()
+            .route("/ok", get(|| async { "OK" }))
+            .route("/err", get(|| async { crate::error::AppError::Forbidden }))
+            .route("/fail", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .route("/slow", get(|| async {
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                "OK"
+            }))
+            .layer(RequestLogLayer::new(RequestLogSampler::new(sample_rate, slow)))
+    }
+
+    /// Send one request per URI through `app`; return how many were logged.
+    async fn logged_for(app: Router, uris: &[&str]) -> usize {
+        let before = REQUESTS_LOGGED.with(|n| n.get());
+        for uri in uris {
+            let req = Request::builder().uri(*uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(req).await.unwrap();
+        }
+        REQUESTS_LOGGED.with(|n| n.get()) - before
+    }
+
+    #[tokio::test]
+    async fn test_zero_sample_rate_logs_only_errors_and_slow_requests() {
+        let app = log_app(0, Duration::from_millis(20));
+        assert_eq!(logged_for(app.clone(), &["/ok", "/ok", "/ok"]).await, 0);
+        assert_eq!(logged_for(app.clone(), &["/err"]).await, 1);
+        assert_eq!(logged_for(app.clone(), &["/fail"]).await, 1);
+        assert_eq!(logged_for(app, &["/slow", "/ok"]).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_sample_rate_logs_one_in_n() {
+        let app = log_app(3, Duration::from_secs(60));
+        assert_eq!(logged_for(app.clone(), &["/ok"; 9]).await, 3);
+        let all = log_app(1, Duration::from_secs(60));
+        assert_eq!(logged_for(all, &["/ok"; 4]).await, 4);
+    }
+
+    #[test]
+    fn test_sampler_ignores_errors_in_sample_count() {
+        let sampler = RequestLogSampler::new(2, Duration::from_secs(1));
+        let fast = Duration::from_millis(1);
+        assert!(sampler.should_log(StatusCode::OK, fast));
+        assert!(sampler.should_log(StatusCode::NOT_FOUND, fast));
+        assert!(!sampler.should_log(StatusCode::OK, fast));
+        assert!(sampler.should_log(StatusCode::OK, fast));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_mode_is_unchanged() {
+        let legacy = EnvelopeOptions::default();
+        let (status, body) = call(legacy, "/dup", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(parse(&body)["success"], false);
+
+        let (status, body) = call(legacy, "/err", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(parse(&body)["error"]["code"], "forbidden");
+    }
+
+    #[tokio::test]
+    async fn test_envelope_wraps_success_failure_and_error() {
+        let opts = EnvelopeOptions { always: true, failure_status: false };
+
+        let (status, body) = call(opts, "/ok", None).await;
+        let v = parse(&body);
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(v["ok"], true);
+        assert_eq!(v["data"]["message"], "Account created");
+        assert!(v["error"].is_null());
+
+        let (status, body) = call(opts, "/dup", None).await;
+        let v = parse(&body);
+        assert_eq!(status, StatusCode::OK, "status kept without failure_status");
+        assert_eq!(v["ok"], false);
+        assert_eq!(v["error"]["code"], "conflict");
+
+        let (status, body) = call(opts, "/err", None).await;
+        let v = parse(&body);
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(v, json!({"ok": false, "data": null, "error": {"code": "forbidden", "message": "Access denied"}}));
+
+        let (_, body) = call(opts, "/text", None).await;
+        assert_eq!(body, "OK", "non-JSON responses pass through");
+    }
+
+    #[tokio::test]
+    async fn test_accept_header_opts_in_to_envelope() {
+        let (_, body) = call(EnvelopeOptions::default(), "/ok", Some(ENVELOPE_MEDIA_TYPE)).await;
+        assert_eq!(parse(&body)["ok"], true);
+    }
+
+    #[tokio::test]
+    async fn test_failure_status_maps_business_failures() {
+        let opts = EnvelopeOptions { always: false, failure_status: true };
+        let (status, body) = call(opts, "/dup", None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(parse(&body)["success"], false, "legacy body kept");
+
+        let (status, _) = call(opts, "/ok", None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_business_failure_status() {
+        assert_eq!(business_failure_status("Card not found or already deleted"), StatusCode::NOT_FOUND);
+        assert_eq!(business_failure_status("No account found with the provided identifier"), StatusCode::NOT_FOUND);
+        assert_eq!(business_failure_status("Invalid credentials"), StatusCode::UNAUTHORIZED);
+        assert_eq!(business_failure_status(ACCOUNT_SUSPENDED_MESSAGE), StatusCode::FORBIDDEN);
+        assert_eq!(business_failure_status("Code must not be empty"), StatusCode::BAD_REQUEST);
+    }
+}
@@ -11,6 +11,15 @@ pub struct Claims {
     pub iat: usize,
     pub jti: String,
     pub iss: String,
+    /// Shared by a refresh token and every token rotated from it, so reuse
+    /// of a consumed refresh token can revoke them all.  Empty on tokens
+    /// issued before families existed.
+    #[serde(default)]
+    pub family: String,
+    /// Roles from `impala_account_roles`, captured when the refresh token
+    /// was issued and carried over to the temporal tokens it yields.
+    #[serde(default)]
+    pub scopes: Vec<String>,
 }
 
 // ── Pagination ─────────────────────────────────────────────────────────
@@ -35,7 +44,7 @@ impl PaginationParams {
     /// Return clamped `(per_page, offset)` suitable for SQL LIMIT/OFFSET.
     /// `per_page` is clamped to `[1, 100]`, `page` to `[1, ..)`.
     pub fn clamped(&self) -> (i64, i64) {
-        let per_page = (self.per_page.max(1).min(100)) as i64;
+        let per_page = self.per_page.clamp(1, 100) as i64;
         let page = self.page.max(1) as i64;
         let offset = (page - 1) * per_page;
         (per_page, offset)
@@ -50,6 +59,16 @@ pub struct PaginatedResponse<T: Serialize> {
     pub total: u64,
 }
 
+// ── Update Preview ─────────────────────────────────────────────────────
+
+/// One field a dry-run update would change.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Option<String>,
+    pub to: String,
+}
+
 // ── Account ────────────────────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -75,7 +94,12 @@ pub struct GetAccountQuery {
     pub stellar_account_id: String,
 }
 
-#[derive(Serialize)]
+#[derive(Deserialize)]
+pub struct GetAccountByPayalaQuery {
+    pub payala_account_id: String,
+}
+
+#[derive(Debug, Serialize)]
 pub struct GetAccountResponse {
     pub payala_account_id: String,
     pub first_name: String,
@@ -96,6 +120,9 @@ pub struct UpdateAccountRequest {
     pub nickname: Option<String>,
     pub affiliation: Option<String>,
     pub gender: Option<String>,
+    /// Report the changes the update would make without applying it.
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 #[derive(Serialize)]
@@ -103,6 +130,58 @@ pub struct UpdateAccountResponse {
     pub success: bool,
     pub message: String,
     pub rows_affected: u64,
+    /// Set for dry runs: the fields whose value would change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<Vec<FieldChange>>,
+}
+
+/// Lifecycle state of an `impala_account` row (`account_status` enum).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountStatus {
+    Active,
+    Suspended,
+    Closed,
+}
+
+impl AccountStatus {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "active" => Some(AccountStatus::Active),
+            "suspended" => Some(AccountStatus::Suspended),
+            "closed" => Some(AccountStatus::Closed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Suspended => "suspended",
+            AccountStatus::Closed => "closed",
+        }
+    }
+
+    /// Whether the account may obtain tokens.
+    pub fn can_authenticate(&self) -> bool {
+        *self == AccountStatus::Active
+    }
+
+    /// Closed accounts are treated as if they did not exist.
+    pub fn is_closed(&self) -> bool {
+        *self == AccountStatus::Closed
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UpdateAccountStatusRequest {
+    pub payala_account_id: String,
+    pub status: AccountStatus,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteAccountRequest {
+    pub stellar_account_id: String,
 }
 
 // ── Authenticate ───────────────────────────────────────────────────────
@@ -118,6 +197,19 @@ pub struct AuthenticateResponse {
     pub success: bool,
     pub message: String,
     pub action: String,
+    /// Failed attempts left before the account locks, on "Invalid credentials".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_attempts: Option<u64>,
+    /// Seconds until the lockout expires, once this attempt has locked it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    pub account_id: String,
+    pub old_password: String,
+    pub new_password: String,
 }
 
 // ── Sync ───────────────────────────────────────────────────────────────
@@ -127,6 +219,12 @@ pub struct SyncRequest {
     pub account_id: String,
 }
 
+/// Stored in Redis at `sync:{account_id}` after each sync.
+#[derive(Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub synced_at: String,
+}
+
 #[derive(Serialize)]
 pub struct SyncResponse {
     pub success: bool,
@@ -143,6 +241,16 @@ pub struct TokenRequest {
     pub refresh_token: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct LogoutRequest {
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RevokeTokenRequest {
+    pub token: String,
+}
+
 #[derive(Serialize)]
 pub struct TokenResponse {
     pub success: bool,
@@ -153,6 +261,24 @@ pub struct TokenResponse {
     pub temporal_token: Option<String>,
 }
 
+// ── Maintenance ────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct MaintenanceRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MaintenanceResponse {
+    pub success: bool,
+    pub message: String,
+    /// Whether non-exempt routes are currently refused.
+    pub enabled: bool,
+    /// `MAINTENANCE_MODE` is set, so maintenance stays on whatever the
+    /// runtime flag says.
+    pub forced_by_config: bool,
+}
+
 // ── Subscribe ──────────────────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -167,6 +293,59 @@ pub struct SubscribeResponse {
     pub message: String,
 }
 
+#[derive(Deserialize)]
+pub struct UnsubscribeRequest {
+    pub network: String,
+    pub listen_endpoint: Option<String>,
+}
+
+// ── Cron Sync ──────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct RegisterCallbackRequest {
+    pub callback_uri: String,
+}
+
+#[derive(Serialize)]
+pub struct RegisterCallbackResponse {
+    pub id: i32,
+    /// Token the callback's origin must serve at `verify_url`.
+    pub verification_token: String,
+    pub verify_url: String,
+    pub verified: bool,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyCallbackRequest {
+    pub id: i32,
+}
+
+#[derive(Serialize)]
+pub struct VerifyCallbackResponse {
+    pub id: i32,
+    pub verified: bool,
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+pub struct CronRunQuery {
+    pub id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CronRunResult {
+    pub id: i32,
+    pub callback_uri: String,
+    pub success: bool,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CronRunResponse {
+    pub results: Vec<CronRunResult>,
+}
+
 // ── Transaction ────────────────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -219,6 +398,7 @@ pub struct DeleteCardRequest {
 pub struct EnrollMfaRequest {
     pub account_id: String,
     pub mfa_type: String,
+    #[allow(dead_code)]
     pub secret: Option<String>,
     pub phone_number: Option<String>,
 }
@@ -240,11 +420,50 @@ pub struct MfaEnrollment {
     pub enabled: bool,
 }
 
+/// Enrollment as returned to its owner by `GET /mfa`: no secret, masked
+/// phone number.
+#[derive(Debug, Serialize)]
+pub struct MfaEnrollmentSummary {
+    pub account_id: String,
+    pub mfa_type: String,
+    pub phone_number: Option<String>,
+    pub enabled: bool,
+}
+
 #[derive(Deserialize)]
 pub struct MfaQuery {
     pub account_id: String,
 }
 
+/// Filters for `GET /admin/mfa`; unset filters match everything.
+#[derive(Debug, Default, Deserialize)]
+pub struct MfaAdminFilter {
+    pub mfa_type: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// Enrollment as listed to administrators: no secret, masked phone number.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct MfaAdminListItem {
+    pub account_id: String,
+    pub mfa_type: String,
+    pub phone_number: Option<String>,
+    pub enabled: bool,
+    /// Unix seconds of the last enrollment change.
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmMfaRequest {
+    pub account_id: String,
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+pub struct SendMfaCodeRequest {
+    pub account_id: String,
+}
+
 #[derive(Deserialize)]
 pub struct VerifyMfaRequest {
     pub account_id: String,
@@ -273,6 +492,9 @@ pub struct NotifyResponse {
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<i32>,
+    /// Set for dry runs of `PUT /notify`: the fields whose value would change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<Vec<FieldChange>>,
 }
 
 #[derive(Deserialize)]
@@ -286,6 +508,9 @@ pub struct UpdateNotifyRequest {
     pub email: Option<String>,
     pub url: Option<String>,
     pub app: Option<String>,
+    /// Report the changes the update would make without applying it.
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 // ── Notification Subscription ──────────────────────────────────────────
@@ -341,6 +566,45 @@ pub struct DeviceTokenResponse {
     pub message: String,
 }
 
+// ── Event Index ────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct ReprocessQuery {
+    /// First ledger sequence to reprocess (inclusive).
+    pub from: u64,
+    /// Last ledger sequence to reprocess (inclusive).
+    pub to: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReprocessResponse {
+    /// Raw payloads found in the range.
+    pub scanned: usize,
+    /// Index entries rewritten.
+    pub reprocessed: usize,
+    /// Stored payloads that could not be read.
+    pub failed: usize,
+}
+
+// ── Strkey ─────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct StrkeyValidateRequest {
+    pub strkey: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StrkeyValidateResponse {
+    pub valid: bool,
+    /// Key type implied by the leading character, even if decoding failed.
+    pub key_type: Option<String>,
+    pub checksum_valid: bool,
+    /// Raw key bytes in hex; never populated for secret seeds.
+    pub raw_hex: Option<String>,
+    /// Multiplexed id (decimal string) for `M...` addresses.
+    pub muxed_id: Option<String>,
+}
+
 // ── Notify List ───────────────────────────────────────────────────────
 
 #[derive(Serialize, sqlx::FromRow)]
@@ -391,6 +655,96 @@ pub struct NetworkInfoResponse {
     pub soroban_contract_id: Option<String>,
 }
 
+// ── Contract ───────────────────────────────────────────────────────────
+
+/// A Soroban contract value as carried in JSON.  64- and 128-bit integers
+/// are decimal strings so they survive JSON round-trips intact.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ContractValue {
+    Void,
+    Bool(bool),
+    U32(u32),
+    I32(i32),
+    U64(#[serde(with = "crate::contract::int_string")] u64),
+    I64(#[serde(with = "crate::contract::int_string")] i64),
+    U128(#[serde(with = "crate::contract::int_string")] u128),
+    I128(#[serde(with = "crate::contract::int_string")] i128),
+    Symbol(String),
+    String(String),
+    Address(String),
+    /// Hex-encoded bytes.
+    Bytes(String),
+    Vec(Vec<ContractValue>),
+    Map(Vec<ContractMapEntry>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractMapEntry {
+    pub key: ContractValue,
+    pub value: ContractValue,
+}
+
+#[derive(Deserialize)]
+pub struct BuildInvokeRequest {
+    pub source_account: String,
+    pub function: String,
+    #[serde(default)]
+    pub args: Vec<ContractValue>,
+    /// For `schedule_unwrap` / `schedule_transfer`: absolute unlock time
+    /// (unix seconds).  The bridge appends `delay_seconds` computed against
+    /// the ledger clock, so `args` must omit it.
+    pub unlock_at: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct BuildInvokeResponse {
+    /// Unsigned, simulation-assembled transaction envelope (base64 XDR).
+    pub transaction_xdr: String,
+    pub min_resource_fee: String,
+    pub latest_ledger: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulated_result: Option<ContractValue>,
+}
+
+#[derive(Deserialize)]
+pub struct SubmitTransactionRequest {
+    /// Signed transaction envelope (base64 XDR).
+    pub transaction_xdr: String,
+    /// Wait up to this many seconds (capped) for the transaction to land.
+    #[serde(default)]
+    pub wait_secs: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct SubmitTransactionResponse {
+    pub hash: String,
+    pub status: String,
+    pub latest_ledger: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_result_xdr: Option<String>,
+    /// Ledger the transaction landed in, when `wait_secs` was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_value: Option<ContractValue>,
+}
+
+#[derive(Deserialize)]
+pub struct TransactionStatusQuery {
+    pub hash: String,
+}
+
+#[derive(Serialize)]
+pub struct TransactionStatusResponse {
+    pub hash: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_value: Option<ContractValue>,
+}
+
 // ── Okta ───────────────────────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -453,4 +807,32 @@ mod tests {
         assert_eq!(per_page, 25);
         assert_eq!(offset, 50);
     }
+
+    #[test]
+    fn test_account_status_parse_roundtrip() {
+        for status in [AccountStatus::Active, AccountStatus::Suspended, AccountStatus::Closed] {
+            assert_eq!(AccountStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(AccountStatus::parse("deleted"), None);
+    }
+
+    #[test]
+    fn test_suspended_account_cannot_authenticate() {
+        assert!(AccountStatus::Active.can_authenticate());
+        assert!(!AccountStatus::Suspended.can_authenticate());
+        assert!(!AccountStatus::Closed.can_authenticate());
+        assert!(!AccountStatus::Suspended.is_closed());
+        assert!(AccountStatus::Closed.is_closed());
+    }
+
+    #[test]
+    fn test_account_status_request_rejects_unknown_status() {
+        let ok: UpdateAccountStatusRequest =
+            serde_json::from_str(r#"{"payala_account_id":"a","status":"suspended"}"#).unwrap();
+        assert_eq!(ok.status, AccountStatus::Suspended);
+        assert!(serde_json::from_str::<UpdateAccountStatusRequest>(
+            r#"{"payala_account_id":"a","status":"frozen"}"#
+        )
+        .is_err());
+    }
 }
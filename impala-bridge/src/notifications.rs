@@ -1,6 +1,8 @@
 use log::{error, info, warn};
 use opentelemetry::KeyValue;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::sns;
@@ -14,9 +16,11 @@ pub enum NotificationEvent {
     LoginFailure {
         account_id: String,
     },
+    #[allow(dead_code)]
     PasswordChange {
         account_id: String,
     },
+    #[allow(dead_code)]
     TransferIncoming {
         account_id: String,
         amount: String,
@@ -45,6 +49,28 @@ impl NotificationEvent {
         }
     }
 
+    /// Variables available to notification templates for this event.
+    fn variables(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut vars = serde_json::Map::new();
+        vars.insert("account_id".into(), self.account_id().into());
+        vars.insert("event_type".into(), self.event_type_str().into());
+        match self {
+            Self::TransferIncoming { amount, from, .. } => {
+                vars.insert("amount".into(), amount.as_str().into());
+                vars.insert("from".into(), from.as_str().into());
+            }
+            Self::TransferOutgoing { amount, to, .. } => {
+                vars.insert("amount".into(), amount.as_str().into());
+                vars.insert("to".into(), to.as_str().into());
+            }
+            Self::ProfileUpdated { fields, .. } => {
+                vars.insert("fields".into(), fields.join(", ").into());
+            }
+            _ => {}
+        }
+        vars
+    }
+
     fn event_type_str(&self) -> &'static str {
         match self {
             Self::LoginSuccess { .. } => "login_success",
@@ -101,6 +127,76 @@ impl NotificationEvent {
     }
 }
 
+/// Medium key used for a template that applies to every medium of an event.
+const DEFAULT_TEMPLATE_MEDIUM: &str = "default";
+
+/// A single notification template.  `{{name}}` placeholders are replaced
+/// with event variables when rendered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationTemplate {
+    #[serde(default)]
+    pub title: Option<String>,
+    pub body: String,
+}
+
+/// Operator-supplied templates keyed by event type, then medium (or
+/// `"default"`).  Loaded from config; see `NOTIFICATION_TEMPLATES_FILE`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NotificationTemplates(HashMap<String, HashMap<String, NotificationTemplate>>);
+
+impl NotificationTemplates {
+    /// Template for `event_type` delivered via `medium`, falling back to the
+    /// event's `"default"` template.
+    pub fn lookup(&self, event_type: &str, medium: &str) -> Option<&NotificationTemplate> {
+        let by_medium = self.0.get(event_type)?;
+        by_medium
+            .get(medium)
+            .or_else(|| by_medium.get(DEFAULT_TEMPLATE_MEDIUM))
+    }
+
+    /// Render the title and body for an event, or `None` when no template
+    /// is configured and the built-in message should be used.
+    pub fn render(
+        &self,
+        event_type: &str,
+        medium: &str,
+        vars: &serde_json::Map<String, serde_json::Value>,
+    ) -> Option<(Option<String>, String)> {
+        let template = self.lookup(event_type, medium)?;
+        Some((
+            template.title.as_deref().map(|t| render_template(t, vars)),
+            render_template(&template.body, vars),
+        ))
+    }
+}
+
+/// Substitute `{{name}}` placeholders with values from `vars`.
+///
+/// Deliberately minimal: a single left-to-right pass with no expressions,
+/// so substituted values are never themselves interpreted.  Unknown
+/// variables render as an empty string; an unterminated `{{` is kept as-is.
+pub fn render_template(template: &str, vars: &serde_json::Map<String, serde_json::Value>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        match vars.get(after[..end].trim()) {
+            Some(serde_json::Value::String(s)) => out.push_str(s),
+            Some(serde_json::Value::Null) | None => {}
+            Some(other) => out.push_str(&other.to_string()),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
 #[derive(sqlx::FromRow)]
 struct SubscriptionTarget {
     notify_id: i32,
@@ -134,6 +230,7 @@ pub async fn dispatch_event(
     let account_id = event.account_id().to_string();
     let event_type = event.event_type_str();
     let (title, body) = event.format_message();
+    let variables = event.variables();
 
     // Query active subscriptions joined with contact info
     let targets = sqlx::query_as::<_, SubscriptionTarget>(
@@ -181,6 +278,8 @@ pub async fn dispatch_event(
             "medium": target.medium,
             "message_title": title,
             "message_body": body,
+            "event_type": event_type,
+            "variables": variables,
         });
 
         // Set destination based on medium
@@ -269,3 +368,66 @@ pub async fn dispatch_event(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn templates() -> NotificationTemplates {
+        serde_json::from_value(serde_json::json!({
+            "transfer_outgoing": {
+                "sms": { "body": "Sent {{amount}} to {{ to }}" },
+                "default": {
+                    "title": "Transfer from {{account_id}}",
+                    "body": "You sent {{amount}} to {{to}}."
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn transfer_vars() -> serde_json::Map<String, serde_json::Value> {
+        NotificationEvent::TransferOutgoing {
+            account_id: "GABC".to_string(),
+            amount: "10.5".to_string(),
+            to: "GXYZ".to_string(),
+        }
+        .variables()
+    }
+
+    #[test]
+    fn test_render_template_with_variables() {
+        let (title, body) = templates()
+            .render("transfer_outgoing", "sms", &transfer_vars())
+            .unwrap();
+        assert_eq!(title, None);
+        assert_eq!(body, "Sent 10.5 to GXYZ");
+    }
+
+    #[test]
+    fn test_render_falls_back_to_default_medium() {
+        let (title, body) = templates()
+            .render("transfer_outgoing", "email", &transfer_vars())
+            .unwrap();
+        assert_eq!(title.as_deref(), Some("Transfer from GABC"));
+        assert_eq!(body, "You sent 10.5 to GXYZ.");
+    }
+
+    #[test]
+    fn test_render_missing_template_uses_builtin() {
+        assert!(templates().render("login_success", "sms", &transfer_vars()).is_none());
+        assert!(NotificationTemplates::default()
+            .render("transfer_outgoing", "sms", &transfer_vars())
+            .is_none());
+    }
+
+    #[test]
+    fn test_render_template_is_not_recursive() {
+        let mut vars = serde_json::Map::new();
+        vars.insert("name".into(), "{{secret}}".into());
+        vars.insert("secret".into(), "leaked".into());
+        assert_eq!(render_template("Hi {{name}}", &vars), "Hi {{secret}}");
+        assert_eq!(render_template("Hi {{missing}}!", &vars), "Hi !");
+        assert_eq!(render_template("Hi {{name", &vars), "Hi {{name");
+    }
+}
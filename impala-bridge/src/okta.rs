@@ -7,6 +7,7 @@ use crate::config::Config;
 use crate::error::AppError;
 
 /// OIDC discovery document from the authorization server.
+#[allow(dead_code)]
 #[derive(Debug, Clone, Deserialize)]
 pub struct OidcDiscovery {
     pub issuer: String,
@@ -36,6 +37,7 @@ pub struct Jwk {
 }
 
 /// Claims from a validated Okta access token.
+#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 pub struct OktaAccessTokenClaims {
     pub sub: String,
@@ -56,6 +58,8 @@ pub struct OktaProvider {
     pub client_id: String,
     pub issuer_url: String,
     pub http_client: reqwest::Client,
+    /// Clock skew tolerated on `exp`/`nbf` (`JWT_LEEWAY_SECS`).
+    pub leeway_secs: u64,
 }
 
 /// Fetch the OIDC discovery document from the authorization server.
@@ -179,6 +183,7 @@ pub async fn init_okta_provider(config: &Config) -> Option<Arc<OktaProvider>> {
         client_id,
         issuer_url: issuer_url.clone(),
         http_client,
+        leeway_secs: config.jwt_leeway_secs,
     };
 
     Some(Arc::new(provider))
@@ -318,6 +323,9 @@ fn try_validate_with_jwks(
     let mut validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::RS256);
     validation.set_issuer(&[provider.issuer_url.as_str()]);
     validation.set_audience(&[&provider.client_id]);
+    validation.leeway = provider.leeway_secs;
+    validation.validate_exp = true;
+    validation.validate_nbf = true;
 
     let token_data = jsonwebtoken::decode::<OktaAccessTokenClaims>(
         token,
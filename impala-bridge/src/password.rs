@@ -0,0 +1,88 @@
+use log::warn;
+use sha1::{Digest, Sha1};
+
+/// Look a password up in a k-anonymity breach corpus (the Pwned Passwords
+/// range API or a compatible mirror at `base_url`).  Only the first five hex
+/// characters of the SHA-1 digest leave the process.
+pub async fn is_breached(client: &reqwest::Client, base_url: &str, password: &str) -> Result<bool, String> {
+    let digest = sha1_hex(password);
+    let (prefix, suffix) = digest.split_at(5);
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), prefix);
+
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("breach check request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("breach check returned HTTP {}", response.status()));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("breach check response unreadable: {}", e))?;
+    Ok(range_contains(&body, suffix))
+}
+
+/// Run the optional breach check.  Lookup failures are logged and treated as
+/// "not breached" so an unavailable corpus does not block registration.
+pub async fn check_breached(base_url: Option<&str>, timeout_secs: u64, password: &str) -> bool {
+    let Some(base_url) = base_url else {
+        return false;
+    };
+    let client = match reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(timeout_secs))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("check_breached: failed to create HTTP client: {}", e);
+            return false;
+        }
+    };
+    match is_breached(&client, base_url, password).await {
+        Ok(breached) => breached,
+        Err(e) => {
+            warn!("check_breached: {}; skipping", e);
+            false
+        }
+    }
+}
+
+/// Upper-case hex SHA-1 of `password`, as the range API expects.
+fn sha1_hex(password: &str) -> String {
+    Sha1::digest(password.as_bytes())
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect()
+}
+
+/// Whether a range response (`SUFFIX:COUNT` per line) lists `suffix` with a
+/// non-zero count.  Padding entries carry a count of 0.
+fn range_contains(body: &str, suffix: &str) -> bool {
+    body.lines().any(|line| {
+        let mut parts = line.trim().splitn(2, ':');
+        let hash = parts.next().unwrap_or("");
+        let count = parts.next().and_then(|c| c.trim().parse::<u64>().ok()).unwrap_or(0);
+        hash.eq_ignore_ascii_case(suffix) && count > 0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_lookup_matches_suffix() {
+        // SHA-1("password") = 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+        let digest = sha1_hex("password");
+        assert_eq!(digest, "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8");
+        let suffix = &digest[5..];
+
+        let body = "003D68EB55068C33ACE09247EE4C639306B:3\r\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824\r\n";
+        assert!(range_contains(body, suffix));
+        // Padding rows (count 0) and other suffixes do not match
+        assert!(!range_contains("1E4C9B93F3F0682250B6CF8331B7EE68FD8:0\r\n", suffix));
+        assert!(!range_contains("003D68EB55068C33ACE09247EE4C639306B:3", suffix));
+    }
+}
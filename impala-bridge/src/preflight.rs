@@ -0,0 +1,251 @@
+use log::info;
+use sqlx::postgres::PgPoolOptions;
+use std::env;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::constants::PREFLIGHT_PROBE_TIMEOUT_SECS;
+use crate::handlers::health::{probe_database, probe_redis};
+use crate::stellar_rpc::StellarRpcClient;
+
+/// Outcome of one dependency probe.
+#[derive(Debug, PartialEq)]
+pub enum ProbeStatus {
+    Ok,
+    Fail(String),
+    /// Not configured, or not safely probeable (reason given).
+    Skipped(String),
+}
+
+#[derive(Debug)]
+pub struct ProbeResult {
+    pub name: &'static str,
+    /// A failed required dependency makes `--check` exit non-zero.
+    pub required: bool,
+    pub status: ProbeStatus,
+}
+
+/// Connection details the probes need that do not live in `Config`.
+pub struct PreflightTargets {
+    pub database_url: Option<String>,
+    /// Set when the database URL comes from a single-use Vault wrapping
+    /// token, which `--check` must not consume.
+    pub database_url_wrapped: bool,
+    pub redis_url: String,
+    pub vault_addr: Option<String>,
+}
+
+impl PreflightTargets {
+    pub fn from_env() -> Self {
+        Self {
+            database_url: env::var("DATABASE_URL").ok(),
+            database_url_wrapped: env::var("DATABASE_URL_WRAPPED").is_ok(),
+            redis_url: env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string()),
+            vault_addr: env::var("VAULT_ADDR").ok(),
+        }
+    }
+}
+
+/// Probe every configured dependency (`impala-bridge --check`), giving
+/// each probe at most `timeout`.
+pub async fn run_checks(
+    config: &Config,
+    targets: &PreflightTargets,
+    timeout: Duration,
+) -> Vec<ProbeResult> {
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .unwrap_or_default();
+
+    let database = match (&targets.database_url, targets.database_url_wrapped) {
+        (Some(url), _) => with_timeout(timeout, check_database(url, timeout)).await,
+        (None, true) => ProbeStatus::Skipped(
+            "DATABASE_URL_WRAPPED is single-use; probe Vault instead".to_string(),
+        ),
+        (None, false) => ProbeStatus::Fail("DATABASE_URL is not set".to_string()),
+    };
+
+    let redis = with_timeout(timeout, check_redis(&targets.redis_url)).await;
+
+    let vault = match &targets.vault_addr {
+        Some(addr) => with_timeout(timeout, check_vault(&client, addr)).await,
+        None => ProbeStatus::Skipped("VAULT_ADDR not set".to_string()),
+    };
+
+    let ldap = match &config.ldap_url {
+        Some(url) if config.features.ldap => with_timeout(timeout, check_ldap(config, url)).await,
+        Some(_) => ProbeStatus::Skipped("disabled by feature flag".to_string()),
+        None => ProbeStatus::Skipped("LDAP_URL not set".to_string()),
+    };
+
+    let rpc = StellarRpcClient::new(client.clone(), config.stellar_rpc_url.clone());
+    let stellar_rpc = with_timeout(timeout, check_stellar_rpc(&rpc)).await;
+
+    vec![
+        ProbeResult { name: "database", required: true, status: database },
+        ProbeResult { name: "redis", required: true, status: redis },
+        ProbeResult { name: "vault", required: targets.database_url_wrapped, status: vault },
+        ProbeResult { name: "ldap", required: config.ldap_url.is_some() && config.features.ldap, status: ldap },
+        ProbeResult { name: "stellar_rpc", required: true, status: stellar_rpc },
+    ]
+}
+
+/// Whether every required dependency passed.
+pub fn all_required_ok(results: &[ProbeResult]) -> bool {
+    results
+        .iter()
+        .all(|r| !r.required || r.status == ProbeStatus::Ok)
+}
+
+/// Render results as a fixed-width OK/FAIL/SKIP table.
+pub fn render_table(results: &[ProbeResult]) -> String {
+    let mut out = format!("{:<12} {:<9} {:<5} {}\n", "DEPENDENCY", "REQUIRED", "STATE", "DETAIL");
+    for r in results {
+        let (state, detail) = match &r.status {
+            ProbeStatus::Ok => ("OK", ""),
+            ProbeStatus::Fail(e) => ("FAIL", e.as_str()),
+            ProbeStatus::Skipped(why) => ("SKIP", why.as_str()),
+        };
+        let required = if r.required { "yes" } else { "no" };
+        out.push_str(&format!("{:<12} {:<9} {:<5} {}\n", r.name, required, state, detail));
+    }
+    out
+}
+
+async fn with_timeout<F>(timeout: Duration, probe: F) -> ProbeStatus
+where
+    F: Future<Output = Result<(), String>>,
+{
+    match tokio::time::timeout(timeout, probe).await {
+        Ok(Ok(())) => ProbeStatus::Ok,
+        Ok(Err(e)) => ProbeStatus::Fail(e),
+        Err(_) => ProbeStatus::Fail(format!("timed out after {:?}", timeout)),
+    }
+}
+
+async fn check_database(url: &str, timeout: Duration) -> Result<(), String> {
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(timeout)
+        .connect_lazy(url)
+        .map_err(|e| e.to_string())?;
+    probe_database(&pool).await
+}
+
+async fn check_redis(url: &str) -> Result<(), String> {
+    let pool = deadpool_redis::Config::from_url(url)
+        .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+        .map_err(|e| e.to_string())?;
+    probe_redis(&pool).await
+}
+
+/// `GET /v1/sys/health`: 200 (active) and 429 (standby) are healthy; 503
+/// means sealed and 501 uninitialized.
+async fn check_vault(client: &reqwest::Client, addr: &str) -> Result<(), String> {
+    let url = format!("{}/v1/sys/health", addr.trim_end_matches('/'));
+    let status = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .status()
+        .as_u16();
+    match status {
+        200 | 429 => Ok(()),
+        503 => Err("Vault is sealed".to_string()),
+        501 => Err("Vault is not initialized".to_string()),
+        other => Err(format!("HTTP {}", other)),
+    }
+}
+
+/// Connect and, when credentials are configured, bind.
+async fn check_ldap(config: &Config, url: &str) -> Result<(), String> {
+    let (conn, mut ldap) = ldap3::LdapConnAsync::new(url)
+        .await
+        .map_err(|e| e.to_string())?;
+    tokio::spawn(async move {
+        let _ = conn.drive().await;
+    });
+    if let (Some(dn), Some(pw)) = (&config.ldap_bind_dn, &config.ldap_bind_password) {
+        let result = ldap.simple_bind(dn, pw).await.map_err(|e| e.to_string())?;
+        if result.rc != 0 {
+            let _ = ldap.unbind().await;
+            return Err(format!("bind failed (rc={}): {}", result.rc, result.text));
+        }
+    }
+    let _ = ldap.unbind().await;
+    Ok(())
+}
+
+/// Soroban RPC `getHealth` must report `"healthy"`.
+async fn check_stellar_rpc(rpc: &StellarRpcClient) -> Result<(), String> {
+    let health = rpc.get_health().await.map_err(|e| e.to_string())?;
+    match health.status.as_str() {
+        "healthy" => Ok(()),
+        other => Err(format!("status {}", other)),
+    }
+}
+
+/// Run the checks, print the table and return the process exit code.
+pub async fn run(config: &Config) -> i32 {
+    info!("Running pre-flight dependency checks");
+    let timeout = Duration::from_secs(PREFLIGHT_PROBE_TIMEOUT_SECS);
+    let results = run_checks(config, &PreflightTargets::from_env(), timeout).await;
+    print!("{}", render_table(&results));
+    if all_required_ok(&results) {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A local address nothing listens on.
+    async fn closed_port() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        addr.to_string()
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_unreachable_dependencies() {
+        let dead = closed_port().await;
+        let mut config = crate::config::load_config();
+        config.ldap_url = None;
+        config.stellar_rpc_url = format!("http://{}/", dead);
+        let targets = PreflightTargets {
+            database_url: Some(format!("postgres://impala:impala@{}/impala", dead)),
+            database_url_wrapped: false,
+            redis_url: format!("redis://{}/", dead),
+            vault_addr: None,
+        };
+
+        let results = run_checks(&config, &targets, Duration::from_secs(1)).await;
+        let status_of = |name: &str| &results.iter().find(|r| r.name == name).unwrap().status;
+        assert!(matches!(status_of("database"), ProbeStatus::Fail(_)));
+        assert!(matches!(status_of("redis"), ProbeStatus::Fail(_)));
+        assert!(matches!(status_of("stellar_rpc"), ProbeStatus::Fail(_)));
+        assert!(matches!(status_of("vault"), ProbeStatus::Skipped(_)));
+        assert!(!all_required_ok(&results));
+
+        let table = render_table(&results);
+        assert!(table.contains("redis"));
+        assert!(table.contains("FAIL"));
+    }
+
+    #[test]
+    fn test_optional_failure_does_not_fail_check() {
+        let results = vec![
+            ProbeResult { name: "database", required: true, status: ProbeStatus::Ok },
+            ProbeResult { name: "vault", required: false, status: ProbeStatus::Fail("sealed".into()) },
+            ProbeResult { name: "ldap", required: false, status: ProbeStatus::Skipped("unset".into()) },
+        ];
+        assert!(all_required_ok(&results));
+    }
+}
@@ -0,0 +1,247 @@
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use deadpool_redis::Pool as RedisPool;
+use redis::aio::ConnectionLike;
+use redis::{RedisResult, Script};
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, LazyLock};
+
+use crate::config::Config;
+use crate::constants::{RATE_LIMIT_MAX_REQUESTS, RATE_LIMIT_WINDOW_SECS};
+use crate::error::AppError;
+use crate::redis_helpers::check_rate_limit;
+
+/// Redis scope of the per-IP counter.  Shared by every login endpoint, so
+/// one address spraying usernames is throttled across all of them.
+const IP_SCOPE: &str = "ip";
+
+/// Increment a rate-limit counter and start its window in one step.  The
+/// TTL is set when the counter is created, and also when a counter has
+/// somehow been left without one, so a key can never outlive its window.
+static INCR_WITH_TTL: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        local count = redis.call('INCR', KEYS[1])
+        if count == 1 or redis.call('TTL', KEYS[1]) == -1 then
+            redis.call('EXPIRE', KEYS[1], ARGV[1])
+        end
+        return count
+        ",
+    )
+});
+
+/// Atomically `INCR` `key`, setting a `window_secs` TTL on the first
+/// increment.  Returns the count including this request.
+pub async fn incr_with_ttl<C: ConnectionLike>(
+    conn: &mut C,
+    key: &str,
+    window_secs: usize,
+) -> RedisResult<u64> {
+    INCR_WITH_TTL
+        .key(key)
+        .arg(window_secs)
+        .invoke_async(conn)
+        .await
+}
+
+/// The caller's IP address, for rate limiting.
+///
+/// The TCP peer address, unless the peer is one of `trusted_proxies`: then
+/// the last `X-Forwarded-For` entry, which is the one appended by that load
+/// balancer.  Earlier entries, and the header from any other peer, are
+/// supplied by the client and could be rotated to dodge the limit.  `None`
+/// when there is no peer address (e.g. in tests that call the router
+/// directly).
+pub struct ClientIp(pub Option<String>);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| *addr);
+        let trusted_proxies = parts
+            .extensions
+            .get::<Arc<Config>>()
+            .map(|config| config.trusted_proxies.as_slice())
+            .unwrap_or_default();
+        Ok(ClientIp(client_ip(&parts.headers, peer, trusted_proxies)))
+    }
+}
+
+fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, trusted_proxies: &[IpAddr]) -> Option<String> {
+    let peer = peer?.ip();
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer.to_string());
+    }
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .rfind(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .or_else(|| Some(peer.to_string()))
+}
+
+/// The counters a login attempt is charged against: the per-account one
+/// under `scope`, then the per-IP one when the address is known.
+fn login_counters(
+    scope: &'static str,
+    account_id: &str,
+    client_ip: &ClientIp,
+    ip_max_requests: u64,
+    ip_window_secs: usize,
+) -> Vec<(&'static str, String, u64, usize)> {
+    let mut counters = vec![(
+        scope,
+        account_id.to_string(),
+        RATE_LIMIT_MAX_REQUESTS,
+        RATE_LIMIT_WINDOW_SECS,
+    )];
+    if let ClientIp(Some(ip)) = client_ip {
+        counters.push((
+            IP_SCOPE,
+            ip.clone(),
+            ip_max_requests,
+            ip_window_secs,
+        ));
+    }
+    counters
+}
+
+/// Rate-limit a login attempt by account and by client IP.  Returns
+/// `AppError::RateLimited` if either counter trips; fails closed like
+/// `check_rate_limit`.
+pub async fn check_login_rate_limit(
+    pool: &RedisPool,
+    config: &Config,
+    scope: &'static str,
+    account_id: &str,
+    client_ip: &ClientIp,
+) -> Result<(), AppError> {
+    let counters = login_counters(
+        scope,
+        account_id,
+        client_ip,
+        config.ip_rate_limit_max_requests,
+        config.ip_rate_limit_window_secs,
+    );
+    for (scope, id, max_requests, window_secs) in counters {
+        check_rate_limit(pool, scope, &id, max_requests, window_secs).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{DEFAULT_IP_RATE_LIMIT_MAX_REQUESTS, DEFAULT_IP_RATE_LIMIT_WINDOW_SECS};
+    use crate::redis_helpers::enforce_limit;
+    use axum::http::HeaderValue;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_client_ip_uses_last_forwarded_entry() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("1.1.1.1, 203.0.113.7"),
+        );
+        let peer: SocketAddr = "10.0.0.5:443".parse().unwrap();
+        let trusted = [peer.ip()];
+        assert_eq!(client_ip(&headers, Some(peer), &trusted).as_deref(), Some("203.0.113.7"));
+        assert_eq!(client_ip(&HeaderMap::new(), Some(peer), &trusted).as_deref(), Some("10.0.0.5"));
+        assert_eq!(client_ip(&headers, None, &trusted), None);
+    }
+
+    #[test]
+    fn test_client_ip_ignores_forwarded_header_from_untrusted_peer() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7"));
+        let peer: SocketAddr = "198.51.100.9:5000".parse().unwrap();
+        assert_eq!(client_ip(&headers, Some(peer), &[]).as_deref(), Some("198.51.100.9"));
+        let proxy: IpAddr = "10.0.0.5".parse().unwrap();
+        assert_eq!(client_ip(&headers, Some(peer), &[proxy]).as_deref(), Some("198.51.100.9"));
+    }
+
+    #[test]
+    fn test_one_ip_spraying_usernames_is_throttled() {
+        let ip = ClientIp(Some("203.0.113.7".to_string()));
+
+        // Replay the Redis counters in memory: each attempt is checked
+        // against every counter, then increments them all.
+        let mut counts: HashMap<(&str, String), u64> = HashMap::new();
+        let mut attempt = |username: &str| -> Result<(), AppError> {
+            let counters = login_counters(
+                "auth",
+                username,
+                &ip,
+                DEFAULT_IP_RATE_LIMIT_MAX_REQUESTS,
+                DEFAULT_IP_RATE_LIMIT_WINDOW_SECS,
+            );
+            for (scope, id, max_requests, window_secs) in &counters {
+                let count = counts.get(&(*scope, id.clone())).copied().unwrap_or(0);
+                enforce_limit(count, *max_requests, *window_secs)?;
+            }
+            for (scope, id, _, _) in counters {
+                *counts.entry((scope, id)).or_insert(0) += 1;
+            }
+            Ok(())
+        };
+
+        for i in 0..DEFAULT_IP_RATE_LIMIT_MAX_REQUESTS {
+            assert!(attempt(&format!("user-{i}")).is_ok());
+        }
+        let err = attempt("user-fresh").unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::RateLimited { retry_after } if retry_after == DEFAULT_IP_RATE_LIMIT_WINDOW_SECS as u64
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn test_incr_with_ttl_sets_ttl_on_first_increment() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let mut conn = redis::Client::open(url)
+            .unwrap()
+            .get_multiplexed_async_connection()
+            .await
+            .expect("Redis not reachable");
+        let key = format!("impala:rate:test:{}", uuid::Uuid::new_v4());
+
+        assert_eq!(incr_with_ttl(&mut conn, &key, 60).await.unwrap(), 1);
+        let ttl: i64 = redis::cmd("TTL").arg(&key).query_async(&mut conn).await.unwrap();
+        assert!(ttl > 0 && ttl <= 60);
+
+        // A counter left without a TTL gets one on its next increment
+        let _: () = redis::cmd("PERSIST").arg(&key).query_async(&mut conn).await.unwrap();
+        assert_eq!(incr_with_ttl(&mut conn, &key, 60).await.unwrap(), 2);
+        let ttl: i64 = redis::cmd("TTL").arg(&key).query_async(&mut conn).await.unwrap();
+        assert!(ttl > 0 && ttl <= 60);
+
+        let _: () = redis::cmd("DEL").arg(&key).query_async(&mut conn).await.unwrap();
+    }
+
+    #[test]
+    fn test_unknown_ip_only_counts_account() {
+        let counters = login_counters(
+            "token",
+            "alice",
+            &ClientIp(None),
+            DEFAULT_IP_RATE_LIMIT_MAX_REQUESTS,
+            DEFAULT_IP_RATE_LIMIT_WINDOW_SECS,
+        );
+        assert_eq!(counters.len(), 1);
+        assert_eq!(counters[0].0, "token");
+    }
+}
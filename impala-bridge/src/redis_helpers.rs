@@ -4,6 +4,7 @@ use redis::AsyncCommands;
 
 use crate::constants::LOCKOUT_DURATION_SECS;
 use crate::error::AppError;
+use crate::models::Claims;
 
 /// Check whether the caller has exceeded the rate limit for the given scope and
 /// identity.  When Redis is unavailable the request is **rejected** (fail-closed).
@@ -21,28 +22,49 @@ pub async fn check_rate_limit(
 
     let key = format!("impala:rate:{scope}:{id}");
 
-    let count: u64 = conn.get(&key).await.map_err(|e| {
-        warn!("check_rate_limit: Redis GET failed for {}: {}", key, e);
-        AppError::InternalError("Service temporarily unavailable".to_string())
-    })?;
+    let count = crate::ratelimit::incr_with_ttl(&mut conn, &key, window_secs)
+        .await
+        .map_err(|e| {
+            warn!("check_rate_limit: Redis increment failed for {}: {}", key, e);
+            AppError::InternalError("Service temporarily unavailable".to_string())
+        })?;
+
+    // `count` includes this request
+    enforce_limit(count.saturating_sub(1), max_requests, window_secs)
+}
 
+/// Reject once `count` requests have already been made in the window.
+pub(crate) fn enforce_limit(count: u64, max_requests: u64, window_secs: usize) -> Result<(), AppError> {
     if count >= max_requests {
         return Err(AppError::RateLimited {
             retry_after: window_secs as u64,
         });
     }
+    Ok(())
+}
 
-    let _: () = conn.incr(&key, 1u64).await.map_err(|e| {
-        warn!("check_rate_limit: Redis INCR failed for {}: {}", key, e);
-        AppError::InternalError("Service temporarily unavailable".to_string())
-    })?;
-
-    let _: () = conn.expire(&key, window_secs as i64).await.map_err(|e| {
-        warn!("check_rate_limit: Redis EXPIRE failed for {}: {}", key, e);
-        AppError::InternalError("Service temporarily unavailable".to_string())
-    })?;
+/// Rate-limit contract build/submit calls by account and contract id, under
+/// its own `contract` namespace so on-chain operations do not share the
+/// authentication budget.
+pub async fn check_contract_rate_limit(
+    pool: &RedisPool,
+    account_id: &str,
+    contract_id: &str,
+    max_requests: u64,
+    window_secs: usize,
+) -> Result<(), AppError> {
+    check_rate_limit(
+        pool,
+        "contract",
+        &contract_rate_limit_id(account_id, contract_id),
+        max_requests,
+        window_secs,
+    )
+    .await
+}
 
-    Ok(())
+fn contract_rate_limit_id(account_id: &str, contract_id: &str) -> String {
+    format!("{account_id}:{contract_id}")
 }
 
 /// Check whether the given identity is currently locked out due to repeated
@@ -65,34 +87,37 @@ pub async fn check_lockout(
     })?;
 
     if count >= threshold {
+        // Report the time actually left on the lockout
+        let ttl: i64 = conn.ttl(&key).await.unwrap_or(-1);
         return Err(AppError::RateLimited {
-            retry_after: LOCKOUT_DURATION_SECS as u64,
+            retry_after: if ttl > 0 { ttl as u64 } else { LOCKOUT_DURATION_SECS as u64 },
         });
     }
 
     Ok(())
 }
 
-/// Increment the lockout counter for the given identity.  Fire-and-forget: errors
-/// are logged but never returned.
-pub async fn increment_lockout(pool: &RedisPool, id: &str, ttl_secs: usize) {
+/// Increment the lockout counter for the given identity and return the new
+/// failure count.  The first failure starts a `ttl_secs` lockout window;
+/// later ones do not extend it.  Fire-and-forget: errors are logged and give
+/// `None`.
+pub async fn increment_lockout(pool: &RedisPool, id: &str, ttl_secs: usize) -> Option<u64> {
     let mut conn = match pool.get().await {
         Ok(c) => c,
         Err(e) => {
             error!("increment_lockout: failed to get Redis connection: {}", e);
-            return;
+            return None;
         }
     };
 
     let key = format!("impala:lockout:{id}");
 
-    if let Err(e) = conn.incr::<_, u64, u64>(&key, 1).await {
-        warn!("increment_lockout: Redis INCR failed for {}: {}", key, e);
-        return;
-    }
-
-    if let Err(e) = conn.expire::<_, ()>(&key, ttl_secs as i64).await {
-        warn!("increment_lockout: Redis EXPIRE failed for {}: {}", key, e);
+    match crate::ratelimit::incr_with_ttl(&mut conn, &key, ttl_secs).await {
+        Ok(failures) => Some(failures),
+        Err(e) => {
+            warn!("increment_lockout: Redis increment failed for {}: {}", key, e);
+            None
+        }
     }
 }
 
@@ -113,106 +138,151 @@ pub async fn clear_lockout(pool: &RedisPool, id: &str) {
     }
 }
 
-/// Check whether a JWT has been revoked.  Fails closed: if Redis is unavailable
-/// the token is treated as revoked (`Err(AppError::Unauthorized)`).
+/// Check whether a JWT, or its whole token family, has been revoked.  Fails
+/// closed: if Redis is unavailable the token is treated as revoked
+/// (`Err(AppError::Unauthorized)`).
 pub async fn is_token_revoked(
     pool: &RedisPool,
-    jti: &str,
+    claims: &Claims,
 ) -> Result<bool, AppError> {
     let mut conn = pool.get().await.map_err(|e| {
         error!("is_token_revoked: failed to get Redis connection: {}", e);
         AppError::Unauthorized
     })?;
 
-    let key = format!("impala:revoked:{jti}");
+    let mut keys = vec![format!("impala:revoked:{}", claims.jti)];
+    if !claims.family.is_empty() {
+        keys.push(family_revocation_key(&claims.family));
+    }
 
-    let exists: bool = conn.exists(&key).await.map_err(|e| {
-        warn!("is_token_revoked: Redis EXISTS failed for {}: {}", key, e);
+    let revoked: u64 = conn.exists(&keys).await.map_err(|e| {
+        warn!("is_token_revoked: Redis EXISTS failed for {:?}: {}", keys, e);
         AppError::Unauthorized
     })?;
 
-    Ok(exists)
+    Ok(revoked > 0)
 }
 
-/// Mark a JWT as revoked for the given TTL.  Fire-and-forget.
-pub async fn revoke_token(pool: &RedisPool, jti: &str, ttl_secs: usize) {
-    let mut conn = match pool.get().await {
-        Ok(c) => c,
-        Err(e) => {
-            error!("revoke_token: failed to get Redis connection: {}", e);
-            return;
-        }
-    };
+fn family_revocation_key(family: &str) -> String {
+    format!("impala:revoked_family:{family}")
+}
 
-    let key = format!("impala:revoked:{jti}");
+/// Mark a refresh token as used by rotation.  Returns `false` if it had
+/// already been consumed, i.e. it is being replayed.  Fails closed.
+pub async fn consume_refresh_token(
+    pool: &RedisPool,
+    jti: &str,
+    ttl_secs: usize,
+) -> Result<bool, AppError> {
+    let mut conn = pool.get().await.map_err(|e| {
+        error!("consume_refresh_token: failed to get Redis connection: {}", e);
+        AppError::Unauthorized
+    })?;
 
-    if let Err(e) = conn.set_ex::<_, &str, ()>(&key, "1", ttl_secs as u64).await {
-        warn!("revoke_token: Redis SET_EX failed for {}: {}", key, e);
-    }
+    let key = format!("impala:consumed:{jti}");
+
+    // SET NX so two concurrent exchanges of one token can't both succeed
+    let set: Option<String> = redis::cmd("SET")
+        .arg(&key)
+        .arg("1")
+        .arg("NX")
+        .arg("EX")
+        .arg(ttl_secs.max(1))
+        .query_async(&mut conn)
+        .await
+        .map_err(|e| {
+            warn!("consume_refresh_token: Redis SET NX failed for {}: {}", key, e);
+            AppError::Unauthorized
+        })?;
+
+    Ok(set.is_some())
 }
 
-/// Check whether MFA verification attempts have been exhausted.  Fails closed.
-pub async fn check_mfa_lockout(
-    pool: &RedisPool,
-    account_id: &str,
-    mfa_type: &str,
-    threshold: u64,
-) -> Result<(), AppError> {
+/// Revoke every token in a family for the given TTL.  Returns
+/// `Err(AppError::ServiceUnavailable)` if Redis did not record it, so callers
+/// never report a revocation that didn't happen.
+pub async fn revoke_token_family(pool: &RedisPool, family: &str, ttl_secs: usize) -> Result<(), AppError> {
     let mut conn = pool.get().await.map_err(|e| {
-        error!("check_mfa_lockout: failed to get Redis connection: {}", e);
-        AppError::InternalError("Service temporarily unavailable".to_string())
+        error!("revoke_token_family: failed to get Redis connection: {}", e);
+        revocation_unavailable()
     })?;
 
-    let key = format!("impala:mfa_attempts:{account_id}:{mfa_type}");
+    let key = family_revocation_key(family);
 
-    let count: u64 = conn.get(&key).await.map_err(|e| {
-        warn!("check_mfa_lockout: Redis GET failed for {}: {}", key, e);
-        AppError::InternalError("Service temporarily unavailable".to_string())
+    conn.set_ex::<_, &str, ()>(&key, "1", ttl_secs as u64).await.map_err(|e| {
+        warn!("revoke_token_family: Redis SET_EX failed for {}: {}", key, e);
+        revocation_unavailable()
+    })
+}
+
+/// Mark a JWT as revoked for the given TTL.  Fails like
+/// `revoke_token_family`.
+pub async fn revoke_token(pool: &RedisPool, jti: &str, ttl_secs: usize) -> Result<(), AppError> {
+    let mut conn = pool.get().await.map_err(|e| {
+        error!("revoke_token: failed to get Redis connection: {}", e);
+        revocation_unavailable()
     })?;
 
-    if count >= threshold {
-        return Err(AppError::RateLimited {
-            retry_after: LOCKOUT_DURATION_SECS as u64,
-        });
-    }
+    let key = format!("impala:revoked:{jti}");
 
-    Ok(())
+    conn.set_ex::<_, &str, ()>(&key, "1", ttl_secs as u64).await.map_err(|e| {
+        warn!("revoke_token: Redis SET_EX failed for {}: {}", key, e);
+        revocation_unavailable()
+    })
+}
+
+fn revocation_unavailable() -> AppError {
+    AppError::ServiceUnavailable("Token revocation is temporarily unavailable".to_string())
+}
+
+/// Redis key counting MFA verification attempts for an account and type.
+fn mfa_attempts_key(account_id: &str, mfa_type: &str) -> String {
+    format!("impala:mfa_attempts:{account_id}:{mfa_type}")
 }
 
-/// Increment the MFA attempt counter.  Fire-and-forget.
-pub async fn increment_mfa_attempts(
+/// Count an MFA verification attempt, refusing it once more than
+/// `threshold` have been made in the window.  The first attempt starts a
+/// `window_secs` window; later ones do not extend it.  The attempt is
+/// counted before the code is checked, in one atomic increment, so parallel
+/// guesses each see their own count and can't all slip in under the
+/// threshold.  `clear_mfa_attempts` resets it on success.  Fails closed.
+pub async fn claim_mfa_attempt(
     pool: &RedisPool,
     account_id: &str,
     mfa_type: &str,
-    ttl_secs: usize,
-) {
-    let mut conn = match pool.get().await {
-        Ok(c) => c,
-        Err(e) => {
-            error!(
-                "increment_mfa_attempts: failed to get Redis connection: {}",
-                e
-            );
-            return;
-        }
-    };
+    threshold: u64,
+    window_secs: usize,
+) -> Result<(), AppError> {
+    let mut conn = pool.get().await.map_err(|e| {
+        error!("claim_mfa_attempt: failed to get Redis connection: {}", e);
+        AppError::InternalError("Service temporarily unavailable".to_string())
+    })?;
 
-    let key = format!("impala:mfa_attempts:{account_id}:{mfa_type}");
+    let key = mfa_attempts_key(account_id, mfa_type);
 
-    if let Err(e) = conn.incr::<_, u64, u64>(&key, 1).await {
-        warn!(
-            "increment_mfa_attempts: Redis INCR failed for {}: {}",
-            key, e
-        );
-        return;
+    let attempts = crate::ratelimit::incr_with_ttl(&mut conn, &key, window_secs)
+        .await
+        .map_err(|e| {
+            warn!("claim_mfa_attempt: Redis increment failed for {}: {}", key, e);
+            AppError::InternalError("Service temporarily unavailable".to_string())
+        })?;
+    if attempts <= threshold {
+        return Ok(());
     }
 
-    if let Err(e) = conn.expire::<_, ()>(&key, ttl_secs as i64).await {
-        warn!(
-            "increment_mfa_attempts: Redis EXPIRE failed for {}: {}",
-            key, e
-        );
+    let ttl: i64 = conn.ttl(&key).await.unwrap_or(-1);
+    enforce_mfa_lockout(attempts, threshold, ttl, window_secs)
+}
+
+/// Reject once `attempts` exceeds `threshold`, reporting the time left on
+/// the counter (`ttl`), or the whole window if Redis could not say.
+fn enforce_mfa_lockout(attempts: u64, threshold: u64, ttl: i64, window_secs: usize) -> Result<(), AppError> {
+    if attempts <= threshold {
+        return Ok(());
     }
+    Err(AppError::RateLimited {
+        retry_after: if ttl > 0 { ttl as u64 } else { window_secs as u64 },
+    })
 }
 
 /// Clear the MFA attempt counter after a successful verification.  Fire-and-forget.
@@ -232,7 +302,7 @@ pub async fn clear_mfa_attempts(
         }
     };
 
-    let key = format!("impala:mfa_attempts:{account_id}:{mfa_type}");
+    let key = mfa_attempts_key(account_id, mfa_type);
 
     if let Err(e) = conn.del::<_, ()>(&key).await {
         warn!(
@@ -241,3 +311,137 @@ pub async fn clear_mfa_attempts(
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{
+        DEFAULT_CONTRACT_RATE_LIMIT_MAX_REQUESTS, DEFAULT_CONTRACT_RATE_LIMIT_WINDOW_SECS,
+    };
+
+    #[test]
+    fn test_enforce_limit_allows_normal_usage() {
+        for count in 0..DEFAULT_CONTRACT_RATE_LIMIT_MAX_REQUESTS {
+            assert!(enforce_limit(
+                count,
+                DEFAULT_CONTRACT_RATE_LIMIT_MAX_REQUESTS,
+                DEFAULT_CONTRACT_RATE_LIMIT_WINDOW_SECS
+            )
+            .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_enforce_limit_trips_on_rapid_submissions() {
+        let err = enforce_limit(
+            DEFAULT_CONTRACT_RATE_LIMIT_MAX_REQUESTS,
+            DEFAULT_CONTRACT_RATE_LIMIT_MAX_REQUESTS,
+            DEFAULT_CONTRACT_RATE_LIMIT_WINDOW_SECS,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::RateLimited { retry_after } if retry_after == DEFAULT_CONTRACT_RATE_LIMIT_WINDOW_SECS as u64
+        ));
+    }
+
+    #[test]
+    fn test_contract_rate_limit_id_separates_contracts() {
+        assert_ne!(
+            contract_rate_limit_id("acct", "CAAA"),
+            contract_rate_limit_id("acct", "CBBB")
+        );
+        assert_eq!(contract_rate_limit_id("acct", "CAAA"), "acct:CAAA");
+    }
+
+    #[test]
+    fn test_repeated_wrong_mfa_codes_trigger_lockout() {
+        let threshold = crate::constants::DEFAULT_MFA_LOCKOUT_THRESHOLD;
+        let window = crate::constants::DEFAULT_MFA_LOCKOUT_WINDOW_SECS;
+
+        // Replay the Redis counter: each code is counted, then checked
+        let mut attempts = 0;
+        for _ in 0..threshold {
+            attempts += 1;
+            assert!(enforce_mfa_lockout(attempts, threshold, window as i64, window).is_ok());
+        }
+        attempts += 1;
+        let err = enforce_mfa_lockout(attempts, threshold, 42, window).unwrap_err();
+        assert!(matches!(err, AppError::RateLimited { retry_after: 42 }));
+
+        // Without a TTL the whole window is reported
+        let err = enforce_mfa_lockout(attempts, threshold, -1, window).unwrap_err();
+        assert!(matches!(err, AppError::RateLimited { retry_after } if retry_after == window as u64));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn test_mfa_attempts_lock_out_and_clear() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let pool = deadpool_redis::Config::from_url(url)
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        pool.get().await.expect("Redis not reachable");
+        let account = format!("test-{}", uuid::Uuid::new_v4());
+
+        for _ in 0..3 {
+            assert!(claim_mfa_attempt(&pool, &account, "totp", 3, 60).await.is_ok());
+        }
+        let err = claim_mfa_attempt(&pool, &account, "totp", 3, 60).await.unwrap_err();
+        assert!(matches!(err, AppError::RateLimited { retry_after } if retry_after > 0 && retry_after <= 60));
+        // Other MFA types have their own counter
+        assert!(claim_mfa_attempt(&pool, &account, "sms", 3, 60).await.is_ok());
+
+        clear_mfa_attempts(&pool, &account, "totp").await;
+        assert!(claim_mfa_attempt(&pool, &account, "totp", 3, 60).await.is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn test_increment_lockout_always_sets_ttl() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let pool = deadpool_redis::Config::from_url(url)
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        let mut conn = pool.get().await.expect("Redis not reachable");
+        let id = format!("test-{}", uuid::Uuid::new_v4());
+        let key = format!("impala:lockout:{id}");
+
+        assert_eq!(increment_lockout(&pool, &id, 60).await, Some(1));
+        let ttl: i64 = conn.ttl(&key).await.unwrap();
+        assert!(ttl > 0 && ttl <= 60);
+
+        // Later failures don't slide the window
+        let _: () = conn.expire(&key, 30).await.unwrap();
+        assert_eq!(increment_lockout(&pool, &id, 60).await, Some(2));
+        let ttl: i64 = conn.ttl(&key).await.unwrap();
+        assert!(ttl > 0 && ttl <= 30);
+
+        // A counter left without a TTL gets one on its next increment
+        let _: () = redis::cmd("PERSIST").arg(&key).query_async(&mut *conn).await.unwrap();
+        assert_eq!(increment_lockout(&pool, &id, 60).await, Some(3));
+        let ttl: i64 = conn.ttl(&key).await.unwrap();
+        assert!(ttl > 0 && ttl <= 60);
+
+        clear_lockout(&pool, &id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn test_parallel_mfa_attempts_cannot_pass_the_threshold() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let pool = deadpool_redis::Config::from_url(url)
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        pool.get().await.expect("Redis not reachable");
+        let account = format!("test-{}", uuid::Uuid::new_v4());
+
+        let attempts = (0..20).map(|_| claim_mfa_attempt(&pool, &account, "totp", 5, 60));
+        let allowed = futures::future::join_all(attempts)
+            .await
+            .into_iter()
+            .filter(Result::is_ok)
+            .count();
+        assert_eq!(allowed, 5);
+    }
+}
@@ -0,0 +1,97 @@
+use log::{info, warn};
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::constants::STARTUP_RETRY_MAX_DELAY_SECS;
+
+/// Run `op` until it succeeds or `attempts` tries have failed, sleeping
+/// `initial_delay` after the first failure and doubling the delay after each
+/// further one (capped at `STARTUP_RETRY_MAX_DELAY_SECS`).  Returns the last
+/// error once the budget is spent.
+pub async fn retry_with_backoff<T, E, F, Fut>(
+    what: &str,
+    attempts: u32,
+    initial_delay: Duration,
+    mut op: F,
+) -> Result<T, E>
+where
+    E: Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let attempts = attempts.max(1);
+    let mut delay = initial_delay;
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => {
+                if attempt > 1 {
+                    info!("{}: connected on attempt {}/{}", what, attempt, attempts);
+                }
+                return Ok(value);
+            }
+            Err(e) if attempt >= attempts => {
+                return Err(e);
+            }
+            Err(e) => {
+                warn!(
+                    "{}: attempt {}/{} failed: {}; retrying in {:?}",
+                    what, attempt, attempts, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                delay = next_delay(delay);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Double `delay`, capped at `STARTUP_RETRY_MAX_DELAY_SECS`.
+fn next_delay(delay: Duration) -> Duration {
+    (delay * 2).min(Duration::from_secs(STARTUP_RETRY_MAX_DELAY_SECS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// A dependency that refuses the first `failures` connections.
+    async fn flaky(calls: &Cell<u32>, failures: u32) -> Result<u32, String> {
+        calls.set(calls.get() + 1);
+        if calls.get() <= failures {
+            Err(format!("connection refused ({})", calls.get()))
+        } else {
+            Ok(calls.get())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_once_dependency_is_up() {
+        let calls = Cell::new(0);
+        let result = retry_with_backoff("database", 5, Duration::from_millis(1), || {
+            flaky(&calls, 3)
+        })
+        .await;
+        assert_eq!(result, Ok(4));
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_budget() {
+        let calls = Cell::new(0);
+        let result = retry_with_backoff("redis", 3, Duration::from_millis(1), || {
+            flaky(&calls, 10)
+        })
+        .await;
+        assert_eq!(result, Err("connection refused (3)".to_string()));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_cap() {
+        assert_eq!(next_delay(Duration::from_secs(1)), Duration::from_secs(2));
+        assert_eq!(next_delay(Duration::from_secs(20)), Duration::from_secs(STARTUP_RETRY_MAX_DELAY_SECS));
+    }
+}
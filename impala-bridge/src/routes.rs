@@ -0,0 +1,200 @@
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use axum::routing::{get, post, put};
+use axum::Router;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use crate::config::FeatureFlags;
+use crate::handlers::{
+    account, admin, authenticate, card, contract, cron, device_token, features, health, index, logout, mfa, network,
+    notification_subscription, notify, okta, strkey, subscribe, sync, token, transaction,
+};
+
+/// Build the application routes.  Route groups whose feature flag is
+/// disabled are omitted entirely, so requests to them return 404.
+pub fn build_router(features: &FeatureFlags) -> Router {
+    let mut app = Router::new()
+        .route("/", get(health::default_route))
+        .route("/health", get(health::health_check))
+        .route("/version", get(health::get_version))
+        .route("/account", post(account::create_account).get(account::get_account).put(account::update_account).delete(account::delete_account))
+        .route("/account/by-payala", get(account::get_account_by_payala))
+        .route("/account/status", put(account::update_account_status))
+        .route("/authenticate", post(authenticate::authenticate))
+        .route("/register", post(authenticate::register))
+        .route("/password", put(authenticate::change_password))
+        .route("/sync", post(sync::sync_account))
+        .route("/token", post(token::token))
+        .route("/token/revoke", post(logout::revoke_token))
+        .route("/transaction", post(transaction::create_transaction))
+        .route("/logout", post(logout::logout))
+        .route("/auth/okta", post(okta::okta_token_exchange))
+        .route("/auth/okta/config", get(okta::okta_config))
+        .route("/healthz", get(health::liveness))
+        .route("/readyz", get(health::readiness))
+        .route("/network", get(network::network_info))
+        .route("/features", get(features::list_features))
+        .route("/stellar/strkey/validate", post(strkey::validate_strkey))
+        .route("/admin/accounts", get(account::list_accounts))
+        .route("/admin/config", get(admin::get_config))
+        .route("/admin/maintenance", get(admin::get_maintenance).put(admin::set_maintenance))
+        .route("/index/reprocess", post(index::reprocess));
+
+    if features.subscribe {
+        app = app
+            .route("/subscribe", post(subscribe::subscribe))
+            .route("/unsubscribe", post(subscribe::unsubscribe));
+    }
+    if features.cards {
+        app = app.route("/card", post(card::create_card).delete(card::delete_card));
+    }
+    if features.mfa {
+        app = app
+            .route("/mfa", post(mfa::enroll_mfa).get(mfa::get_mfa))
+            .route("/mfa/confirm", post(mfa::confirm_mfa))
+            .route("/mfa/send", post(mfa::send_mfa_code))
+            .route("/mfa/verify", post(mfa::verify_mfa))
+            .route("/admin/mfa", get(mfa::list_mfa_enrollments));
+    }
+    if features.notifications {
+        app = app
+            .route("/notify", get(notify::list_notify).post(notify::create_notify).put(notify::update_notify))
+            .route("/notification/subscriptions", get(notification_subscription::list_subscriptions).post(notification_subscription::create_subscription))
+            .route("/notification/subscriptions/{id}", put(notification_subscription::update_subscription).delete(notification_subscription::delete_subscription))
+            .route("/device-token", post(device_token::register_device_token).delete(device_token::delete_device_token));
+    }
+    if features.cron_sync {
+        app = app
+            .route("/cron/callback", post(cron::register_callback))
+            .route("/cron/callback/verify", post(cron::verify_callback))
+            .route("/cron/run", post(cron::run_now));
+    }
+    if features.contract {
+        app = app
+            .route("/contract/build-invoke", post(contract::build_invoke))
+            .route("/contract/submit", post(contract::submit))
+            .route("/contract/status", get(contract::status));
+    }
+
+    app
+}
+
+/// CORS policy for `CORS_ALLOWED_ORIGINS`: `*` allows any origin, anything
+/// else is a comma-separated allow-list.  A malformed origin is an error so
+/// a typo fails at startup instead of silently locking browsers out.
+pub fn cors_layer(allowed_origins: &str) -> Result<CorsLayer, String> {
+    let allow_origin = if allowed_origins.trim() == "*" {
+        AllowOrigin::from(Any)
+    } else {
+        let origins = allowed_origins
+            .split(',')
+            .map(str::trim)
+            .filter(|o| !o.is_empty())
+            .map(|o| o.parse::<HeaderValue>().map_err(|_| format!("invalid origin '{}'", o)))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static("x-request-nonce"),
+        ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    async fn status_of(features: &FeatureFlags, method: &str, uri: &str) -> StatusCode {
+        build_router(features)
+            .oneshot(Request::builder().method(method).uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_disabled_feature_routes_are_absent() {
+        let features = FeatureFlags {
+            mfa: false,
+            cards: false,
+            subscribe: false,
+            notifications: false,
+            contract: false,
+            ..FeatureFlags::default()
+        };
+        for (method, uri) in [
+            ("GET", "/mfa"),
+            ("POST", "/mfa/confirm"),
+            ("POST", "/mfa/send"),
+            ("POST", "/mfa/verify"),
+            ("GET", "/admin/mfa"),
+            ("POST", "/card"),
+            ("POST", "/subscribe"),
+            ("POST", "/unsubscribe"),
+            ("GET", "/notify"),
+            ("DELETE", "/notification/subscriptions/1"),
+            ("POST", "/contract/submit"),
+        ] {
+            assert_eq!(status_of(&features, method, uri).await, StatusCode::NOT_FOUND, "{} {}", method, uri);
+        }
+        // Core routes remain
+        assert_eq!(status_of(&features, "GET", "/").await, StatusCode::OK);
+        assert_ne!(status_of(&features, "POST", "/register").await, StatusCode::NOT_FOUND);
+    }
+
+    async fn allow_origin_for(allowed_origins: &str, origin: &str) -> Option<HeaderValue> {
+        build_router(&FeatureFlags::default())
+            .layer(cors_layer(allowed_origins).unwrap())
+            .oneshot(Request::builder().uri("/").header(header::ORIGIN, origin).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .cloned()
+    }
+
+    #[tokio::test]
+    async fn test_cors_allowed_origin_gets_header() {
+        let allowed = "https://app.example.com, https://admin.example.com";
+        assert_eq!(
+            allow_origin_for(allowed, "https://admin.example.com").await.unwrap(),
+            "https://admin.example.com"
+        );
+        assert!(allow_origin_for(allowed, "https://evil.example.com").await.is_none());
+        assert_eq!(allow_origin_for("*", "https://anywhere.example.com").await.unwrap(), "*");
+    }
+
+    #[test]
+    fn test_cors_rejects_malformed_origin() {
+        let err = cors_layer("https://app.example.com,bad\norigin").unwrap_err();
+        assert!(err.contains("bad"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_exempt_routes_exist() {
+        let features = FeatureFlags::default();
+        for uri in crate::maintenance::EXEMPT_ROUTES {
+            assert_ne!(status_of(&features, "POST", uri).await, StatusCode::NOT_FOUND, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enabled_feature_routes_are_present() {
+        let features = FeatureFlags::default();
+        for (method, uri) in [
+            ("GET", "/mfa"),
+            ("POST", "/card"),
+            ("POST", "/subscribe"),
+            ("DELETE", "/notification/subscriptions/1"),
+            ("GET", "/contract/status"),
+        ] {
+            assert_ne!(status_of(&features, method, uri).await, StatusCode::NOT_FOUND, "{} {}", method, uri);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use log::{debug, warn};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+
+use crate::models::FieldChange;
+
+/// Number of distinct `$N` placeholders in `sql`.
+pub fn placeholder_count(sql: &str) -> usize {
+    let mut seen = std::collections::BTreeSet::new();
+    let mut chars = sql.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c != '$' {
+            continue;
+        }
+        let start = i + 1;
+        let mut end = start;
+        while let Some((j, d)) = chars.peek().copied() {
+            if !d.is_ascii_digit() {
+                break;
+            }
+            end = j + 1;
+            chars.next();
+        }
+        if end > start {
+            seen.insert(&sql[start..end]);
+        }
+    }
+    seen.len()
+}
+
+/// In debug mode, log a dynamically built statement and how many parameters
+/// it binds (never their values), warning when that disagrees with the
+/// placeholders in the SQL.
+pub fn log_dynamic_query(fn_name: &str, sql: &str, binds: usize, debug_mode: bool) {
+    if !debug_mode {
+        return;
+    }
+    debug!("{}: sql=\"{}\" binds={}", fn_name, sql, binds);
+    let placeholders = placeholder_count(sql);
+    if placeholders != binds {
+        warn!(
+            "{}: {} placeholder(s) but {} bind(s) in \"{}\"",
+            fn_name, placeholders, binds, sql
+        );
+    }
+}
+
+/// Read `columns` of `row` as optional text, in order.
+pub fn row_values(
+    row: &PgRow,
+    columns: &[&'static str],
+) -> Result<Vec<(&'static str, Option<String>)>, sqlx::Error> {
+    columns
+        .iter()
+        .map(|column| Ok((*column, row.try_get::<Option<String>, _>(*column)?)))
+        .collect()
+}
+
+/// The changes applying `fields` would make to `current`.  Fields already
+/// holding the new value are left out.
+pub fn diff_fields(current: &[(&str, Option<String>)], fields: &[(&str, &str)]) -> Vec<FieldChange> {
+    fields
+        .iter()
+        .filter_map(|(column, new_value)| {
+            let old = current
+                .iter()
+                .find(|(c, _)| c == column)
+                .and_then(|(_, v)| v.clone());
+            (old.as_deref() != Some(*new_value)).then(|| FieldChange {
+                field: column.to_string(),
+                from: old,
+                to: new_value.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_fields_reports_only_changes() {
+        let current = [
+            ("first_name", Some("Ada".to_string())),
+            ("nickname", None),
+            ("gender", Some("f".to_string())),
+        ];
+        let changes = diff_fields(&current, &[("first_name", "Augusta"), ("nickname", "ada"), ("gender", "f")]);
+        assert_eq!(
+            changes,
+            [
+                FieldChange { field: "first_name".into(), from: Some("Ada".into()), to: "Augusta".into() },
+                FieldChange { field: "nickname".into(), from: None, to: "ada".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_placeholder_count() {
+        assert_eq!(placeholder_count("SELECT 1"), 0);
+        assert_eq!(placeholder_count("UPDATE t SET a = $2, b = $3 WHERE id = $1"), 3);
+        assert_eq!(placeholder_count("SET medium = $2::notify_medium WHERE id = $1 OR id = $1"), 2);
+        assert_eq!(placeholder_count("a = $10, b = $11"), 2);
+        assert_eq!(placeholder_count("cost $ and $x"), 0);
+    }
+}
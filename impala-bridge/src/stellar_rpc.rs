@@ -0,0 +1,527 @@
+use log::{error, warn};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::AppError;
+
+/// Failure of a Soroban RPC call.
+#[derive(Debug, PartialEq)]
+pub enum RpcError {
+    /// The request could not be sent or the connection failed.
+    Transport(String),
+    /// The body was not a JSON-RPC response of the expected shape.
+    InvalidResponse(String),
+    /// The server answered with a JSON-RPC `error` object.
+    Rpc { code: i64, message: String },
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::Transport(e) => write!(f, "Stellar RPC request failed: {}", e),
+            RpcError::InvalidResponse(e) => write!(f, "Invalid Stellar RPC response: {}", e),
+            RpcError::Rpc { code, message } => write!(f, "Stellar RPC error {}: {}", code, message),
+        }
+    }
+}
+
+impl From<RpcError> for AppError {
+    fn from(e: RpcError) -> Self {
+        match e {
+            RpcError::Transport(_) => AppError::InternalError("Stellar RPC unavailable".to_string()),
+            RpcError::InvalidResponse(_) => {
+                AppError::InternalError("Invalid Stellar RPC response".to_string())
+            }
+            RpcError::Rpc { message, .. } => {
+                AppError::BadRequest(format!("Stellar RPC error: {}", message))
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RpcRequest<'a, P> {
+    jsonrpc: &'static str,
+    id: u32,
+    method: &'a str,
+    params: &'a P,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<R> {
+    result: Option<R>,
+    error: Option<RpcErrorObject>,
+}
+
+#[derive(Deserialize)]
+struct RpcErrorObject {
+    #[serde(default)]
+    code: i64,
+    #[serde(default)]
+    message: String,
+}
+
+/// Pagination for list methods such as `getTransactions`.
+#[derive(Debug, Default, Serialize)]
+pub struct Pagination {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTransactionsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_ledger: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+}
+
+/// One entry of a `getTransactions` page.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionInfo {
+    /// Older RPC versions call this `id`.
+    #[serde(alias = "id")]
+    pub tx_hash: String,
+    pub status: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTransactionsResponse {
+    #[serde(default)]
+    pub transactions: Vec<TransactionInfo>,
+    #[serde(default)]
+    pub latest_ledger: u32,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// `getTransaction` result.  `status` is `SUCCESS`, `FAILED` or `NOT_FOUND`;
+/// the remaining fields are only present once the transaction is in a ledger.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTransactionResponse {
+    pub status: String,
+    #[serde(default)]
+    pub ledger: Option<u32>,
+    #[serde(default)]
+    pub result_meta_xdr: Option<String>,
+}
+
+/// Result of one simulated host function invocation.
+#[derive(Debug, Deserialize)]
+pub struct SimulateHostFunctionResult {
+    #[serde(default)]
+    pub auth: Vec<String>,
+    pub xdr: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateTransactionResponse {
+    #[serde(default)]
+    pub latest_ledger: u32,
+    /// Stroops, as a decimal string.
+    #[serde(default)]
+    pub min_resource_fee: Option<String>,
+    #[serde(default)]
+    pub transaction_data: Option<String>,
+    #[serde(default)]
+    pub results: Vec<SimulateHostFunctionResult>,
+    /// Set when the invocation itself failed in simulation.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// `sendTransaction` result.  `status` is `PENDING`, `DUPLICATE`,
+/// `TRY_AGAIN_LATER` or `ERROR`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTransactionResponse {
+    pub hash: String,
+    pub status: String,
+    #[serde(default)]
+    pub latest_ledger: u32,
+    #[serde(default)]
+    pub error_result_xdr: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LatestLedger {
+    pub sequence: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerInfo {
+    /// Unix seconds, as a decimal string.
+    pub ledger_close_time: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetLedgersResponse {
+    #[serde(default)]
+    pub ledgers: Vec<LedgerInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HealthResponse {
+    pub status: String,
+}
+
+/// Typed Soroban RPC client over a shared `reqwest::Client`.
+#[derive(Clone)]
+pub struct StellarRpcClient {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl StellarRpcClient {
+    pub fn new(http: reqwest::Client, url: impl Into<String>) -> Self {
+        Self { http, url: url.into() }
+    }
+
+    /// The underlying HTTP client, for other Stellar endpoints (Horizon).
+    pub fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    /// Issue a JSON-RPC call and deserialize its `result`.
+    pub async fn call<P, R>(&self, method: &str, params: &P) -> Result<R, RpcError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let request = RpcRequest { jsonrpc: "2.0", id: 1, method, params };
+        let response = self.http.post(&self.url).json(&request).send().await.map_err(|e| {
+            error!("stellar_rpc: {} request failed: {}", method, e);
+            RpcError::Transport(e.to_string())
+        })?;
+        let bytes = response.bytes().await.map_err(|e| {
+            error!("stellar_rpc: {} response could not be read: {}", method, e);
+            RpcError::Transport(e.to_string())
+        })?;
+        parse_response(method, &bytes)
+    }
+
+    pub async fn get_transactions(
+        &self,
+        request: &GetTransactionsRequest,
+    ) -> Result<GetTransactionsResponse, RpcError> {
+        self.call("getTransactions", request).await
+    }
+
+    pub async fn get_transaction(&self, hash: &str) -> Result<GetTransactionResponse, RpcError> {
+        self.call("getTransaction", &serde_json::json!({ "hash": hash })).await
+    }
+
+    pub async fn simulate_transaction(
+        &self,
+        transaction_xdr: &str,
+    ) -> Result<SimulateTransactionResponse, RpcError> {
+        self.call("simulateTransaction", &serde_json::json!({ "transaction": transaction_xdr }))
+            .await
+    }
+
+    pub async fn send_transaction(
+        &self,
+        transaction_xdr: &str,
+    ) -> Result<SendTransactionResponse, RpcError> {
+        self.call("sendTransaction", &serde_json::json!({ "transaction": transaction_xdr }))
+            .await
+    }
+
+    pub async fn get_latest_ledger(&self) -> Result<LatestLedger, RpcError> {
+        self.call("getLatestLedger", &serde_json::json!({})).await
+    }
+
+    pub async fn get_ledgers(&self, start_ledger: u64, limit: u32) -> Result<GetLedgersResponse, RpcError> {
+        self.call(
+            "getLedgers",
+            &serde_json::json!({ "startLedger": start_ledger, "pagination": { "limit": limit } }),
+        )
+        .await
+    }
+
+    pub async fn get_health(&self) -> Result<HealthResponse, RpcError> {
+        self.call("getHealth", &serde_json::json!({})).await
+    }
+
+    /// Close time (unix seconds) of the latest ledger.
+    pub async fn ledger_time(&self) -> Result<u64, RpcError> {
+        let latest = self.get_latest_ledger().await?;
+        let ledgers = self.get_ledgers(latest.sequence, 1).await?;
+        ledgers
+            .ledgers
+            .first()
+            .and_then(|l| l.ledger_close_time.parse().ok())
+            .ok_or_else(|| {
+                error!("stellar_rpc: getLedgers response missing ledgerCloseTime");
+                RpcError::InvalidResponse("missing ledgerCloseTime".to_string())
+            })
+    }
+
+    /// Poll `getTransaction` every `interval` until the transaction
+    /// succeeds or fails.  Never blocks past `max_wait`: a call still in
+    /// flight when the deadline passes is abandoned and the result is
+    /// `NotFound`.
+    pub async fn poll_transaction(
+        &self,
+        hash: &str,
+        max_wait: Duration,
+        interval: Duration,
+    ) -> Result<PolledTransaction, RpcError> {
+        let deadline = tokio::time::Instant::now() + max_wait;
+        let mut last = None;
+        loop {
+            match tokio::time::timeout_at(deadline, self.get_transaction(hash)).await {
+                Ok(result) => {
+                    let result = result?;
+                    let status = match result.status.as_str() {
+                        "SUCCESS" => Some(PollStatus::Success),
+                        "FAILED" => Some(PollStatus::Failed),
+                        _ => None,
+                    };
+                    if let Some(status) = status {
+                        return Ok(PolledTransaction { status, result: Some(result) });
+                    }
+                    last = Some(result);
+                }
+                Err(_) => break,
+            }
+            if tokio::time::Instant::now() + interval >= deadline {
+                break;
+            }
+            tokio::time::sleep(interval).await;
+        }
+        warn!("poll_transaction: {} not found after {:?}", hash, max_wait);
+        Ok(PolledTransaction { status: PollStatus::NotFound, result: last })
+    }
+}
+
+/// Decode a JSON-RPC response body into its `result` or an [`RpcError`].
+fn parse_response<R: DeserializeOwned>(method: &str, body: &[u8]) -> Result<R, RpcError> {
+    let response: RpcResponse<R> = serde_json::from_slice(body).map_err(|e| {
+        error!("stellar_rpc: {} returned an invalid response: {}", method, e);
+        RpcError::InvalidResponse(e.to_string())
+    })?;
+    if let Some(err) = response.error {
+        warn!("stellar_rpc: {} error {}: {}", method, err.code, err.message);
+        return Err(RpcError::Rpc { code: err.code, message: err.message });
+    }
+    response.result.ok_or_else(|| {
+        error!("stellar_rpc: {} response has neither result nor error", method);
+        RpcError::InvalidResponse("missing result".to_string())
+    })
+}
+
+/// Final state of a transaction observed by
+/// [`StellarRpcClient::poll_transaction`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PollStatus {
+    Success,
+    Failed,
+    /// Still `NOT_FOUND` when `max_wait` ran out.
+    NotFound,
+}
+
+impl PollStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "SUCCESS",
+            Self::Failed => "FAILED",
+            Self::NotFound => "NOT_FOUND",
+        }
+    }
+}
+
+/// Outcome of a poll: the final status plus the last `getTransaction`
+/// result (`None` if no poll completed).
+#[derive(Debug)]
+pub struct PolledTransaction {
+    pub status: PollStatus,
+    pub result: Option<GetTransactionResponse>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parse_get_transactions() {
+        let body = br#"{"jsonrpc":"2.0","id":1,"result":{
+            "transactions":[
+                {"status":"SUCCESS","applicationOrder":1,"feeBump":false,"envelopeXdr":"AAAA",
+                 "resultXdr":"AAAA","resultMetaXdr":"AAAA","ledger":1888539,"createdAt":1717166042,
+                 "txHash":"b8b7a3d6"},
+                {"status":"FAILED","ledger":1888540,"id":"c9c8b4e7"}
+            ],
+            "latestLedger":1888542,"latestLedgerCloseTimestamp":1717166057,
+            "oldestLedger":1871263,"oldestLedgerCloseTimestamp":1717075350,"cursor":"8111217537191937"}}"#;
+        let page: GetTransactionsResponse = parse_response("getTransactions", body).unwrap();
+        assert_eq!(page.transactions.len(), 2);
+        assert_eq!(page.transactions[0].tx_hash, "b8b7a3d6");
+        assert_eq!(page.transactions[1].tx_hash, "c9c8b4e7");
+        assert_eq!(page.transactions[1].status, "FAILED");
+        assert_eq!(page.latest_ledger, 1888542);
+        assert_eq!(page.cursor.as_deref(), Some("8111217537191937"));
+    }
+
+    #[test]
+    fn test_parse_get_transaction() {
+        let body = br#"{"jsonrpc":"2.0","id":1,"result":{
+            "status":"SUCCESS","latestLedger":2540076,"latestLedgerCloseTime":"1700086333",
+            "oldestLedger":2538637,"oldestLedgerCloseTime":"1700078796","applicationOrder":1,
+            "envelopeXdr":"ENV","resultXdr":"RES","resultMetaXdr":"META","ledger":2540064,
+            "createdAt":"1700086268"}}"#;
+        let tx: GetTransactionResponse = parse_response("getTransaction", body).unwrap();
+        assert_eq!(tx.status, "SUCCESS");
+        assert_eq!(tx.ledger, Some(2540064));
+        assert_eq!(tx.result_meta_xdr.as_deref(), Some("META"));
+
+        let pending: GetTransactionResponse = parse_response(
+            "getTransaction",
+            br#"{"jsonrpc":"2.0","id":1,"result":{"status":"NOT_FOUND","latestLedger":2540099}}"#,
+        )
+        .unwrap();
+        assert_eq!(pending.ledger, None);
+    }
+
+    #[test]
+    fn test_parse_simulate_transaction() {
+        let body = br#"{"jsonrpc":"2.0","id":1,"result":{
+            "transactionData":"TXDATA","minResourceFee":"58181",
+            "events":["EVT"],
+            "results":[{"auth":["AUTH1","AUTH2"],"xdr":"RETVAL"}],
+            "cost":{"cpuInsns":"1240100","memBytes":"161637"},
+            "latestLedger":1479}}"#;
+        let sim: SimulateTransactionResponse = parse_response("simulateTransaction", body).unwrap();
+        assert_eq!(sim.min_resource_fee.as_deref(), Some("58181"));
+        assert_eq!(sim.transaction_data.as_deref(), Some("TXDATA"));
+        assert_eq!(sim.results[0].auth, ["AUTH1", "AUTH2"]);
+        assert_eq!(sim.results[0].xdr, "RETVAL");
+        assert!(sim.error.is_none());
+
+        let failed: SimulateTransactionResponse = parse_response(
+            "simulateTransaction",
+            br#"{"jsonrpc":"2.0","id":1,"result":{"error":"HostError: Error(Contract, #3)","latestLedger":1480}}"#,
+        )
+        .unwrap();
+        assert!(failed.error.unwrap().contains("#3"));
+        assert!(failed.results.is_empty());
+    }
+
+    #[test]
+    fn test_parse_send_transaction() {
+        let body = br#"{"jsonrpc":"2.0","id":1,"result":{
+            "status":"ERROR","errorResultXdr":"ERRXDR",
+            "hash":"d8ec9b68","latestLedger":1479,"latestLedgerCloseTime":"1690594566"}}"#;
+        let sent: SendTransactionResponse = parse_response("sendTransaction", body).unwrap();
+        assert_eq!(sent.hash, "d8ec9b68");
+        assert_eq!(sent.status, "ERROR");
+        assert_eq!(sent.error_result_xdr.as_deref(), Some("ERRXDR"));
+    }
+
+    #[test]
+    fn test_parse_json_rpc_errors() {
+        let err = parse_response::<GetTransactionResponse>(
+            "getTransaction",
+            br#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"invalid hash"}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(err, RpcError::Rpc { code: -32602, message: "invalid hash".to_string() });
+        assert!(matches!(AppError::from(err), AppError::BadRequest(m) if m.contains("invalid hash")));
+
+        assert!(matches!(
+            parse_response::<GetTransactionResponse>("getTransaction", b"<html>bad gateway</html>"),
+            Err(RpcError::InvalidResponse(_))
+        ));
+        assert!(matches!(
+            parse_response::<GetTransactionResponse>("getTransaction", br#"{"jsonrpc":"2.0","id":1}"#),
+            Err(RpcError::InvalidResponse(_))
+        ));
+    }
+
+    /// Serve `handler` as a mock Soroban RPC; returns a client for it.
+    async fn mock_rpc<F>(handler: F) -> StellarRpcClient
+    where
+        F: Fn(serde_json::Value) -> serde_json::Value + Clone + Send + Sync + 'static,
+    {
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(req): axum::Json<serde_json::Value>| {
+                let handler = handler.clone();
+                async move {
+                    axum::Json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": handler(req) }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        StellarRpcClient::new(reqwest::Client::new(), url)
+    }
+
+    /// Mock answering `getTransaction` with NOT_FOUND for the first
+    /// `not_found_polls` calls, then SUCCESS.
+    async fn mock_polls(not_found_polls: usize) -> (StellarRpcClient, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = mock_rpc(move |_| {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            let status = if n < not_found_polls { "NOT_FOUND" } else { "SUCCESS" };
+            serde_json::json!({ "status": status, "ledger": 99 })
+        })
+        .await;
+        (client, calls)
+    }
+
+    #[tokio::test]
+    async fn test_poll_transaction_waits_for_success() {
+        let (client, calls) = mock_polls(2).await;
+        let polled = client
+            .poll_transaction("abc", Duration::from_secs(5), Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert_eq!(polled.status, PollStatus::Success);
+        assert_eq!(polled.result.unwrap().ledger, Some(99));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_poll_transaction_times_out() {
+        let (client, calls) = mock_polls(usize::MAX).await;
+        let start = std::time::Instant::now();
+        let polled = client
+            .poll_transaction("abc", Duration::from_millis(200), Duration::from_millis(20))
+            .await
+            .unwrap();
+        assert_eq!(polled.status, PollStatus::NotFound);
+        assert!(start.elapsed() < Duration::from_millis(400));
+        assert!(calls.load(Ordering::SeqCst) > 1);
+    }
+
+    #[tokio::test]
+    async fn test_ledger_time_reads_close_time() {
+        let client = mock_rpc(|req| match req["method"].as_str() {
+            Some("getLatestLedger") => serde_json::json!({ "id": "abc", "protocolVersion": 22, "sequence": 4242 }),
+            Some("getLedgers") => {
+                assert_eq!(req["params"]["startLedger"], 4242);
+                serde_json::json!({ "ledgers": [{ "hash": "h", "sequence": 4242, "ledgerCloseTime": "1700000000" }] })
+            }
+            other => panic!("unexpected method {:?}", other),
+        })
+        .await;
+        assert_eq!(client.ledger_time().await.unwrap(), 1_700_000_000);
+    }
+}
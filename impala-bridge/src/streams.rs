@@ -1,70 +1,153 @@
-use crate::constants::{CRON_SYNC_INTERVAL_SECS, DEFAULT_HTTP_CLIENT_TIMEOUT_SECS, MAX_SSE_BUFFER_SIZE};
+use crate::constants::{
+    CALLBACK_VERIFY_PATH, CRON_SYNC_INTERVAL_SECS, DEFAULT_HTTP_CLIENT_TIMEOUT_SECS, MAX_SSE_BUFFER_SIZE,
+    PAYALA_DRAIN_TIMEOUT_SECS, STELLAR_RAW_EVENTS_KEY, STELLAR_RAW_EVENTS_TTL_SECS,
+};
+use crate::cache::redis_set_json;
+use crate::models::CronRunResult;
 use crate::validate::validate_callback_url;
 use futures::StreamExt;
 use log::{debug, error, info, warn};
 use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::{JoinHandle, JoinSet};
 use tokio_util::sync::CancellationToken;
 
-/// Background task that periodically fetches callback URIs from the
-/// `cron_sync` table, invokes each one, and stores the JSON response back
-/// into the `callback_result` column.  Respects cancellation for graceful shutdown.
-pub async fn cron_sync_task(pool: PgPool, cancel: CancellationToken) {
-    let client = reqwest::Client::builder()
+/// Build the HTTP client used to invoke cron_sync callbacks.
+pub fn callback_http_client() -> Result<reqwest::Client, reqwest::Error> {
+    reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(DEFAULT_HTTP_CLIENT_TIMEOUT_SECS))
         .build()
-        .expect("Failed to create HTTP client");
-    loop {
-        let rows =
-            sqlx::query_as::<_, (i32, String)>("SELECT id, callback_uri FROM cron_sync")
-                .fetch_all(&pool)
-                .await;
+}
+
+/// Load verified callbacks, optionally restricted to a single id.
+pub async fn fetch_verified_callbacks(
+    pool: &PgPool,
+    id: Option<i32>,
+) -> Result<Vec<(i32, String)>, sqlx::Error> {
+    sqlx::query_as::<_, (i32, String)>(
+        "SELECT id, callback_uri FROM cron_sync WHERE verified = TRUE AND ($1::INT IS NULL OR id = $1) ORDER BY id",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Persist a callback's JSON response into `cron_sync.callback_result`.
+pub async fn store_callback_result(
+    pool: &PgPool,
+    id: i32,
+    body: &serde_json::Value,
+) -> Result<(), String> {
+    sqlx::query("UPDATE cron_sync SET callback_result = $1 WHERE id = $2")
+        .bind(body)
+        .bind(id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("failed to store result: {}", e))
+}
+
+/// Invoke a single callback: validate the URL (SSRF), fetch it, and hand the
+/// JSON response to `store`.  Shared by the background task and `POST /cron/run`.
+pub async fn run_callback(
+    client: &reqwest::Client,
+    id: i32,
+    callback_uri: &str,
+    store: impl AsyncFnOnce(i32, &serde_json::Value) -> Result<(), String>,
+) -> CronRunResult {
+    let outcome = |error: Option<String>, result: Option<serde_json::Value>| CronRunResult {
+        id,
+        callback_uri: callback_uri.to_string(),
+        success: error.is_none(),
+        result,
+        error,
+    };
+
+    // Validate callback URL to prevent SSRF
+    if let Err(e) = validate_callback_url(callback_uri) {
+        warn!("cron_sync: skipping id {} due to invalid callback_uri: {}", id, e);
+        return outcome(Some(format!("invalid callback_uri: {}", e)), None);
+    }
+
+    let body = match client.get(callback_uri).send().await {
+        Ok(response) => match response.json::<serde_json::Value>().await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("cron_sync: JSON parse error for id {} ({}): {}", id, callback_uri, e);
+                return outcome(Some(format!("invalid JSON response: {}", e)), None);
+            }
+        },
+        Err(e) => {
+            error!("cron_sync: request failed for id {} ({}): {}", id, callback_uri, e);
+            return outcome(Some(format!("request failed: {}", e)), None);
+        }
+    };
+
+    if let Err(e) = store(id, &body).await {
+        error!("cron_sync: failed to update result for id {}: {}", id, e);
+        return outcome(Some(e), Some(body));
+    }
+    debug!("cron_sync: updated result for id {}", id);
+    outcome(None, Some(body))
+}
+
+/// Timing of the cron_sync loop.
+#[derive(Debug, Clone, Copy)]
+pub struct CronSchedule {
+    pub interval_secs: u64,
+    /// ± jitter applied to each sleep, as a percentage of `interval_secs`.
+    pub jitter_percent: u64,
+    /// Pause between callbacks within one run.
+    pub stagger_ms: u64,
+}
+
+impl CronSchedule {
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        CronSchedule {
+            interval_secs: CRON_SYNC_INTERVAL_SECS,
+            jitter_percent: config.cron_sync_jitter_percent,
+            stagger_ms: config.cron_sync_stagger_ms,
+        }
+    }
+
+    /// Sleep before the next run; `unit` in `[0, 1)` picks a point in the
+    /// `interval ± jitter%` range.
+    pub fn next_sleep(&self, unit: f64) -> Duration {
+        let base = self.interval_secs as f64;
+        let spread = base * self.jitter_percent.min(100) as f64 / 100.0;
+        let offset = (unit.clamp(0.0, 1.0) * 2.0 - 1.0) * spread;
+        Duration::from_secs_f64((base + offset).max(0.0))
+    }
+}
 
-        match rows {
+/// Background task that periodically fetches callback URIs from the
+/// `cron_sync` table, invokes each one, and stores the JSON response back
+/// into the `callback_result` column.  Only callbacks that have passed
+/// ownership verification are invoked.  The interval is jittered and
+/// callbacks may be staggered so instances do not fire in lockstep.
+/// Respects cancellation for graceful shutdown.
+pub async fn cron_sync_task(pool: PgPool, cancel: CancellationToken, schedule: CronSchedule) {
+    let client = callback_http_client().expect("Failed to create HTTP client");
+    loop {
+        match fetch_verified_callbacks(&pool, None).await {
             Ok(rows) => {
                 debug!("cron_sync: processing {} callback(s)", rows.len());
-                for (id, callback_uri) in rows {
-                    // Validate callback URL to prevent SSRF
-                    if let Err(e) = validate_callback_url(&callback_uri) {
-                        warn!(
-                            "cron_sync: skipping id {} due to invalid callback_uri: {}",
-                            id, e
-                        );
-                        continue;
-                    }
-
-                    match client.get(&callback_uri).send().await {
-                        Ok(response) => match response.json::<serde_json::Value>().await {
-                            Ok(body) => {
-                                if let Err(e) = sqlx::query(
-                                    "UPDATE cron_sync SET callback_result = $1 WHERE id = $2",
-                                )
-                                .bind(&body)
-                                .bind(id)
-                                .execute(&pool)
-                                .await
-                                {
-                                    error!(
-                                        "cron_sync: failed to update result for id {}: {}",
-                                        id, e
-                                    );
-                                } else {
-                                    debug!("cron_sync: updated result for id {}", id);
-                                }
-                            }
-                            Err(e) => {
-                                warn!(
-                                    "cron_sync: JSON parse error for id {} ({}): {}",
-                                    id, callback_uri, e
-                                );
+                for (i, (id, callback_uri)) in rows.into_iter().enumerate() {
+                    if i > 0 && schedule.stagger_ms > 0 {
+                        tokio::select! {
+                            _ = tokio::time::sleep(Duration::from_millis(schedule.stagger_ms)) => {}
+                            _ = cancel.cancelled() => {
+                                info!("cron_sync_task: shutdown requested, exiting");
+                                return;
                             }
-                        },
-                        Err(e) => {
-                            error!(
-                                "cron_sync: request failed for id {} ({}): {}",
-                                id, callback_uri, e
-                            );
                         }
                     }
+                    run_callback(&client, id, &callback_uri, async |id, body| {
+                        store_callback_result(&pool, id, body).await
+                    })
+                    .await;
                 }
             }
             Err(e) => {
@@ -72,8 +155,10 @@ pub async fn cron_sync_task(pool: PgPool, cancel: CancellationToken) {
             }
         }
 
+        let sleep = schedule.next_sleep(rand::random::<f64>());
+        debug!("cron_sync: next run in {:.1}s", sleep.as_secs_f64());
         tokio::select! {
-            _ = tokio::time::sleep(std::time::Duration::from_secs(CRON_SYNC_INTERVAL_SECS)) => {}
+            _ = tokio::time::sleep(sleep) => {}
             _ = cancel.cancelled() => {
                 info!("cron_sync_task: shutdown requested, exiting");
                 return;
@@ -82,10 +167,152 @@ pub async fn cron_sync_task(pool: PgPool, cancel: CancellationToken) {
     }
 }
 
-/// Long-running SSE consumer for Stellar Horizon ledger events.
+/// Generate a fresh callback verification token.
+pub fn new_verification_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// URL at the callback's origin where the verification token must be served.
+pub fn verification_url(callback_uri: &str) -> Result<String, String> {
+    let parsed = url::Url::parse(callback_uri).map_err(|e| format!("Invalid URL: {}", e))?;
+    let host = parsed.host_str().ok_or_else(|| "URL must have a host".to_string())?;
+    let port = parsed.port().map(|p| format!(":{}", p)).unwrap_or_default();
+    Ok(format!("{}://{}{}{}", parsed.scheme(), host, port, CALLBACK_VERIFY_PATH))
+}
+
+/// Fetch `verify_url` and check that it echoes `token` (surrounding
+/// whitespace ignored).  Any transport error or non-2xx response counts as
+/// unverified.  The caller is responsible for SSRF validation of the URL.
+pub async fn check_callback_ownership(
+    client: &reqwest::Client,
+    verify_url: &str,
+    token: &str,
+) -> bool {
+    let response = match client.get(verify_url).send().await {
+        Ok(r) if r.status().is_success() => r,
+        Ok(r) => {
+            debug!("check_callback_ownership: {} returned {}", verify_url, r.status());
+            return false;
+        }
+        Err(e) => {
+            debug!("check_callback_ownership: request to {} failed: {}", verify_url, e);
+            return false;
+        }
+    };
+    match response.text().await {
+        Ok(body) => {
+            use subtle::ConstantTimeEq;
+            body.trim().as_bytes().ct_eq(token.as_bytes()).into()
+        }
+        Err(_) => false,
+    }
+}
+
+/// Check that Horizon's `current_protocol_version` is at least `min`.
+/// Returns the reported version, or an error message explaining why the
+/// stream must not start (too old, unreachable, or unreadable).
+pub async fn check_horizon_protocol(
+    client: &reqwest::Client,
+    horizon_url: &str,
+    min: u32,
+) -> Result<u64, String> {
+    let root: serde_json::Value = client
+        .get(horizon_url)
+        .send()
+        .await
+        .map_err(|e| format!("Horizon unreachable: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid Horizon root response: {}", e))?;
+    let version = root["current_protocol_version"]
+        .as_u64()
+        .ok_or_else(|| "Horizon did not report current_protocol_version".to_string())?;
+    if version < min as u64 {
+        return Err(format!(
+            "Horizon protocol version {} is below the required minimum {}",
+            version, min
+        ));
+    }
+    Ok(version)
+}
+
+/// Fields the bridge derives from a Horizon ledger event.
+#[derive(Debug, PartialEq)]
+pub struct LedgerRecord {
+    pub sequence: String,
+}
+
+/// Parse a Horizon ledger event into the record the bridge indexes.
+pub fn parse_ledger_event(raw: &serde_json::Value) -> LedgerRecord {
+    LedgerRecord {
+        sequence: raw["sequence"]
+            .as_u64()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "unknown".to_string()),
+    }
+}
+
+/// Redis entries written for a parsed ledger received at `received_at`.
+pub fn ledger_record_entries(record: &LedgerRecord, received_at: &str) -> Vec<(String, String)> {
+    vec![(format!("stellar:ledger:{}", record.sequence), received_at.to_string())]
+}
+
+/// A raw ledger payload as kept in `STELLAR_RAW_EVENTS_KEY`.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct StoredRawEvent {
+    pub received_at: String,
+    pub payload: serde_json::Value,
+}
+
+/// Re-run the current parser over stored raw events.  Returns the Redis
+/// entries to write and the number of stored members that could not be read.
+pub fn reprocess_raw_events(members: &[String]) -> (Vec<(String, String)>, usize) {
+    let mut entries = Vec::new();
+    let mut failed = 0;
+    for member in members {
+        match serde_json::from_str::<StoredRawEvent>(member) {
+            Ok(event) => entries.extend(ledger_record_entries(
+                &parse_ledger_event(&event.payload),
+                &event.received_at,
+            )),
+            Err(e) => {
+                warn!("reprocess_raw_events: skipping unreadable raw event: {}", e);
+                failed += 1;
+            }
+        }
+    }
+    (entries, failed)
+}
+
+/// Append a raw payload to the replay set, keeping only the newest
+/// `retention` ledgers.  Best-effort: failures are logged.
+async fn store_raw_event(
+    conn: &mut deadpool_redis::Connection,
+    raw: &serde_json::Value,
+    received_at: &str,
+    retention: usize,
+) {
+    use redis::AsyncCommands;
+    let member = serde_json::json!({ "received_at": received_at, "payload": raw }).to_string();
+    let score = raw["sequence"].as_f64().unwrap_or(0.0);
+    let result: redis::RedisResult<()> = async {
+        conn.zadd::<_, _, _, ()>(STELLAR_RAW_EVENTS_KEY, member, score).await?;
+        conn.zremrangebyrank::<_, ()>(STELLAR_RAW_EVENTS_KEY, 0, -(retention as isize) - 1)
+            .await?;
+        conn.expire::<_, ()>(STELLAR_RAW_EVENTS_KEY, STELLAR_RAW_EVENTS_TTL_SECS).await
+    }
+    .await;
+    if let Err(e) = result {
+        warn!("stellar_stream: failed to store raw event: {}", e);
+    }
+}
+
+/// Long-running SSE consumer for Stellar Horizon ledger events.  When
+/// `raw_retention` is non-zero the raw payloads are also kept for replay.
 pub async fn stellar_stream(
     url: &str,
     redis_pool: &deadpool_redis::Pool,
+    raw_retention: usize,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(DEFAULT_HTTP_CLIENT_TIMEOUT_SECS))
@@ -134,27 +361,28 @@ pub async fn stellar_stream(
                 }
                 event_data.push_str(data);
             } else if line.is_empty() && !event_data.is_empty() {
-                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&event_data) {
-                    let sequence = parsed["sequence"]
-                        .as_u64()
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| "unknown".to_string());
+                if let Ok(raw) = serde_json::from_str::<serde_json::Value>(&event_data) {
+                    let record = parse_ledger_event(&raw);
 
-                    info!("stellar_stream: ledger event sequence={}", sequence);
+                    info!("stellar_stream: ledger event sequence={}", record.sequence);
 
                     if let Ok(mut conn) = redis_pool.get().await {
                         let _: Result<(), _> = redis::AsyncCommands::set(
                             &mut *conn,
                             "stellar:latest_ledger",
-                            &sequence,
+                            &record.sequence,
                         )
                         .await;
 
                         let timestamp =
                             chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string();
-                        let event_key = format!("stellar:ledger:{}", sequence);
-                        let _: Result<(), _> =
-                            redis::AsyncCommands::set(&mut *conn, &event_key, &timestamp).await;
+                        for (key, value) in ledger_record_entries(&record, &timestamp) {
+                            let _: Result<(), _> =
+                                redis::AsyncCommands::set(&mut *conn, &key, &value).await;
+                        }
+                        if raw_retention > 0 {
+                            store_raw_event(&mut conn, &raw, &timestamp, raw_retention).await;
+                        }
                     }
                 }
                 event_data.clear();
@@ -165,10 +393,75 @@ pub async fn stellar_stream(
     Ok(())
 }
 
+/// Running Payala listeners keyed by `listen_endpoint`, so `/unsubscribe`
+/// can stop a listener started by `/subscribe`.
+pub struct PayalaListeners {
+    parent: CancellationToken,
+    key_prefix: Arc<str>,
+    listeners: tokio::sync::Mutex<HashMap<String, (CancellationToken, JoinHandle<()>)>>,
+}
+
+impl PayalaListeners {
+    /// Listener tokens are children of `parent`, so cancelling it (graceful
+    /// shutdown) stops every listener as well.
+    pub fn new(parent: CancellationToken, key_prefix: &str) -> Self {
+        Self {
+            parent,
+            key_prefix: Arc::from(key_prefix),
+            listeners: tokio::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Spawn a listener on `listen_endpoint`.  Returns `false` if one is
+    /// already running there.
+    pub async fn start(&self, listen_endpoint: &str, redis_pool: Arc<deadpool_redis::Pool>) -> bool {
+        let mut listeners = self.listeners.lock().await;
+        if let Some((_, handle)) = listeners.get(listen_endpoint) {
+            if !handle.is_finished() {
+                return false;
+            }
+        }
+
+        let shutdown = self.parent.child_token();
+        let task_shutdown = shutdown.clone();
+        let endpoint = listen_endpoint.to_string();
+        let key_prefix = self.key_prefix.clone();
+        let handle = tokio::spawn(async move {
+            if let Err(e) = payala_stream(&endpoint, &redis_pool, key_prefix, task_shutdown).await {
+                error!("payala_stream: terminated with error: {}", e);
+            }
+        });
+        listeners.insert(listen_endpoint.to_string(), (shutdown, handle));
+        true
+    }
+
+    /// Signal the listener on `listen_endpoint` to stop and wait for it to
+    /// drain and release the port.  Returns `false` if none was running.
+    pub async fn stop(&self, listen_endpoint: &str) -> bool {
+        let entry = self.listeners.lock().await.remove(listen_endpoint);
+        match entry {
+            Some((shutdown, handle)) => {
+                shutdown.cancel();
+                if let Err(e) = handle.await {
+                    error!("payala_stream: listener task failed during stop: {}", e);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 /// Long-running TCP listener for Payala network events.
+///
+/// When `shutdown` is cancelled the listener stops accepting, lets each open
+/// connection finish the frame it is processing (bounded by
+/// `PAYALA_DRAIN_TIMEOUT_SECS`), then returns, releasing the port.
 pub async fn payala_stream(
     listen_endpoint: &str,
     redis_pool: &deadpool_redis::Pool,
+    key_prefix: Arc<str>,
+    shutdown: CancellationToken,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let addr: std::net::SocketAddr = listen_endpoint.parse().map_err(|e| {
         error!(
@@ -190,58 +483,370 @@ pub async fn payala_stream(
             redis::AsyncCommands::set(&mut *conn, "payala:listen_endpoint", listen_endpoint).await;
     }
 
+    let mut connections = JoinSet::new();
+
     loop {
-        let (mut socket, peer_addr) = listener.accept().await?;
+        let (socket, peer_addr) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            _ = shutdown.cancelled() => break,
+        };
         let redis = redis_pool.clone();
+        let conn_shutdown = shutdown.clone();
+        connections.spawn(handle_payala_connection(
+            socket,
+            peer_addr,
+            redis,
+            key_prefix.clone(),
+            conn_shutdown,
+        ));
 
-        tokio::spawn(async move {
-            info!("payala_stream: connection accepted from {}", peer_addr);
-
-            let mut buf = vec![0u8; 65536];
-            loop {
-                let n = match tokio::io::AsyncReadExt::read(&mut socket, &mut buf).await {
-                    Ok(0) => break,
-                    Ok(n) => n,
-                    Err(e) => {
-                        error!("payala_stream: read error from {}: {}", peer_addr, e);
-                        break;
-                    }
-                };
+        // Reap finished connections so the set doesn't grow unbounded.
+        while connections.try_join_next().is_some() {}
+    }
 
-                let raw = String::from_utf8_lossy(&buf[..n]).to_string();
+    drop(listener);
+    info!(
+        "payala_stream: stopped accepting on {}, draining {} connection(s)",
+        addr,
+        connections.len()
+    );
 
-                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&raw) {
-                    let event_type = parsed["type"]
-                        .as_str()
-                        .unwrap_or("unknown")
-                        .to_string();
+    let drain = async { while connections.join_next().await.is_some() {} };
+    if tokio::time::timeout(Duration::from_secs(PAYALA_DRAIN_TIMEOUT_SECS), drain)
+        .await
+        .is_err()
+    {
+        warn!(
+            "payala_stream: drain timed out after {}s, aborting {} connection(s)",
+            PAYALA_DRAIN_TIMEOUT_SECS,
+            connections.len()
+        );
+        connections.shutdown().await;
+    }
 
-                    info!(
-                        "payala_stream: event from {}: type={}",
-                        peer_addr, event_type
-                    );
+    info!("payala_stream: listener on {} closed", addr);
+    Ok(())
+}
 
-                    if let Ok(mut conn) = redis.get().await {
-                        let timestamp =
-                            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string();
-                        let event_key = format!(
-                            "payala:event:{}:{}",
-                            timestamp,
-                            uuid::Uuid::new_v4()
-                        );
-                        let _: Result<(), _> =
-                            redis::AsyncCommands::set(&mut *conn, &event_key, &raw).await;
-                        let _: Result<(), _> =
-                            redis::AsyncCommands::set(&mut *conn, "payala:latest_event", &raw)
-                                .await;
+/// Read frames from a single Payala connection until EOF, error, or shutdown.
+/// A frame that has already been read is always processed before exiting.
+async fn handle_payala_connection(
+    mut socket: tokio::net::TcpStream,
+    peer_addr: std::net::SocketAddr,
+    redis: deadpool_redis::Pool,
+    key_prefix: Arc<str>,
+    shutdown: CancellationToken,
+) {
+    info!("payala_stream: connection accepted from {}", peer_addr);
+
+    let mut buf = vec![0u8; 65536];
+    loop {
+        let read = tokio::select! {
+            read = tokio::io::AsyncReadExt::read(&mut socket, &mut buf) => read,
+            _ = shutdown.cancelled() => {
+                debug!("payala_stream: closing connection from {} for shutdown", peer_addr);
+                break;
+            }
+        };
+        let n = match read {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) => {
+                error!("payala_stream: read error from {}: {}", peer_addr, e);
+                break;
+            }
+        };
+
+        let raw = String::from_utf8_lossy(&buf[..n]).to_string();
+
+        if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&raw) {
+            let event_type = parsed["type"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string();
+
+            info!(
+                "payala_stream: event from {}: type={}",
+                peer_addr, event_type
+            );
+
+            if let Ok(mut conn) = redis.get().await {
+                let timestamp =
+                    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string();
+                let event_key = format!(
+                    "payala:event:{}:{}",
+                    timestamp,
+                    uuid::Uuid::new_v4()
+                );
+                for key in [event_key.as_str(), "payala:latest_event"] {
+                    if let Err(e) = redis_set_json(&mut conn, &key_prefix, key, &parsed, None).await {
+                        warn!("payala_stream: failed to store event: {}", e);
                     }
-                } else {
-                    warn!(
-                        "payala_stream: non-JSON data from {}: {} bytes",
-                        peer_addr, n
-                    );
                 }
             }
+        } else {
+            warn!(
+                "payala_stream: non-JSON data from {}: {} bytes",
+                peer_addr, n
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn test_redis_pool() -> Arc<deadpool_redis::Pool> {
+        // Never connected to in these tests; pool creation is lazy.
+        Arc::new(
+            deadpool_redis::Config::from_url("redis://127.0.0.1:1/")
+                .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+                .unwrap(),
+        )
+    }
+
+    async fn free_endpoint() -> String {
+        let probe = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        probe.local_addr().unwrap().to_string()
+    }
+
+    async fn wait_for_listener(endpoint: &str) -> tokio::net::TcpStream {
+        for _ in 0..50 {
+            if let Ok(stream) = tokio::net::TcpStream::connect(endpoint).await {
+                return stream;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("listener on {} never came up", endpoint);
+    }
+
+    #[tokio::test]
+    async fn test_port_rebindable_after_unsubscribe() {
+        let listeners = PayalaListeners::new(CancellationToken::new(), "");
+        let endpoint = free_endpoint().await;
+
+        assert!(listeners.start(&endpoint, test_redis_pool()).await);
+        // Hold an open connection so the drain path is exercised.
+        let _client = wait_for_listener(&endpoint).await;
+
+        assert!(listeners.stop(&endpoint).await);
+        tokio::net::TcpListener::bind(&endpoint)
+            .await
+            .expect("port should be free after unsubscribe");
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_subscribe_rejected_and_stop_unknown() {
+        let listeners = PayalaListeners::new(CancellationToken::new(), "");
+        let endpoint = free_endpoint().await;
+
+        assert!(listeners.start(&endpoint, test_redis_pool()).await);
+        assert!(!listeners.start(&endpoint, test_redis_pool()).await);
+        assert!(listeners.stop(&endpoint).await);
+        assert!(!listeners.stop(&endpoint).await);
+    }
+
+    #[tokio::test]
+    async fn test_parent_cancel_stops_listener() {
+        let parent = CancellationToken::new();
+        let endpoint = free_endpoint().await;
+        let redis = test_redis_pool();
+
+        let task_endpoint = endpoint.clone();
+        let task_shutdown = parent.child_token();
+        let handle = tokio::spawn(async move {
+            payala_stream(&task_endpoint, &redis, Arc::from(""), task_shutdown).await
         });
+        let _client = wait_for_listener(&endpoint).await;
+
+        parent.cancel();
+        tokio::time::timeout(Duration::from_secs(PAYALA_DRAIN_TIMEOUT_SECS + 1), handle)
+            .await
+            .expect("listener should exit after cancel")
+            .unwrap()
+            .unwrap();
+        tokio::net::TcpListener::bind(&endpoint).await.unwrap();
+    }
+
+    /// Serve `body` at the verification path; returns the server's origin.
+    async fn serve_verification(body: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        let app = axum::Router::new()
+            .route(CALLBACK_VERIFY_PATH, axum::routing::get(move || async move { body }));
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        origin
+    }
+
+    /// Serve a Horizon root document reporting `version`.
+    pub(crate) async fn serve_horizon_root(version: u64) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::get(move || async move {
+                axum::Json(serde_json::json!({ "current_protocol_version": version }))
+            }),
+        );
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        origin
+    }
+
+    #[tokio::test]
+    async fn test_check_horizon_protocol() {
+        let origin = serve_horizon_root(22).await;
+        let client = reqwest::Client::new();
+        assert_eq!(check_horizon_protocol(&client, &origin, 22).await, Ok(22));
+        let err = check_horizon_protocol(&client, &origin, 23).await.unwrap_err();
+        assert!(err.contains("below the required minimum 23"), "{}", err);
+
+        let closed = format!("http://{}", free_endpoint().await);
+        assert!(check_horizon_protocol(&client, &closed, 1).await.is_err());
+    }
+
+    #[test]
+    fn test_reprocess_rederives_records_from_raw_payloads() {
+        let stored: Vec<String> = [(5u64, "2026-01-01T00:00:05Z"), (6, "2026-01-01T00:00:10Z")]
+            .iter()
+            .map(|(seq, at)| {
+                serde_json::to_string(&StoredRawEvent {
+                    received_at: at.to_string(),
+                    payload: serde_json::json!({ "sequence": seq, "hash": "ab" }),
+                })
+                .unwrap()
+            })
+            .chain(std::iter::once("not json".to_string()))
+            .collect();
+
+        let (entries, failed) = reprocess_raw_events(&stored);
+        assert_eq!(failed, 1);
+        assert_eq!(
+            entries,
+            vec![
+                ("stellar:ledger:5".to_string(), "2026-01-01T00:00:05Z".to_string()),
+                ("stellar:ledger:6".to_string(), "2026-01-01T00:00:10Z".to_string()),
+            ]
+        );
+        // Reprocessing yields exactly what the live stream derived.
+        let live = ledger_record_entries(
+            &parse_ledger_event(&serde_json::json!({ "sequence": 5 })),
+            "2026-01-01T00:00:05Z",
+        );
+        assert_eq!(entries[..1], live[..]);
+    }
+
+    #[test]
+    fn test_verification_url_uses_origin() {
+        assert_eq!(
+            verification_url("https://hooks.example.com/a/b?c=d").unwrap(),
+            "https://hooks.example.com/.well-known/impala-verify"
+        );
+        assert_eq!(
+            verification_url("http://hooks.example.com:8443/cb").unwrap(),
+            "http://hooks.example.com:8443/.well-known/impala-verify"
+        );
+        assert!(verification_url("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_callback_verified_when_token_echoed() {
+        let origin = serve_verification("abc123\n").await;
+        let client = reqwest::Client::new();
+        let url = format!("{}{}", origin, CALLBACK_VERIFY_PATH);
+        assert!(check_callback_ownership(&client, &url, "abc123").await);
+    }
+
+    #[tokio::test]
+    async fn test_callback_unverified_on_wrong_or_missing_token() {
+        let origin = serve_verification("something-else").await;
+        let client = reqwest::Client::new();
+        let url = format!("{}{}", origin, CALLBACK_VERIFY_PATH);
+        assert!(!check_callback_ownership(&client, &url, "abc123").await);
+
+        // Token served at the wrong path does not count
+        let wrong_path = format!("{}/other", origin);
+        assert!(!check_callback_ownership(&client, &wrong_path, "abc123").await);
+
+        // Unreachable origin
+        let closed = format!("http://{}{}", free_endpoint().await, CALLBACK_VERIFY_PATH);
+        assert!(!check_callback_ownership(&client, &closed, "abc123").await);
+    }
+
+    #[test]
+    fn test_verification_tokens_are_unique() {
+        let a = new_verification_token();
+        assert_eq!(a.len(), 32);
+        assert_ne!(a, new_verification_token());
+    }
+
+    #[tokio::test]
+    async fn test_run_callback_stores_result() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = axum::Router::new().route(
+            "/cb",
+            axum::routing::get(|| async { axum::Json(serde_json::json!({"balance": "42"})) }),
+        );
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        // A public-looking hostname passes SSRF validation; resolve it locally.
+        let client = reqwest::Client::builder()
+            .resolve("callback.example.com", addr)
+            .build()
+            .unwrap();
+        let uri = format!("http://callback.example.com:{}/cb", addr.port());
+
+        let stored = tokio::sync::Mutex::new(Vec::new());
+        let result = run_callback(&client, 7, &uri, async |id, body| {
+            stored.lock().await.push((id, body.clone()));
+            Ok(())
+        })
+        .await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.result, Some(serde_json::json!({"balance": "42"})));
+        assert_eq!(
+            stored.into_inner(),
+            vec![(7, serde_json::json!({"balance": "42"}))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_callback_rejects_invalid_url_without_storing() {
+        let client = reqwest::Client::new();
+        let mut called = false;
+        let result = run_callback(&client, 1, "http://127.0.0.1/cb", async |_, _| {
+            called = true;
+            Ok(())
+        })
+        .await;
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("invalid callback_uri"));
+        assert!(!called);
+    }
+
+    #[test]
+    fn test_cron_sleep_within_jitter_range() {
+        let schedule = CronSchedule { interval_secs: 60, jitter_percent: 10, stagger_ms: 0 };
+        assert_eq!(schedule.next_sleep(0.0), Duration::from_secs(54));
+        assert_eq!(schedule.next_sleep(0.5), Duration::from_secs(60));
+        assert_eq!(schedule.next_sleep(1.0), Duration::from_secs(66));
+        for _ in 0..1000 {
+            let sleep = schedule.next_sleep(rand::random::<f64>());
+            assert!(sleep >= Duration::from_secs(54) && sleep <= Duration::from_secs(66), "{:?}", sleep);
+        }
+    }
+
+    #[test]
+    fn test_cron_sleep_without_jitter_is_fixed() {
+        let schedule = CronSchedule { interval_secs: 60, jitter_percent: 0, stagger_ms: 0 };
+        assert_eq!(schedule.next_sleep(0.0), Duration::from_secs(60));
+        assert_eq!(schedule.next_sleep(0.99), Duration::from_secs(60));
     }
 }
@@ -0,0 +1,102 @@
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+use std::sync::Arc;
+
+static TLS12_AND_UP: &[&rustls::SupportedProtocolVersion] =
+    &[&rustls::version::TLS13, &rustls::version::TLS12];
+static TLS13_ONLY: &[&rustls::SupportedProtocolVersion] = &[&rustls::version::TLS13];
+
+/// Parse a minimum TLS version setting ("1.2" or "1.3").
+pub fn parse_min_version(value: &str) -> Result<&'static [&'static rustls::SupportedProtocolVersion], String> {
+    match value.trim() {
+        "1.2" | "tls1.2" | "TLS1.2" => Ok(TLS12_AND_UP),
+        "1.3" | "tls1.3" | "TLS1.3" => Ok(TLS13_ONLY),
+        other => Err(format!("Unsupported minimum TLS version: {}", other)),
+    }
+}
+
+/// Build a rustls server config from PEM-encoded certificate chain and
+/// private key material.
+pub fn server_config_from_pem(
+    cert_pem: &[u8],
+    key_pem: &[u8],
+    min_version: &str,
+) -> Result<ServerConfig, String> {
+    let certs: Vec<CertificateDer<'static>> = CertificateDer::pem_slice_iter(cert_pem)
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Invalid TLS certificate: {}", e))?;
+    if certs.is_empty() {
+        return Err("TLS certificate file contains no certificates".to_string());
+    }
+    let key = PrivateKeyDer::from_pem_slice(key_pem)
+        .map_err(|e| format!("Invalid TLS private key: {}", e))?;
+    let versions = parse_min_version(min_version)?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::aws_lc_rs::default_provider(),
+    ))
+    .with_protocol_versions(versions)
+    .map_err(|e| format!("Invalid TLS protocol configuration: {}", e))?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)
+    .map_err(|e| format!("Invalid TLS certificate/key pair: {}", e))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+/// Read the certificate and key from disk and build a server config.
+pub fn load_server_config(
+    cert_path: &str,
+    key_path: &str,
+    min_version: &str,
+) -> Result<ServerConfig, String> {
+    let cert_pem = std::fs::read(cert_path)
+        .map_err(|e| format!("Failed to read TLS certificate {}: {}", cert_path, e))?;
+    let key_pem = std::fs::read(key_path)
+        .map_err(|e| format!("Failed to read TLS private key {}: {}", key_path, e))?;
+    server_config_from_pem(&cert_pem, &key_pem, min_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn self_signed() -> (String, String) {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        (cert.cert.pem(), cert.signing_key.serialize_pem())
+    }
+
+    #[test]
+    fn test_server_config_from_valid_pem() {
+        let (cert, key) = self_signed();
+        let config = server_config_from_pem(cert.as_bytes(), key.as_bytes(), "1.2").unwrap();
+        assert!(config.alpn_protocols.contains(&b"h2".to_vec()));
+    }
+
+    #[test]
+    fn test_server_config_tls13_only() {
+        let (cert, key) = self_signed();
+        assert!(server_config_from_pem(cert.as_bytes(), key.as_bytes(), "1.3").is_ok());
+    }
+
+    #[test]
+    fn test_server_config_rejects_bad_material() {
+        let (cert, _) = self_signed();
+        assert!(server_config_from_pem(b"", b"", "1.2").is_err());
+        assert!(server_config_from_pem(cert.as_bytes(), b"not a key", "1.2").is_err());
+    }
+
+    #[test]
+    fn test_parse_min_version() {
+        assert_eq!(parse_min_version("1.2").unwrap().len(), 2);
+        assert_eq!(parse_min_version("1.3").unwrap().len(), 1);
+        assert!(parse_min_version("1.1").is_err());
+    }
+
+    #[test]
+    fn test_load_server_config_unreadable_path() {
+        let err = load_server_config("/nonexistent/cert.pem", "/nonexistent/key.pem", "1.2").unwrap_err();
+        assert!(err.contains("/nonexistent/cert.pem"));
+    }
+}
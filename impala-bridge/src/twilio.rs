@@ -0,0 +1,28 @@
+/// Send an SMS through the Twilio Messages API using account `sid` and auth
+/// `token`.  Returns the HTTP status and response body; interpreting a
+/// non-2xx status is left to the caller.
+pub async fn send_sms(
+    client: &reqwest::Client,
+    sid: &str,
+    token: &str,
+    from: &str,
+    to: &str,
+    body: &str,
+) -> Result<(u16, String), String> {
+    let url = format!(
+        "https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
+        sid
+    );
+
+    let response = client
+        .post(&url)
+        .basic_auth(sid, Some(token))
+        .form(&[("To", to), ("From", from), ("Body", body)])
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let status = response.status().as_u16();
+    let response_body = response.text().await.unwrap_or_default();
+    Ok((status, response_body))
+}
@@ -1,4 +1,5 @@
-use crate::constants::{MAX_EMAIL_LENGTH, STELLAR_ACCOUNT_ID_LENGTH};
+use crate::config::PasswordPolicy;
+use crate::constants::{MAX_EMAIL_LENGTH, MAX_PASSWORD_LENGTH, STELLAR_ACCOUNT_ID_LENGTH};
 use crate::error::AppError;
 use std::net::IpAddr;
 
@@ -23,6 +24,158 @@ pub fn validate_stellar_account_id(id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Check a new password for `account_id` against the password policy: the
+/// configured length and complexity rules, `MAX_PASSWORD_LENGTH`, and not
+/// being the account ID.  The error names the rule that failed, never the
+/// password.
+pub fn validate_password(account_id: &str, password: &str, policy: &PasswordPolicy) -> Result<(), AppError> {
+    let length = password.chars().count();
+    if length < policy.min_length {
+        return Err(AppError::BadRequest(format!(
+            "Password must be at least {} characters",
+            policy.min_length
+        )));
+    }
+    if length > MAX_PASSWORD_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Password must be at most {} characters",
+            MAX_PASSWORD_LENGTH
+        )));
+    }
+    if password.eq_ignore_ascii_case(account_id) {
+        return Err(AppError::BadRequest(
+            "Password must not match the account ID".to_string(),
+        ));
+    }
+    if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        return Err(AppError::BadRequest(
+            "Password must contain a digit".to_string(),
+        ));
+    }
+    if policy.require_upper && !password.chars().any(char::is_uppercase) {
+        return Err(AppError::BadRequest(
+            "Password must contain an uppercase letter".to_string(),
+        ));
+    }
+    if policy.require_symbol && !password.chars().any(|c| !c.is_alphanumeric() && !c.is_whitespace()) {
+        return Err(AppError::BadRequest(
+            "Password must contain a symbol".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A strkey decoded by [`decode_strkey`].
+#[derive(Debug, PartialEq)]
+pub struct DecodedStrkey {
+    /// Key type name, e.g. `account`, `contract`, `muxed_account`, `secret_seed`.
+    pub key_type: &'static str,
+    /// Raw key bytes; `None` for secret seeds, which are never echoed.
+    pub raw: Option<Vec<u8>>,
+    /// Multiplexed id for `M...` addresses.
+    pub muxed_id: Option<u64>,
+}
+
+/// Strkey type for a leading character, per SEP-23.
+pub fn strkey_type_for_prefix(prefix: char) -> Option<&'static str> {
+    match prefix {
+        'G' => Some("account"),
+        'S' => Some("secret_seed"),
+        'M' => Some("muxed_account"),
+        'C' => Some("contract"),
+        'T' => Some("pre_auth_tx"),
+        'X' => Some("sha256_hash"),
+        'P' => Some("signed_payload"),
+        'L' => Some("liquidity_pool"),
+        'B' => Some("claimable_balance"),
+        _ => None,
+    }
+}
+
+/// Whether the trailing CRC16-XModem checksum of a strkey matches its
+/// contents.  `None` when the input is not valid unpadded Base32.
+pub fn strkey_checksum_valid(key: &str) -> Option<bool> {
+    let data = decode_base32(key)?;
+    if data.len() < 3 {
+        return Some(false);
+    }
+    let (body, crc) = data.split_at(data.len() - 2);
+    Some(crc16_xmodem(body).to_le_bytes() == [crc[0], crc[1]])
+}
+
+/// Decode any Stellar strkey (G, S, M, C, T, X, P, L, B).
+pub fn decode_strkey(key: &str) -> Result<DecodedStrkey, AppError> {
+    use stellar_strkey::Strkey;
+    let strkey = Strkey::from_string(key)
+        .map_err(|_| AppError::BadRequest("Invalid strkey".to_string()))?;
+    let (key_type, raw, muxed_id) = match strkey {
+        Strkey::PublicKeyEd25519(k) => ("account", Some(k.0.to_vec()), None),
+        Strkey::PrivateKeyEd25519(_) => ("secret_seed", None, None),
+        Strkey::MuxedAccountEd25519(m) => ("muxed_account", Some(m.ed25519.to_vec()), Some(m.id)),
+        Strkey::Contract(c) => ("contract", Some(c.0.to_vec()), None),
+        Strkey::PreAuthTx(t) => ("pre_auth_tx", Some(t.0.to_vec()), None),
+        Strkey::HashX(h) => ("sha256_hash", Some(h.0.to_vec()), None),
+        Strkey::SignedPayloadEd25519(p) => {
+            let mut raw = p.ed25519.to_vec();
+            raw.extend_from_slice(&p.payload);
+            ("signed_payload", Some(raw), None)
+        }
+        Strkey::LiquidityPool(l) => ("liquidity_pool", Some(l.0.to_vec()), None),
+        Strkey::ClaimableBalance(stellar_strkey::ClaimableBalance::V0(b)) => {
+            ("claimable_balance", Some(b.to_vec()), None)
+        }
+    };
+    Ok(DecodedStrkey { key_type, raw, muxed_id })
+}
+
+fn decode_base32(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in input.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// Normalize a personal name for storage: Unicode NFC, control and
+/// zero-width characters removed, whitespace trimmed and collapsed to
+/// single spaces.
+pub fn normalize_name(name: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    let cleaned: String = name
+        .nfc()
+        .filter(|&c| c.is_whitespace() || !(c.is_control() || is_zero_width(c)))
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_zero_width(c: char) -> bool {
+    matches!(c, '\u{200B}'..='\u{200F}' | '\u{2060}' | '\u{FEFF}' | '\u{00AD}')
+}
+
 /// Validate an email address with a basic check.
 pub fn validate_email(email: &str) -> Result<(), AppError> {
     if email.len() > MAX_EMAIL_LENGTH {
@@ -198,6 +351,81 @@ pub fn ldap_escape(input: &str) -> String {
 mod tests {
     use super::*;
 
+    // ── Strkey ──────────────────────────────────────────────────────────
+
+    const PAYLOAD_HEX: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[test]
+    fn test_decode_strkey_types() {
+        for (key, key_type) in [
+            ("GAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB7JZX", "account"),
+            ("CAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6N4O", "contract"),
+        ] {
+            let decoded = decode_strkey(key).unwrap();
+            assert_eq!(decoded.key_type, key_type);
+            assert_eq!(crate::contract::encode_hex(&decoded.raw.unwrap()), PAYLOAD_HEX);
+            assert_eq!(decoded.muxed_id, None);
+            assert_eq!(strkey_checksum_valid(key), Some(true));
+        }
+    }
+
+    #[test]
+    fn test_decode_muxed_strkey() {
+        let key = "MAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6AAAAAAAAAAAA6X66";
+        let decoded = decode_strkey(key).unwrap();
+        assert_eq!(decoded.key_type, "muxed_account");
+        assert_eq!(crate::contract::encode_hex(&decoded.raw.unwrap()), PAYLOAD_HEX);
+        assert_eq!(decoded.muxed_id, Some(7));
+    }
+
+    #[test]
+    fn test_decode_secret_seed_hides_bytes() {
+        let key = "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI";
+        let decoded = decode_strkey(key).unwrap();
+        assert_eq!(decoded.key_type, "secret_seed");
+        assert_eq!(decoded.raw, None);
+    }
+
+    #[test]
+    fn test_corrupted_strkey_checksum() {
+        // Last character altered
+        let key = "GAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB7JZY";
+        assert_eq!(strkey_checksum_valid(key), Some(false));
+        assert!(decode_strkey(key).is_err());
+        assert_eq!(strkey_checksum_valid("gaaa!"), None);
+    }
+
+    #[test]
+    fn test_strkey_type_for_prefix() {
+        assert_eq!(strkey_type_for_prefix('G'), Some("account"));
+        assert_eq!(strkey_type_for_prefix('S'), Some("secret_seed"));
+        assert_eq!(strkey_type_for_prefix('Z'), None);
+    }
+
+    // ── Name normalization ─────────────────────────────────────────────
+
+    #[test]
+    fn test_normalize_name_whitespace() {
+        assert_eq!(normalize_name("  Ada   Lovelace \t"), "Ada Lovelace");
+        assert_eq!(normalize_name("Mary\nJane"), "Mary Jane");
+        assert_eq!(normalize_name("   "), "");
+    }
+
+    #[test]
+    fn test_normalize_name_nfc() {
+        // "e" + combining acute accent composes to a single "é"
+        let decomposed = "Jose\u{0301}";
+        assert_eq!(normalize_name(decomposed), "Jos\u{00E9}");
+        assert_eq!(normalize_name(decomposed), normalize_name("Jos\u{00E9}"));
+    }
+
+    #[test]
+    fn test_normalize_name_strips_zero_width_and_control() {
+        assert_eq!(normalize_name("Ad\u{200B}a"), "Ada");
+        assert_eq!(normalize_name("\u{FEFF}Ada\u{200D}"), "Ada");
+        assert_eq!(normalize_name("Ada\u{0007}"), "Ada");
+    }
+
     // ── Stellar account ID ─────────────────────────────────────────────
 
     #[test]
@@ -410,6 +638,78 @@ mod tests {
         assert!(validate_ec_pubkey("abcdef").is_err());
     }
 
+    // ── Password ────────────────────────────────────────────────────────
+
+    fn strict_policy() -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: 12,
+            require_digit: true,
+            require_upper: true,
+            require_symbol: true,
+            history_size: 0,
+        }
+    }
+
+    fn rejection(password: &str) -> String {
+        match validate_password("payala-1", password, &strict_policy()) {
+            Err(AppError::BadRequest(msg)) => msg,
+            other => panic!("expected BadRequest, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_password_default_policy_only_checks_length() {
+        let policy = PasswordPolicy::default();
+        assert!(validate_password("payala-1", "all lowercase", &policy).is_ok());
+        assert!(validate_password("payala-1", "short", &policy).is_err());
+    }
+
+    #[test]
+    fn test_password_fixed_rules() {
+        let policy = PasswordPolicy::default();
+        assert!(validate_password("1234", &"x".repeat(MAX_PASSWORD_LENGTH + 1), &policy).is_err());
+        assert!(validate_password("acct-00001", "ACCT-00001", &policy).is_err());
+        assert!(validate_password("1234", "correct horse battery", &policy).is_ok());
+    }
+
+    #[test]
+    fn test_password_min_length_follows_policy() {
+        let policy = PasswordPolicy { min_length: 20, ..PasswordPolicy::default() };
+        assert!(validate_password("payala-1", "correct horse", &policy).is_err());
+        assert!(validate_password("payala-1", "correct horse battery", &policy).is_ok());
+    }
+
+    #[test]
+    fn test_password_min_length() {
+        assert_eq!(rejection("Ab1!"), "Password must be at least 12 characters");
+    }
+
+    #[test]
+    fn test_password_require_digit() {
+        assert_eq!(rejection("Abcdefghijk!"), "Password must contain a digit");
+    }
+
+    #[test]
+    fn test_password_require_upper() {
+        assert_eq!(rejection("abcdefghij1!"), "Password must contain an uppercase letter");
+    }
+
+    #[test]
+    fn test_password_require_symbol() {
+        assert_eq!(rejection("Abcdefghij12"), "Password must contain a symbol");
+        assert_eq!(rejection("Abcdefghij 12"), "Password must contain a symbol");
+    }
+
+    #[test]
+    fn test_password_meets_strict_policy() {
+        assert!(validate_password("payala-1", "Abcdefghij1!", &strict_policy()).is_ok());
+    }
+
+    #[test]
+    fn test_password_error_does_not_echo_password() {
+        assert!(!rejection("Secretpassword").contains("Secretpassword"));
+    }
+
     // ── RSA Public Key ──────────────────────────────────────────────────
 
     #[test]
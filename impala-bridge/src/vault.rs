@@ -6,11 +6,58 @@ struct VaultUnwrapResponse {
     data: serde_json::Value,
 }
 
-#[derive(Debug)]
+/// Vault's standard error body: `{"errors": ["..."]}`.
+#[derive(Deserialize, Debug, Default)]
+struct VaultErrorBody {
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
 pub enum BoxUnwrapError {
     VaultUrlMissing,
+    /// The request never got an HTTP response (connection, TLS, timeout).
     RequestFailed(String),
     InvalidResponse(String),
+    /// 404: nothing at the requested path.
+    NotFound(String),
+    /// 403: the token lacks the required policy.
+    PermissionDenied(String),
+    /// 503: Vault is sealed and must be unsealed before secrets can be read.
+    Sealed(String),
+    /// The token (or wrapping token) has expired, was revoked, or was
+    /// already used.
+    TokenExpired(String),
+    /// Any other non-success status.
+    Http { status: u16, message: String },
+}
+
+impl BoxUnwrapError {
+    /// Map a non-success Vault response to an error variant, using the
+    /// messages from Vault's `{"errors": [...]}` body when present.
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let errors = serde_json::from_str::<VaultErrorBody>(body)
+            .map(|b| b.errors)
+            .unwrap_or_default();
+        let message = if errors.is_empty() {
+            body.trim().to_string()
+        } else {
+            errors.join("; ")
+        };
+        let lower = message.to_lowercase();
+        let token_gone = lower.contains("expired")
+            || lower.contains("wrapping token is not valid")
+            || lower.contains("token not found")
+            || lower.contains("revoked");
+
+        match status {
+            503 => BoxUnwrapError::Sealed(message),
+            400 | 403 if token_gone => BoxUnwrapError::TokenExpired(message),
+            403 => BoxUnwrapError::PermissionDenied(message),
+            404 => BoxUnwrapError::NotFound(message),
+            _ => BoxUnwrapError::Http { status, message },
+        }
+    }
 }
 
 impl std::fmt::Display for BoxUnwrapError {
@@ -21,6 +68,25 @@ impl std::fmt::Display for BoxUnwrapError {
             }
             BoxUnwrapError::RequestFailed(msg) => write!(f, "Vault request failed: {}", msg),
             BoxUnwrapError::InvalidResponse(msg) => write!(f, "Invalid Vault response: {}", msg),
+            BoxUnwrapError::NotFound(msg) => {
+                write!(f, "Vault path not found ({}); check the secret path", msg)
+            }
+            BoxUnwrapError::PermissionDenied(msg) => write!(
+                f,
+                "Vault permission denied ({}); check the token's policies",
+                msg
+            ),
+            BoxUnwrapError::Sealed(msg) => {
+                write!(f, "Vault is sealed ({}); unseal it and restart", msg)
+            }
+            BoxUnwrapError::TokenExpired(msg) => write!(
+                f,
+                "Vault token expired or already used ({}); issue a new wrapping token",
+                msg
+            ),
+            BoxUnwrapError::Http { status, message } => {
+                write!(f, "Vault returned HTTP {}: {}", status, message)
+            }
         }
     }
 }
@@ -49,15 +115,9 @@ pub async fn box_unwrap(wrapping_token: &str) -> Result<serde_json::Value, BoxUn
         .map_err(|e| BoxUnwrapError::RequestFailed(e.to_string()))?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(BoxUnwrapError::RequestFailed(format!(
-            "HTTP {}: {}",
-            status, error_text
-        )));
+        let status = response.status().as_u16();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(BoxUnwrapError::from_response(status, &error_text));
     }
 
     let unwrap_response: VaultUnwrapResponse = response
@@ -67,3 +127,51 @@ pub async fn box_unwrap(wrapping_token: &str) -> Result<serde_json::Value, BoxUn
 
     Ok(unwrap_response.data)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sealed_vault() {
+        let err = BoxUnwrapError::from_response(503, r#"{"errors":["Vault is sealed"]}"#);
+        assert_eq!(err, BoxUnwrapError::Sealed("Vault is sealed".to_string()));
+        assert!(err.to_string().starts_with("Vault is sealed"));
+    }
+
+    #[test]
+    fn test_permission_denied() {
+        let err = BoxUnwrapError::from_response(403, r#"{"errors":["1 error occurred:\n\t* permission denied\n\n"]}"#);
+        assert!(matches!(err, BoxUnwrapError::PermissionDenied(_)));
+    }
+
+    #[test]
+    fn test_token_expired() {
+        let err = BoxUnwrapError::from_response(
+            400,
+            r#"{"errors":["wrapping token is not valid or does not exist"]}"#,
+        );
+        assert!(matches!(err, BoxUnwrapError::TokenExpired(_)));
+
+        let err = BoxUnwrapError::from_response(403, r#"{"errors":["token expired"]}"#);
+        assert!(matches!(err, BoxUnwrapError::TokenExpired(_)));
+    }
+
+    #[test]
+    fn test_not_found_with_empty_errors() {
+        let err = BoxUnwrapError::from_response(404, r#"{"errors":[]}"#);
+        assert!(matches!(err, BoxUnwrapError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_other_status_keeps_non_json_body() {
+        let err = BoxUnwrapError::from_response(500, "upstream exploded\n");
+        assert_eq!(
+            err,
+            BoxUnwrapError::Http {
+                status: 500,
+                message: "upstream exploded".to_string()
+            }
+        );
+    }
+}
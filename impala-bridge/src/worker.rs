@@ -11,6 +11,7 @@ use tokio::sync::Semaphore;
 use crate::config::Config;
 use crate::constants::DEFAULT_HTTP_CLIENT_TIMEOUT_SECS;
 use crate::jobs;
+use crate::stellar_rpc::StellarRpcClient;
 use crate::telemetry::AppMetrics;
 
 /// Shared context passed to all job handlers.
@@ -19,7 +20,8 @@ pub struct WorkerContext {
     pub redis_pool: Arc<deadpool_redis::Pool>,
     pub http_client: reqwest::Client,
     pub config: Config,
-    pub stellar_rpc_url: String,
+    pub stellar_rpc: StellarRpcClient,
+    #[allow(dead_code)]
     pub horizon_url: String,
     pub ses_client: Option<aws_sdk_sesv2::Client>,
     pub fcm_project_id: Option<String>,
@@ -70,7 +72,6 @@ pub async fn run(pool: PgPool, redis_pool: Arc<deadpool_redis::Pool>, config: Co
         .expect("SQS_QUEUE_URL must be set when RUN_MODE=worker")
         .clone();
 
-    let stellar_rpc_url = config.stellar_rpc_url.clone();
     let horizon_url = config.stellar_horizon_url.clone();
 
     let aws_config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
@@ -84,15 +85,18 @@ pub async fn run(pool: PgPool, redis_pool: Arc<deadpool_redis::Pool>, config: Co
 
     let fcm_project_id = config.fcm_project_id.clone();
 
+    let http_client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(DEFAULT_HTTP_CLIENT_TIMEOUT_SECS))
+        .build()
+        .expect("Failed to create HTTP client");
+    let stellar_rpc = StellarRpcClient::new(http_client.clone(), config.stellar_rpc_url.clone());
+
     let ctx = Arc::new(WorkerContext {
         pool,
         redis_pool,
-        http_client: reqwest::Client::builder()
-            .timeout(Duration::from_secs(DEFAULT_HTTP_CLIENT_TIMEOUT_SECS))
-            .build()
-            .expect("Failed to create HTTP client"),
+        http_client,
         config: config.clone(),
-        stellar_rpc_url,
+        stellar_rpc,
         horizon_url,
         ses_client,
         fcm_project_id,
@@ -242,7 +246,7 @@ async fn process_message(
     );
 
     let job_type_attr = KeyValue::new("job_type", job.job_type.clone());
-    ctx.metrics.jobs_active.add(1, &[job_type_attr.clone()]);
+    ctx.metrics.jobs_active.add(1, std::slice::from_ref(&job_type_attr));
     let start = std::time::Instant::now();
 
     let result = match job.job_type.as_str() {
@@ -260,10 +264,10 @@ async fn process_message(
     };
 
     let duration = start.elapsed().as_secs_f64();
-    ctx.metrics.jobs_active.add(-1, &[job_type_attr.clone()]);
+    ctx.metrics.jobs_active.add(-1, std::slice::from_ref(&job_type_attr));
     ctx.metrics
         .job_duration
-        .record(duration, &[job_type_attr.clone()]);
+        .record(duration, std::slice::from_ref(&job_type_attr));
 
     let outcome = match &result {
         Ok(()) => "success",
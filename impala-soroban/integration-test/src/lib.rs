@@ -1,9 +1,12 @@
-//! MultisigAssetWrapper — a Soroban smart contract that wraps a Stellar token
+//! MultisigAssetWrapper — a Soroban smart contract that wraps Stellar tokens
 //! with multisig authorization and time-locked operations.
 //!
 //! # Design
 //!
-//! The contract holds an underlying Stellar token on behalf of users. Deposits
+//! The contract holds one or more underlying Stellar tokens on behalf of
+//! users, tracking wrapped balances separately per token. The first token is
+//! supplied to `initialize`; further tokens are allowed with `add_token`
+//! (multisig). Deposits
 //! (`wrap`) are immediate, but withdrawals (`unwrap`) and inter-account
 //! transfers require a two-phase schedule/execute pattern with a configurable
 //! minimum delay, giving signers time to detect and cancel unauthorized
@@ -12,13 +15,25 @@
 //! All mutating operations require multisig: at least `threshold` signers
 //! from the authorized list must call `require_auth()`.
 //!
+//! The wrapped form of the first token is also exposed through the SEP-41
+//! token interface (`balance`, `transfer`, `approve`, ...), so wallets and
+//! explorers can read and move it like any other Stellar token.
+//!
 //! # Storage
 //!
-//! All state is stored at the instance level via `env.storage().instance()`.
+//! Balances, holders and timelocks are persistent entries, allowances
+//! temporary ones; everything else is stored at the instance level, whose
+//! TTL every state-changing call extends.
 
 #![no_std]
+// `events().publish` is deprecated in soroban-sdk 23 in favour of
+// `#[contractevent]`; kept until the event schema is migrated.
+#![allow(deprecated)]
+// `initialize` takes its whole configuration as arguments; the lint also
+// fires on the argument helpers `#[contractimpl]` generates for it.
+#![allow(clippy::too_many_arguments)]
 use soroban_sdk::{
-    contract, contractimpl, contracttype, token, Address, Env, Vec, symbol_short
+    contract, contractimpl, contracttype, token, Address, BytesN, Env, String, Symbol, Vec, symbol_short
 };
 
 /// Configuration for the multisig signer set.
@@ -26,11 +41,14 @@ use soroban_sdk::{
 pub struct MultisigConfig {
     /// Authorized signer addresses.
     pub signers: Vec<Address>,
-    /// Minimum number of signers required to authorize an operation.
+    /// Weight of each signer, parallel to `signers`.  Empty means every
+    /// signer has weight 1.
+    pub weights: Vec<u32>,
+    /// Total signer weight required to authorize an operation.
     pub threshold: u32,
 }
 
-/// Tracks the underlying token and total amount wrapped by the contract.
+/// Tracks an underlying token and the total amount of it wrapped by the contract.
 #[contracttype]
 pub struct WrappedAsset {
     /// Address of the underlying Stellar token (SAC or custom).
@@ -43,14 +61,21 @@ pub struct WrappedAsset {
 /// after `unlock_time` has passed.
 #[contracttype]
 pub struct TimeLock {
-    /// Operation type: 1=unwrap, 2=transfer.
+    /// Sequential ID the timelock is stored under.
+    pub id: u64,
+    /// Operation type: 1=unwrap, 2=transfer, 3=config_change,
+    /// 4=delegated unwrap (`signers` holds just the spender),
+    /// 5=daily unwrap cap change (`amount` holds the new cap).
     pub operation_type: u32,
     /// Signers who authorized the scheduling of this operation.
     pub signers: Vec<Address>,
-    /// Source address whose balance is debited (sender for transfers, balance holder for unwraps).
+    /// Source address whose balance is debited (sender for transfers, balance
+    /// holder for unwraps; the contract itself for config and cap changes).
     pub sender: Address,
     /// Recipient address for the operation's output.
     pub recipient: Address,
+    /// Underlying token the operation applies to.
+    pub token: Address,
     /// Token amount involved in the operation.
     pub amount: i128,
     /// Ledger timestamp (seconds) after which the operation can be executed.
@@ -59,62 +84,250 @@ pub struct TimeLock {
     pub executed: bool,
 }
 
+/// SEP-41 metadata for the wrapped form of the first token.
+#[contracttype]
+#[derive(Clone)]
+pub struct TokenMetadata {
+    pub decimal: u32,
+    pub name: String,
+    pub symbol: String,
+}
+
+/// A SEP-41 allowance: `amount` may be spent until `expiration_ledger`.
+#[contracttype]
+#[derive(Clone)]
+pub struct AllowanceValue {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
+/// Timelock settings passed to `initialize`.
+#[contracttype]
+#[derive(Clone)]
+pub struct TimelockLimits {
+    /// Shortest allowed delay (seconds).
+    pub min_lock_duration: u64,
+    /// Longest allowed delay (seconds); 0 means no limit beyond
+    /// `MAX_LOCK_DURATION`.
+    pub max_lock_duration: u64,
+    /// Seconds after unlocking in which a timelock must be executed; 0 for
+    /// no limit.
+    pub execution_window: u64,
+    /// Most pending unwraps and transfers one recipient may have; 0 for no
+    /// cap.
+    pub max_pending_per_recipient: u32,
+}
+
+/// Parameters needed to build a `schedule_*` call, returned in one read.
+#[contracttype]
+pub struct SchedulingInfo {
+    /// ID the next scheduled timelock will receive.
+    pub next_timelock_id: u64,
+    /// Minimum allowed delay (seconds).
+    pub min_lock_duration: u64,
+    /// Maximum allowed delay (seconds).
+    pub max_lock_duration: u64,
+}
+
+/// One page of `list_timelocks`.
+#[contracttype]
+pub struct TimelockPage {
+    /// Pending timelocks among the IDs examined.
+    pub timelocks: Vec<TimeLock>,
+    /// `start_id` for the next page; reaches `timelock_count` once every ID
+    /// has been examined.
+    pub next_id: u64,
+}
+
 /// Storage keys for contract state.
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
     MultisigConfig,
-    WrappedAsset,
-    /// Per-address wrapped token balance.
-    Balance(Address),
+    /// Wrapped totals for an allowed underlying token.
+    WrappedAsset(Address),
+    /// Allowed underlying tokens, in the order they were added.
+    Tokens,
+    /// Per-token, per-address wrapped balance: `(token, holder)`.  Held in
+    /// persistent storage.
+    Balance(Address, Address),
     Initialized,
-    /// Time-locked operation, keyed by sequential ID.
+    /// Time-locked operation, keyed by sequential ID.  Held in persistent
+    /// storage.
     TimeLock(u64),
     NextTimeLockId,
     MinLockDuration,
+    /// Seconds after `unlock_time` during which a timelock may still be
+    /// executed; 0 means no limit.
+    ExecutionWindow,
+    /// Longest delay a `schedule_*` call may use (seconds); 0 leaves only
+    /// the built-in `MAX_LOCK_DURATION` ceiling.
+    MaxLockDuration,
     /// Whether the contract is paused.
     Paused,
+    /// Lifetime sum of fees taken on unwraps.
+    TotalFeesCollected,
+    /// Fee taken on each unwrap, in basis points of the amount.
+    UnwrapFeeBps,
+    /// Address that receives unwrap fees in the underlying token.
+    FeeCollector,
+    /// Number of addresses that have held a wrapped balance of a token.
+    /// Held in persistent storage.
+    HolderCount(Address),
+    /// Holder of a token by the order it first received a balance:
+    /// `(token, index)`.  Held in persistent storage, one entry each.
+    Holder(Address, u32),
+    /// Signer set and threshold proposed by a config-change timelock.
+    PendingConfig(u64),
+    /// Bitmap of purged timelock IDs, 128 per bucket: bit `id % 128` of
+    /// bucket `id / 128`.  Held in persistent storage.
+    ExecutedTimeLocks(u64),
+    /// SEP-41 name, symbol and decimals.
+    Metadata,
+    /// SEP-41 allowance granted by `(owner, spender)`.  Held in temporary
+    /// storage, living until its expiration ledger.
+    Allowance(Address, Address),
+    /// Unwrap allowance granted with `approve_unwrap`:
+    /// `(token, owner, spender)`.  Held in temporary storage.
+    UnwrapAllowance(Address, Address, Address),
+    /// Code version, 1 at `initialize` and bumped by each `upgrade`.
+    Version,
+    /// Pending unwrap and transfer timelocks paying out to an address.  Held
+    /// in persistent storage.
+    PendingCount(Address),
+    /// Most pending timelocks one recipient may have; 0 means unlimited.
+    MaxPendingPerRecipient,
+    /// Most of a token that unwraps may pay out per 24h window; 0 means no
+    /// cap.
+    DailyUnwrapCap(Address),
+    /// Amount of a token unwrapped in the current window.
+    DailyUnwrapUsed(Address),
+    /// Ledger timestamp at which a token's current window opened.
+    DailyWindowStart(Address),
+}
+
+/// Configuration as stored by the first deployed version, before signer
+/// weights.  Read only by `migrate`.
+#[contracttype]
+pub struct LegacyMultisigConfig {
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+}
+
+/// A timelock as stored by the first deployed version, before timelocks
+/// carried their ID and token.  Read only by `migrate`.
+#[contracttype]
+pub struct LegacyTimeLock {
+    pub operation_type: u32,
+    pub signers: Vec<Address>,
+    pub sender: Address,
+    pub recipient: Address,
+    pub amount: i128,
+    pub unlock_time: u64,
+    pub executed: bool,
+}
+
+/// Instance storage keys of the first deployed version, which wrapped a
+/// single token.  Read only by `migrate`; its timelocks used the same
+/// `DataKey::TimeLock(id)` key.
+#[contracttype]
+#[derive(Clone)]
+pub enum LegacyDataKey {
+    /// The one `WrappedAsset`.
+    WrappedAsset,
+    /// Per-address wrapped balance of that token.
+    Balance(Address),
 }
 
 /// Maximum allowed delay for timelocked operations (365 days in seconds).
 const MAX_LOCK_DURATION: u64 = 31_536_000;
 
+/// Persistent entries (balances and timelocks) with fewer than this many
+/// ledgers of TTL left are extended when touched, about 7 days at 5s/ledger.
+const PERSISTENT_TTL_THRESHOLD: u32 = 120_960;
+
+/// TTL persistent entries are extended to, about 30 days at 5s/ledger.
+const PERSISTENT_TTL_EXTEND_TO: u32 = 518_400;
+
+/// Instance storage (configuration, totals and the contract code) is
+/// extended on every state-changing call once its TTL drops below this,
+/// about 7 days at 5s/ledger.
+const INSTANCE_TTL_THRESHOLD: u32 = 120_960;
+
+/// TTL instance storage is extended to, about 30 days at 5s/ledger.
+const INSTANCE_TTL_EXTEND_TO: u32 = 518_400;
+
+/// Denominator for basis-point fees; also the largest allowed fee.
+const MAX_FEE_BPS: u32 = 10_000;
+
+/// Length of the daily unwrap cap window (24h in seconds).
+const DAILY_WINDOW: u64 = 86_400;
+
+/// Largest page `balances` returns.
+const MAX_BALANCES_PAGE: u32 = 100;
+
+/// Most timelock IDs one `list_timelocks` or `migrate` call examines.
+const MAX_TIMELOCKS_PAGE: u32 = 100;
+
 #[contract]
 pub struct MultisigAssetWrapper;
 
 #[contractimpl]
 impl MultisigAssetWrapper {
-    /// Initialize with minimum lock duration (in seconds)
+    /// Initialize with the signer set and per-signer `weights` (empty for
+    /// weight 1 each, so `threshold` is a signer count), the timelock
+    /// `limits`, the unwrap fee (basis points, 0 for none) paid to
+    /// `fee_collector`, and the SEP-41 metadata of the wrapped
+    /// `underlying_token`.
     pub fn initialize(
         env: Env,
         signers: Vec<Address>,
+        weights: Vec<u32>,
         threshold: u32,
         underlying_token: Address,
-        min_lock_duration: u64,
+        limits: TimelockLimits,
+        unwrap_fee_bps: u32,
+        fee_collector: Address,
+        metadata: TokenMetadata,
     ) {
         if env.storage().instance().has(&DataKey::Initialized) {
             panic!("Already initialized");
         }
 
-        if threshold == 0 || threshold > signers.len() {
-            panic!("Invalid threshold");
+        let config = Self::signer_config(signers, weights, threshold);
+
+        if limits.max_lock_duration != 0
+            && (limits.max_lock_duration < limits.min_lock_duration
+                || limits.max_lock_duration > MAX_LOCK_DURATION)
+        {
+            panic!("Invalid max lock duration");
+        }
+
+        if unwrap_fee_bps > MAX_FEE_BPS {
+            panic!("Invalid fee");
         }
 
-        let config = MultisigConfig { signers, threshold };
         env.storage().instance().set(&DataKey::MultisigConfig, &config);
 
-        let wrapped_asset = WrappedAsset {
-            underlying_token: underlying_token.clone(),
-            total_wrapped: 0,
-        };
-        env.storage().instance().set(&DataKey::WrappedAsset, &wrapped_asset);
-        env.storage().instance().set(&DataKey::MinLockDuration, &min_lock_duration);
+        Self::register_token(&env, &underlying_token);
+        env.storage().instance().set(&DataKey::MinLockDuration, &limits.min_lock_duration);
+        env.storage().instance().set(&DataKey::MaxLockDuration, &limits.max_lock_duration);
+        env.storage().instance().set(&DataKey::ExecutionWindow, &limits.execution_window);
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxPendingPerRecipient, &limits.max_pending_per_recipient);
+        env.storage().instance().set(&DataKey::UnwrapFeeBps, &unwrap_fee_bps);
+        env.storage().instance().set(&DataKey::FeeCollector, &fee_collector);
+        env.storage().instance().set(&DataKey::Metadata, &metadata);
+        env.storage().instance().set(&DataKey::Version, &1u32);
         env.storage().instance().set(&DataKey::NextTimeLockId, &0u64);
         env.storage().instance().set(&DataKey::Initialized, &true);
+        Self::extend_instance_ttl(&env);
     }
 
     /// Pause the contract (requires multisig). Blocks all operations except cancel_timelock.
     pub fn pause(env: Env, signers: Vec<Address>) {
+        Self::extend_instance_ttl(&env);
         Self::verify_multisig(&env, &signers);
         env.storage().instance().set(&DataKey::Paused, &true);
         env.events().publish(
@@ -125,6 +338,7 @@ impl MultisigAssetWrapper {
 
     /// Unpause the contract (requires multisig).
     pub fn unpause(env: Env, signers: Vec<Address>) {
+        Self::extend_instance_ttl(&env);
         Self::verify_multisig(&env, &signers);
         env.storage().instance().set(&DataKey::Paused, &false);
         env.events().publish(
@@ -133,1451 +347,4328 @@ impl MultisigAssetWrapper {
         );
     }
 
-    /// Rotate the authorized signer set (requires current multisig).
-    pub fn rotate_signers(
-        env: Env,
-        current_signers: Vec<Address>,
-        new_signers: Vec<Address>,
-        new_threshold: u32,
-    ) {
-        if new_signers.len() == 0 {
-            panic!("New signers must not be empty");
+    /// Add `new_signer` to the authorized signer set with weight 1
+    /// (requires multisig).  The threshold is unchanged.
+    pub fn add_signer(env: Env, signers: Vec<Address>, new_signer: Address) {
+        Self::extend_instance_ttl(&env);
+        Self::verify_multisig(&env, &signers);
+
+        let mut config = Self::load_config(&env);
+        if config.signers.contains(&new_signer) {
+            panic!("Signer already exists");
         }
-        if new_threshold == 0 || new_threshold > new_signers.len() {
-            panic!("Invalid new threshold");
+        config.signers.push_back(new_signer.clone());
+        if !config.weights.is_empty() {
+            config.weights.push_back(1);
         }
-
-        Self::verify_multisig(&env, &current_signers);
-
-        let config = MultisigConfig {
-            signers: new_signers,
-            threshold: new_threshold,
-        };
         env.storage().instance().set(&DataKey::MultisigConfig, &config);
 
         env.events().publish(
-            (symbol_short!("rotate"),),
-            new_threshold,
+            (symbol_short!("add_sig"), new_signer),
+            config.signers.len(),
         );
     }
 
-    /// Wrap tokens (immediate execution, no timelock)
-    pub fn wrap(env: Env, signers: Vec<Address>, amount: i128) {
-        Self::require_not_paused(&env);
-
-        if amount <= 0 {
-            panic!("Amount must be positive");
-        }
-
+    /// Remove `target` from the authorized signer set (requires multisig).
+    /// Refused if the remaining signers' weight would fall below `threshold`.
+    pub fn remove_signer(env: Env, signers: Vec<Address>, target: Address) {
+        Self::extend_instance_ttl(&env);
         Self::verify_multisig(&env, &signers);
 
-        let mut wrapped_asset: WrappedAsset = env
-            .storage()
-            .instance()
-            .get(&DataKey::WrappedAsset)
-            .unwrap();
-
-        let token_client = token::Client::new(&env, &wrapped_asset.underlying_token);
-        let contract_address = env.current_contract_address();
-        
-        signers.get(0).unwrap().require_auth();
-        token_client.transfer(
-            &signers.get(0).unwrap(),
-            &contract_address,
-            &amount,
-        );
-
-        let balance_key = DataKey::Balance(signers.get(0).unwrap());
-        let current_balance: i128 = env
-            .storage()
-            .instance()
-            .get(&balance_key)
-            .unwrap_or(0);
-        
-        let new_balance = current_balance.checked_add(amount).expect("Balance overflow");
-        env.storage()
-            .instance()
-            .set(&balance_key, &new_balance);
-
-        wrapped_asset.total_wrapped = wrapped_asset.total_wrapped
-            .checked_add(amount).expect("Total supply overflow");
-        env.storage()
-            .instance()
-            .set(&DataKey::WrappedAsset, &wrapped_asset);
+        let mut config = Self::load_config(&env);
+        let index = config
+            .signers
+            .first_index_of(&target)
+            .expect("Signer not found");
+        config.signers.remove(index);
+        if !config.weights.is_empty() {
+            config.weights.remove(index);
+        }
+        if Self::total_weight(&config) < config.threshold {
+            panic!("Removal would leave fewer signers than threshold");
+        }
+        env.storage().instance().set(&DataKey::MultisigConfig, &config);
 
         env.events().publish(
-            (symbol_short!("wrap"), signers.get(0).unwrap()),
-            amount,
+            (symbol_short!("rm_sig"), target),
+            config.signers.len(),
         );
     }
 
-    /// Schedule a time-locked unwrap operation
-    pub fn schedule_unwrap(
+    /// Schedule a time-locked replacement of the signer set, weights and
+    /// threshold (requires current multisig).  `new_weights` works as in
+    /// `initialize`: empty gives every new signer weight 1, and
+    /// `new_threshold` must be reachable with the weights given.  The
+    /// change only takes effect after `delay_seconds`, giving other signers
+    /// time to cancel it.
+    pub fn schedule_config_change(
         env: Env,
         signers: Vec<Address>,
-        recipient: Address,
-        amount: i128,
+        new_signers: Vec<Address>,
+        new_weights: Vec<u32>,
+        new_threshold: u32,
         delay_seconds: u64,
     ) -> u64 {
-        Self::require_not_paused(&env);
-
-        if amount <= 0 {
-            panic!("Amount must be positive");
-        }
+        Self::extend_instance_ttl(&env);
+        let pending = Self::signer_config(new_signers, new_weights, new_threshold);
 
         Self::verify_multisig(&env, &signers);
+        Self::check_delay(&env, delay_seconds);
 
-        let min_duration: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::MinLockDuration)
-            .unwrap();
-
-        if delay_seconds < min_duration {
-            panic!("Delay too short");
-        }
-
-        if delay_seconds > MAX_LOCK_DURATION {
-            panic!("Delay exceeds maximum lock duration");
-        }
-
-        let balance_key = DataKey::Balance(recipient.clone());
-        let current_balance: i128 = env
-            .storage()
-            .instance()
-            .get(&balance_key)
-            .unwrap_or(0);
-
-        if current_balance < amount {
-            panic!("Insufficient wrapped balance");
-        }
-
-        let timelock_id: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::NextTimeLockId)
-            .unwrap();
-
+        let timelock_id = Self::next_timelock_id(&env);
         let unlock_time = env.ledger().timestamp() + delay_seconds;
+        let contract_address = env.current_contract_address();
 
         let timelock = TimeLock {
-            operation_type: 1,  // unwrap
+            id: timelock_id,
+            operation_type: 3,  // config_change
             signers: signers.clone(),
-            sender: recipient.clone(),
-            recipient: recipient.clone(),
-            amount,
+            sender: contract_address.clone(),
+            recipient: contract_address.clone(),
+            token: contract_address,
+            amount: 0,
             unlock_time,
             executed: false,
         };
 
+        Self::store_timelock(&env, &timelock);
         env.storage()
             .instance()
-            .set(&DataKey::TimeLock(timelock_id), &timelock);
-        
-        env.storage()
-            .instance()
-            .set(&DataKey::NextTimeLockId, &(timelock_id + 1));
+            .set(&DataKey::PendingConfig(timelock_id), &pending);
 
         env.events().publish(
-            (symbol_short!("sched_unw"), recipient, timelock_id),
+            (symbol_short!("sched_cfg"), timelock_id),
             unlock_time,
         );
 
         timelock_id
     }
 
-    /// Execute a time-locked unwrap operation
-    pub fn execute_unwrap(env: Env, timelock_id: u64) {
-        Self::require_not_paused(&env);
+    /// Apply a matured config-change timelock, replacing the signer set and
+    /// threshold.  The signers who scheduled it must still authorize.
+    pub fn execute_config_change(env: Env, timelock_id: u64) {
+        Self::extend_instance_ttl(&env);
+        let mut timelock = Self::require_timelock(&env, timelock_id);
 
-        let mut timelock: TimeLock = env
-            .storage()
-            .instance()
-            .get(&DataKey::TimeLock(timelock_id))
-            .expect("Timelock not found");
+        if timelock.operation_type != 3 {
+            panic!("Not a config change");
+        }
 
         if timelock.executed {
             panic!("Already executed");
         }
 
-        if env.ledger().timestamp() < timelock.unlock_time {
-            panic!("Timelock not expired");
-        }
+        Self::check_matured(&env, &timelock);
 
-        // Verify original signers still authorize this
         Self::verify_multisig(&env, &timelock.signers);
 
-        let balance_key = DataKey::Balance(timelock.recipient.clone());
-        let current_balance: i128 = env
+        let pending: MultisigConfig = env
             .storage()
             .instance()
-            .get(&balance_key)
-            .unwrap_or(0);
-
-        if current_balance < timelock.amount {
-            panic!("Insufficient balance");
-        }
+            .get(&DataKey::PendingConfig(timelock_id))
+            .expect("Pending config not found");
 
-        // Mark as executed BEFORE external calls (reentrancy prevention)
         timelock.executed = true;
+        Self::store_timelock(&env, &timelock);
         env.storage()
             .instance()
-            .set(&DataKey::TimeLock(timelock_id), &timelock);
+            .remove(&DataKey::PendingConfig(timelock_id));
+
+        let new_threshold = pending.threshold;
+        env.storage().instance().set(&DataKey::MultisigConfig, &pending);
+
+        env.events().publish(
+            (symbol_short!("cfg_chg"), timelock_id),
+            new_threshold,
+        );
+    }
+
+    /// Send `amount` of any token held by the contract to `to` (requires
+    /// multisig), recovering tokens transferred in directly instead of via
+    /// `wrap`.  For an allowed underlying token only the surplus over
+    /// `total_wrapped` can be moved, so the reserves backing wrapped
+    /// balances are never touched.
+    pub fn rescue(env: Env, signers: Vec<Address>, token: Address, to: Address, amount: i128) {
+        Self::extend_instance_ttl(&env);
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        Self::verify_multisig(&env, &signers);
 
-        let mut wrapped_asset: WrappedAsset = env
+        let token_client = token::Client::new(&env, &token);
+        let contract_address = env.current_contract_address();
+        let reserved = env
             .storage()
             .instance()
-            .get(&DataKey::WrappedAsset)
-            .unwrap();
+            .get::<_, WrappedAsset>(&DataKey::WrappedAsset(token.clone()))
+            .map(|asset| asset.total_wrapped)
+            .unwrap_or(0);
+        let surplus = token_client.balance(&contract_address) - reserved;
+        if amount > surplus {
+            panic!("Amount exceeds rescuable balance");
+        }
 
-        // Burn wrapped tokens
-        let new_balance = current_balance.checked_sub(timelock.amount).expect("Balance underflow");
-        env.storage()
-            .instance()
-            .set(&balance_key, &new_balance);
+        token_client.transfer(&contract_address, &to, &amount);
 
-        // Transfer underlying tokens
-        let token_client = token::Client::new(&env, &wrapped_asset.underlying_token);
-        let contract_address = env.current_contract_address();
+        env.events().publish(
+            (symbol_short!("rescue"), token, to),
+            amount,
+        );
+    }
 
-        token_client.transfer(&contract_address, &timelock.recipient, &timelock.amount);
+    /// Replace the contract code with the uploaded `new_wasm_hash`, keeping
+    /// all storage (requires multisig).  Returns the new `version()`.
+    pub fn upgrade(env: Env, signers: Vec<Address>, new_wasm_hash: BytesN<32>) -> u32 {
+        Self::extend_instance_ttl(&env);
+        Self::verify_multisig(&env, &signers);
 
-        wrapped_asset.total_wrapped = wrapped_asset.total_wrapped
-            .checked_sub(timelock.amount).expect("Total supply underflow");
-        env.storage()
-            .instance()
-            .set(&DataKey::WrappedAsset, &wrapped_asset);
+        let version = Self::version(env.clone()).checked_add(1).expect("Version overflow");
+        env.storage().instance().set(&DataKey::Version, &version);
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
 
         env.events().publish(
-            (symbol_short!("exec_unw"), timelock.recipient, timelock_id),
-            timelock.amount,
+            (symbol_short!("upgrade"),),
+            (new_wasm_hash, version),
         );
+        version
     }
 
-    /// Schedule a time-locked transfer
-    pub fn schedule_transfer(
+    /// Version of the running code: 1 as deployed, plus one per `upgrade`.
+    pub fn version(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Version).unwrap_or(1)
+    }
+
+    /// Move state laid out by the first deployed version into the current
+    /// layout (requires multisig).  The first call converts the signer
+    /// config and the single `WrappedAsset` (which becomes the first token).
+    /// Each call then converts the legacy timelocks among the
+    /// `timelock_limit` IDs from `timelock_start` (at most
+    /// `MAX_TIMELOCKS_PAGE`), and moves the balances of the addresses in
+    /// `holders` (taken from past `wrap` and `exec_tx` events), which sat
+    /// in instance storage under keys that cannot be enumerated on-chain.
+    /// Call it in batches until every timelock ID below `timelock_count`
+    /// and every holder is covered.  IDs and addresses with nothing left
+    /// to move are skipped, so repeating a batch is harmless.  Returns the
+    /// number of entries moved.
+    pub fn migrate(
         env: Env,
         signers: Vec<Address>,
-        from: Address,
-        to: Address,
-        amount: i128,
-        delay_seconds: u64,
-    ) -> u64 {
-        Self::require_not_paused(&env);
+        holders: Vec<Address>,
+        timelock_start: u64,
+        timelock_limit: u32,
+    ) -> u32 {
+        Self::extend_instance_ttl(&env);
+        let instance = env.storage().instance();
+
+        // The legacy config cannot be read as a `MultisigConfig`, so it is
+        // converted before the signers are checked against it.
+        let legacy = instance.has(&LegacyDataKey::WrappedAsset);
+        if legacy {
+            let config: LegacyMultisigConfig = instance
+                .get(&DataKey::MultisigConfig)
+                .expect("Not initialized");
+            let config = MultisigConfig {
+                signers: config.signers,
+                weights: Vec::new(&env),
+                threshold: config.threshold,
+            };
+            instance.set(&DataKey::MultisigConfig, &config);
+        }
 
-        if amount <= 0 {
-            panic!("Amount must be positive");
+        Self::verify_multisig(&env, &signers);
+
+        let mut moved: u32 = 0;
+        if legacy {
+            let asset: WrappedAsset = instance.get(&LegacyDataKey::WrappedAsset).unwrap();
+            instance.remove(&LegacyDataKey::WrappedAsset);
+            let token = asset.underlying_token.clone();
+            instance.set(&DataKey::WrappedAsset(token.clone()), &asset);
+            instance.set(&DataKey::Tokens, &Vec::from_array(&env, [token]));
         }
 
-        if from == to {
-            panic!("Self-transfer not allowed");
+        let token = Self::primary_token(&env);
+        let end = timelock_start
+            .saturating_add(timelock_limit.min(MAX_TIMELOCKS_PAGE) as u64)
+            .min(Self::timelock_count(env.clone()));
+        for id in timelock_start..end {
+            let key = DataKey::TimeLock(id);
+            if let Some(old) = instance.get::<_, LegacyTimeLock>(&key) {
+                let timelock = TimeLock {
+                    id,
+                    operation_type: old.operation_type,
+                    signers: old.signers,
+                    sender: old.sender,
+                    recipient: old.recipient,
+                    token: token.clone(),
+                    amount: old.amount,
+                    unlock_time: old.unlock_time,
+                    executed: old.executed,
+                };
+                instance.remove(&key);
+                Self::store_timelock(&env, &timelock);
+                moved += 1;
+            }
         }
 
-        Self::verify_multisig(&env, &signers);
+        for holder in holders.iter() {
+            let key = LegacyDataKey::Balance(holder.clone());
+            if let Some(balance) = instance.get::<_, i128>(&key) {
+                instance.remove(&key);
+                // Added to, not replacing, anything credited since the upgrade
+                Self::credit(&env, &token, &holder, balance);
+                moved += 1;
+            }
+        }
 
-        let min_duration: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::MinLockDuration)
-            .unwrap();
+        env.events().publish((symbol_short!("migrate"),), moved);
+        moved
+    }
 
-        if delay_seconds < min_duration {
-            panic!("Delay too short");
-        }
+    /// Allow an additional underlying token (requires multisig).
+    pub fn add_token(env: Env, signers: Vec<Address>, token: Address) {
+        Self::extend_instance_ttl(&env);
+        Self::verify_multisig(&env, &signers);
 
-        if delay_seconds > MAX_LOCK_DURATION {
-            panic!("Delay exceeds maximum lock duration");
+        if env.storage().instance().has(&DataKey::WrappedAsset(token.clone())) {
+            panic!("Token already supported");
         }
+        Self::register_token(&env, &token);
 
-        let from_key = DataKey::Balance(from.clone());
-        let from_balance: i128 = env.storage().instance().get(&from_key).unwrap_or(0);
+        env.events().publish(
+            (symbol_short!("add_token"), token),
+            0,
+        );
+    }
 
-        if from_balance < amount {
-            panic!("Insufficient balance");
+    /// Lower how much of `token` unwraps may pay out in any 24h window
+    /// (requires multisig), independent of who signs them.  Only tightening
+    /// applies at once: a higher cap, or 0 to remove it, must go through
+    /// `schedule_daily_unwrap_cap`, so the signers who approve unwraps
+    /// cannot lift the cap just before executing them.
+    pub fn set_daily_unwrap_cap(env: Env, signers: Vec<Address>, token: Address, cap: i128) {
+        Self::extend_instance_ttl(&env);
+        Self::verify_multisig(&env, &signers);
+        Self::load_asset(&env, &token);
+
+        if cap < 0 {
+            panic!("Invalid cap");
+        }
+        let current = Self::daily_unwrap_cap(&env, &token);
+        if cap == 0 || (current != 0 && cap > current) {
+            panic!("Loosening the cap requires a timelock");
         }
+        Self::write_daily_unwrap_cap(&env, &token, cap);
+    }
 
-        let timelock_id: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::NextTimeLockId)
-            .unwrap();
+    /// Schedule a time-locked change of `token`'s daily unwrap cap to `cap`
+    /// (0 removes it), for raising or removing it (requires multisig).  The
+    /// change applies with `execute_daily_unwrap_cap` after `delay_seconds`,
+    /// giving other signers time to cancel it.
+    pub fn schedule_daily_unwrap_cap(
+        env: Env,
+        signers: Vec<Address>,
+        token: Address,
+        cap: i128,
+        delay_seconds: u64,
+    ) -> u64 {
+        Self::extend_instance_ttl(&env);
+        if cap < 0 {
+            panic!("Invalid cap");
+        }
+
+        Self::verify_multisig(&env, &signers);
+        Self::check_delay(&env, delay_seconds);
+        Self::load_asset(&env, &token);
 
+        let timelock_id = Self::next_timelock_id(&env);
         let unlock_time = env.ledger().timestamp() + delay_seconds;
+        let contract_address = env.current_contract_address();
 
         let timelock = TimeLock {
-            operation_type: 2,  // transfer
-            signers: signers.clone(),
-            sender: from.clone(),
-            recipient: to.clone(),
-            amount,
+            id: timelock_id,
+            operation_type: 5,  // daily cap change
+            signers,
+            sender: contract_address.clone(),
+            recipient: contract_address,
+            token,
+            amount: cap,
             unlock_time,
             executed: false,
         };
-
-        env.storage()
-            .instance()
-            .set(&DataKey::TimeLock(timelock_id), &timelock);
-        
-        env.storage()
-            .instance()
-            .set(&DataKey::NextTimeLockId, &(timelock_id + 1));
+        Self::store_timelock(&env, &timelock);
 
         env.events().publish(
-            (symbol_short!("sched_tx"), from, to, timelock_id),
+            (symbol_short!("sched_cap"), timelock_id),
             unlock_time,
         );
 
         timelock_id
     }
 
-    /// Execute a time-locked transfer using the sender stored at schedule time.
-    pub fn execute_transfer(env: Env, timelock_id: u64) {
-        Self::require_not_paused(&env);
+    /// Apply a matured daily unwrap cap change.  The signers who scheduled
+    /// it must still authorize.
+    pub fn execute_daily_unwrap_cap(env: Env, timelock_id: u64) {
+        Self::extend_instance_ttl(&env);
+        let mut timelock = Self::require_timelock(&env, timelock_id);
 
-        let mut timelock: TimeLock = env
-            .storage()
-            .instance()
-            .get(&DataKey::TimeLock(timelock_id))
-            .expect("Timelock not found");
+        if timelock.operation_type != 5 {
+            panic!("Not a cap change");
+        }
 
         if timelock.executed {
             panic!("Already executed");
         }
 
-        if env.ledger().timestamp() < timelock.unlock_time {
-            panic!("Timelock not expired");
+        Self::check_matured(&env, &timelock);
+
+        Self::verify_multisig(&env, &timelock.signers);
+
+        timelock.executed = true;
+        Self::store_timelock(&env, &timelock);
+        Self::write_daily_unwrap_cap(&env, &timelock.token, timelock.amount);
+    }
+
+    /// Amount of `token` that can still be unwrapped in the current 24h
+    /// window, or `i128::MAX` when no cap is set.
+    pub fn daily_unwrap_remaining(env: Env, token: Address) -> i128 {
+        let cap = Self::daily_unwrap_cap(&env, &token);
+        if cap == 0 {
+            return i128::MAX;
+        }
+        (cap - Self::daily_unwrap_used(&env, &token)).max(0)
+    }
+
+    /// List the allowed underlying tokens.
+    pub fn tokens(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Tokens)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Wrap tokens (immediate execution, no timelock), crediting the first
+    /// signer.
+    pub fn wrap(env: Env, signers: Vec<Address>, token: Address, amount: i128) {
+        let payer = signers.get(0).expect("Insufficient signers");
+        Self::wrap_to(env, signers, token, payer, amount);
+    }
+
+    /// Wrap tokens paid by the first signer and credit the wrapped balance
+    /// to `recipient` (immediate execution, no timelock).
+    pub fn wrap_to(env: Env, signers: Vec<Address>, token: Address, recipient: Address, amount: i128) {
+        Self::extend_instance_ttl(&env);
+        Self::require_not_paused(&env);
+
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        Self::verify_multisig(&env, &signers);
+
+        let payer = signers.get(0).unwrap();
+        let mut wrapped_asset = Self::load_asset(&env, &token);
+
+        let token_client = token::Client::new(&env, &wrapped_asset.underlying_token);
+        let contract_address = env.current_contract_address();
+
+        // The payer already authorized as a signer in verify_multisig
+        token_client.transfer(&payer, &contract_address, &amount);
+
+        Self::credit(&env, &token, &recipient, amount);
+
+        wrapped_asset.total_wrapped = wrapped_asset.total_wrapped
+            .checked_add(amount).expect("Total supply overflow");
+        env.storage()
+            .instance()
+            .set(&DataKey::WrappedAsset(token.clone()), &wrapped_asset);
+
+        env.events().publish(
+            (symbol_short!("wrap"), token, payer, recipient),
+            amount,
+        );
+    }
+
+    /// Wrap for several recipients at once: the first signer pays the sum of
+    /// `amounts` in one transfer and each recipient is credited its amount.
+    /// Any non-positive amount aborts the whole batch.
+    pub fn batch_wrap(
+        env: Env,
+        signers: Vec<Address>,
+        token: Address,
+        recipients: Vec<Address>,
+        amounts: Vec<i128>,
+    ) {
+        Self::extend_instance_ttl(&env);
+        Self::require_not_paused(&env);
+
+        if recipients.len() != amounts.len() {
+            panic!("Recipients and amounts length mismatch");
+        }
+        if recipients.is_empty() {
+            panic!("No recipients");
+        }
+
+        let mut total: i128 = 0;
+        for amount in amounts.iter() {
+            if amount <= 0 {
+                panic!("Amount must be positive");
+            }
+            total = total.checked_add(amount).expect("Total supply overflow");
+        }
+
+        Self::verify_multisig(&env, &signers);
+
+        let payer = signers.get(0).unwrap();
+        let mut wrapped_asset = Self::load_asset(&env, &token);
+
+        let token_client = token::Client::new(&env, &wrapped_asset.underlying_token);
+        let contract_address = env.current_contract_address();
+        token_client.transfer(&payer, &contract_address, &total);
+
+        for (recipient, amount) in recipients.iter().zip(amounts.iter()) {
+            Self::credit(&env, &token, &recipient, amount);
+        }
+
+        wrapped_asset.total_wrapped = wrapped_asset.total_wrapped
+            .checked_add(total).expect("Total supply overflow");
+        env.storage()
+            .instance()
+            .set(&DataKey::WrappedAsset(token.clone()), &wrapped_asset);
+
+        env.events().publish(
+            (Symbol::new(&env, "batch_wrap"), token, payer),
+            total,
+        );
+    }
+
+    /// Schedule a time-locked unwrap that burns `from`'s wrapped balance and
+    /// sends the underlying to `recipient`. `from` must authorize, either as
+    /// one of the signers or with its own `require_auth()`.
+    pub fn schedule_unwrap(
+        env: Env,
+        signers: Vec<Address>,
+        token: Address,
+        from: Address,
+        recipient: Address,
+        amount: i128,
+        delay_seconds: u64,
+    ) -> u64 {
+        Self::extend_instance_ttl(&env);
+        Self::require_not_paused(&env);
+
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        Self::verify_multisig(&env, &signers);
+
+        Self::check_delay(&env, delay_seconds);
+
+        Self::load_asset(&env, &token);
+
+        // The holder whose balance is burned must consent; signers have
+        // already authorized via verify_multisig.
+        if !signers.contains(&from) {
+            from.require_auth();
+        }
+
+        Self::store_unwrap(&env, 1, signers, token, from, recipient, amount, delay_seconds)
+    }
+
+    /// Let `spender` schedule unwraps of up to `amount` of `owner`'s wrapped
+    /// `token` with `schedule_unwrap_from` until `expiration_ledger`,
+    /// replacing any earlier unwrap allowance.  `spender` need not be a
+    /// signer.
+    pub fn approve_unwrap(
+        env: Env,
+        owner: Address,
+        spender: Address,
+        token: Address,
+        amount: i128,
+        expiration_ledger: u32,
+    ) {
+        Self::extend_instance_ttl(&env);
+        owner.require_auth();
+        Self::load_asset(&env, &token);
+
+        let key = DataKey::UnwrapAllowance(token.clone(), owner.clone(), spender.clone());
+        Self::write_allowance(&env, &key, amount, expiration_ledger);
+
+        env.events().publish(
+            (symbol_short!("appr_unw"), owner, spender, token),
+            (amount, expiration_ledger),
+        );
+    }
+
+    /// Unwrap allowance left from `owner` to `spender` for `token`.
+    pub fn unwrap_allowance(env: Env, owner: Address, spender: Address, token: Address) -> i128 {
+        Self::read_allowance(&env, &DataKey::UnwrapAllowance(token, owner, spender)).amount
+    }
+
+    /// `schedule_unwrap` by a spender holding an `approve_unwrap` allowance
+    /// from `from`, instead of the multisig.  The allowance is reduced now
+    /// and restored if the timelock is cancelled; the spender authorizes the
+    /// execution.
+    pub fn schedule_unwrap_from(
+        env: Env,
+        spender: Address,
+        token: Address,
+        from: Address,
+        recipient: Address,
+        amount: i128,
+        delay_seconds: u64,
+    ) -> u64 {
+        Self::extend_instance_ttl(&env);
+        Self::require_not_paused(&env);
+
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        spender.require_auth();
+
+        Self::check_delay(&env, delay_seconds);
+
+        Self::load_asset(&env, &token);
+
+        let key = DataKey::UnwrapAllowance(token.clone(), from.clone(), spender.clone());
+        let allowance: AllowanceValue = env
+            .storage()
+            .temporary()
+            .get(&key)
+            .expect("Insufficient allowance");
+        if allowance.expiration_ledger < env.ledger().sequence() {
+            panic!("Allowance expired");
+        }
+        if allowance.amount < amount {
+            panic!("Insufficient allowance");
+        }
+        let remaining = AllowanceValue {
+            amount: allowance.amount - amount,
+            expiration_ledger: allowance.expiration_ledger,
+        };
+        env.storage().temporary().set(&key, &remaining);
+
+        let signers = Vec::from_array(&env, [spender]);
+        Self::store_unwrap(&env, 4, signers, token, from, recipient, amount, delay_seconds)
+    }
+
+    /// Execute a time-locked unwrap operation.  With `purge` the timelock
+    /// is deleted instead of kept as executed, as by `purge_timelock`.
+    pub fn execute_unwrap(env: Env, timelock_id: u64, purge: bool) {
+        Self::extend_instance_ttl(&env);
+        Self::require_not_paused(&env);
+
+        let mut timelock = Self::require_timelock(&env, timelock_id);
+
+        if timelock.operation_type != 1 && timelock.operation_type != 4 {
+            panic!("Not an unwrap");
+        }
+
+        if timelock.executed {
+            panic!("Already executed");
+        }
+
+        Self::check_matured(&env, &timelock);
+
+        // Verify original signers (or the delegated spender) still authorize this
+        if timelock.operation_type == 4 {
+            timelock.signers.get(0).unwrap().require_auth();
+        } else {
+            Self::verify_multisig(&env, &timelock.signers);
+        }
+
+        let balance_key = DataKey::Balance(timelock.token.clone(), timelock.sender.clone());
+        let current_balance: i128 = Self::read_balance(&env, &balance_key);
+
+        if current_balance < timelock.amount {
+            panic!("Insufficient balance");
+        }
+
+        Self::spend_daily_cap(&env, &timelock.token, timelock.amount);
+
+        // Mark as executed BEFORE external calls (reentrancy prevention)
+        Self::settle_timelock(&env, &mut timelock, purge);
+        Self::release_pending(&env, &timelock.recipient);
+
+        let mut wrapped_asset = Self::load_asset(&env, &timelock.token);
+
+        // Burn wrapped tokens
+        let new_balance = current_balance.checked_sub(timelock.amount).expect("Balance underflow");
+        Self::write_balance(&env, &balance_key, new_balance);
+
+        // Transfer underlying tokens, less the fee
+        let fee_bps: u32 = env.storage().instance().get(&DataKey::UnwrapFeeBps).unwrap_or(0);
+        let fee = timelock.amount
+            .checked_mul(fee_bps as i128).expect("Fee overflow")
+            / MAX_FEE_BPS as i128;
+        let payout = timelock.amount - fee;
+
+        let token_client = token::Client::new(&env, &wrapped_asset.underlying_token);
+        let contract_address = env.current_contract_address();
+
+        token_client.transfer(&contract_address, &timelock.recipient, &payout);
+        if fee > 0 {
+            let collector: Address = env
+                .storage()
+                .instance()
+                .get(&DataKey::FeeCollector)
+                .expect("Fee collector not set");
+            token_client.transfer(&contract_address, &collector, &fee);
+
+            let total_fees = Self::total_fees_collected(env.clone())
+                .checked_add(fee).expect("Fee total overflow");
+            env.storage().instance().set(&DataKey::TotalFeesCollected, &total_fees);
+        }
+
+        wrapped_asset.total_wrapped = wrapped_asset.total_wrapped
+            .checked_sub(timelock.amount).expect("Total supply underflow");
+        env.storage()
+            .instance()
+            .set(&DataKey::WrappedAsset(timelock.token.clone()), &wrapped_asset);
+
+        env.events().publish(
+            (symbol_short!("exec_unw"), timelock.recipient, timelock_id),
+            (timelock.amount, fee),
+        );
+    }
+
+    /// Schedule a time-locked transfer
+    pub fn schedule_transfer(
+        env: Env,
+        signers: Vec<Address>,
+        token: Address,
+        from: Address,
+        to: Address,
+        amount: i128,
+        delay_seconds: u64,
+    ) -> u64 {
+        Self::extend_instance_ttl(&env);
+        Self::require_not_paused(&env);
+
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        if from == to {
+            panic!("Self-transfer not allowed");
+        }
+
+        Self::verify_multisig(&env, &signers);
+
+        Self::check_delay(&env, delay_seconds);
+
+        Self::load_asset(&env, &token);
+
+        let from_key = DataKey::Balance(token.clone(), from.clone());
+        let from_balance: i128 = Self::read_balance(&env, &from_key);
+
+        if from_balance < amount {
+            panic!("Insufficient balance");
+        }
+
+        Self::add_pending(&env, &to);
+
+        let timelock_id = Self::next_timelock_id(&env);
+
+        let unlock_time = env.ledger().timestamp() + delay_seconds;
+
+        let timelock = TimeLock {
+            id: timelock_id,
+            operation_type: 2,  // transfer
+            signers: signers.clone(),
+            sender: from.clone(),
+            recipient: to.clone(),
+            token,
+            amount,
+            unlock_time,
+            executed: false,
+        };
+
+        Self::store_timelock(&env, &timelock);
+
+        env.events().publish(
+            (symbol_short!("sched_tx"), from, to, timelock_id),
+            unlock_time,
+        );
+
+        timelock_id
+    }
+
+    /// Execute a time-locked transfer using the sender stored at schedule
+    /// time.  `purge` works as in `execute_unwrap`.
+    pub fn execute_transfer(env: Env, timelock_id: u64, purge: bool) {
+        Self::extend_instance_ttl(&env);
+        Self::require_not_paused(&env);
+
+        let mut timelock = Self::require_timelock(&env, timelock_id);
+
+        if timelock.operation_type != 2 {
+            panic!("Not a transfer");
+        }
+
+        if timelock.executed {
+            panic!("Already executed");
         }
 
+        Self::check_matured(&env, &timelock);
+
         Self::verify_multisig(&env, &timelock.signers);
 
-        let from_key = DataKey::Balance(timelock.sender.clone());
-        let to_key = DataKey::Balance(timelock.recipient.clone());
+        let from_key = DataKey::Balance(timelock.token.clone(), timelock.sender.clone());
+
+        let from_balance: i128 = Self::read_balance(&env, &from_key);
+
+        if from_balance < timelock.amount {
+            panic!("Insufficient balance");
+        }
+
+        // Mark as executed BEFORE state changes (reentrancy prevention)
+        Self::settle_timelock(&env, &mut timelock, purge);
+        Self::release_pending(&env, &timelock.recipient);
+
+        let new_from = from_balance.checked_sub(timelock.amount).expect("Balance underflow");
+        Self::write_balance(&env, &from_key, new_from);
+        Self::credit(&env, &timelock.token, &timelock.recipient, timelock.amount);
+
+        env.events().publish(
+            (symbol_short!("exec_tx"), timelock.sender, timelock.recipient, timelock_id),
+            timelock.amount,
+        );
+    }
+
+    /// Execute every unwrap or transfer in `timelock_ids` that has matured,
+    /// in one call.  Ids that are unknown, already executed, still locked,
+    /// past the daily unwrap cap or of another operation type are skipped
+    /// rather than aborting the batch.  With `purge` each executed timelock
+    /// is deleted as in `execute_unwrap`.
+    /// Returns the ids actually executed.
+    pub fn execute_ready(env: Env, timelock_ids: Vec<u64>, purge: bool) -> Vec<u64> {
+        Self::require_not_paused(&env);
+
+        let now = env.ledger().timestamp();
+        let mut executed = Vec::new(&env);
+        for timelock_id in timelock_ids.iter() {
+            let Some(timelock) = Self::load_timelock(&env, timelock_id) else {
+                continue;
+            };
+            if timelock.executed || now < timelock.unlock_time || Self::window_passed(&env, &timelock, now) {
+                continue;
+            }
+            match timelock.operation_type {
+                1 | 4 => {
+                    if timelock.amount > Self::daily_unwrap_remaining(env.clone(), timelock.token.clone()) {
+                        continue;
+                    }
+                    Self::execute_unwrap(env.clone(), timelock_id, purge)
+                }
+                2 => Self::execute_transfer(env.clone(), timelock_id, purge),
+                _ => continue,
+            }
+            executed.push_back(timelock_id);
+        }
+        executed
+    }
+
+    /// Cancel a pending timelock (requires multisig).  With `purge` the
+    /// timelock is deleted instead of kept as cancelled, as by
+    /// `purge_timelock`.
+    pub fn cancel_timelock(env: Env, signers: Vec<Address>, timelock_id: u64, purge: bool) {
+        Self::extend_instance_ttl(&env);
+        Self::verify_multisig(&env, &signers);
+
+        let mut timelock = Self::require_timelock(&env, timelock_id);
+
+        if timelock.executed {
+            panic!("Already executed");
+        }
+
+        // Mark as executed to prevent future execution
+        Self::settle_timelock(&env, &mut timelock, purge);
+        env.storage()
+            .instance()
+            .remove(&DataKey::PendingConfig(timelock_id));
+        if matches!(timelock.operation_type, 1 | 2 | 4) {
+            Self::release_pending(&env, &timelock.recipient);
+        }
+
+        // Hand a delegated unwrap's amount back to the spender's allowance,
+        // unless the allowance has since lapsed out of storage.
+        if timelock.operation_type == 4 {
+            let spender = timelock.signers.get(0).unwrap();
+            let key = DataKey::UnwrapAllowance(timelock.token.clone(), timelock.sender.clone(), spender);
+            if let Some(mut allowance) = env.storage().temporary().get::<_, AllowanceValue>(&key) {
+                allowance.amount = allowance.amount.checked_add(timelock.amount).expect("Allowance overflow");
+                env.storage().temporary().set(&key, &allowance);
+            }
+        }
+
+        env.events().publish(
+            (symbol_short!("cancel"), timelock_id),
+            0,
+        );
+    }
+
+    /// Push a pending timelock's unlock time out to `new_delay_seconds` from
+    /// now (requires multisig), keeping its ID.  The new delay must satisfy
+    /// the same limits as scheduling, and the unlock time may not move
+    /// earlier.
+    pub fn reschedule_timelock(env: Env, signers: Vec<Address>, timelock_id: u64, new_delay_seconds: u64) {
+        Self::extend_instance_ttl(&env);
+        Self::verify_multisig(&env, &signers);
+        Self::check_delay(&env, new_delay_seconds);
+
+        let mut timelock = Self::require_timelock(&env, timelock_id);
+
+        if timelock.executed {
+            panic!("Already executed");
+        }
+
+        let old_unlock_time = timelock.unlock_time;
+        let new_unlock_time = env.ledger().timestamp() + new_delay_seconds;
+        if new_unlock_time < old_unlock_time {
+            panic!("Unlock time cannot move earlier");
+        }
+
+        timelock.unlock_time = new_unlock_time;
+        Self::store_timelock(&env, &timelock);
+
+        env.events().publish(
+            (symbol_short!("resched"), timelock_id),
+            (old_unlock_time, new_unlock_time),
+        );
+    }
+
+    /// Delete an executed or cancelled timelock to reclaim its storage
+    /// (requires multisig, as it erases the on-chain record).  The ID is
+    /// kept in the `ExecutedTimeLocks` bitmap, so it still reads as already
+    /// executed rather than unknown.
+    pub fn purge_timelock(env: Env, signers: Vec<Address>, timelock_id: u64) {
+        Self::extend_instance_ttl(&env);
+        Self::verify_multisig(&env, &signers);
+
+        let timelock = Self::require_timelock(&env, timelock_id);
+
+        if !timelock.executed {
+            panic!("Timelock still pending");
+        }
+
+        Self::mark_purged(&env, timelock_id);
+        env.storage().persistent().remove(&DataKey::TimeLock(timelock_id));
+
+        env.events().publish(
+            (symbol_short!("purge"), timelock_id),
+            0,
+        );
+    }
+
+    /// Get timelock details
+    pub fn get_timelock(env: Env, timelock_id: u64) -> TimeLock {
+        Self::load_timelock(&env, timelock_id).expect("Timelock not found")
+    }
+
+    /// Pending (not executed or cancelled) timelocks among the `limit` IDs
+    /// from `start_id` (at most `MAX_TIMELOCKS_PAGE`).  The cost is bounded
+    /// by the IDs examined, not the timelocks found, so a page can be short
+    /// or empty while later IDs are still pending: keep calling from
+    /// `next_id` until it reaches `timelock_count`.
+    pub fn list_timelocks(env: Env, start_id: u64, limit: u32) -> TimelockPage {
+        let end = start_id
+            .saturating_add(limit.min(MAX_TIMELOCKS_PAGE) as u64)
+            .min(Self::timelock_count(env.clone()))
+            .max(start_id);
+        let mut timelocks = Vec::new(&env);
+        for id in start_id..end {
+            if let Some(timelock) = Self::load_timelock(&env, id) {
+                if !timelock.executed {
+                    timelocks.push_back(timelock);
+                }
+            }
+        }
+        TimelockPage { timelocks, next_id: end }
+    }
+
+    /// Number of timelocks ever scheduled (the next ID to be assigned).
+    pub fn timelock_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::NextTimeLockId)
+            .unwrap_or(0)
+    }
+
+    /// Number of pending unwrap and transfer timelocks paying out to
+    /// `recipient`.
+    pub fn pending_count(env: Env, recipient: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PendingCount(recipient))
+            .unwrap_or(0)
+    }
+
+    /// Sum of `token` amounts committed to pending unwraps and transfers.
+    /// This reads every timelock ID up to `timelock_count`, so its cost
+    /// grows with the contract's history: call it from simulation (a
+    /// read-only RPC query), not from inside a transaction.
+    pub fn pending_locked_total(env: Env, token: Address) -> i128 {
+        let mut total: i128 = 0;
+        for id in 0..Self::timelock_count(env.clone()) {
+            if let Some(timelock) = Self::load_timelock(&env, id) {
+                if !timelock.executed && matches!(timelock.operation_type, 1 | 2 | 4) && timelock.token == token {
+                    total = total.checked_add(timelock.amount).expect("Total overflow");
+                }
+            }
+        }
+        total
+    }
+
+    /// Seconds until `timelock_id` unlocks, by the ledger clock.  0 once it
+    /// has unlocked, been executed or cancelled, or if it does not exist.
+    pub fn timelock_remaining(env: Env, timelock_id: u64) -> u64 {
+        Self::load_timelock(&env, timelock_id)
+            .filter(|timelock| !timelock.executed)
+            .map(|timelock| timelock.unlock_time.saturating_sub(env.ledger().timestamp()))
+            .unwrap_or(0)
+    }
+
+    /// Return the next timelock ID and the allowed delay range.
+    pub fn get_scheduling_info(env: Env) -> SchedulingInfo {
+        SchedulingInfo {
+            next_timelock_id: env
+                .storage()
+                .instance()
+                .get(&DataKey::NextTimeLockId)
+                .unwrap_or(0),
+            min_lock_duration: env
+                .storage()
+                .instance()
+                .get(&DataKey::MinLockDuration)
+                .unwrap_or(0),
+            max_lock_duration: Self::max_lock_duration(&env),
+        }
+    }
+
+    /// The current signer set and threshold.  Panics before initialization.
+    pub fn get_config(env: Env) -> MultisigConfig {
+        Self::load_config(&env)
+    }
+
+    /// Minimum delay (seconds) accepted by the `schedule_*` operations.
+    pub fn get_min_lock_duration(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MinLockDuration)
+            .unwrap_or(0)
+    }
+
+    /// Whether `address` is one of the authorized signers.  `false` before
+    /// initialization.
+    pub fn is_signer(env: Env, address: Address) -> bool {
+        env.storage()
+            .instance()
+            .get::<_, MultisigConfig>(&DataKey::MultisigConfig)
+            .map(|config| config.signers.contains(&address))
+            .unwrap_or(false)
+    }
+
+    /// Number of signers required to authorize an operation.  0 before
+    /// initialization.
+    pub fn threshold(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get::<_, MultisigConfig>(&DataKey::MultisigConfig)
+            .map(|config| config.threshold)
+            .unwrap_or(0)
+    }
+
+    /// Query the wrapped balance of `token` for a given address.
+    pub fn balance_of(env: Env, token: Address, address: Address) -> i128 {
+        let balance_key = DataKey::Balance(token, address);
+        Self::read_balance(&env, &balance_key)
+    }
+
+    /// Page through the holders of `token`: the non-zero `(address, balance)`
+    /// pairs among tracked holders `start..start + limit` (at most
+    /// `MAX_BALANCES_PAGE`).  Holders whose balance has dropped to zero are
+    /// left out, so a page can be shorter than `limit`; stop once `start`
+    /// reaches `holder_count`.
+    pub fn balances(env: Env, token: Address, start: u32, limit: u32) -> Vec<(Address, i128)> {
+        let end = start
+            .saturating_add(limit.min(MAX_BALANCES_PAGE))
+            .min(Self::holder_count(env.clone(), token.clone()));
+        let mut page = Vec::new(&env);
+        for i in start..end {
+            let holder = Self::holder_at(&env, &token, i);
+            let balance = Self::read_balance(&env, &DataKey::Balance(token.clone(), holder.clone()));
+            if balance != 0 {
+                page.push_back((holder, balance));
+            }
+        }
+        page
+    }
+
+    /// Number of tracked holders of `token`, for paging `balances`.
+    pub fn holder_count(env: Env, token: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::HolderCount(token))
+            .unwrap_or(0)
+    }
+
+    /// Query the total wrapped supply of `token` across all addresses.
+    pub fn total_supply(env: Env, token: Address) -> i128 {
+        Self::load_asset(&env, &token).total_wrapped
+    }
+
+    /// Lifetime sum of fees taken on unwraps.  Stays 0 until an unwrap fee
+    /// is configured.
+    pub fn total_fees_collected(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalFeesCollected)
+            .unwrap_or(0)
+    }
+
+    // ---------------------------------------------------------------------
+    // SEP-41 token interface, over the wrapped form of the first token
+    // ---------------------------------------------------------------------
+
+    /// Amount `spender` may still move out of `from` with `transfer_from`.
+    pub fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        Self::read_allowance(&env, &DataKey::Allowance(from, spender)).amount
+    }
+
+    /// Let `spender` move up to `amount` of `from`'s wrapped balance until
+    /// `expiration_ledger`, replacing any earlier allowance.
+    pub fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        Self::extend_instance_ttl(&env);
+        from.require_auth();
+
+        let key = DataKey::Allowance(from.clone(), spender.clone());
+        Self::write_allowance(&env, &key, amount, expiration_ledger);
+
+        env.events().publish(
+            (symbol_short!("approve"), from, spender),
+            (amount, expiration_ledger),
+        );
+    }
+
+    /// SEP-41 balance: the wrapped balance of the first token.
+    pub fn balance(env: Env, id: Address) -> i128 {
+        let token = Self::primary_token(&env);
+        Self::read_balance(&env, &DataKey::Balance(token, id))
+    }
+
+    /// Move wrapped balance from `from` to `to` immediately.  Unlike
+    /// `schedule_transfer` there is no timelock; `from` alone authorizes.
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        Self::extend_instance_ttl(&env);
+        from.require_auth();
+        Self::require_not_paused(&env);
+        Self::move_balance(&env, &from, &to, amount);
+    }
+
+    /// `transfer` on `from`'s behalf, spending `spender`'s allowance.
+    pub fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        Self::extend_instance_ttl(&env);
+        spender.require_auth();
+        Self::require_not_paused(&env);
+        Self::spend_allowance(&env, &from, &spender, amount);
+        Self::move_balance(&env, &from, &to, amount);
+    }
+
+    pub fn decimals(env: Env) -> u32 {
+        Self::load_metadata(&env).decimal
+    }
+
+    pub fn name(env: Env) -> String {
+        Self::load_metadata(&env).name
+    }
+
+    pub fn symbol(env: Env) -> String {
+        Self::load_metadata(&env).symbol
+    }
+
+    /// The token whose wrapped form the SEP-41 interface exposes: the one
+    /// given to `initialize`.
+    fn primary_token(env: &Env) -> Address {
+        let tokens: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Tokens)
+            .expect("Not initialized");
+        tokens.get(0).unwrap()
+    }
+
+    fn load_metadata(env: &Env) -> TokenMetadata {
+        env.storage()
+            .instance()
+            .get(&DataKey::Metadata)
+            .expect("Not initialized")
+    }
+
+    /// The allowance stored under `key` (`Allowance` or `UnwrapAllowance`);
+    /// an expired one reads as 0.
+    fn read_allowance(env: &Env, key: &DataKey) -> AllowanceValue {
+        match env.storage().temporary().get::<_, AllowanceValue>(key) {
+            Some(allowance) if allowance.expiration_ledger >= env.ledger().sequence() => allowance,
+            _ => AllowanceValue { amount: 0, expiration_ledger: 0 },
+        }
+    }
+
+    /// Store an allowance in temporary storage, living until
+    /// `expiration_ledger`.
+    fn write_allowance(env: &Env, key: &DataKey, amount: i128, expiration_ledger: u32) {
+        if amount < 0 {
+            panic!("Amount must not be negative");
+        }
+
+        let ledger = env.ledger().sequence();
+        if amount > 0 && expiration_ledger < ledger {
+            panic!("Expiration ledger in the past");
+        }
+
+        let allowance = AllowanceValue { amount, expiration_ledger };
+        env.storage().temporary().set(key, &allowance);
+        if amount > 0 {
+            let live_for = expiration_ledger - ledger;
+            env.storage().temporary().extend_ttl(key, live_for, live_for);
+        }
+    }
+
+    fn spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
+        let key = DataKey::Allowance(from.clone(), spender.clone());
+        let allowance = Self::read_allowance(env, &key);
+        if allowance.amount < amount {
+            panic!("Insufficient allowance");
+        }
+        if amount > 0 {
+            let remaining = AllowanceValue {
+                amount: allowance.amount - amount,
+                expiration_ledger: allowance.expiration_ledger,
+            };
+            env.storage().temporary().set(&key, &remaining);
+        }
+    }
+
+    /// Move `amount` of the first token's wrapped balance from `from` to
+    /// `to` and publish the SEP-41 `transfer` event.  Zero amounts are
+    /// refused: they would move nothing yet still list `to` as a holder.
+    fn move_balance(env: &Env, from: &Address, to: &Address, amount: i128) {
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        let token = Self::primary_token(env);
+        let from_key = DataKey::Balance(token.clone(), from.clone());
+        let from_balance = Self::read_balance(env, &from_key);
+        if from_balance < amount {
+            panic!("Insufficient balance");
+        }
+        Self::write_balance(env, &from_key, from_balance - amount);
+        Self::credit(env, &token, to, amount);
+
+        env.events().publish(
+            (symbol_short!("transfer"), from.clone(), to.clone()),
+            amount,
+        );
+    }
+
+    /// Record `token` as an allowed underlying token with nothing wrapped.
+    fn register_token(env: &Env, token: &Address) {
+        let wrapped_asset = WrappedAsset {
+            underlying_token: token.clone(),
+            total_wrapped: 0,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::WrappedAsset(token.clone()), &wrapped_asset);
+
+        let mut tokens: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Tokens)
+            .unwrap_or(Vec::new(env));
+        tokens.push_back(token.clone());
+        env.storage().instance().set(&DataKey::Tokens, &tokens);
+    }
+
+    /// A wrapped balance from persistent storage (0 if absent), extending
+    /// its TTL when present.
+    fn read_balance(env: &Env, key: &DataKey) -> i128 {
+        let storage = env.storage().persistent();
+        let balance = storage.get(key);
+        if balance.is_some() {
+            storage.extend_ttl(key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+        }
+        balance.unwrap_or(0)
+    }
+
+    fn write_balance(env: &Env, key: &DataKey, amount: i128) {
+        let storage = env.storage().persistent();
+        storage.set(key, &amount);
+        storage.extend_ttl(key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+    }
+
+    /// A timelock from persistent storage, extending its TTL when present.
+    fn load_timelock(env: &Env, timelock_id: u64) -> Option<TimeLock> {
+        let key = DataKey::TimeLock(timelock_id);
+        let storage = env.storage().persistent();
+        let timelock = storage.get(&key);
+        if timelock.is_some() {
+            storage.extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+        }
+        timelock
+    }
+
+    /// The timelock an `execute_*`, `cancel_timelock` or `purge_timelock`
+    /// call acts on.  A purged ID panics as already executed.
+    fn require_timelock(env: &Env, timelock_id: u64) -> TimeLock {
+        match Self::load_timelock(env, timelock_id) {
+            Some(timelock) => timelock,
+            None if Self::is_purged(env, timelock_id) => panic!("Already executed"),
+            None => panic!("Timelock not found"),
+        }
+    }
+
+    /// Mark `timelock` as executed or, with `purge`, delete it and record
+    /// its ID in the `ExecutedTimeLocks` bitmap instead.
+    fn settle_timelock(env: &Env, timelock: &mut TimeLock, purge: bool) {
+        timelock.executed = true;
+        if purge {
+            Self::mark_purged(env, timelock.id);
+            env.storage().persistent().remove(&DataKey::TimeLock(timelock.id));
+        } else {
+            Self::store_timelock(env, timelock);
+        }
+    }
+
+    fn mark_purged(env: &Env, timelock_id: u64) {
+        let key = DataKey::ExecutedTimeLocks(timelock_id / 128);
+        let storage = env.storage().persistent();
+        let bits: u128 = storage.get(&key).unwrap_or(0);
+        storage.set(&key, &(bits | 1 << (timelock_id % 128)));
+        storage.extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+    }
+
+    fn is_purged(env: &Env, timelock_id: u64) -> bool {
+        let key = DataKey::ExecutedTimeLocks(timelock_id / 128);
+        let bits: u128 = env.storage().persistent().get(&key).unwrap_or(0);
+        bits & 1 << (timelock_id % 128) != 0
+    }
+
+    /// Save `timelock` under its own ID in persistent storage.
+    fn store_timelock(env: &Env, timelock: &TimeLock) {
+        let key = DataKey::TimeLock(timelock.id);
+        let storage = env.storage().persistent();
+        storage.set(&key, timelock);
+        storage.extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+    }
+
+    /// Add `amount` to `holder`'s wrapped balance of `token`, tracking it as
+    /// a holder if this is its first balance.  Totals are the caller's job.
+    fn credit(env: &Env, token: &Address, holder: &Address, amount: i128) {
+        let balance_key = DataKey::Balance(token.clone(), holder.clone());
+        let first_balance = !env.storage().persistent().has(&balance_key);
+        let current_balance: i128 = Self::read_balance(env, &balance_key);
+
+        let new_balance = current_balance.checked_add(amount).expect("Balance overflow");
+        Self::write_balance(env, &balance_key, new_balance);
+        if first_balance {
+            Self::track_holder(env, token, holder);
+        }
+    }
+
+    /// The holder of `token` at `index`, extending its entry's TTL.
+    fn holder_at(env: &Env, token: &Address, index: u32) -> Address {
+        let key = DataKey::Holder(token.clone(), index);
+        let storage = env.storage().persistent();
+        let holder = storage.get(&key).expect("Holder not found");
+        storage.extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+        holder
+    }
+
+    /// Append `holder` to the holders of `token`.  Called when its balance
+    /// entry is first created; a drained balance is kept at zero rather
+    /// than removed, so a returning holder is not listed twice.
+    fn track_holder(env: &Env, token: &Address, holder: &Address) {
+        let index = Self::holder_count(env.clone(), token.clone());
+        let storage = env.storage().persistent();
+
+        let holder_key = DataKey::Holder(token.clone(), index);
+        storage.set(&holder_key, holder);
+        storage.extend_ttl(&holder_key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+
+        let count_key = DataKey::HolderCount(token.clone());
+        storage.set(&count_key, &(index + 1));
+        storage.extend_ttl(&count_key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+    }
+
+    fn load_config(env: &Env) -> MultisigConfig {
+        env.storage()
+            .instance()
+            .get(&DataKey::MultisigConfig)
+            .unwrap()
+    }
+
+    /// Load the wrapped totals for `token`. Panics if the token is not allowed.
+    fn load_asset(env: &Env, token: &Address) -> WrappedAsset {
+        env.storage()
+            .instance()
+            .get(&DataKey::WrappedAsset(token.clone()))
+            .expect("Token not supported")
+    }
+
+    /// Check `from`'s balance and record an unwrap timelock (type 1 or 4)
+    /// once the caller has authorized it.
+    fn store_unwrap(
+        env: &Env,
+        operation_type: u32,
+        signers: Vec<Address>,
+        token: Address,
+        from: Address,
+        recipient: Address,
+        amount: i128,
+        delay_seconds: u64,
+    ) -> u64 {
+        let balance_key = DataKey::Balance(token.clone(), from.clone());
+        let current_balance: i128 = Self::read_balance(env, &balance_key);
+
+        if current_balance < amount {
+            panic!("Insufficient wrapped balance");
+        }
+
+        Self::add_pending(env, &recipient);
+
+        let timelock_id = Self::next_timelock_id(env);
+
+        let unlock_time = env.ledger().timestamp() + delay_seconds;
+
+        let timelock = TimeLock {
+            id: timelock_id,
+            operation_type,
+            signers,
+            sender: from.clone(),
+            recipient: recipient.clone(),
+            token,
+            amount,
+            unlock_time,
+            executed: false,
+        };
+
+        Self::store_timelock(env, &timelock);
+
+        env.events().publish(
+            (symbol_short!("sched_unw"), from, recipient, timelock_id),
+            unlock_time,
+        );
+
+        timelock_id
+    }
+
+    /// Claim the next timelock ID: returns the stored counter and advances
+    /// it in the same step, so every schedule path (including several in one
+    /// invocation) gets a distinct ID.
+    fn next_timelock_id(env: &Env) -> u64 {
+        let id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextTimeLockId)
+            .unwrap_or(0);
+        let next = id.checked_add(1).expect("Timelock ID overflow");
+        env.storage().instance().set(&DataKey::NextTimeLockId, &next);
+        id
+    }
+
+    /// Count a new pending timelock against `recipient`, refusing it once
+    /// the recipient holds `MaxPendingPerRecipient` of them.
+    fn add_pending(env: &Env, recipient: &Address) {
+        let count = Self::pending_count(env.clone(), recipient.clone());
+        let max: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MaxPendingPerRecipient)
+            .unwrap_or(0);
+        if max != 0 && count >= max {
+            panic!("Too many pending timelocks");
+        }
+
+        let key = DataKey::PendingCount(recipient.clone());
+        let storage = env.storage().persistent();
+        storage.set(&key, &(count + 1));
+        storage.extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+    }
+
+    /// Drop a settled timelock from `recipient`'s pending count.  Timelocks
+    /// scheduled before counting began were never added, so the count stops
+    /// at zero.
+    fn release_pending(env: &Env, recipient: &Address) {
+        let key = DataKey::PendingCount(recipient.clone());
+        let count = Self::pending_count(env.clone(), recipient.clone());
+        if count <= 1 {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &(count - 1));
+        }
+    }
+
+    /// Panics unless `delay_seconds` lies between the configured minimum
+    /// and maximum lock durations, and within `MAX_LOCK_DURATION`.
+    fn check_delay(env: &Env, delay_seconds: u64) {
+        let min_duration: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MinLockDuration)
+            .unwrap();
+
+        if delay_seconds < min_duration {
+            panic!("Delay too short");
+        }
+
+        let configured_max: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MaxLockDuration)
+            .unwrap_or(0);
+        if configured_max != 0 && delay_seconds > configured_max {
+            panic!("Delay too long");
+        }
+
+        if delay_seconds > MAX_LOCK_DURATION {
+            panic!("Delay exceeds maximum lock duration");
+        }
+    }
+
+    /// Panics unless `timelock` has unlocked and, with an execution window
+    /// configured, is still inside it.  A stale timelock can only be
+    /// cancelled and rescheduled.
+    fn check_matured(env: &Env, timelock: &TimeLock) {
+        let now = env.ledger().timestamp();
+        if now < timelock.unlock_time {
+            panic!("Timelock not expired");
+        }
+        if Self::window_passed(env, timelock, now) {
+            panic!("Execution window expired");
+        }
+    }
+
+    fn window_passed(env: &Env, timelock: &TimeLock, now: u64) -> bool {
+        let window: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ExecutionWindow)
+            .unwrap_or(0);
+        window != 0 && now > timelock.unlock_time.saturating_add(window)
+    }
+
+    /// The longest delay `check_delay` accepts: the configured maximum, or
+    /// `MAX_LOCK_DURATION` when none was set.
+    fn max_lock_duration(env: &Env) -> u64 {
+        match env.storage().instance().get(&DataKey::MaxLockDuration) {
+            Some(max) if max != 0 => max,
+            _ => MAX_LOCK_DURATION,
+        }
+    }
+
+    /// `token`'s daily unwrap cap, 0 when none is set.
+    fn daily_unwrap_cap(env: &Env, token: &Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::DailyUnwrapCap(token.clone()))
+            .unwrap_or(0)
+    }
+
+    fn write_daily_unwrap_cap(env: &Env, token: &Address, cap: i128) {
+        env.storage()
+            .instance()
+            .set(&DataKey::DailyUnwrapCap(token.clone()), &cap);
+
+        env.events().publish(
+            (symbol_short!("daily_cap"), token.clone()),
+            cap,
+        );
+    }
+
+    /// Whether `token` has no open daily window: none was opened yet, or
+    /// the last one has run its 24h.
+    fn daily_window_expired(env: &Env, token: &Address) -> bool {
+        match env
+            .storage()
+            .instance()
+            .get::<_, u64>(&DataKey::DailyWindowStart(token.clone()))
+        {
+            Some(start) => env.ledger().timestamp() >= start.saturating_add(DAILY_WINDOW),
+            None => true,
+        }
+    }
+
+    /// Amount of `token` unwrapped in the current daily window, 0 if none
+    /// is open.
+    fn daily_unwrap_used(env: &Env, token: &Address) -> i128 {
+        if Self::daily_window_expired(env, token) {
+            return 0;
+        }
+        env.storage()
+            .instance()
+            .get(&DataKey::DailyUnwrapUsed(token.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Count `amount` of `token` against its daily unwrap cap, opening a new
+    /// window if the last one has expired.  Panics if the cap would be
+    /// exceeded; does nothing when no cap is set.
+    fn spend_daily_cap(env: &Env, token: &Address, amount: i128) {
+        let cap = Self::daily_unwrap_cap(env, token);
+        if cap == 0 {
+            return;
+        }
+
+        let used = Self::daily_unwrap_used(env, token)
+            .checked_add(amount).expect("Daily total overflow");
+        if used > cap {
+            panic!("Daily unwrap cap exceeded");
+        }
+        if Self::daily_window_expired(env, token) {
+            env.storage()
+                .instance()
+                .set(&DataKey::DailyWindowStart(token.clone()), &env.ledger().timestamp());
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::DailyUnwrapUsed(token.clone()), &used);
+    }
+
+    /// Keep instance storage, and with it the contract code, from expiring.
+    fn extend_instance_ttl(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO);
+    }
+
+    /// Panics if the contract is currently paused.
+    fn require_not_paused(env: &Env) {
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false);
+        if paused {
+            panic!("Contract is paused");
+        }
+    }
+
+    /// Check a proposed signer set, weights (empty for weight 1 each) and
+    /// threshold, as given to `initialize` or `schedule_config_change`.  Panics unless the signers are non-empty
+    /// and distinct, the weights match them and are non-zero, and the
+    /// threshold is reachable.
+    fn signer_config(signers: Vec<Address>, weights: Vec<u32>, threshold: u32) -> MultisigConfig {
+        if signers.is_empty() {
+            panic!("Signers must not be empty");
+        }
+        Self::require_distinct(&signers);
+
+        if !weights.is_empty() && weights.len() != signers.len() {
+            panic!("Weights length mismatch");
+        }
+        if weights.contains(0) {
+            panic!("Invalid weight");
+        }
+
+        let config = MultisigConfig { signers, weights, threshold };
+        if threshold == 0 || threshold > Self::total_weight(&config) {
+            panic!("Invalid threshold");
+        }
+        config
+    }
+
+    /// Panics if a proposed signer set lists any address twice, which would
+    /// let one key count towards the threshold more than once.
+    fn require_distinct(signers: &Vec<Address>) {
+        for i in 0..signers.len() {
+            for j in (i + 1)..signers.len() {
+                if signers.get(i).unwrap() == signers.get(j).unwrap() {
+                    panic!("Duplicate signer");
+                }
+            }
+        }
+    }
+
+    /// Weight of the signer at `index` in `config.signers`.
+    fn signer_weight(config: &MultisigConfig, index: u32) -> u32 {
+        if config.weights.is_empty() {
+            1
+        } else {
+            config.weights.get(index).unwrap()
+        }
+    }
+
+    /// Combined weight of every signer in `config`.
+    fn total_weight(config: &MultisigConfig) -> u32 {
+        if config.weights.is_empty() {
+            config.signers.len()
+        } else {
+            config.weights.iter().fold(0u32, |sum, w| sum.saturating_add(w))
+        }
+    }
+
+    /// Verify that the provided signers are in the authorized signer list,
+    /// that each has called `require_auth()`, and that their combined weight
+    /// reaches `threshold`.  Panics if any provided signer is not authorized,
+    /// the threshold is not met, or duplicate signers are provided.
+    fn verify_multisig(env: &Env, provided_signers: &Vec<Address>) {
+        let config: MultisigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::MultisigConfig)
+            .unwrap();
+
+        if config.weights.is_empty() && provided_signers.len() < config.threshold {
+            panic!("Insufficient signers");
+        }
+
+        // Check for duplicate signers
+        for i in 0..provided_signers.len() {
+            for j in (i + 1)..provided_signers.len() {
+                if provided_signers.get(i).unwrap() == provided_signers.get(j).unwrap() {
+                    panic!("Duplicate signer detected");
+                }
+            }
+        }
+
+        let mut weight: u32 = 0;
+        for provided_signer in provided_signers.iter() {
+            let index = match config.signers.first_index_of(&provided_signer) {
+                Some(index) => index,
+                None => panic!("Signer not authorized"),
+            };
+            provided_signer.require_auth();
+            weight = weight.saturating_add(Self::signer_weight(&config, index));
+        }
+
+        if weight < config.threshold {
+            panic!("Insufficient signers");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::{testutils::storage::Instance as _, testutils::storage::Persistent as _, testutils::Address as _, testutils::Ledger, vec, Env};
+
+    fn setup_env() -> (Env, Address, Address, Address, Address) {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(MultisigAssetWrapper, ());
+        let token_admin = Address::generate(&env);
+        let signer1 = Address::generate(&env);
+        let signer2 = Address::generate(&env);
+        (env, contract_id, token_admin, signer1, signer2)
+    }
+
+    fn create_token(env: &Env, admin: &Address) -> Address {
+        let token_contract_id = env.register_stellar_asset_contract_v2(admin.clone());
+        token_contract_id.address()
+    }
+
+    fn metadata(env: &Env) -> TokenMetadata {
+        TokenMetadata {
+            decimal: 7,
+            name: String::from_str(env, "Wrapped Test Token"),
+            symbol: String::from_str(env, "wTEST"),
+        }
+    }
+
+    fn limits(min_lock_duration: u64) -> TimelockLimits {
+        TimelockLimits {
+            min_lock_duration,
+            max_lock_duration: 0,
+            execution_window: 0,
+            max_pending_per_recipient: 0,
+        }
+    }
+
+    fn init_contract(
+        env: &Env,
+        contract_id: &Address,
+        token_addr: &Address,
+        signer1: &Address,
+        signer2: &Address,
+    ) {
+        let client = MultisigAssetWrapperClient::new(env, contract_id);
+        let signers = vec![env, signer1.clone(), signer2.clone()];
+        client.initialize(&signers, &vec![env], &1, token_addr, &limits(10), &0, signer1, &metadata(env));
+    }
+
+    #[test]
+    fn test_initialize_sets_state() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        assert_eq!(client.balance_of(&token_addr, &s1), 0);
+        assert_eq!(client.balance_of(&token_addr, &s2), 0);
+        assert_eq!(client.total_supply(&token_addr), 0);
+    }
+
+    #[test]
+    fn test_get_config_reads_back_initialize() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let config = client.get_config();
+        assert_eq!(config.signers, vec![&env, s1.clone(), s2.clone()]);
+        assert_eq!(config.threshold, 1);
+        assert_eq!(client.get_min_lock_duration(), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Already initialized")]
+    fn test_double_initialize_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        // Second init should panic
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid threshold")]
+    fn test_zero_threshold_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone(), s2.clone()];
+        client.initialize(&signers, &vec![&env], &0, &token_addr, &limits(10), &0, &s1, &metadata(&env));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid threshold")]
+    fn test_threshold_exceeding_signers_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone(), s2.clone()];
+        client.initialize(&signers, &vec![&env], &3, &token_addr, &limits(10), &0, &s1, &metadata(&env));
+    }
+
+    #[test]
+    fn test_total_supply_zero_after_init() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        assert_eq!(client.total_supply(&token_addr), 0);
+    }
+
+    #[test]
+    fn test_is_signer_and_threshold() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        assert!(!client.is_signer(&s1));
+        assert_eq!(client.threshold(), 0);
+
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        assert!(client.is_signer(&s1));
+        assert!(client.is_signer(&s2));
+        assert!(!client.is_signer(&Address::generate(&env)));
+        assert_eq!(client.threshold(), 1);
+    }
+
+    #[test]
+    fn test_total_fees_collected_zero_without_fee() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        assert_eq!(client.total_fees_collected(), 0);
+
+        let signers = vec![&env, s1.clone()];
+        let id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        client.execute_unwrap(&id, &false);
+        assert_eq!(client.total_fees_collected(), 0);
+    }
+
+    #[test]
+    fn test_unwrap_fee_goes_to_collector() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let collector = Address::generate(&env);
+        client.initialize(&vec![&env, s1.clone(), s2.clone()], &vec![&env], &1, &token_addr, &limits(10), &250, &collector, &metadata(&env));
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
+
+        let signers = vec![&env, s1.clone()];
+        let recipient = Address::generate(&env);
+        let first = client.schedule_unwrap(&signers, &token_addr, &s1, &recipient, &400, &10);
+        let second = client.schedule_unwrap(&signers, &token_addr, &s1, &recipient, &39, &10);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        client.execute_unwrap(&first, &false);
+        client.execute_unwrap(&second, &false);
+
+        // 2.5% of 400 is 10; 2.5% of 39 rounds down to 0.
+        let underlying = token::Client::new(&env, &token_addr);
+        assert_eq!(underlying.balance(&recipient), 429);
+        assert_eq!(underlying.balance(&collector), 10);
+        assert_eq!(client.total_fees_collected(), 10);
+        assert_eq!(client.balance_of(&token_addr, &s1), 561);
+        assert_eq!(client.total_supply(&token_addr), 561);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid fee")]
+    fn test_fee_above_10000_bps_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(&vec![&env, s1.clone(), s2.clone()], &vec![&env], &1, &token_addr, &limits(10), &10_001, &s1, &metadata(&env));
+    }
+
+    #[test]
+    fn test_balance_defaults_to_zero() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let unknown = Address::generate(&env);
+        assert_eq!(client.balance_of(&token_addr, &unknown), 0);
+    }
+
+    #[test]
+    fn test_balance_zero_for_all_signers_after_init() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        assert_eq!(client.balance_of(&token_addr, &s1), 0);
+        assert_eq!(client.balance_of(&token_addr, &s2), 0);
+    }
+
+    #[test]
+    fn test_init_with_single_signer_threshold_one() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(MultisigAssetWrapper, ());
+        let admin = Address::generate(&env);
+        let signer = Address::generate(&env);
+        let token_addr = create_token(&env, &admin);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, signer.clone()];
+        client.initialize(&signers, &vec![&env], &1, &token_addr, &limits(60), &0, &signer, &metadata(&env));
+
+        assert_eq!(client.balance_of(&token_addr, &signer), 0);
+        assert_eq!(client.total_supply(&token_addr), 0);
+    }
+
+    #[test]
+    fn test_init_with_max_threshold() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(MultisigAssetWrapper, ());
+        let admin = Address::generate(&env);
+        let s1 = Address::generate(&env);
+        let s2 = Address::generate(&env);
+        let s3 = Address::generate(&env);
+        let token_addr = create_token(&env, &admin);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone(), s2.clone(), s3.clone()];
+        // threshold == signers.len() is valid
+        client.initialize(&signers, &vec![&env], &3, &token_addr, &limits(10), &0, &s1, &metadata(&env));
+
+        assert_eq!(client.total_supply(&token_addr), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient signers")]
+    fn test_schedule_unwrap_insufficient_signers() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+
+        // Initialize with threshold=2
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone(), s2.clone()];
+        client.initialize(&signers, &vec![&env], &2, &token_addr, &limits(10), &0, &s1, &metadata(&env));
+
+        // Provide only 1 signer when 2 are required
+        let insufficient = vec![&env, s1.clone()];
+        client.schedule_unwrap(&insufficient, &token_addr, &s1, &s1, &100, &10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient wrapped balance")]
+    fn test_schedule_unwrap_zero_balance() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        // No tokens wrapped — balance is 0
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Delay too short")]
+    fn test_schedule_unwrap_delay_below_minimum() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        // Seed a balance directly via storage to bypass wrap's auth issue
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        // min_lock_duration is 10, providing 5
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &5);
+    }
+
+    #[test]
+    fn test_schedule_unwrap_creates_timelock() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        // Seed a balance via direct storage access
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &1000_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 1000;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &200, &10);
+        assert_eq!(tl_id, 0);
+
+        let tl = client.get_timelock(&tl_id);
+        assert_eq!(tl.operation_type, 1);
+        assert_eq!(tl.amount, 200);
+        assert!(!tl.executed);
+    }
+
+    #[test]
+    fn test_cancel_timelock_marks_executed() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        // Seed a balance
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &200, &10);
+        client.cancel_timelock(&signers, &tl_id, &false);
+
+        let tl = client.get_timelock(&tl_id);
+        assert!(tl.executed, "Cancelled timelock should be marked as executed");
+    }
+
+    #[test]
+    fn test_schedule_transfer_creates_timelock() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        // Seed a balance for s1
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+
+        let tl = client.get_timelock(&tl_id);
+        assert_eq!(tl.operation_type, 2);
+        assert_eq!(tl.amount, 100);
+        assert!(!tl.executed);
+    }
+
+    #[test]
+    fn test_timelock_ids_are_sequential() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        // Seed a large balance
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &5000_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 5000;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+
+        let id0 = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        let id1 = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        let id2 = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+
+        assert_eq!(id0, 0);
+        assert_eq!(id1, 1);
+        assert_eq!(id2, 2);
+    }
+
+    #[test]
+    fn test_next_timelock_id_batch_is_consecutive() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        // Three claims within one invocation must not reuse an ID.
+        let ids = env.as_contract(&contract_id, || {
+            [
+                MultisigAssetWrapper::next_timelock_id(&env),
+                MultisigAssetWrapper::next_timelock_id(&env),
+                MultisigAssetWrapper::next_timelock_id(&env),
+            ]
+        });
+        assert_eq!(ids, [0, 1, 2]);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        assert_eq!(client.get_scheduling_info().next_timelock_id, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient balance")]
+    fn test_schedule_transfer_insufficient_balance() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        // s1 has no balance — should panic
+        client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Timelock not found")]
+    fn test_get_timelock_nonexistent() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.get_timelock(&99);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount must be positive")]
+    fn test_schedule_unwrap_zero_amount_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &0, &10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount must be positive")]
+    fn test_schedule_transfer_zero_amount_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.schedule_transfer(&signers, &token_addr, &s1, &s2, &0, &10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount must be positive")]
+    fn test_schedule_transfer_negative_amount_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.schedule_transfer(&signers, &token_addr, &s1, &s2, &-100, &10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Signer not authorized")]
+    fn test_verify_multisig_rejects_unauthorized_signer() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let unauthorized = Address::generate(&env);
+        // Include an unauthorized address in the signer list
+        let signers = vec![&env, unauthorized.clone()];
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+    }
+
+    #[test]
+    fn test_schedule_transfer_stores_sender() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        // Seed a balance for s1
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+
+        let tl = client.get_timelock(&tl_id);
+        assert_eq!(tl.sender, s1);
+        assert_eq!(tl.recipient, s2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Already executed")]
+    fn test_cancel_already_cancelled_timelock_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &200, &10);
+        client.cancel_timelock(&signers, &tl_id, &false);
+        // Second cancel should panic
+        client.cancel_timelock(&signers, &tl_id, &false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient signers")]
+    fn test_schedule_transfer_insufficient_signers() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+
+        // Initialize with threshold=2
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone(), s2.clone()];
+        client.initialize(&signers, &vec![&env], &2, &token_addr, &limits(10), &0, &s1, &metadata(&env));
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        // Only 1 signer provided when 2 are required
+        let insufficient = vec![&env, s1.clone()];
+        client.schedule_transfer(&insufficient, &token_addr, &s1, &s2, &100, &10);
+    }
+
+    #[test]
+    fn test_multiple_balances_independent() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        // Seed different balances for s1 and s2
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &1000_i128);
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s2.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 1500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        assert_eq!(client.balance_of(&token_addr, &s1), 1000);
+        assert_eq!(client.balance_of(&token_addr, &s2), 500);
+        assert_eq!(client.total_supply(&token_addr), 1500);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount must be positive")]
+    fn test_schedule_unwrap_negative_amount_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &-50, &10);
+    }
+
+    #[test]
+    fn test_schedule_unwrap_exact_balance() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &300_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 300;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        // Unwrap exactly the full balance — should succeed
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &300, &10);
+        let tl = client.get_timelock(&tl_id);
+        assert_eq!(tl.amount, 300);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient wrapped balance")]
+    fn test_schedule_unwrap_exceeds_balance() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &300_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 300;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        // Attempt to unwrap more than balance
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &301, &10);
+    }
+
+    // ---- New tests ----
+
+    #[test]
+    #[should_panic(expected = "Amount must be positive")]
+    fn test_wrap_zero_amount_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.wrap(&signers, &token_addr, &0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount must be positive")]
+    fn test_wrap_negative_amount_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.wrap(&signers, &token_addr, &-1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate signer")]
+    fn test_initialize_with_duplicate_signer_panics() {
+        let (env, contract_id, admin, s1, _s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone(), s1.clone()];
+        client.initialize(&signers, &vec![&env], &2, &token_addr, &limits(10), &0, &s1, &metadata(&env));
+    }
+
+    #[test]
+    #[should_panic(expected = "Signers must not be empty")]
+    fn test_initialize_with_no_signers_panics() {
+        let (env, contract_id, admin, s1, _s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(&vec![&env], &vec![&env], &1, &token_addr, &limits(10), &0, &s1, &metadata(&env));
+    }
+
+    #[test]
+    fn test_config_change_rejects_duplicate_signers() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let doubled = vec![&env, s2.clone(), s2.clone()];
+        assert!(client.try_schedule_config_change(&signers, &doubled, &vec![&env], &2, &10).is_err());
+        assert_eq!(client.get_config().signers, vec![&env, s1.clone(), s2.clone()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate signer detected")]
+    fn test_duplicate_signers_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        // Pass duplicate addresses
+        let signers = vec![&env, s1.clone(), s1.clone()];
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate signer detected")]
+    fn test_repeated_signer_cannot_meet_threshold_two() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(&vec![&env, s1.clone(), s2.clone()], &vec![&env], &2, &token_addr, &limits(10), &0, &s1, &metadata(&env));
+
+        // One real signer listed twice must not count as two approvals
+        client.pause(&vec![&env, s1.clone(), s1.clone()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Already executed")]
+    fn test_cancel_prevents_execution() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &200, &10);
+        client.cancel_timelock(&signers, &tl_id, &false);
+
+        // Advance time past the unlock
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1000;
+        });
+
+        // Try to execute cancelled timelock — should panic
+        client.execute_unwrap(&tl_id, &false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Self-transfer not allowed")]
+    fn test_self_transfer_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        // from == to should panic
+        client.schedule_transfer(&signers, &token_addr, &s1, &s1, &100, &10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Delay exceeds maximum lock duration")]
+    fn test_delay_too_long_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        // MAX_LOCK_DURATION is 31_536_000; exceed it
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &31_536_001);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn test_pause_blocks_wrap() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+
+        // Pause the contract
+        client.pause(&signers);
+
+        // Try to wrap — should panic
+        client.wrap(&signers, &token_addr, &100);
+    }
+
+    #[test]
+    fn test_unpause_allows_operations() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+
+        // Seed a balance for s1
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        // Pause then unpause
+        client.pause(&signers);
+        client.unpause(&signers);
+
+        // schedule_unwrap should succeed after unpause
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        let tl = client.get_timelock(&tl_id);
+        assert_eq!(tl.amount, 100);
+        assert!(!tl.executed);
+    }
+
+    #[test]
+    fn test_cancel_still_works_while_paused() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+
+        // Schedule a timelock before pausing
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &200, &10);
+
+        // Pause the contract
+        client.pause(&signers);
+
+        // Cancel should still work while paused
+        client.cancel_timelock(&signers, &tl_id, &false);
+
+        let tl = client.get_timelock(&tl_id);
+        assert!(tl.executed);
+    }
+
+    #[test]
+    fn test_rotate_signers() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let current_signers = vec![&env, s1.clone()];
+
+        // Generate new signers
+        let new_s1 = Address::generate(&env);
+        let new_s2 = Address::generate(&env);
+        let new_signers = vec![&env, new_s1.clone(), new_s2.clone()];
+
+        // Rotate
+        let tl_id = client.schedule_config_change(&current_signers, &new_signers, &vec![&env], &1, &10);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        client.execute_config_change(&tl_id);
+
+        // Seed a balance for new_s1
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), new_s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        // New signers should be able to operate
+        let new_signer_vec = vec![&env, new_s1.clone()];
+        let tl_id = client.schedule_unwrap(&new_signer_vec, &token_addr, &new_s1, &new_s1, &100, &10);
+        let tl = client.get_timelock(&tl_id);
+        assert_eq!(tl.amount, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Signer not authorized")]
+    fn test_rotate_signers_old_signers_rejected() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let current_signers = vec![&env, s1.clone()];
+
+        // Generate new signers
+        let new_s1 = Address::generate(&env);
+        let new_signers = vec![&env, new_s1.clone()];
+
+        // Rotate
+        let tl_id = client.schedule_config_change(&current_signers, &new_signers, &vec![&env], &1, &10);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        client.execute_config_change(&tl_id);
+
+        // Seed a balance for s1
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        // Old signer should be rejected
+        let old_signer_vec = vec![&env, s1.clone()];
+        client.schedule_unwrap(&old_signer_vec, &token_addr, &s1, &s1, &100, &10);
+    }
+
+    #[test]
+    fn test_add_and_remove_signer() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let s3 = Address::generate(&env);
+
+        client.add_signer(&signers, &s3);
+        assert!(client.is_signer(&s3));
+
+        client.remove_signer(&signers, &s2);
+        assert!(!client.is_signer(&s2));
+        assert!(client.is_signer(&s1));
+        assert_eq!(client.threshold(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Signer already exists")]
+    fn test_add_duplicate_signer_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.add_signer(&vec![&env, s1.clone()], &s2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Removal would leave fewer signers than threshold")]
+    fn test_remove_signer_below_threshold_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(&vec![&env, s1.clone(), s2.clone()], &vec![&env], &2, &token_addr, &limits(10), &0, &s1, &metadata(&env));
+
+        client.remove_signer(&vec![&env, s1.clone(), s2.clone()], &s2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Signer not found")]
+    fn test_remove_unknown_signer_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.remove_signer(&vec![&env, s1.clone()], &Address::generate(&env));
+    }
+
+    #[test]
+    fn test_config_change_applies_after_delay() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let s3 = Address::generate(&env);
+        let new_signers = vec![&env, s2.clone(), s3.clone()];
+
+        let tl_id = client.schedule_config_change(&signers, &new_signers, &vec![&env], &2, &10);
+        let tl = client.get_timelock(&tl_id);
+        assert_eq!(tl.operation_type, 3);
+        assert_eq!(tl.amount, 0);
+        // Nothing changes until the timelock executes
+        assert!(client.is_signer(&s1));
+        assert_eq!(client.threshold(), 1);
+
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1000;
+        });
+        client.execute_config_change(&tl_id);
+
+        assert!(!client.is_signer(&s1));
+        assert!(client.is_signer(&s3));
+        assert_eq!(client.threshold(), 2);
+        assert!(client.get_timelock(&tl_id).executed);
+    }
+
+    #[test]
+    #[should_panic(expected = "Timelock not expired")]
+    fn test_config_change_before_unlock_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_config_change(&signers, &vec![&env, s2.clone()], &vec![&env], &1, &10);
+        client.execute_config_change(&tl_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid threshold")]
+    fn test_config_change_invalid_threshold_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.schedule_config_change(&signers, &vec![&env, s2.clone()], &vec![&env], &2, &10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Delay too short")]
+    fn test_config_change_respects_min_lock_duration() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.schedule_config_change(&signers, &vec![&env, s2.clone()], &vec![&env], &1, &5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not an unwrap")]
+    fn test_config_change_cannot_execute_as_unwrap() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_config_change(&signers, &vec![&env, s2.clone()], &vec![&env], &1, &10);
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1000;
+        });
+        client.execute_unwrap(&tl_id, &false);
+    }
+
+    #[test]
+    fn test_execute_transfer_with_time_advancement() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &200, &10);
+
+        // Advance ledger time past the unlock
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1000;
+        });
+
+        client.execute_transfer(&tl_id, &false);
+
+        assert_eq!(client.balance_of(&token_addr, &s1), 300);
+        assert_eq!(client.balance_of(&token_addr, &s2), 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "Timelock not expired")]
+    fn test_execute_transfer_before_unlock_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &200, &10);
+
+        // Do NOT advance time — try to execute immediately
+        client.execute_transfer(&tl_id, &false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Already executed")]
+    fn test_double_execute_transfer_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
 
-        let from_balance: i128 = env.storage().instance().get(&from_key).unwrap_or(0);
-        let to_balance: i128 = env.storage().instance().get(&to_key).unwrap_or(0);
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token_addr.clone(), s1.clone()), &500_i128);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token_addr.clone()))
+                .unwrap();
+            wa.total_wrapped = 500;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token_addr.clone()), &wa);
+        });
 
-        if from_balance < timelock.amount {
-            panic!("Insufficient balance");
-        }
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &200, &10);
 
-        // Mark as executed BEFORE state changes (reentrancy prevention)
-        timelock.executed = true;
-        env.storage()
-            .instance()
-            .set(&DataKey::TimeLock(timelock_id), &timelock);
+        // Advance time past the unlock
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1000;
+        });
 
-        let new_from = from_balance.checked_sub(timelock.amount).expect("Balance underflow");
-        let new_to = to_balance.checked_add(timelock.amount).expect("Balance overflow");
-        env.storage().instance().set(&from_key, &new_from);
-        env.storage().instance().set(&to_key, &new_to);
+        // Execute once — should succeed
+        client.execute_transfer(&tl_id, &false);
 
-        env.events().publish(
-            (symbol_short!("exec_tx"), timelock.sender, timelock.recipient, timelock_id),
-            timelock.amount,
-        );
+        // Execute again — should panic
+        client.execute_transfer(&tl_id, &false);
     }
 
-    /// Cancel a pending timelock (requires multisig)
-    pub fn cancel_timelock(env: Env, signers: Vec<Address>, timelock_id: u64) {
-        Self::verify_multisig(&env, &signers);
+    #[test]
+    fn test_add_token_lists_tokens() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let second_token = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
 
-        let mut timelock: TimeLock = env
-            .storage()
-            .instance()
-            .get(&DataKey::TimeLock(timelock_id))
-            .expect("Timelock not found");
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.add_token(&vec![&env, s1.clone()], &second_token);
 
-        if timelock.executed {
-            panic!("Already executed");
-        }
+        assert_eq!(client.tokens(), vec![&env, token_addr.clone(), second_token.clone()]);
+        assert_eq!(client.total_supply(&second_token), 0);
+    }
 
-        // Mark as executed to prevent future execution
-        timelock.executed = true;
-        env.storage()
-            .instance()
-            .set(&DataKey::TimeLock(timelock_id), &timelock);
+    #[test]
+    #[should_panic(expected = "Token already supported")]
+    fn test_add_token_twice_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
 
-        env.events().publish(
-            (symbol_short!("cancel"), timelock_id),
-            0,
-        );
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.add_token(&vec![&env, s1.clone()], &token_addr);
     }
 
-    /// Get timelock details
-    pub fn get_timelock(env: Env, timelock_id: u64) -> TimeLock {
-        env.storage()
-            .instance()
-            .get(&DataKey::TimeLock(timelock_id))
-            .expect("Timelock not found")
-    }
+    #[test]
+    #[should_panic(expected = "Token not supported")]
+    fn test_schedule_unwrap_unsupported_token_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let other_token = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
 
-    /// Query the wrapped token balance for a given address.
-    pub fn balance(env: Env, address: Address) -> i128 {
-        let balance_key = DataKey::Balance(address);
-        env.storage().instance().get(&balance_key).unwrap_or(0)
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.schedule_unwrap(&signers, &other_token, &s1, &s1, &100, &10);
     }
 
-    /// Query the total wrapped token supply across all addresses.
-    pub fn total_supply(env: Env) -> i128 {
-        let wrapped_asset: WrappedAsset = env
-            .storage()
-            .instance()
-            .get(&DataKey::WrappedAsset)
-            .unwrap();
-        wrapped_asset.total_wrapped
+    /// Seed a wrapped balance of `token` for `holder`, backed by minted
+    /// underlying tokens held by the contract.
+    fn seed_wrapped(env: &Env, contract_id: &Address, token: &Address, holder: &Address, amount: i128) {
+        token::StellarAssetClient::new(env, token).mint(contract_id, &amount);
+        env.as_contract(contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(token.clone(), holder.clone()), &amount);
+            let mut wa: WrappedAsset = env
+                .storage()
+                .instance()
+                .get(&DataKey::WrappedAsset(token.clone()))
+                .unwrap();
+            wa.total_wrapped += amount;
+            env.storage()
+                .instance()
+                .set(&DataKey::WrappedAsset(token.clone()), &wa);
+        });
     }
 
-    /// Panics if the contract is currently paused.
-    fn require_not_paused(env: &Env) {
-        let paused: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::Paused)
-            .unwrap_or(false);
-        if paused {
-            panic!("Contract is paused");
-        }
+    #[test]
+    fn test_two_tokens_tracked_separately() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_a = create_token(&env, &admin);
+        let token_b = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_a, &s1, &s2);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.add_token(&signers, &token_b);
+
+        seed_wrapped(&env, &contract_id, &token_a, &s1, 400);
+        seed_wrapped(&env, &contract_id, &token_b, &s1, 1_500);
+
+        assert_eq!(client.balance_of(&token_a, &s1), 400);
+        assert_eq!(client.balance_of(&token_b, &s1), 1_500);
+        assert_eq!(client.total_supply(&token_a), 400);
+        assert_eq!(client.total_supply(&token_b), 1_500);
+
+        // Unwrapping one token leaves the other untouched
+        let tl_id = client.schedule_unwrap(&signers, &token_b, &s1, &s1, &500, &10);
+        assert_eq!(client.get_timelock(&tl_id).token, token_b);
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1000;
+        });
+        client.execute_unwrap(&tl_id, &false);
+
+        assert_eq!(client.balance_of(&token_a, &s1), 400);
+        assert_eq!(client.balance_of(&token_b, &s1), 1_000);
+        assert_eq!(client.total_supply(&token_a), 400);
+        assert_eq!(client.total_supply(&token_b), 1_000);
+        assert_eq!(token::Client::new(&env, &token_b).balance(&s1), 500);
+        assert_eq!(token::Client::new(&env, &token_a).balance(&contract_id), 400);
     }
 
-    /// Verify that at least `threshold` of the provided signers are in the
-    /// authorized signer list and that each has called `require_auth()`.
-    /// Panics if any provided signer is not authorized, the threshold is not met,
-    /// or duplicate signers are provided.
-    fn verify_multisig(env: &Env, provided_signers: &Vec<Address>) {
-        let config: MultisigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::MultisigConfig)
-            .unwrap();
+    #[test]
+    #[should_panic(expected = "Insufficient balance")]
+    fn test_transfer_does_not_draw_on_other_token() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_a = create_token(&env, &admin);
+        let token_b = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_a, &s1, &s2);
 
-        if provided_signers.len() < config.threshold {
-            panic!("Insufficient signers");
-        }
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.add_token(&signers, &token_b);
+        seed_wrapped(&env, &contract_id, &token_a, &s1, 1_000);
 
-        // Check for duplicate signers
-        for i in 0..provided_signers.len() {
-            for j in (i + 1)..provided_signers.len() {
-                if provided_signers.get(i).unwrap() == provided_signers.get(j).unwrap() {
-                    panic!("Duplicate signer detected");
-                }
-            }
-        }
+        // s1 holds wrapped token A only; a token B transfer must fail
+        client.schedule_transfer(&signers, &token_b, &s1, &s2, &100, &10);
+    }
 
-        for provided_signer in provided_signers.iter() {
-            let mut is_authorized = false;
-            for authorized_signer in config.signers.iter() {
-                if provided_signer == authorized_signer {
-                    is_authorized = true;
-                    break;
-                }
-            }
-            if !is_authorized {
-                panic!("Signer not authorized");
-            }
-            provided_signer.require_auth();
-        }
+    #[test]
+    fn test_scheduling_info_tracks_next_id() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let info = client.get_scheduling_info();
+        assert_eq!(info.next_timelock_id, 0);
+        assert_eq!(info.min_lock_duration, 10);
+        assert_eq!(info.max_lock_duration, MAX_LOCK_DURATION);
+
+        let signers = vec![&env, s1.clone()];
+        let predicted = info.next_timelock_id;
+        let id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        assert_eq!(id, predicted);
+        client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+        assert_eq!(client.get_scheduling_info().next_timelock_id, 2);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use soroban_sdk::{testutils::Address as _, testutils::Ledger, vec, Env};
+    #[test]
+    fn test_configured_max_lock_duration_bounds_schedules() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(&vec![&env, s1.clone(), s2.clone()], &vec![&env], &1, &token_addr, &TimelockLimits { max_lock_duration: 3_600, ..limits(10) }, &0, &s1, &metadata(&env));
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
-    fn setup_env() -> (Env, Address, Address, Address, Address) {
-        let env = Env::default();
-        env.mock_all_auths();
-        let contract_id = env.register(MultisigAssetWrapper, ());
-        let token_admin = Address::generate(&env);
-        let signer1 = Address::generate(&env);
-        let signer2 = Address::generate(&env);
-        (env, contract_id, token_admin, signer1, signer2)
+        assert_eq!(client.get_scheduling_info().max_lock_duration, 3_600);
+
+        let signers = vec![&env, s1.clone()];
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &3_600);
+        let result = client.try_schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &3_601);
+        assert!(result.is_err());
+        assert_eq!(client.get_scheduling_info().next_timelock_id, 1);
     }
 
-    fn create_token(env: &Env, admin: &Address) -> Address {
-        let token_contract_id = env.register_stellar_asset_contract_v2(admin.clone());
-        token_contract_id.address()
+    #[test]
+    #[should_panic(expected = "Delay too long")]
+    fn test_delay_beyond_configured_max_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(&vec![&env, s1.clone(), s2.clone()], &vec![&env], &1, &token_addr, &TimelockLimits { max_lock_duration: 3_600, ..limits(10) }, &0, &s1, &metadata(&env));
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
+
+        let signers = vec![&env, s1.clone()];
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &3_601);
     }
 
-    fn init_contract(
-        env: &Env,
-        contract_id: &Address,
-        token_addr: &Address,
-        signer1: &Address,
-        signer2: &Address,
-    ) {
-        let client = MultisigAssetWrapperClient::new(env, contract_id);
-        let signers = vec![env, signer1.clone(), signer2.clone()];
-        client.initialize(&signers, &1, token_addr, &10);
+    #[test]
+    #[should_panic(expected = "Invalid max lock duration")]
+    fn test_max_lock_duration_below_min_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(&vec![&env, s1.clone(), s2.clone()], &vec![&env], &1, &token_addr, &TimelockLimits { max_lock_duration: 5, ..limits(10) }, &0, &s1, &metadata(&env));
     }
 
     #[test]
-    fn test_initialize_sets_state() {
+    fn test_unwrap_to_different_recipient() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        assert_eq!(client.balance(&s1), 0);
-        assert_eq!(client.balance(&s2), 0);
-        assert_eq!(client.total_supply(), 0);
+        let signers = vec![&env, s1.clone()];
+        let recipient = Address::generate(&env);
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &recipient, &300, &10);
+
+        let tl = client.get_timelock(&tl_id);
+        assert_eq!(tl.sender, s1);
+        assert_eq!(tl.recipient, recipient);
+
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1000;
+        });
+        client.execute_unwrap(&tl_id, &false);
+
+        // from's wrapped balance is burned; recipient gets the underlying
+        assert_eq!(client.balance_of(&token_addr, &s1), 700);
+        assert_eq!(client.balance_of(&token_addr, &recipient), 0);
+        assert_eq!(client.total_supply(&token_addr), 700);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&recipient), 300);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&s1), 0);
     }
 
     #[test]
-    #[should_panic(expected = "Already initialized")]
-    fn test_double_initialize_panics() {
+    #[should_panic(expected = "Insufficient wrapped balance")]
+    fn test_unwrap_checks_from_balance_not_recipient() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-        // Second init should panic
-        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        // The recipient holds a balance, but `from` does not
+        seed_wrapped(&env, &contract_id, &token_addr, &s2, 1_000);
+
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let signers = vec![&env, s1.clone()];
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s2, &300, &10);
     }
 
     #[test]
-    #[should_panic(expected = "Invalid threshold")]
-    fn test_zero_threshold_panics() {
+    fn test_unwrap_from_non_signer_requires_holder_auth() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let holder = Address::generate(&env);
+        seed_wrapped(&env, &contract_id, &token_addr, &holder, 1_000);
+
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone(), s2.clone()];
-        client.initialize(&signers, &0, &token_addr, &10);
+        let signers = vec![&env, s1.clone()];
+        client.schedule_unwrap(&signers, &token_addr, &holder, &s2, &300, &10);
+
+        let auths = env.auths();
+        assert!(auths.iter().any(|(addr, _)| *addr == s1), "signer must authorize");
+        assert!(
+            auths.iter().any(|(addr, _)| *addr == holder),
+            "holder of the burned balance must authorize"
+        );
     }
 
     #[test]
-    #[should_panic(expected = "Invalid threshold")]
-    fn test_threshold_exceeding_signers_panics() {
+    fn test_wrap_to_credits_recipient() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let sac = token::StellarAssetClient::new(&env, &token_addr);
+        sac.mint(&s1, &1_000);
+
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone(), s2.clone()];
-        client.initialize(&signers, &3, &token_addr, &10);
+        let beneficiary = Address::generate(&env);
+        client.wrap_to(&vec![&env, s1.clone()], &token_addr, &beneficiary, &300);
+
+        // The payer funds it, the beneficiary holds it
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&s1), 700);
+        assert_eq!(client.balance_of(&token_addr, &s1), 0);
+        assert_eq!(client.balance_of(&token_addr, &beneficiary), 300);
+        assert_eq!(client.total_supply(&token_addr), 300);
+        assert_eq!(
+            client.balances(&token_addr, &0, &10),
+            vec![&env, (beneficiary.clone(), 300_i128)]
+        );
+
+        // Plain wrap still credits the first signer
+        client.wrap(&vec![&env, s1.clone()], &token_addr, &100);
+        assert_eq!(client.balance_of(&token_addr, &s1), 100);
     }
 
     #[test]
-    fn test_total_supply_zero_after_init() {
+    fn test_batch_wrap_credits_each_recipient() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let sac = token::StellarAssetClient::new(&env, &token_addr);
+        sac.mint(&s1, &1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        assert_eq!(client.total_supply(), 0);
+        let r1 = Address::generate(&env);
+        let r2 = Address::generate(&env);
+        client.batch_wrap(
+            &vec![&env, s1.clone()],
+            &token_addr,
+            &vec![&env, r1.clone(), r2.clone(), r1.clone()],
+            &vec![&env, 100_i128, 250, 50],
+        );
+
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&s1), 600);
+        assert_eq!(client.balance_of(&token_addr, &r1), 150);
+        assert_eq!(client.balance_of(&token_addr, &r2), 250);
+        assert_eq!(client.total_supply(&token_addr), 400);
+        assert_eq!(client.holder_count(&token_addr), 2);
     }
 
     #[test]
-    fn test_balance_defaults_to_zero() {
+    fn test_batch_wrap_rejects_non_positive_amount_atomically() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let sac = token::StellarAssetClient::new(&env, &token_addr);
+        sac.mint(&s1, &1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let unknown = Address::generate(&env);
-        assert_eq!(client.balance(&unknown), 0);
+        let r1 = Address::generate(&env);
+        let result = client.try_batch_wrap(
+            &vec![&env, s1.clone()],
+            &token_addr,
+            &vec![&env, r1.clone(), s2.clone()],
+            &vec![&env, 100_i128, 0],
+        );
+        assert!(result.is_err());
+        assert_eq!(client.balance_of(&token_addr, &r1), 0);
+        assert_eq!(client.total_supply(&token_addr), 0);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&s1), 1_000);
     }
 
     #[test]
-    fn test_balance_zero_for_all_signers_after_init() {
+    #[should_panic(expected = "Recipients and amounts length mismatch")]
+    fn test_batch_wrap_length_mismatch_panics() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        assert_eq!(client.balance(&s1), 0);
-        assert_eq!(client.balance(&s2), 0);
+        client.batch_wrap(
+            &vec![&env, s1.clone()],
+            &token_addr,
+            &vec![&env, s2.clone()],
+            &vec![&env, 100_i128, 200],
+        );
     }
 
     #[test]
-    fn test_init_with_single_signer_threshold_one() {
-        let env = Env::default();
-        env.mock_all_auths();
-        let contract_id = env.register(MultisigAssetWrapper, ());
-        let admin = Address::generate(&env);
-        let signer = Address::generate(&env);
+    fn test_execute_ready_skips_pending_and_executed() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let sac = token::StellarAssetClient::new(&env, &token_addr);
+        sac.mint(&s1, &1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, signer.clone()];
-        client.initialize(&signers, &1, &token_addr, &60);
+        let signers = vec![&env, s1.clone()];
+        client.wrap(&signers, &token_addr, &1_000);
+
+        let recipient = Address::generate(&env);
+        let unwrap_id = client.schedule_unwrap(&signers, &token_addr, &s1, &recipient, &100, &10);
+        let transfer_id = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &200, &10);
+        let late_id = client.schedule_unwrap(&signers, &token_addr, &s1, &recipient, &300, &1_000);
+        let done_id = client.schedule_unwrap(&signers, &token_addr, &s1, &recipient, &50, &10);
 
-        assert_eq!(client.balance(&signer), 0);
-        assert_eq!(client.total_supply(), 0);
+        env.ledger().with_mut(|li| li.timestamp += 20);
+        client.execute_unwrap(&done_id, &false);
+
+        let executed = client.execute_ready(&vec![&env, unwrap_id, transfer_id, late_id, done_id, 99], &false);
+        assert_eq!(executed, vec![&env, unwrap_id, transfer_id]);
+
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&recipient), 150);
+        assert_eq!(client.balance_of(&token_addr, &s1), 650);
+        assert_eq!(client.balance_of(&token_addr, &s2), 200);
+        assert_eq!(client.list_timelocks(&0, &10).timelocks.len(), 1);
+
+        assert_eq!(client.execute_ready(&vec![&env, unwrap_id, transfer_id], &false).len(), 0);
     }
 
     #[test]
-    fn test_init_with_max_threshold() {
-        let env = Env::default();
-        env.mock_all_auths();
-        let contract_id = env.register(MultisigAssetWrapper, ());
-        let admin = Address::generate(&env);
-        let s1 = Address::generate(&env);
-        let s2 = Address::generate(&env);
-        let s3 = Address::generate(&env);
+    fn test_balances_and_timelocks_use_persistent_storage() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        token::StellarAssetClient::new(&env, &token_addr).mint(&s1, &1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone(), s2.clone(), s3.clone()];
-        // threshold == signers.len() is valid
-        client.initialize(&signers, &3, &token_addr, &10);
+        let signers = vec![&env, s1.clone()];
+        client.wrap(&signers, &token_addr, &1_000);
+        let id = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
 
-        assert_eq!(client.total_supply(), 0);
+        env.as_contract(&contract_id, || {
+            let balance_key = DataKey::Balance(token_addr.clone(), s1.clone());
+            let timelock_key = DataKey::TimeLock(id);
+            assert!(!env.storage().instance().has(&balance_key));
+            assert!(!env.storage().instance().has(&timelock_key));
+            assert_eq!(env.storage().persistent().get_ttl(&balance_key), PERSISTENT_TTL_EXTEND_TO);
+            assert_eq!(env.storage().persistent().get_ttl(&timelock_key), PERSISTENT_TTL_EXTEND_TO);
+        });
     }
 
     #[test]
-    #[should_panic(expected = "Insufficient signers")]
-    fn test_schedule_unwrap_insufficient_signers() {
+    fn test_migrate_moves_first_version_layout() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
+        token::StellarAssetClient::new(&env, &token_addr).mint(&contract_id, &800);
+
+        // Lay out state the way the first deployed version stored it
+        let s3 = Address::generate(&env);
+        let pending = LegacyTimeLock {
+            operation_type: 1,
+            signers: vec![&env, s1.clone()],
+            sender: s2.clone(),
+            recipient: s2.clone(),
+            amount: 100,
+            unlock_time: 10,
+            executed: false,
+        };
+        env.as_contract(&contract_id, || {
+            let instance = env.storage().instance();
+            let config = LegacyMultisigConfig { signers: vec![&env, s1.clone(), s2.clone()], threshold: 1 };
+            instance.set(&DataKey::MultisigConfig, &config);
+            let asset = WrappedAsset { underlying_token: token_addr.clone(), total_wrapped: 800 };
+            instance.set(&LegacyDataKey::WrappedAsset, &asset);
+            instance.set(&LegacyDataKey::Balance(s1.clone()), &500_i128);
+            instance.set(&LegacyDataKey::Balance(s2.clone()), &200_i128);
+            instance.set(&LegacyDataKey::Balance(s3.clone()), &100_i128);
+            instance.set(&DataKey::TimeLock(0), &pending);
+            instance.set(&DataKey::NextTimeLockId, &1u64);
+            instance.set(&DataKey::MinLockDuration, &10u64);
+            instance.set(&DataKey::Initialized, &true);
+        });
 
-        // Initialize with threshold=2
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone(), s2.clone()];
-        client.initialize(&signers, &2, &token_addr, &10);
+        let signers = vec![&env, s1.clone()];
+        assert_eq!(client.migrate(&signers, &vec![&env, s1.clone(), s2.clone()], &0, &1), 3);
+        assert_eq!(client.tokens(), vec![&env, token_addr.clone()]);
+        assert_eq!(client.total_supply(&token_addr), 800);
+        assert_eq!(client.balance_of(&token_addr, &s1), 500);
+        assert_eq!(client.balance(&s2), 200);
+        assert_eq!(client.get_config().threshold, 1);
+        let timelock = client.get_timelock(&0);
+        assert_eq!(timelock.id, 0);
+        assert_eq!(timelock.token, token_addr);
+
+        // A later batch picks up the rest; repeats move nothing
+        assert_eq!(client.balance_of(&token_addr, &s3), 0);
+        assert_eq!(client.migrate(&signers, &vec![&env, s3.clone(), s1.clone()], &0, &1), 1);
+        assert_eq!(client.balance_of(&token_addr, &s3), 100);
+        assert_eq!(client.balance_of(&token_addr, &s1), 500);
+        assert_eq!(client.holder_count(&token_addr), 3);
+        env.as_contract(&contract_id, || {
+            let instance = env.storage().instance();
+            assert!(!instance.has(&LegacyDataKey::WrappedAsset));
+            assert!(!instance.has(&LegacyDataKey::Balance(s1.clone())));
+            assert!(!instance.has(&DataKey::TimeLock(0)));
+        });
 
-        // Provide only 1 signer when 2 are required
-        let insufficient = vec![&env, s1.clone()];
-        client.schedule_unwrap(&insufficient, &s1, &100, &10);
+        // The migrated timelock runs against the migrated balance
+        env.ledger().with_mut(|li| li.timestamp = 10);
+        client.execute_unwrap(&0, &false);
+        assert_eq!(client.balance_of(&token_addr, &s2), 100);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&s2), 100);
     }
 
     #[test]
-    #[should_panic(expected = "Insufficient wrapped balance")]
-    fn test_schedule_unwrap_zero_balance() {
+    fn test_migrate_requires_multisig() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone()];
-        // No tokens wrapped — balance is 0
-        client.schedule_unwrap(&signers, &s1, &100, &10);
+        let outsider = Address::generate(&env);
+        assert!(client.try_migrate(&vec![&env, outsider], &vec![&env], &0, &0).is_err());
+        assert_eq!(client.migrate(&vec![&env, s1.clone()], &vec![&env, s2.clone()], &0, &10), 0);
     }
 
     #[test]
-    #[should_panic(expected = "Delay too short")]
-    fn test_schedule_unwrap_delay_below_minimum() {
+    fn test_migrate_converts_timelocks_in_batches() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
-        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        // Seed a balance directly via storage to bypass wrap's auth issue
+        let count = MAX_TIMELOCKS_PAGE as u64 + 5;
         env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
+            let instance = env.storage().instance();
+            let config = LegacyMultisigConfig { signers: vec![&env, s1.clone()], threshold: 1 };
+            instance.set(&DataKey::MultisigConfig, &config);
+            let asset = WrappedAsset { underlying_token: token_addr.clone(), total_wrapped: 0 };
+            instance.set(&LegacyDataKey::WrappedAsset, &asset);
+            for id in 0..count {
+                let timelock = LegacyTimeLock {
+                    operation_type: 2,
+                    signers: vec![&env, s1.clone()],
+                    sender: s1.clone(),
+                    recipient: s2.clone(),
+                    amount: 1,
+                    unlock_time: 10,
+                    executed: true,
+                };
+                instance.set(&DataKey::TimeLock(id), &timelock);
+            }
+            instance.set(&DataKey::NextTimeLockId, &count);
+            instance.set(&DataKey::Initialized, &true);
         });
 
+        // Each call converts at most one page of IDs, past the end nothing
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        // min_lock_duration is 10, providing 5
-        client.schedule_unwrap(&signers, &s1, &100, &5);
+        assert_eq!(client.migrate(&signers, &vec![&env], &0, &u32::MAX), MAX_TIMELOCKS_PAGE);
+        assert!(client.try_get_timelock(&(count - 1)).is_err());
+        assert_eq!(client.migrate(&signers, &vec![&env], &(MAX_TIMELOCKS_PAGE as u64), &100), 5);
+        assert_eq!(client.migrate(&signers, &vec![&env], &0, &100), 0);
+        assert_eq!(client.migrate(&signers, &vec![&env], &count, &100), 0);
+        for id in 0..count {
+            assert_eq!(client.get_timelock(&id).token, token_addr);
+        }
     }
 
     #[test]
-    fn test_schedule_unwrap_creates_timelock() {
+    fn test_state_changes_extend_instance_ttl() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        token::StellarAssetClient::new(&env, &token_addr).mint(&s1, &1_000);
+        let instance_ttl = || env.as_contract(&contract_id, || env.storage().instance().get_ttl());
+        assert_eq!(instance_ttl(), INSTANCE_TTL_EXTEND_TO);
 
-        // Seed a balance via direct storage access
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &1000_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 1000;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
-
+        // Once the TTL runs below the threshold, the next write renews it
+        env.ledger().with_mut(|li| li.sequence_number += INSTANCE_TTL_EXTEND_TO - INSTANCE_TTL_THRESHOLD + 1);
+        assert!(instance_ttl() < INSTANCE_TTL_THRESHOLD);
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone()];
-        let tl_id = client.schedule_unwrap(&signers, &s1, &200, &10);
-        assert_eq!(tl_id, 0);
-
-        let tl = client.get_timelock(&tl_id);
-        assert_eq!(tl.operation_type, 1);
-        assert_eq!(tl.amount, 200);
-        assert!(!tl.executed);
+        client.wrap(&vec![&env, s1.clone()], &token_addr, &100);
+        assert_eq!(instance_ttl(), INSTANCE_TTL_EXTEND_TO);
     }
 
     #[test]
-    fn test_cancel_timelock_marks_executed() {
+    fn test_purge_timelock_removes_entry_but_blocks_replay() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        // Seed a balance
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        let tl_id = client.schedule_unwrap(&signers, &s1, &200, &10);
-        client.cancel_timelock(&signers, &tl_id);
+        let executed = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+        let cancelled = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+        let pending = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        client.execute_transfer(&executed, &false);
+        client.cancel_timelock(&signers, &cancelled, &false);
 
-        let tl = client.get_timelock(&tl_id);
-        assert!(tl.executed, "Cancelled timelock should be marked as executed");
+        client.purge_timelock(&signers, &executed);
+        client.purge_timelock(&signers, &cancelled);
+        assert!(client.try_purge_timelock(&signers, &pending).is_err());
+
+        env.as_contract(&contract_id, || {
+            assert!(!env.storage().persistent().has(&DataKey::TimeLock(executed)));
+            assert!(!env.storage().persistent().has(&DataKey::TimeLock(cancelled)));
+        });
+        assert!(client.try_get_timelock(&executed).is_err());
+        assert!(client.try_execute_transfer(&executed, &false).is_err());
+        assert!(client.try_purge_timelock(&signers, &executed).is_err());
+        assert_eq!(client.execute_ready(&vec![&env, executed, cancelled, pending], &false), vec![&env, pending]);
+        assert_eq!(client.balance_of(&token_addr, &s2), 200);
     }
 
     #[test]
-    fn test_schedule_transfer_creates_timelock() {
+    fn test_purge_timelock_requires_multisig() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        // Seed a balance for s1
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        let tl_id = client.schedule_transfer(&signers, &s1, &s2, &100, &10);
+        let id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        client.cancel_timelock(&signers, &id, &false);
 
-        let tl = client.get_timelock(&tl_id);
-        assert_eq!(tl.operation_type, 2);
-        assert_eq!(tl.amount, 100);
-        assert!(!tl.executed);
+        let outsider = Address::generate(&env);
+        assert!(client.try_purge_timelock(&vec![&env, outsider], &id).is_err());
+        assert!(client.try_purge_timelock(&vec![&env], &id).is_err());
+        assert!(client.get_timelock(&id).executed);
     }
 
     #[test]
-    fn test_timelock_ids_are_sequential() {
+    #[should_panic(expected = "Already executed")]
+    fn test_purged_timelock_reports_already_executed() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        // Seed a large balance
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &5000_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 5000;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-
-        let id0 = client.schedule_unwrap(&signers, &s1, &100, &10);
-        let id1 = client.schedule_unwrap(&signers, &s1, &100, &10);
-        let id2 = client.schedule_transfer(&signers, &s1, &s2, &100, &10);
-
-        assert_eq!(id0, 0);
-        assert_eq!(id1, 1);
-        assert_eq!(id2, 2);
+        let id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        client.cancel_timelock(&signers, &id, &false);
+        client.purge_timelock(&signers, &id);
+        client.cancel_timelock(&signers, &id, &false);
     }
 
     #[test]
-    #[should_panic(expected = "Insufficient balance")]
-    fn test_schedule_transfer_insufficient_balance() {
+    fn test_execute_and_cancel_can_purge_the_timelock() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        // s1 has no balance — should panic
-        client.schedule_transfer(&signers, &s1, &s2, &100, &10);
+        let unwrap_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        let transfer_id = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+        let batched_id = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+        let cancelled_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+
+        client.execute_unwrap(&unwrap_id, &true);
+        client.execute_transfer(&transfer_id, &true);
+        assert_eq!(client.execute_ready(&vec![&env, batched_id], &true), vec![&env, batched_id]);
+        client.cancel_timelock(&signers, &cancelled_id, &true);
+
+        for id in [unwrap_id, transfer_id, batched_id, cancelled_id] {
+            env.as_contract(&contract_id, || {
+                assert!(!env.storage().persistent().has(&DataKey::TimeLock(id)));
+            });
+            assert!(client.try_get_timelock(&id).is_err());
+            assert!(client.try_execute_unwrap(&id, &false).is_err());
+            assert!(client.try_execute_transfer(&id, &false).is_err());
+            assert!(client.try_cancel_timelock(&signers, &id, &false).is_err());
+            assert!(client.try_purge_timelock(&signers, &id).is_err());
+        }
+        assert_eq!(
+            client.execute_ready(&vec![&env, unwrap_id, transfer_id, batched_id, cancelled_id], &false).len(),
+            0
+        );
+        assert_eq!(client.balance_of(&token_addr, &s1), 700);
+        assert_eq!(client.balance_of(&token_addr, &s2), 200);
+        assert_eq!(client.pending_count(&s1), 0);
+        assert_eq!(client.pending_count(&s2), 0);
     }
 
     #[test]
-    #[should_panic(expected = "Timelock not found")]
-    fn test_get_timelock_nonexistent() {
+    #[should_panic(expected = "Already executed")]
+    fn test_unwrap_purged_on_execute_cannot_run_again() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        client.get_timelock(&99);
+        let signers = vec![&env, s1.clone()];
+        let id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        client.execute_unwrap(&id, &true);
+        client.execute_unwrap(&id, &true);
     }
 
     #[test]
-    #[should_panic(expected = "Amount must be positive")]
-    fn test_schedule_unwrap_zero_amount_panics() {
+    fn test_sep41_metadata_and_balance() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 700);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone()];
-        client.schedule_unwrap(&signers, &s1, &0, &10);
+        assert_eq!(client.decimals(), 7);
+        assert_eq!(client.name(), String::from_str(&env, "Wrapped Test Token"));
+        assert_eq!(client.symbol(), String::from_str(&env, "wTEST"));
+        assert_eq!(client.balance(&s1), 700);
+        assert_eq!(client.balance(&s1), client.balance_of(&token_addr, &s1));
     }
 
     #[test]
-    #[should_panic(expected = "Amount must be positive")]
-    fn test_schedule_transfer_zero_amount_panics() {
+    fn test_sep41_transfer_moves_balance_immediately() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 700);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone()];
-        client.schedule_transfer(&signers, &s1, &s2, &0, &10);
+        let outsider = Address::generate(&env);
+        client.transfer(&s1, &outsider, &200);
+        client.transfer(&s1, &s1, &100);
+
+        assert_eq!(client.balance(&s1), 500);
+        assert_eq!(client.balance(&outsider), 200);
+        assert_eq!(client.total_supply(&token_addr), 700);
+        // seed_wrapped does not track s1; the transfer recipient is tracked
+        assert_eq!(client.balances(&token_addr, &0, &10), vec![&env, (outsider.clone(), 200)]);
+        assert!(client.try_transfer(&outsider, &s1, &201).is_err());
     }
 
     #[test]
-    #[should_panic(expected = "Amount must be positive")]
-    fn test_schedule_transfer_negative_amount_panics() {
+    fn test_sep41_zero_transfer_is_refused() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        token::StellarAssetClient::new(&env, &token_addr).mint(&s1, &1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone()];
-        client.schedule_transfer(&signers, &s1, &s2, &-100, &10);
+        client.wrap(&vec![&env, s1.clone()], &token_addr, &500);
+        client.approve(&s1, &s2, &100, &1_000);
+
+        let fresh = Address::generate(&env);
+        assert!(client.try_transfer(&s1, &fresh, &0).is_err());
+        assert!(client.try_transfer_from(&s2, &s1, &fresh, &0).is_err());
+        assert!(client.try_transfer(&s1, &fresh, &-1).is_err());
+        assert_eq!(client.holder_count(&token_addr), 1);
     }
 
     #[test]
-    #[should_panic(expected = "Signer not authorized")]
-    fn test_verify_multisig_rejects_unauthorized_signer() {
+    fn test_sep41_transfer_from_spends_allowance() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 700);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let unauthorized = Address::generate(&env);
-        // Include an unauthorized address in the signer list
-        let signers = vec![&env, unauthorized.clone()];
-        client.schedule_unwrap(&signers, &s1, &100, &10);
+        let spender = Address::generate(&env);
+        let expiration = env.ledger().sequence() + 100;
+        client.approve(&s1, &spender, &300, &expiration);
+        assert_eq!(client.allowance(&s1, &spender), 300);
+
+        client.transfer_from(&spender, &s1, &s2, &250);
+        assert_eq!(client.allowance(&s1, &spender), 50);
+        assert_eq!(client.balance(&s1), 450);
+        assert_eq!(client.balance(&s2), 250);
+        assert!(client.try_transfer_from(&spender, &s1, &s2, &51).is_err());
+
+        env.ledger().with_mut(|li| li.sequence_number = expiration + 1);
+        assert_eq!(client.allowance(&s1, &spender), 0);
     }
 
     #[test]
-    fn test_schedule_transfer_stores_sender() {
+    #[should_panic(expected = "Expiration ledger in the past")]
+    fn test_sep41_approve_rejects_past_expiration() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        // Seed a balance for s1
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        env.ledger().with_mut(|li| li.sequence_number = 50);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone()];
-        let tl_id = client.schedule_transfer(&signers, &s1, &s2, &100, &10);
-
-        let tl = client.get_timelock(&tl_id);
-        assert_eq!(tl.sender, s1);
-        assert_eq!(tl.recipient, s2);
+        client.approve(&s1, &s2, &100, &49);
     }
 
     #[test]
-    #[should_panic(expected = "Already executed")]
-    fn test_cancel_already_cancelled_timelock_panics() {
+    fn test_delegated_unwrap_spends_and_restores_allowance() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        let owner = Address::generate(&env);
+        seed_wrapped(&env, &contract_id, &token_addr, &owner, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone()];
-        let tl_id = client.schedule_unwrap(&signers, &s1, &200, &10);
-        client.cancel_timelock(&signers, &tl_id);
-        // Second cancel should panic
-        client.cancel_timelock(&signers, &tl_id);
+        let custodian = Address::generate(&env);
+        let expiration = env.ledger().sequence() + 1_000;
+        client.approve_unwrap(&owner, &custodian, &token_addr, &500, &expiration);
+
+        let recipient = Address::generate(&env);
+        let first = client.schedule_unwrap_from(&custodian, &token_addr, &owner, &recipient, &300, &10);
+        let second = client.schedule_unwrap_from(&custodian, &token_addr, &owner, &recipient, &200, &10);
+        assert_eq!(client.unwrap_allowance(&owner, &custodian, &token_addr), 0);
+        assert_eq!(client.get_timelock(&first).operation_type, 4);
+        assert!(client
+            .try_schedule_unwrap_from(&custodian, &token_addr, &owner, &recipient, &1, &10)
+            .is_err());
+
+        client.cancel_timelock(&vec![&env, s1.clone()], &second, &false);
+        assert_eq!(client.unwrap_allowance(&owner, &custodian, &token_addr), 200);
+
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        assert_eq!(client.execute_ready(&vec![&env, first], &false), vec![&env, first]);
+        assert_eq!(client.balance_of(&token_addr, &owner), 700);
+        assert_eq!(token::Client::new(&env, &token_addr).balance(&recipient), 300);
     }
 
     #[test]
-    #[should_panic(expected = "Insufficient signers")]
-    fn test_schedule_transfer_insufficient_signers() {
+    #[should_panic(expected = "Allowance expired")]
+    fn test_delegated_unwrap_with_expired_allowance_panics() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
-        // Initialize with threshold=2
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone(), s2.clone()];
-        client.initialize(&signers, &2, &token_addr, &10);
-
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        let custodian = Address::generate(&env);
+        let expiration = env.ledger().sequence() + 10;
+        client.approve_unwrap(&s1, &custodian, &token_addr, &500, &expiration);
 
-        // Only 1 signer provided when 2 are required
-        let insufficient = vec![&env, s1.clone()];
-        client.schedule_transfer(&insufficient, &s1, &s2, &100, &10);
+        env.ledger().with_mut(|li| li.sequence_number = expiration + 1);
+        client.schedule_unwrap_from(&custodian, &token_addr, &s1, &s1, &100, &10);
     }
 
     #[test]
-    fn test_multiple_balances_independent() {
+    fn test_upgrade_requires_multisig_and_uploaded_wasm() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
 
-        // Seed different balances for s1 and s2
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &1000_i128);
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s2.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 1500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
-
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        assert_eq!(client.balance(&s1), 1000);
-        assert_eq!(client.balance(&s2), 500);
-        assert_eq!(client.total_supply(), 1500);
+        assert_eq!(client.version(), 1);
+
+        let hash = BytesN::from_array(&env, &[7; 32]);
+        assert!(client.try_upgrade(&vec![&env], &hash).is_err());
+        // Nothing was uploaded under `hash`, so even a valid quorum fails and
+        // the version is left alone.
+        assert!(client.try_upgrade(&vec![&env, s1.clone()], &hash).is_err());
+        assert_eq!(client.version(), 1);
     }
 
     #[test]
-    #[should_panic(expected = "Amount must be positive")]
-    fn test_schedule_unwrap_negative_amount_panics() {
+    fn test_pending_locked_total_and_timelock_count() {
         let (env, contract_id, admin, s1, s2) = setup_env();
-        let token_addr = create_token(&env, &admin);
-        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let token_a = create_token(&env, &admin);
+        let token_b = create_token(&env, &admin);
+        init_contract(&env, &contract_id, &token_a, &s1, &s2);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        client.schedule_unwrap(&signers, &s1, &-50, &10);
+        client.add_token(&signers, &token_b);
+        seed_wrapped(&env, &contract_id, &token_a, &s1, 1_000);
+        seed_wrapped(&env, &contract_id, &token_b, &s1, 1_000);
+        assert_eq!(client.timelock_count(), 0);
+        assert_eq!(client.pending_locked_total(&token_a), 0);
+
+        let executed = client.schedule_unwrap(&signers, &token_a, &s1, &s1, &100, &10);
+        let cancelled = client.schedule_transfer(&signers, &token_a, &s1, &s2, &200, &10);
+        client.schedule_transfer(&signers, &token_a, &s1, &s2, &300, &10);
+        client.schedule_unwrap(&signers, &token_b, &s1, &s1, &400, &10);
+        client.schedule_config_change(&signers, &vec![&env, s2.clone()], &vec![&env], &1, &10);
+        assert_eq!(client.pending_locked_total(&token_a), 600);
+
+        client.cancel_timelock(&signers, &cancelled, &false);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        client.execute_unwrap(&executed, &false);
+
+        assert_eq!(client.timelock_count(), 5);
+        assert_eq!(client.pending_locked_total(&token_a), 300);
+        assert_eq!(client.pending_locked_total(&token_b), 400);
     }
 
     #[test]
-    fn test_schedule_unwrap_exact_balance() {
+    fn test_execution_window_expiry_leaves_cancel() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
-        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(&vec![&env, s1.clone(), s2.clone()], &vec![&env], &1, &token_addr, &TimelockLimits { execution_window: 100, ..limits(10) }, &0, &s1, &metadata(&env));
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &300_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 300;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        let signers = vec![&env, s1.clone()];
+        let on_time = client.schedule_transfer(&signers, &token_addr, &s1, &s2, &100, &10);
+        let stale = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+
+        // Unlocked at +10; the window closes at +110
+        env.ledger().with_mut(|li| li.timestamp += 110);
+        client.execute_transfer(&on_time, &false);
+
+        env.ledger().with_mut(|li| li.timestamp += 1);
+        assert!(client.try_execute_unwrap(&stale, &false).is_err());
+        assert_eq!(client.execute_ready(&vec![&env, stale], &false).len(), 0);
+        client.cancel_timelock(&signers, &stale, &false);
+        assert!(client.get_timelock(&stale).executed);
+        assert_eq!(client.balance_of(&token_addr, &s1), 900);
+    }
 
+    #[test]
+    #[should_panic(expected = "Execution window expired")]
+    fn test_execute_after_window_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(&vec![&env, s1.clone(), s2.clone()], &vec![&env], &1, &token_addr, &TimelockLimits { execution_window: 100, ..limits(10) }, &0, &s1, &metadata(&env));
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
+
         let signers = vec![&env, s1.clone()];
-        // Unwrap exactly the full balance — should succeed
-        let tl_id = client.schedule_unwrap(&signers, &s1, &300, &10);
-        let tl = client.get_timelock(&tl_id);
-        assert_eq!(tl.amount, 300);
+        let id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        env.ledger().with_mut(|li| li.timestamp += 111);
+        client.execute_unwrap(&id, &false);
     }
 
     #[test]
-    #[should_panic(expected = "Insufficient wrapped balance")]
-    fn test_schedule_unwrap_exceeds_balance() {
+    fn test_weighted_signer_alone_meets_threshold() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
-        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let s3 = Address::generate(&env);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(
+            &vec![&env, s1.clone(), s2.clone(), s3.clone()],
+            &vec![&env, 2, 1, 1],
+            &2,
+            &token_addr, &limits(10), &0, &s1, &metadata(&env),
+        );
 
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &300_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 300;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        // s1 (weight 2) alone, or s2 + s3 together, reach the threshold
+        client.pause(&vec![&env, s1.clone()]);
+        client.unpause(&vec![&env, s2.clone(), s3.clone()]);
+        assert!(client.try_pause(&vec![&env, s2.clone()]).is_err());
+
+        // Removing s1 leaves weight 2, still enough; then s2 cannot go
+        client.remove_signer(&vec![&env, s1.clone()], &s1);
+        assert_eq!(client.get_config().weights, vec![&env, 1, 1]);
+        assert!(client.try_remove_signer(&vec![&env, s2.clone(), s3.clone()], &s2).is_err());
+    }
+
+    #[test]
+    fn test_config_change_keeps_weights() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let s3 = Address::generate(&env);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        let weighted = vec![&env, s1.clone(), s2.clone(), s3.clone()];
+        client.initialize(&weighted, &vec![&env, 2, 1, 1], &2, &token_addr, &limits(10), &0, &s1, &metadata(&env));
+
+        // The heavy signer is sufficient alone
+        client.pause(&vec![&env, s1.clone()]);
+
+        // A threshold the given weights cannot reach is refused
+        assert!(client
+            .try_schedule_config_change(&vec![&env, s1.clone()], &weighted, &vec![&env, 1, 1, 1], &4, &10)
+            .is_err());
+        assert!(client
+            .try_schedule_config_change(&vec![&env, s1.clone()], &weighted, &vec![&env, 2, 1], &2, &10)
+            .is_err());
+
+        let tl_id = client.schedule_config_change(
+            &vec![&env, s1.clone()],
+            &vec![&env, s2.clone(), s3.clone()],
+            &vec![&env, 3, 1],
+            &3,
+            &10,
+        );
+        assert!(client
+            .try_schedule_config_change(&vec![&env, s1.clone()], &weighted, &vec![&env, 0, 1, 1], &1, &10)
+            .is_err());
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        client.execute_config_change(&tl_id);
+        assert_eq!(client.get_config().weights, vec![&env, 3, 1]);
+        client.unpause(&vec![&env, s2.clone()]);
+    }
 
+    #[test]
+    #[should_panic(expected = "Weights length mismatch")]
+    fn test_weights_must_match_signers() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone()];
-        // Attempt to unwrap more than balance
-        client.schedule_unwrap(&signers, &s1, &301, &10);
+        client.initialize(
+            &vec![&env, s1.clone(), s2.clone()],
+            &vec![&env, 2],
+            &2,
+            &token_addr, &limits(10), &0, &s1, &metadata(&env),
+        );
     }
 
-    // ---- New tests ----
+    #[test]
+    #[should_panic(expected = "Invalid threshold")]
+    fn test_threshold_above_total_weight_panics() {
+        let (env, contract_id, admin, s1, s2) = setup_env();
+        let token_addr = create_token(&env, &admin);
+        let client = MultisigAssetWrapperClient::new(&env, &contract_id);
+        client.initialize(
+            &vec![&env, s1.clone(), s2.clone()],
+            &vec![&env, 2, 1],
+            &4,
+            &token_addr, &limits(10), &0, &s1, &metadata(&env),
+        );
+    }
 
     #[test]
-    #[should_panic(expected = "Amount must be positive")]
-    fn test_wrap_zero_amount_panics() {
+    fn test_rescue_moves_only_surplus_of_underlying() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
+        let stray_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let sac = token::StellarAssetClient::new(&env, &token_addr);
+        sac.mint(&s1, &1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        client.wrap(&signers, &0);
+        client.wrap(&signers, &token_addr, &1_000);
+        // Sent straight to the contract, bypassing wrap
+        sac.mint(&contract_id, &250);
+        token::StellarAssetClient::new(&env, &stray_addr).mint(&contract_id, &40);
+
+        let rescuer = Address::generate(&env);
+        assert!(client.try_rescue(&signers, &token_addr, &rescuer, &251).is_err());
+        client.rescue(&signers, &token_addr, &rescuer, &250);
+        client.rescue(&signers, &stray_addr, &rescuer, &40);
+
+        let underlying = token::Client::new(&env, &token_addr);
+        assert_eq!(underlying.balance(&rescuer), 250);
+        assert_eq!(underlying.balance(&contract_id), 1_000);
+        assert_eq!(token::Client::new(&env, &stray_addr).balance(&rescuer), 40);
+        assert!(client.try_rescue(&signers, &token_addr, &rescuer, &1).is_err());
     }
 
     #[test]
-    #[should_panic(expected = "Duplicate signer detected")]
-    fn test_duplicate_signers_panics() {
+    fn test_pending_timelocks_capped_per_recipient() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
-        init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
-
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        // Pass duplicate addresses
-        let signers = vec![&env, s1.clone(), s1.clone()];
-        client.schedule_unwrap(&signers, &s1, &100, &10);
+        let signers = vec![&env, s1.clone()];
+        client.initialize(&vec![&env, s1.clone(), s2.clone()], &vec![&env], &1, &token_addr, &TimelockLimits { max_pending_per_recipient: 2, ..limits(10) }, &0, &s1, &metadata(&env));
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
+
+        let recipient = Address::generate(&env);
+        let first = client.schedule_unwrap(&signers, &token_addr, &s1, &recipient, &100, &10);
+        client.schedule_transfer(&signers, &token_addr, &s1, &recipient, &100, &10);
+        assert_eq!(client.pending_count(&recipient), 2);
+        assert!(client
+            .try_schedule_unwrap(&signers, &token_addr, &s1, &recipient, &100, &10)
+            .is_err());
+        // Other recipients are unaffected
+        client.schedule_unwrap(&signers, &token_addr, &s1, &s2, &100, &10);
+
+        client.cancel_timelock(&signers, &first, &false);
+        assert_eq!(client.pending_count(&recipient), 1);
+        let third = client.schedule_unwrap(&signers, &token_addr, &s1, &recipient, &100, &10);
+
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        client.execute_unwrap(&third, &false);
+        assert_eq!(client.pending_count(&recipient), 1);
     }
 
     #[test]
-    #[should_panic(expected = "Already executed")]
-    fn test_cancel_prevents_execution() {
+    fn test_pending_cap_zero_is_unlimited() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        let tl_id = client.schedule_unwrap(&signers, &s1, &200, &10);
-        client.cancel_timelock(&signers, &tl_id);
-
-        // Advance time past the unlock
-        env.ledger().with_mut(|li| {
-            li.timestamp = 1000;
-        });
-
-        // Try to execute cancelled timelock — should panic
-        client.execute_unwrap(&tl_id);
+        for _ in 0..5 {
+            client.schedule_unwrap(&signers, &token_addr, &s1, &s2, &10, &10);
+        }
+        assert_eq!(client.pending_count(&s2), 5);
     }
 
     #[test]
-    #[should_panic(expected = "Self-transfer not allowed")]
-    fn test_self_transfer_panics() {
+    fn test_reschedule_timelock_extends_unlock_time() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 500);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        // from == to should panic
-        client.schedule_transfer(&signers, &s1, &s1, &100, &10);
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &200, &100);
+        let scheduled = client.get_timelock(&tl_id).unlock_time;
+
+        env.ledger().with_mut(|li| li.timestamp += 50);
+        // Below the minimum, or earlier than the current unlock time
+        assert!(client.try_reschedule_timelock(&signers, &tl_id, &5).is_err());
+        assert!(client.try_reschedule_timelock(&signers, &tl_id, &20).is_err());
+
+        client.reschedule_timelock(&signers, &tl_id, &200);
+        assert_eq!(client.get_timelock(&tl_id).unlock_time, scheduled + 150);
+
+        env.ledger().with_mut(|li| li.timestamp += 60);
+        assert!(client.try_execute_unwrap(&tl_id, &false).is_err());
+        env.ledger().with_mut(|li| li.timestamp += 150);
+        client.execute_unwrap(&tl_id, &false);
+        assert!(client.try_reschedule_timelock(&signers, &tl_id, &200).is_err());
     }
 
     #[test]
-    #[should_panic(expected = "Delay exceeds maximum lock duration")]
-    fn test_delay_too_long_panics() {
+    fn test_daily_unwrap_cap_resets_after_window() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        // MAX_LOCK_DURATION is 31_536_000; exceed it
-        client.schedule_unwrap(&signers, &s1, &100, &31_536_001);
+        assert_eq!(client.daily_unwrap_remaining(&token_addr), i128::MAX);
+        client.set_daily_unwrap_cap(&signers, &token_addr, &300);
+
+        let first = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &200, &10);
+        let second = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &200, &10);
+        let small = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &10);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+
+        client.execute_unwrap(&first, &false);
+        assert_eq!(client.daily_unwrap_remaining(&token_addr), 100);
+        assert!(client.try_execute_unwrap(&second, &false).is_err());
+        // The batch skips what no longer fits instead of aborting
+        assert_eq!(client.execute_ready(&vec![&env, second, small], &false), vec![&env, small]);
+        assert_eq!(client.daily_unwrap_remaining(&token_addr), 0);
+
+        env.ledger().with_mut(|li| li.timestamp += DAILY_WINDOW);
+        assert_eq!(client.daily_unwrap_remaining(&token_addr), 300);
+        client.execute_unwrap(&second, &false);
+        assert_eq!(client.daily_unwrap_remaining(&token_addr), 100);
     }
 
     #[test]
-    #[should_panic(expected = "Contract is paused")]
-    fn test_pause_blocks_wrap() {
+    fn test_zero_daily_cap_disables_limit() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-
-        // Pause the contract
-        client.pause(&signers);
-
-        // Try to wrap — should panic
-        client.wrap(&signers, &100);
+        client.set_daily_unwrap_cap(&signers, &token_addr, &100);
+        let tl_id = client.schedule_daily_unwrap_cap(&signers, &token_addr, &0, &10);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        client.execute_daily_unwrap_cap(&tl_id);
+        assert!(client.try_set_daily_unwrap_cap(&signers, &token_addr, &-1).is_err());
+        assert_eq!(client.daily_unwrap_remaining(&token_addr), i128::MAX);
+
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &1_000, &10);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        client.execute_unwrap(&tl_id, &false);
+        assert_eq!(client.balance_of(&token_addr, &s1), 0);
     }
 
     #[test]
-    fn test_unpause_allows_operations() {
+    fn test_loosening_daily_cap_waits_for_timelock() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        seed_wrapped(&env, &contract_id, &token_addr, &s1, 1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-
-        // Seed a balance for s1
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
-
-        // Pause then unpause
-        client.pause(&signers);
-        client.unpause(&signers);
-
-        // schedule_unwrap should succeed after unpause
-        let tl_id = client.schedule_unwrap(&signers, &s1, &100, &10);
-        let tl = client.get_timelock(&tl_id);
-        assert_eq!(tl.amount, 100);
-        assert!(!tl.executed);
+        client.set_daily_unwrap_cap(&signers, &token_addr, &300);
+        client.set_daily_unwrap_cap(&signers, &token_addr, &200);
+
+        // Raising or removing the cap cannot happen in the same ledger as
+        // the unwrap it would let through
+        assert!(client.try_set_daily_unwrap_cap(&signers, &token_addr, &1_000).is_err());
+        assert!(client.try_set_daily_unwrap_cap(&signers, &token_addr, &0).is_err());
+        let unwrap_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &1_000, &10);
+        let raise_id = client.schedule_daily_unwrap_cap(&signers, &token_addr, &1_000, &10);
+        assert!(client.try_execute_daily_unwrap_cap(&raise_id).is_err());
+        assert_eq!(client.daily_unwrap_remaining(&token_addr), 200);
+
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        assert!(client.try_execute_unwrap(&unwrap_id, &false).is_err());
+        assert!(client.try_execute_unwrap(&raise_id, &false).is_err());
+        client.execute_daily_unwrap_cap(&raise_id);
+        assert_eq!(client.daily_unwrap_remaining(&token_addr), 1_000);
+        client.execute_unwrap(&unwrap_id, &false);
+
+        // A scheduled loosening can be cancelled like any timelock, and is
+        // not counted as locked value
+        let removal = client.schedule_daily_unwrap_cap(&signers, &token_addr, &0, &10);
+        assert_eq!(client.pending_locked_total(&token_addr), 0);
+        client.cancel_timelock(&signers, &removal, &false);
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        assert!(client.try_execute_daily_unwrap_cap(&removal).is_err());
     }
 
     #[test]
-    fn test_cancel_still_works_while_paused() {
+    fn test_balances_pages_tracked_holders() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        let sac = token::StellarAssetClient::new(&env, &token_addr);
+        sac.mint(&s1, &1_000);
+        sac.mint(&s2, &1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let signers = vec![&env, s1.clone()];
+        client.wrap(&vec![&env, s1.clone()], &token_addr, &300);
+        client.wrap(&vec![&env, s2.clone()], &token_addr, &200);
+        client.wrap(&vec![&env, s1.clone()], &token_addr, &100);
+        assert_eq!(client.holder_count(&token_addr), 2);
 
-        // Schedule a timelock before pausing
-        let tl_id = client.schedule_unwrap(&signers, &s1, &200, &10);
-
-        // Pause the contract
-        client.pause(&signers);
+        // A transfer adds its recipient; draining s2 hides it from pages
+        let s3 = Address::generate(&env);
+        let signers = vec![&env, s1.clone()];
+        let tl_id = client.schedule_transfer(&signers, &token_addr, &s2, &s3, &200, &10);
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1000;
+        });
+        client.execute_transfer(&tl_id, &false);
+        assert_eq!(client.holder_count(&token_addr), 3);
 
-        // Cancel should still work while paused
-        client.cancel_timelock(&signers, &tl_id);
+        assert_eq!(
+            client.balances(&token_addr, &0, &10),
+            vec![&env, (s1.clone(), 400_i128), (s3.clone(), 200_i128)]
+        );
+        assert_eq!(client.balances(&token_addr, &0, &1), vec![&env, (s1.clone(), 400_i128)]);
+        assert_eq!(client.balances(&token_addr, &2, &1), vec![&env, (s3.clone(), 200_i128)]);
+        assert_eq!(client.balances(&token_addr, &5, &10).len(), 0);
 
-        let tl = client.get_timelock(&tl_id);
-        assert!(tl.executed);
+        let page_total: i128 = client.balances(&token_addr, &0, &10).iter().map(|(_, b)| b).sum();
+        assert_eq!(page_total, client.total_supply(&token_addr));
     }
 
     #[test]
-    fn test_rotate_signers() {
+    fn test_holders_are_indexed_in_persistent_storage() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let sac = token::StellarAssetClient::new(&env, &token_addr);
+        sac.mint(&s1, &1_000);
+        sac.mint(&s2, &1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let current_signers = vec![&env, s1.clone()];
-
-        // Generate new signers
-        let new_s1 = Address::generate(&env);
-        let new_s2 = Address::generate(&env);
-        let new_signers = vec![&env, new_s1.clone(), new_s2.clone()];
+        client.wrap(&vec![&env, s1.clone()], &token_addr, &300);
+        client.wrap(&vec![&env, s2.clone()], &token_addr, &200);
 
-        // Rotate
-        client.rotate_signers(&current_signers, &new_signers, &1);
+        // Draining s2 and crediting it again keeps one entry per holder
+        client.transfer(&s2, &s1, &200);
+        client.transfer(&s1, &s2, &50);
+        assert_eq!(client.holder_count(&token_addr), 2);
 
-        // Seed a balance for new_s1
         env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(new_s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
+            let persistent = env.storage().persistent();
+            assert_eq!(persistent.get::<_, Address>(&DataKey::Holder(token_addr.clone(), 0)), Some(s1.clone()));
+            assert_eq!(persistent.get::<_, Address>(&DataKey::Holder(token_addr.clone(), 1)), Some(s2.clone()));
+            assert!(!persistent.has(&DataKey::Holder(token_addr.clone(), 2)));
+            assert_eq!(persistent.get_ttl(&DataKey::HolderCount(token_addr.clone())), PERSISTENT_TTL_EXTEND_TO);
         });
-
-        // New signers should be able to operate
-        let new_signer_vec = vec![&env, new_s1.clone()];
-        let tl_id = client.schedule_unwrap(&new_signer_vec, &new_s1, &100, &10);
-        let tl = client.get_timelock(&tl_id);
-        assert_eq!(tl.amount, 100);
+        assert_eq!(
+            client.balances(&token_addr, &1, &1),
+            vec![&env, (s2.clone(), 50_i128)]
+        );
     }
 
     #[test]
-    #[should_panic(expected = "Signer not authorized")]
-    fn test_rotate_signers_old_signers_rejected() {
+    fn test_list_timelocks_skips_executed_and_caps_page() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
+        let sac = token::StellarAssetClient::new(&env, &token_addr);
+        sac.mint(&s1, &1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
-        let current_signers = vec![&env, s1.clone()];
-
-        // Generate new signers
-        let new_s1 = Address::generate(&env);
-        let new_signers = vec![&env, new_s1.clone()];
-
-        // Rotate
-        client.rotate_signers(&current_signers, &new_signers, &1);
-
-        // Seed a balance for s1
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
-
-        // Old signer should be rejected
-        let old_signer_vec = vec![&env, s1.clone()];
-        client.schedule_unwrap(&old_signer_vec, &s1, &100, &10);
+        let signers = vec![&env, s1.clone()];
+        client.wrap(&signers, &token_addr, &500);
+        let ids = [0, 1, 2].map(|_| client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &10, &10));
+        client.cancel_timelock(&signers, &ids[1], &false);
+
+        let page = client.list_timelocks(&0, &10);
+        assert_eq!(page.timelocks.len(), 2);
+        assert_eq!(page.timelocks.get(0).unwrap().id, ids[0]);
+        assert_eq!(page.timelocks.get(1).unwrap().id, ids[2]);
+        assert_eq!(page.next_id, 3);
+
+        // The cancelled id still uses up its slot: a short page, but the
+        // cursor moves on
+        let first = client.list_timelocks(&1, &1);
+        assert_eq!(first.timelocks.len(), 0);
+        assert_eq!(first.next_id, 2);
+        let rest = client.list_timelocks(&first.next_id, &10);
+        assert_eq!(rest.timelocks.len(), 1);
+        assert_eq!(rest.timelocks.get(0).unwrap().id, ids[2]);
+        assert_eq!(rest.next_id, client.timelock_count());
+
+        let past_end = client.list_timelocks(&100, &10);
+        assert_eq!(past_end.timelocks.len(), 0);
+        assert_eq!(past_end.next_id, 100);
+        assert_eq!(client.list_timelocks(&0, &0).next_id, 0);
     }
 
     #[test]
-    fn test_execute_transfer_with_time_advancement() {
+    fn test_list_timelocks_limit_is_capped() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
-
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        let tl_id = client.schedule_transfer(&signers, &s1, &s2, &200, &10);
-
-        // Advance ledger time past the unlock
-        env.ledger().with_mut(|li| {
-            li.timestamp = 1000;
-        });
-
-        client.execute_transfer(&tl_id);
-
-        assert_eq!(client.balance(&s1), 300);
-        assert_eq!(client.balance(&s2), 200);
+        for _ in 0..(MAX_TIMELOCKS_PAGE + 5) {
+            client.schedule_config_change(&signers, &vec![&env, s2.clone()], &vec![&env], &1, &10);
+        }
+        let page = client.list_timelocks(&0, &u32::MAX);
+        assert_eq!(page.timelocks.len(), MAX_TIMELOCKS_PAGE);
+        assert_eq!(page.next_id, MAX_TIMELOCKS_PAGE as u64);
     }
 
     #[test]
-    #[should_panic(expected = "Timelock not expired")]
-    fn test_execute_transfer_before_unlock_panics() {
+    fn test_list_timelocks_bounds_ids_examined() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
-
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        let tl_id = client.schedule_transfer(&signers, &s1, &s2, &200, &10);
 
-        // Do NOT advance time — try to execute immediately
-        client.execute_transfer(&tl_id);
+        // A long run of settled IDs ahead of the one still pending
+        for _ in 0..(MAX_TIMELOCKS_PAGE + 5) {
+            let id = client.schedule_config_change(&signers, &vec![&env, s2.clone()], &vec![&env], &1, &10);
+            client.cancel_timelock(&signers, &id, &false);
+        }
+        let pending = client.schedule_config_change(&signers, &vec![&env, s2.clone()], &vec![&env], &1, &10);
+
+        let first = client.list_timelocks(&0, &u32::MAX);
+        assert_eq!(first.timelocks.len(), 0);
+        assert_eq!(first.next_id, MAX_TIMELOCKS_PAGE as u64);
+
+        let second = client.list_timelocks(&first.next_id, &u32::MAX);
+        assert_eq!(second.timelocks.len(), 1);
+        assert_eq!(second.timelocks.get(0).unwrap().id, pending);
+        assert_eq!(second.next_id, client.timelock_count());
     }
 
     #[test]
-    #[should_panic(expected = "Already executed")]
-    fn test_double_execute_transfer_panics() {
+    fn test_timelock_remaining_counts_down_to_zero() {
         let (env, contract_id, admin, s1, s2) = setup_env();
         let token_addr = create_token(&env, &admin);
         init_contract(&env, &contract_id, &token_addr, &s1, &s2);
-
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Balance(s1.clone()), &500_i128);
-            let mut wa: WrappedAsset = env
-                .storage()
-                .instance()
-                .get(&DataKey::WrappedAsset)
-                .unwrap();
-            wa.total_wrapped = 500;
-            env.storage()
-                .instance()
-                .set(&DataKey::WrappedAsset, &wa);
-        });
+        let sac = token::StellarAssetClient::new(&env, &token_addr);
+        sac.mint(&s1, &1_000);
 
         let client = MultisigAssetWrapperClient::new(&env, &contract_id);
         let signers = vec![&env, s1.clone()];
-        let tl_id = client.schedule_transfer(&signers, &s1, &s2, &200, &10);
-
-        // Advance time past the unlock
+        client.wrap(&signers, &token_addr, &500);
         env.ledger().with_mut(|li| {
             li.timestamp = 1000;
         });
+        let tl_id = client.schedule_unwrap(&signers, &token_addr, &s1, &s1, &100, &60);
+        assert_eq!(client.timelock_remaining(&tl_id), 60);
 
-        // Execute once — should succeed
-        client.execute_transfer(&tl_id);
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1045;
+        });
+        assert_eq!(client.timelock_remaining(&tl_id), 15);
 
-        // Execute again — should panic
-        client.execute_transfer(&tl_id);
+        // Past the unlock time, and after execution, nothing remains
+        env.ledger().with_mut(|li| {
+            li.timestamp = 2000;
+        });
+        assert_eq!(client.timelock_remaining(&tl_id), 0);
+        client.execute_unwrap(&tl_id, &false);
+        assert_eq!(client.timelock_remaining(&tl_id), 0);
+
+        // Unknown IDs read as unlocked rather than panicking
+        assert_eq!(client.timelock_remaining(&999), 0);
     }
 }
@@ -8,9 +8,9 @@
 use std::path::PathBuf;
 use std::process::{Command, Output};
 
-const TESTNET_RPC: &str = "https://soroban-testnet.stellar.org";
-const TESTNET_NETWORK_PASSPHRASE: &str = "Test SDF Network ; September 2015";
-const FRIENDBOT_URL: &str = "https://friendbot.stellar.org";
+pub const TESTNET_RPC: &str = "https://soroban-testnet.stellar.org";
+pub const TESTNET_NETWORK_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+pub const FRIENDBOT_URL: &str = "https://friendbot.stellar.org";
 
 /// Result type for testnet operations.
 pub type TestResult<T> = Result<T, Box<dyn std::error::Error>>;
@@ -70,8 +70,29 @@ pub fn contract_wasm_path() -> PathBuf {
     path
 }
 
+/// Number of times `deploy_contract_with_salt` re-runs the CLI before giving up.
+const DEPLOY_ATTEMPTS: u32 = 3;
+
 /// Deploy the contract WASM to testnet using a source identity. Returns the contract ID.
+///
+/// A random salt is chosen so the resulting contract ID is known before the
+/// deploy runs; see [`deploy_contract_with_salt`].
 pub fn deploy_contract(source_identity: &str) -> TestResult<String> {
+    let salt: String = rand::random::<[u8; 32]>()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    deploy_contract_with_salt(source_identity, &salt)
+}
+
+/// Deploy the contract WASM with a fixed salt. Returns the contract ID.
+///
+/// The CLI can time out after the deploy has landed on-chain. Because the
+/// contract ID is derived from the deployer and salt, a failed attempt is
+/// checked against the network and the ID recovered if the contract exists.
+/// Retrying with the same salt is idempotent: it either deploys once or finds
+/// the contract an earlier attempt created.
+pub fn deploy_contract_with_salt(source_identity: &str, salt: &str) -> TestResult<String> {
     let wasm_path = contract_wasm_path();
     if !wasm_path.exists() {
         return Err(format!(
@@ -81,20 +102,116 @@ pub fn deploy_contract(source_identity: &str) -> TestResult<String> {
         .into());
     }
 
-    let output = stellar_cmd(&[
+    let id_output = stellar_cmd(&[
         "contract",
-        "deploy",
-        "--wasm",
-        wasm_path.to_str().unwrap(),
+        "id",
+        "wasm",
+        "--salt",
+        salt,
         "--source",
         source_identity,
         "--network",
         "testnet",
     ])?;
-    assert_cmd_success(&output, "contract deploy");
+    assert_cmd_success(&id_output, "contract id wasm");
+    let expected_id = String::from_utf8(id_output.stdout)?.trim().to_string();
 
-    let contract_id = String::from_utf8(output.stdout)?.trim().to_string();
-    Ok(contract_id)
+    let mut last_err = String::new();
+    for attempt in 1..=DEPLOY_ATTEMPTS {
+        let output = stellar_cmd(&[
+            "contract",
+            "deploy",
+            "--wasm",
+            wasm_path.to_str().unwrap(),
+            "--salt",
+            salt,
+            "--source",
+            source_identity,
+            "--network",
+            "testnet",
+        ])?;
+
+        match resolve_deploy_outcome(&output_to_cmd(&output), &expected_id, contract_exists) {
+            Ok(id) => return Ok(id),
+            Err(e) => {
+                eprintln!("contract deploy attempt {attempt}/{DEPLOY_ATTEMPTS} failed: {e}");
+                last_err = e.to_string();
+            }
+        }
+    }
+
+    Err(format!("contract deploy failed after {DEPLOY_ATTEMPTS} attempts: {last_err}").into())
+}
+
+/// Captured result of a CLI invocation, decoupled from `std::process::Output`
+/// so deploy recovery can be exercised without running the CLI.
+pub struct CmdOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+fn output_to_cmd(output: &Output) -> CmdOutput {
+    CmdOutput {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    }
+}
+
+/// Decide the contract ID for a deploy attempt.
+///
+/// On success the ID printed by the CLI is used. On failure, any contract ID
+/// mentioned in the output (or else `expected_id`) is checked with
+/// `is_deployed`, and returned if the contract is live on-chain.
+pub fn resolve_deploy_outcome(
+    output: &CmdOutput,
+    expected_id: &str,
+    mut is_deployed: impl FnMut(&str) -> bool,
+) -> TestResult<String> {
+    if output.success {
+        return Ok(extract_contract_id(&output.stdout)
+            .unwrap_or_else(|| output.stdout.trim().to_string()));
+    }
+
+    let combined = format!("{}\n{}", output.stdout, output.stderr);
+    let candidate = extract_contract_id(&combined).unwrap_or_else(|| expected_id.to_string());
+    if is_deployed(&candidate) {
+        eprintln!("contract deploy reported failure but {candidate} exists on-chain; recovered");
+        return Ok(candidate);
+    }
+
+    Err(format!(
+        "stellar contract deploy failed:\nstdout: {}\nstderr: {}",
+        output.stdout.trim(),
+        output.stderr.trim()
+    )
+    .into())
+}
+
+/// Find the first contract strkey (`C` + 55 base32 characters) in `text`.
+pub fn extract_contract_id(text: &str) -> Option<String> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .find(|token| {
+            token.len() == 56
+                && token.starts_with('C')
+                && token.chars().all(|c| matches!(c, 'A'..='Z' | '2'..='7'))
+        })
+        .map(str::to_string)
+}
+
+/// Whether a contract with `contract_id` is deployed on testnet.
+pub fn contract_exists(contract_id: &str) -> bool {
+    stellar_cmd(&[
+        "contract",
+        "fetch",
+        "--id",
+        contract_id,
+        "--network",
+        "testnet",
+    ])
+    .map(|o| o.status.success())
+    .unwrap_or(false)
 }
 
 /// Invoke a contract function on testnet. Returns stdout as a string.
@@ -155,46 +272,269 @@ pub fn invoke_expect_fail(
 
 /// Deploy the native XLM Stellar Asset Contract and return its contract ID.
 pub fn deploy_sac_native(source_identity: &str) -> TestResult<String> {
+    deploy_sac(source_identity, "native")
+}
+
+/// Deploy the Stellar Asset Contract for `asset` (`native` or `CODE:ISSUER`)
+/// and return its contract ID.
+///
+/// The ID is derived with `stellar contract id asset`, which is deterministic
+/// and independent of whether this deploy created the SAC or found it
+/// already deployed; see [`resolve_sac_id`].
+pub fn deploy_sac(source_identity: &str, asset: &str) -> TestResult<String> {
+    let derived = stellar_cmd(&[
+        "contract",
+        "id",
+        "asset",
+        "--asset",
+        asset,
+        "--network",
+        "testnet",
+    ])
+    .ok()
+    .map(|o| output_to_cmd(&o));
+
     let output = stellar_cmd(&[
         "contract",
         "asset",
         "deploy",
         "--asset",
-        "native",
+        asset,
         "--source",
         source_identity,
         "--network",
         "testnet",
     ])?;
 
-    // SAC deploy may "fail" if already deployed, but still returns the ID
-    let combined = format!(
-        "{}{}",
-        String::from_utf8_lossy(&output.stdout),
-        String::from_utf8_lossy(&output.stderr)
-    );
+    resolve_sac_id(&output_to_cmd(&output), derived.as_ref(), contract_exists)
+}
+
+/// Decide the SAC contract ID after a deploy attempt.
+///
+/// The ID derived by `contract id asset` is preferred. It is used as-is when
+/// the deploy succeeded, and when the deploy failed (typically "already
+/// exists") it is used if `is_deployed` confirms it is live. Only when no
+/// ID could be derived is one scraped from the deploy output.
+pub fn resolve_sac_id(
+    deploy: &CmdOutput,
+    derived: Option<&CmdOutput>,
+    mut is_deployed: impl FnMut(&str) -> bool,
+) -> TestResult<String> {
+    let derived_id = derived
+        .filter(|d| d.success)
+        .and_then(|d| extract_contract_id(&d.stdout));
+    if let Some(id) = derived_id {
+        if deploy.success || is_deployed(&id) {
+            return Ok(id);
+        }
+        return Err(format!(
+            "SAC deploy failed and {id} is not deployed:\nstderr: {}",
+            deploy.stderr.trim()
+        )
+        .into());
+    }
 
-    // Extract contract ID — it's the 56-char hex string on its own line
+    // Fallback: scrape the deploy output. The SAC deploy may "fail" if
+    // already deployed but still print the ID.
+    let combined = format!("{}\n{}", deploy.stdout, deploy.stderr);
+    if let Some(id) = extract_contract_id(&combined) {
+        return Ok(id);
+    }
     for line in combined.lines() {
         let trimmed = line.trim();
         if trimmed.len() == 56 && trimmed.chars().all(|c| c.is_ascii_hexdigit()) {
             return Ok(trimmed.to_string());
         }
-        // Also accept C-prefixed contract addresses
-        if trimmed.starts_with('C') && trimmed.len() == 56 {
-            return Ok(trimmed.to_string());
+    }
+    // If the command succeeded, stdout likely IS the contract id
+    if deploy.success && !deploy.stdout.trim().is_empty() {
+        return Ok(deploy.stdout.trim().to_string());
+    }
+
+    Err(format!("Could not extract SAC contract ID from: {combined}").into())
+}
+
+/// Known panic messages raised by the `MultisigAssetWrapper` contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractPanic {
+    AlreadyInitialized,
+    InvalidThreshold,
+    NewSignersEmpty,
+    InvalidNewThreshold,
+    AmountNotPositive,
+    DelayTooShort,
+    DelayExceedsMaximum,
+    InsufficientWrappedBalance,
+    AlreadyExecuted,
+    TimelockNotExpired,
+    InsufficientBalance,
+    SelfTransfer,
+    ContractPaused,
+    InsufficientSigners,
+    DuplicateSigner,
+    SignerNotAuthorized,
+}
+
+impl ContractPanic {
+    pub const ALL: [ContractPanic; 16] = [
+        ContractPanic::AlreadyInitialized,
+        ContractPanic::InvalidThreshold,
+        ContractPanic::NewSignersEmpty,
+        ContractPanic::InvalidNewThreshold,
+        ContractPanic::AmountNotPositive,
+        ContractPanic::DelayTooShort,
+        ContractPanic::DelayExceedsMaximum,
+        ContractPanic::InsufficientWrappedBalance,
+        ContractPanic::AlreadyExecuted,
+        ContractPanic::TimelockNotExpired,
+        ContractPanic::InsufficientBalance,
+        ContractPanic::SelfTransfer,
+        ContractPanic::ContractPaused,
+        ContractPanic::InsufficientSigners,
+        ContractPanic::DuplicateSigner,
+        ContractPanic::SignerNotAuthorized,
+    ];
+
+    /// The panic string as written in the contract.
+    pub fn message(self) -> &'static str {
+        match self {
+            ContractPanic::AlreadyInitialized => "Already initialized",
+            ContractPanic::InvalidThreshold => "Invalid threshold",
+            ContractPanic::NewSignersEmpty => "New signers must not be empty",
+            ContractPanic::InvalidNewThreshold => "Invalid new threshold",
+            ContractPanic::AmountNotPositive => "Amount must be positive",
+            ContractPanic::DelayTooShort => "Delay too short",
+            ContractPanic::DelayExceedsMaximum => "Delay exceeds maximum lock duration",
+            ContractPanic::InsufficientWrappedBalance => "Insufficient wrapped balance",
+            ContractPanic::AlreadyExecuted => "Already executed",
+            ContractPanic::TimelockNotExpired => "Timelock not expired",
+            ContractPanic::InsufficientBalance => "Insufficient balance",
+            ContractPanic::SelfTransfer => "Self-transfer not allowed",
+            ContractPanic::ContractPaused => "Contract is paused",
+            ContractPanic::InsufficientSigners => "Insufficient signers",
+            ContractPanic::DuplicateSigner => "Duplicate signer detected",
+            ContractPanic::SignerNotAuthorized => "Signer not authorized",
         }
     }
 
-    // If the command succeeded, stdout likely IS the contract id
-    if output.status.success() {
-        let id = String::from_utf8(output.stdout)?.trim().to_string();
-        if !id.is_empty() {
-            return Ok(id);
+    /// Find a known panic message in `text`.  Longer messages are checked
+    /// first so "Insufficient wrapped balance" is not reported as
+    /// "Insufficient balance".
+    pub fn find_in(text: &str) -> Option<ContractPanic> {
+        let mut known = Self::ALL;
+        known.sort_by_key(|p| std::cmp::Reverse(p.message().len()));
+        known.into_iter().find(|p| text.contains(p.message()))
+    }
+}
+
+/// Structured view of a failed `stellar contract invoke`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvokeFailure {
+    /// `N` from `Error(Contract, #N)`, for contracts using `contracterror` codes.
+    pub contract_code: Option<u32>,
+    /// Host error as `(type, code)`, e.g. `("WasmVm", "InvalidAction")`.
+    pub host_error: Option<(String, String)>,
+    /// Known contract panic message, if one appears in the output.
+    pub panic: Option<ContractPanic>,
+    /// The original stderr.
+    pub raw: String,
+}
+
+/// Parse the stderr of a failed invocation into an [`InvokeFailure`].
+pub fn parse_invoke_failure(stderr: &str) -> InvokeFailure {
+    let mut contract_code = None;
+    let mut host_error = None;
+
+    let mut rest = stderr;
+    while let Some(start) = rest.find("Error(") {
+        let after = &rest[start + "Error(".len()..];
+        let Some(end) = after.find(')') else { break };
+        let mut parts = after[..end].splitn(2, ',').map(str::trim);
+        if let (Some(kind), Some(code)) = (parts.next(), parts.next()) {
+            if kind == "Contract" {
+                if let Some(n) = code.strip_prefix('#').and_then(|n| n.parse().ok()) {
+                    contract_code.get_or_insert(n);
+                }
+            } else if !kind.is_empty() && kind.chars().all(|c| c.is_ascii_alphanumeric()) {
+                host_error.get_or_insert((kind.to_string(), code.to_string()));
+            }
         }
+        rest = &after[end..];
     }
 
-    Err(format!("Could not extract SAC contract ID from: {combined}").into())
+    InvokeFailure {
+        contract_code,
+        host_error,
+        panic: ContractPanic::find_in(stderr),
+        raw: stderr.to_string(),
+    }
+}
+
+/// Parse the JSON the CLI prints for a `Vec<(Address, i128)>` result (such
+/// as `balances`), where each amount may be a number or a decimal string.
+pub fn parse_address_amounts(json: &str) -> TestResult<Vec<(String, i128)>> {
+    let value: serde_json::Value = serde_json::from_str(json.trim())?;
+    let entries = value.as_array().ok_or("expected a JSON array")?;
+    entries
+        .iter()
+        .map(|entry| {
+            let (address, amount) = match entry.as_array().map(Vec::as_slice) {
+                Some([address, amount]) => (address, amount),
+                _ => return Err(format!("expected an [address, amount] pair, got {entry}").into()),
+            };
+            let address = address.as_str().ok_or("address must be a string")?.to_string();
+            let amount = match amount {
+                serde_json::Value::String(s) => s.parse()?,
+                serde_json::Value::Number(n) => n.to_string().parse()?,
+                other => return Err(format!("unexpected amount {other}").into()),
+            };
+            Ok((address, amount))
+        })
+        .collect()
+}
+
+/// Establish a trustline from `source_identity` to a `CODE:ISSUER` asset.
+pub fn change_trust(source_identity: &str, asset: &str) -> TestResult<()> {
+    let output = stellar_cmd(&[
+        "tx",
+        "new",
+        "change-trust",
+        "--source",
+        source_identity,
+        "--line",
+        asset,
+        "--network",
+        "testnet",
+    ])?;
+    assert_cmd_success(&output, "tx new change-trust");
+    Ok(())
+}
+
+/// Send `amount` stroops of `asset` from `source_identity` to `destination`.
+pub fn pay_asset(
+    source_identity: &str,
+    destination: &str,
+    asset: &str,
+    amount: i64,
+) -> TestResult<()> {
+    let amount = amount.to_string();
+    let output = stellar_cmd(&[
+        "tx",
+        "new",
+        "payment",
+        "--source",
+        source_identity,
+        "--destination",
+        destination,
+        "--asset",
+        asset,
+        "--amount",
+        &amount,
+        "--network",
+        "testnet",
+    ])?;
+    assert_cmd_success(&output, "tx new payment");
+    Ok(())
 }
 
 fn stellar_cmd(args: &[&str]) -> TestResult<Output> {
@@ -211,3 +551,207 @@ fn assert_cmd_success(output: &Output, context: &str) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT_ID: &str = "CAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6N4O";
+
+    fn failed(stdout: &str, stderr: &str) -> CmdOutput {
+        CmdOutput {
+            success: false,
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+        }
+    }
+
+    fn succeeded(stdout: &str) -> CmdOutput {
+        CmdOutput {
+            success: true,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_sac_first_deploy_uses_derived_id() {
+        let deploy = succeeded(&format!("{CONTRACT_ID}\n"));
+        let derived = succeeded(&format!("{CONTRACT_ID}\n"));
+        let id = resolve_sac_id(&deploy, Some(&derived), |_| panic!("no lookup on success"))
+            .unwrap();
+        assert_eq!(id, CONTRACT_ID);
+    }
+
+    #[test]
+    fn test_sac_already_deployed_uses_derived_id() {
+        let deploy = failed(
+            "",
+            "❌ error: transaction simulation failed: HostError: Error(Storage, ExistingValue)\n\
+             contract already exists",
+        );
+        let derived = succeeded(&format!("{CONTRACT_ID}\n"));
+        let mut looked_up = None;
+        let id = resolve_sac_id(&deploy, Some(&derived), |c| {
+            looked_up = Some(c.to_string());
+            true
+        })
+        .unwrap();
+        assert_eq!(id, CONTRACT_ID);
+        assert_eq!(looked_up.as_deref(), Some(CONTRACT_ID));
+    }
+
+    #[test]
+    fn test_sac_failed_deploy_not_on_chain_is_error() {
+        let deploy = failed("", "error: insufficient balance");
+        let derived = succeeded(CONTRACT_ID);
+        assert!(resolve_sac_id(&deploy, Some(&derived), |_| false).is_err());
+    }
+
+    #[test]
+    fn test_sac_falls_back_to_scraping_output() {
+        let deploy = failed("", &format!("contract already exists: {CONTRACT_ID}"));
+        let id = resolve_sac_id(&deploy, None, |_| panic!("no lookup when scraping")).unwrap();
+        assert_eq!(id, CONTRACT_ID);
+
+        let hex = "ab".repeat(28);
+        let id = resolve_sac_id(&succeeded(&format!("{hex}\n")), Some(&failed("", "no")), |_| false)
+            .unwrap();
+        assert_eq!(id, hex);
+
+        assert!(resolve_sac_id(&failed("", "boom"), None, |_| true).is_err());
+    }
+
+    #[test]
+    fn test_successful_deploy_uses_stdout() {
+        let output = CmdOutput {
+            success: true,
+            stdout: format!("{CONTRACT_ID}\n"),
+            stderr: String::new(),
+        };
+        let id = resolve_deploy_outcome(&output, "unused", |_| panic!("no lookup on success"))
+            .unwrap();
+        assert_eq!(id, CONTRACT_ID);
+    }
+
+    #[test]
+    fn test_timeout_after_success_recovers_expected_id() {
+        let output = failed(
+            "",
+            "error: transaction submission timeout\nwaiting for transaction result",
+        );
+        let mut lookups = Vec::new();
+        let id = resolve_deploy_outcome(&output, CONTRACT_ID, |id| {
+            lookups.push(id.to_string());
+            true
+        })
+        .unwrap();
+        assert_eq!(id, CONTRACT_ID);
+        assert_eq!(lookups, vec![CONTRACT_ID.to_string()]);
+    }
+
+    #[test]
+    fn test_failure_prefers_id_from_output() {
+        let output = failed(
+            "",
+            &format!("Deploying... https://stellar.expert/explorer/testnet/contract/{CONTRACT_ID}\nerror: timeout"),
+        );
+        let id = resolve_deploy_outcome(&output, "CEXPECTED", |id| id == CONTRACT_ID).unwrap();
+        assert_eq!(id, CONTRACT_ID);
+    }
+
+    #[test]
+    fn test_failure_without_onchain_contract_is_error() {
+        let output = failed("", "error: insufficient balance");
+        assert!(resolve_deploy_outcome(&output, CONTRACT_ID, |_| false).is_err());
+    }
+
+    #[test]
+    fn test_retry_after_partial_deploy_is_idempotent() {
+        // The second attempt fails with "already exists" because the first
+        // landed; recovery returns the same ID rather than deploying again.
+        let first = failed("", "error: timeout");
+        let second = failed("", "error: contract already exists");
+        let first_id = resolve_deploy_outcome(&first, CONTRACT_ID, |_| true).unwrap();
+        let second_id = resolve_deploy_outcome(&second, CONTRACT_ID, |_| true).unwrap();
+        assert_eq!(first_id, second_id);
+    }
+
+    #[test]
+    fn test_extract_contract_id_ignores_other_strkeys() {
+        let text = "source GAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB7JZX";
+        assert_eq!(extract_contract_id(text), None);
+        assert_eq!(
+            extract_contract_id(&format!("id: {CONTRACT_ID}.")),
+            Some(CONTRACT_ID.to_string())
+        );
+    }
+
+    const PANIC_STDERR: &str = r#"❌ error: transaction simulation failed: HostError: Error(WasmVm, InvalidAction)
+
+Event log (newest first):
+   0: [Diagnostic Event] contract:CAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6N4O, topics:[error, Error(WasmVm, InvalidAction)], data:["VM call trapped: UnreachableCodeReached", execute_unwrap]
+   1: [Diagnostic Event] contract:CAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6N4O, topics:[log], data:"caller panicked: Already executed"
+"#;
+
+    #[test]
+    fn test_parse_address_amounts() {
+        let json = r#"[["GAAA","400"],["GBBB",200]]"#;
+        assert_eq!(
+            parse_address_amounts(json).unwrap(),
+            vec![("GAAA".to_string(), 400), ("GBBB".to_string(), 200)]
+        );
+        assert!(parse_address_amounts("[]").unwrap().is_empty());
+        assert!(parse_address_amounts(r#"[["GAAA"]]"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_panic_with_host_error() {
+        let failure = parse_invoke_failure(PANIC_STDERR);
+        assert_eq!(failure.panic, Some(ContractPanic::AlreadyExecuted));
+        assert_eq!(
+            failure.host_error,
+            Some(("WasmVm".to_string(), "InvalidAction".to_string()))
+        );
+        assert_eq!(failure.contract_code, None);
+    }
+
+    #[test]
+    fn test_parse_contract_error_code() {
+        let stderr = "error: transaction simulation failed: HostError: Error(Contract, #7)\n\
+                      topics:[error, Error(Contract, #7)]";
+        let failure = parse_invoke_failure(stderr);
+        assert_eq!(failure.contract_code, Some(7));
+        assert_eq!(failure.host_error, None);
+        assert_eq!(failure.panic, None);
+    }
+
+    #[test]
+    fn test_parse_known_panics() {
+        for (text, expected) in [
+            ("panicked at src/lib.rs:237: Delay too short", ContractPanic::DelayTooShort),
+            ("data:\"Insufficient signers\"", ContractPanic::InsufficientSigners),
+            ("Timelock not expired", ContractPanic::TimelockNotExpired),
+            ("Insufficient wrapped balance", ContractPanic::InsufficientWrappedBalance),
+            ("Insufficient balance", ContractPanic::InsufficientBalance),
+        ] {
+            assert_eq!(parse_invoke_failure(text).panic, Some(expected), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_parse_unrecognized_failure() {
+        let failure = parse_invoke_failure("error: network unreachable");
+        assert_eq!(failure.contract_code, None);
+        assert_eq!(failure.host_error, None);
+        assert_eq!(failure.panic, None);
+        assert_eq!(failure.raw, "error: network unreachable");
+    }
+
+    #[test]
+    fn test_panic_messages_roundtrip() {
+        for panic in ContractPanic::ALL {
+            assert_eq!(ContractPanic::find_in(panic.message()), Some(panic));
+        }
+    }
+}
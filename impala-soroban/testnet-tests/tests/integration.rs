@@ -46,11 +46,21 @@ impl TestFixture {
     }
 
     fn initialize(&self, threshold: u32, min_lock_duration: u64) {
+        self.initialize_with_fee(threshold, min_lock_duration, 0);
+    }
+
+    /// Initialize with an unwrap fee paid to the deployer.
+    fn initialize_with_fee(&self, threshold: u32, min_lock_duration: u64, unwrap_fee_bps: u32) {
         let signers_json = format!(
             r#"["{}","{}"]"#,
             self.signer1.public_key, self.signer2.public_key
         );
 
+        let limits_json = format!(
+            r#"{{"execution_window":0,"max_lock_duration":0,"max_pending_per_recipient":0,"min_lock_duration":{}}}"#,
+            min_lock_duration
+        );
+
         invoke(
             &self.contract_id,
             &self.deployer.name,
@@ -58,18 +68,30 @@ impl TestFixture {
             &[
                 "--signers",
                 &signers_json,
+                "--weights",
+                "[]",
                 "--threshold",
                 &threshold.to_string(),
                 "--underlying_token",
                 &self.native_sac_id,
-                "--min_lock_duration",
-                &min_lock_duration.to_string(),
+                "--limits",
+                &limits_json,
+                "--unwrap_fee_bps",
+                &unwrap_fee_bps.to_string(),
+                "--fee_collector",
+                &self.deployer.public_key,
+                "--metadata",
+                r#"{"decimal":7,"name":"Wrapped XLM","symbol":"wXLM"}"#,
             ],
         )
         .expect("initialize contract");
     }
 
     fn wrap_tokens(&self, signer: &TestIdentity, amount: i128) {
+        self.wrap_token(&self.native_sac_id, signer, amount);
+    }
+
+    fn wrap_token(&self, token: &str, signer: &TestIdentity, amount: i128) {
         let signers_json = format!(r#"["{}"]"#, signer.public_key);
 
         invoke(
@@ -79,6 +101,8 @@ impl TestFixture {
             &[
                 "--signers",
                 &signers_json,
+                "--token",
+                token,
                 "--amount",
                 &amount.to_string(),
             ],
@@ -86,12 +110,27 @@ impl TestFixture {
         .expect("wrap tokens");
     }
 
+    fn add_token(&self, token: &str) {
+        let signers_json = format!(r#"["{}"]"#, self.signer1.public_key);
+        invoke(
+            &self.contract_id,
+            &self.signer1.name,
+            "add_token",
+            &["--signers", &signers_json, "--token", token],
+        )
+        .expect("add_token");
+    }
+
     fn query_balance(&self, address: &str) -> i128 {
+        self.query_balance_of(&self.native_sac_id, address)
+    }
+
+    fn query_balance_of(&self, token: &str, address: &str) -> i128 {
         let result = invoke(
             &self.contract_id,
             &self.deployer.name,
-            "balance",
-            &["--address", address],
+            "balance_of",
+            &["--token", token, "--address", address],
         )
         .expect("query balance");
 
@@ -102,12 +141,118 @@ impl TestFixture {
             .unwrap_or(0)
     }
 
+    /// One `balances` page for `token`.
+    fn query_balances(&self, token: &str, start: u32, limit: u32) -> Vec<(String, i128)> {
+        let result = invoke(
+            &self.contract_id,
+            &self.deployer.name,
+            "balances",
+            &[
+                "--token",
+                token,
+                "--start",
+                &start.to_string(),
+                "--limit",
+                &limit.to_string(),
+            ],
+        )
+        .expect("query balances");
+        parse_address_amounts(&result).expect("balances JSON")
+    }
+
+    /// Seconds until `timelock_id` unlocks, per the contract's ledger clock.
+    fn query_timelock_remaining(&self, timelock_id: &str) -> u64 {
+        let result = invoke(
+            &self.contract_id,
+            &self.deployer.name,
+            "timelock_remaining",
+            &["--timelock_id", timelock_id],
+        )
+        .expect("query timelock_remaining");
+        result.trim().trim_matches('"').parse().expect("timelock_remaining u64")
+    }
+
+    /// Returns `(next_timelock_id, min_lock_duration, max_lock_duration)`.
+    fn query_scheduling_info(&self) -> (u64, u64, u64) {
+        let result = invoke(
+            &self.contract_id,
+            &self.deployer.name,
+            "get_scheduling_info",
+            &[],
+        )
+        .expect("query get_scheduling_info");
+
+        let info: serde_json::Value =
+            serde_json::from_str(result.trim()).expect("scheduling info JSON");
+        let field = |name: &str| -> u64 {
+            match &info[name] {
+                serde_json::Value::Number(n) => n.as_u64().expect(name),
+                serde_json::Value::String(s) => s.parse().expect(name),
+                other => panic!("unexpected {name}: {other}"),
+            }
+        };
+        (
+            field("next_timelock_id"),
+            field("min_lock_duration"),
+            field("max_lock_duration"),
+        )
+    }
+
+    fn query_is_signer(&self, address: &str) -> bool {
+        let result = invoke(
+            &self.contract_id,
+            &self.deployer.name,
+            "is_signer",
+            &["--address", address],
+        )
+        .expect("query is_signer");
+        result.trim().parse().expect("is_signer bool")
+    }
+
+    /// Returns `(signers, threshold)` from `get_config`.
+    fn query_config(&self) -> (Vec<String>, u32) {
+        let result = invoke(&self.contract_id, &self.deployer.name, "get_config", &[])
+            .expect("query get_config");
+        let config: serde_json::Value =
+            serde_json::from_str(result.trim()).expect("config JSON");
+        let signers = config["signers"]
+            .as_array()
+            .expect("signers array")
+            .iter()
+            .map(|s| s.as_str().expect("signer address").to_string())
+            .collect();
+        let threshold = config["threshold"].as_u64().expect("threshold") as u32;
+        (signers, threshold)
+    }
+
+    fn query_min_lock_duration(&self) -> u64 {
+        let result = invoke(&self.contract_id, &self.deployer.name, "get_min_lock_duration", &[])
+            .expect("query get_min_lock_duration");
+        result.trim().trim_matches('"').parse().expect("min lock duration u64")
+    }
+
+    fn query_total_fees_collected(&self) -> i128 {
+        let result = invoke(&self.contract_id, &self.deployer.name, "total_fees_collected", &[])
+            .expect("query total_fees_collected");
+        result.trim().trim_matches('"').parse().expect("total_fees_collected i128")
+    }
+
+    fn query_threshold(&self) -> u32 {
+        let result = invoke(&self.contract_id, &self.deployer.name, "threshold", &[])
+            .expect("query threshold");
+        result.trim().trim_matches('"').parse().expect("threshold u32")
+    }
+
     fn query_total_supply(&self) -> i128 {
+        self.query_total_supply_of(&self.native_sac_id)
+    }
+
+    fn query_total_supply_of(&self, token: &str) -> i128 {
         let result = invoke(
             &self.contract_id,
             &self.deployer.name,
             "total_supply",
-            &[],
+            &["--token", token],
         )
         .expect("query total_supply");
 
@@ -134,6 +279,22 @@ fn test_deploy_and_initialize() {
 
     let supply = f.query_total_supply();
     assert_eq!(supply, 0, "Initial total supply should be 0");
+
+    assert!(f.query_is_signer(&f.signer1.public_key));
+    assert!(f.query_is_signer(&f.signer2.public_key));
+    assert!(
+        !f.query_is_signer(&f.deployer.public_key),
+        "Deployer is not a signer"
+    );
+    assert_eq!(f.query_threshold(), 1, "Threshold should match initialize");
+
+    let (signers, threshold) = f.query_config();
+    assert_eq!(
+        signers,
+        vec![f.signer1.public_key.clone(), f.signer2.public_key.clone()]
+    );
+    assert_eq!(threshold, 1);
+    assert_eq!(f.query_min_lock_duration(), 60);
 }
 
 #[test]
@@ -170,6 +331,10 @@ fn test_schedule_and_execute_unwrap() {
         &[
             "--signers",
             &signers_json,
+            "--token",
+            &f.native_sac_id,
+            "--from",
+            &f.signer1.public_key,
             "--recipient",
             &f.signer1.public_key,
             "--amount",
@@ -229,6 +394,8 @@ fn test_schedule_and_execute_transfer() {
         &[
             "--signers",
             &signers_json,
+            "--token",
+            &f.native_sac_id,
             "--from",
             &f.signer1.public_key,
             "--to",
@@ -287,6 +454,10 @@ fn test_cancel_timelock() {
         &[
             "--signers",
             &signers_json,
+            "--token",
+            &f.native_sac_id,
+            "--from",
+            &f.signer1.public_key,
             "--recipient",
             &f.signer1.public_key,
             "--amount",
@@ -324,9 +495,12 @@ fn test_cancel_timelock() {
     )
     .expect("execute_unwrap should return error");
 
-    assert!(
-        err.contains("Already executed") || err.contains("Error") || !err.is_empty(),
-        "Cancelled timelock execution should fail"
+    let failure = parse_invoke_failure(&err);
+    assert_eq!(
+        failure.panic,
+        Some(ContractPanic::AlreadyExecuted),
+        "Cancelled timelock execution should fail as already executed: {}",
+        failure.raw
     );
 
     // Balance should be unchanged
@@ -350,14 +524,344 @@ fn test_insufficient_signers_rejected() {
         &[
             "--signers",
             &signers_json,
+            "--token",
+            &f.native_sac_id,
             "--amount",
             "1000000",
         ],
     )
     .expect("wrap with insufficient signers should fail");
 
+    let failure = parse_invoke_failure(&err);
+    assert_eq!(
+        failure.panic,
+        Some(ContractPanic::InsufficientSigners),
+        "Should reject operation with insufficient signers: {}",
+        failure.raw
+    );
+}
+
+#[test]
+fn test_wrap_two_assets_in_one_instance() {
+    let f = TestFixture::setup("multi-asset");
+    f.initialize(1, 60);
+
+    // Issue a second asset from the deployer and deploy its SAC
+    let asset = format!("IMPT:{}", f.deployer.public_key);
+    let issued_sac_id = deploy_sac(&f.deployer.name, &asset).expect("deploy issued SAC");
+    change_trust(&f.signer1.name, &asset).expect("signer1 trustline");
+    pay_asset(&f.deployer.name, &f.signer1.public_key, &asset, 50_000_000)
+        .expect("fund signer1 with issued asset");
+
+    f.add_token(&issued_sac_id);
+
+    let native_amount: i128 = 1_000_000;
+    let issued_amount: i128 = 25_000_000;
+    f.wrap_tokens(&f.signer1, native_amount);
+    f.wrap_token(&issued_sac_id, &f.signer1, issued_amount);
+
+    assert_eq!(f.query_balance(&f.signer1.public_key), native_amount);
+    assert_eq!(
+        f.query_balance_of(&issued_sac_id, &f.signer1.public_key),
+        issued_amount
+    );
+    assert_eq!(f.query_total_supply(), native_amount);
+    assert_eq!(f.query_total_supply_of(&issued_sac_id), issued_amount);
+}
+
+#[test]
+fn test_scheduling_info_matches_schedule_count() {
+    let f = TestFixture::setup("sched-info");
+    f.initialize(1, 5);
+    f.wrap_tokens(&f.signer1, 2_000_000);
+
+    let (next_id, min_delay, max_delay) = f.query_scheduling_info();
+    assert_eq!(next_id, 0, "No timelocks scheduled yet");
+    assert_eq!(min_delay, 5);
+    assert_eq!(max_delay, 31_536_000);
+
+    let signers_json = format!(r#"["{}"]"#, f.signer1.public_key);
+    let schedules = 2;
+    for _ in 0..schedules {
+        invoke(
+            &f.contract_id,
+            &f.signer1.name,
+            "schedule_unwrap",
+            &[
+                "--signers",
+                &signers_json,
+                "--token",
+                &f.native_sac_id,
+                "--from",
+                &f.signer1.public_key,
+                "--recipient",
+                &f.signer1.public_key,
+                "--amount",
+                "100000",
+                "--delay_seconds",
+                "6",
+            ],
+        )
+        .expect("schedule_unwrap");
+    }
+
+    let (next_id, _, _) = f.query_scheduling_info();
+    assert_eq!(next_id, schedules, "next_timelock_id should equal schedules performed");
+}
+
+#[test]
+fn test_balances_lists_holders_after_wraps() {
+    let f = TestFixture::setup("balances");
+    f.initialize(1, 60);
+
+    f.wrap_tokens(&f.signer1, 1_000_000);
+    f.wrap_tokens(&f.signer2, 2_000_000);
+    f.wrap_tokens(&f.signer1, 500_000);
+
+    let all = f.query_balances(&f.native_sac_id, 0, 10);
+    assert_eq!(
+        all,
+        vec![
+            (f.signer1.public_key.clone(), 1_500_000),
+            (f.signer2.public_key.clone(), 2_000_000),
+        ],
+        "holders listed in first-wrap order with cumulative balances"
+    );
+    let total: i128 = all.iter().map(|(_, b)| b).sum();
+    assert_eq!(total, f.query_total_supply());
+
+    // Paging splits the same list
+    let first = f.query_balances(&f.native_sac_id, 0, 1);
+    let second = f.query_balances(&f.native_sac_id, 1, 1);
+    assert_eq!([first, second].concat(), all);
+    assert!(f.query_balances(&f.native_sac_id, 2, 10).is_empty());
+}
+
+#[test]
+fn test_timelock_remaining_within_scheduled_delay() {
+    let f = TestFixture::setup("tl-remaining");
+    f.initialize(1, 5);
+    f.wrap_tokens(&f.signer1, 2_000_000);
+
+    let signers_json = format!(r#"["{}"]"#, f.signer1.public_key);
+    let delay: u64 = 300;
+    let timelock_id = invoke(
+        &f.contract_id,
+        &f.signer1.name,
+        "schedule_unwrap",
+        &[
+            "--signers",
+            &signers_json,
+            "--token",
+            &f.native_sac_id,
+            "--from",
+            &f.signer1.public_key,
+            "--recipient",
+            &f.signer1.public_key,
+            "--amount",
+            "1000000",
+            "--delay_seconds",
+            &delay.to_string(),
+        ],
+    )
+    .expect("schedule_unwrap");
+    let timelock_id = timelock_id.trim().trim_matches('"');
+
+    // The query lands a few ledgers after scheduling; allow a minute of slack
+    let remaining = f.query_timelock_remaining(timelock_id);
     assert!(
-        err.contains("Insufficient signers") || err.contains("Error") || !err.is_empty(),
-        "Should reject operation with insufficient signers"
+        remaining <= delay && remaining >= delay - 60,
+        "remaining {remaining}s should be just under the {delay}s delay"
+    );
+
+    // Cancelled and unknown timelocks have nothing left to wait for
+    invoke(
+        &f.contract_id,
+        &f.signer1.name,
+        "cancel_timelock",
+        &["--signers", &signers_json, "--timelock_id", timelock_id],
+    )
+    .expect("cancel_timelock");
+    assert_eq!(f.query_timelock_remaining(timelock_id), 0);
+    assert_eq!(f.query_timelock_remaining("999"), 0);
+}
+
+#[test]
+fn test_paused_contract_rejects_wrap() {
+    let f = TestFixture::setup("pause");
+    f.initialize(1, 5);
+    f.wrap_tokens(&f.signer1, 1_000_000);
+
+    let signers_json = format!(r#"["{}"]"#, f.signer1.public_key);
+    invoke(&f.contract_id, &f.signer1.name, "pause", &["--signers", &signers_json])
+        .expect("pause");
+
+    let err = invoke_expect_fail(
+        &f.contract_id,
+        &f.signer1.name,
+        "wrap",
+        &[
+            "--signers",
+            &signers_json,
+            "--token",
+            &f.native_sac_id,
+            "--amount",
+            "1000000",
+        ],
+    )
+    .expect("wrap should fail while paused");
+    let failure = parse_invoke_failure(&err);
+    assert_eq!(
+        failure.panic,
+        Some(ContractPanic::ContractPaused),
+        "wrap on a paused contract: {}",
+        failure.raw
+    );
+
+    // Reads keep working while paused
+    assert_eq!(f.query_balance(&f.signer1.public_key), 1_000_000);
+
+    invoke(&f.contract_id, &f.signer1.name, "unpause", &["--signers", &signers_json])
+        .expect("unpause");
+    f.wrap_tokens(&f.signer1, 1_000_000);
+    assert_eq!(f.query_balance(&f.signer1.public_key), 2_000_000);
+}
+
+#[test]
+fn test_unwrap_fees_accumulate() {
+    let f = TestFixture::setup("unwrap-fees");
+    f.initialize_with_fee(1, 5, 100); // 1% unwrap fee
+
+    let wrap_amount: i128 = 3_000_000;
+    f.wrap_tokens(&f.signer1, wrap_amount);
+    assert_eq!(f.query_total_fees_collected(), 0);
+
+    let signers_json = format!(r#"["{}"]"#, f.signer1.public_key);
+    let amounts: [i128; 3] = [1_000_000, 250_000, 12_345];
+    let timelock_ids: Vec<String> = amounts
+        .iter()
+        .map(|amount| {
+            invoke(
+                &f.contract_id,
+                &f.signer1.name,
+                "schedule_unwrap",
+                &[
+                    "--signers",
+                    &signers_json,
+                    "--token",
+                    &f.native_sac_id,
+                    "--from",
+                    &f.signer1.public_key,
+                    "--recipient",
+                    &f.signer1.public_key,
+                    "--amount",
+                    &amount.to_string(),
+                    "--delay_seconds",
+                    "6",
+                ],
+            )
+            .expect("schedule_unwrap")
+            .trim()
+            .trim_matches('"')
+            .to_string()
+        })
+        .collect();
+
+    thread::sleep(Duration::from_secs(10));
+
+    for timelock_id in &timelock_ids {
+        invoke(
+            &f.contract_id,
+            &f.signer1.name,
+            "execute_unwrap",
+            &["--timelock_id", timelock_id],
+        )
+        .expect("execute_unwrap");
+    }
+
+    let expected_fees: i128 = amounts.iter().map(|amount| amount * 100 / 10_000).sum();
+    assert_eq!(
+        f.query_total_fees_collected(),
+        expected_fees,
+        "Counter should equal the sum of per-unwrap fees"
+    );
+
+    let unwrapped: i128 = amounts.iter().sum();
+    assert_eq!(
+        f.query_total_supply(),
+        wrap_amount - unwrapped,
+        "Total supply should fall by the full unwrap amounts, fees included"
     );
 }
+
+#[test]
+fn test_non_positive_amounts_rejected() {
+    let f = TestFixture::setup("non-positive");
+    f.initialize(1, 5);
+    f.wrap_tokens(&f.signer1, 1_000_000);
+
+    let signers_json = format!(r#"["{}"]"#, f.signer1.public_key);
+    for amount in ["--amount=0", "--amount=-1"] {
+        let unwrap_err = invoke_expect_fail(
+            &f.contract_id,
+            &f.signer1.name,
+            "schedule_unwrap",
+            &[
+                "--signers",
+                &signers_json,
+                "--token",
+                &f.native_sac_id,
+                "--from",
+                &f.signer1.public_key,
+                "--recipient",
+                &f.signer1.public_key,
+                amount,
+                "--delay_seconds",
+                "6",
+            ],
+        )
+        .expect("schedule_unwrap should return error");
+        let transfer_err = invoke_expect_fail(
+            &f.contract_id,
+            &f.signer1.name,
+            "schedule_transfer",
+            &[
+                "--signers",
+                &signers_json,
+                "--token",
+                &f.native_sac_id,
+                "--from",
+                &f.signer1.public_key,
+                "--to",
+                &f.signer2.public_key,
+                amount,
+                "--delay_seconds",
+                "6",
+            ],
+        )
+        .expect("schedule_transfer should return error");
+        let wrap_err = invoke_expect_fail(
+            &f.contract_id,
+            &f.signer1.name,
+            "wrap",
+            &["--signers", &signers_json, "--token", &f.native_sac_id, amount],
+        )
+        .expect("wrap should return error");
+
+        for err in [unwrap_err, transfer_err, wrap_err] {
+            let failure = parse_invoke_failure(&err);
+            assert_eq!(
+                failure.panic,
+                Some(ContractPanic::AmountNotPositive),
+                "{amount} should be rejected: {}",
+                failure.raw
+            );
+        }
+    }
+
+    // Nothing was scheduled or credited
+    let (next_id, _, _) = f.query_scheduling_info();
+    assert_eq!(next_id, 0, "No timelock should have been created");
+    assert_eq!(f.query_balance(&f.signer1.public_key), 1_000_000);
+    assert_eq!(f.query_total_supply(), 1_000_000);
+}